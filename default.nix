@@ -162,8 +162,10 @@ rec {
 
   apiPort = 5005;
 
+  memoryBudgets = builtins.fromJSON (builtins.readFile ./ts-tests/memory-budgets.json);
+
   # Tests don't yet run on Darwin
-  runTests = { appExe, device, variant ? "", speculosCmd }:
+  runTests = { appExe, device, variant ? "", speculosCmd, testEnv ? {} }:
   if pkgs.stdenv.hostPlatform.isDarwin
   then null
   else
@@ -181,6 +183,7 @@ rec {
 
     until wget -O/dev/null -o/dev/null http://localhost:${toString apiPort}; do sleep 0.1; done;
 
+    ${lib.concatStrings (lib.mapAttrsToList (name: value: "export ${name}=${value}\n") testEnv)}
     ${testScript}/bin/mocha-wrapper
     rv=$?
     kill -9 $SPECULOS
@@ -203,6 +206,22 @@ rec {
     stack-sizes --mem-limit=${toString memLimit} ${rootCrate}/bin/${appName} ${rootCrate}/bin/*.o | tee $out
   '';
 
+  # Fails once the app takes more flash than the budget, so that growth is noticed before it does
+  # not fit on the device anymore
+  makeSizeCheck = { rootCrate, device, flashLimit }:
+  pkgs.runCommandNoCC "size-check-${device}" {
+    nativeBuildInputs = [ pkgs.buildPackages.llvm ];
+    strictDeps = true;
+  } ''
+    # Code and initialized data is what goes to flash
+    size=$(llvm-size ${rootCrate}/bin/${appName} | awk 'NR == 2 { print $1 + $2 }')
+    echo "${device}: $size of ${toString flashLimit} bytes of flash" | tee $out
+    if [ "$size" -gt ${toString flashLimit} ]; then
+      echo "Over the flash budget of ${device}" >&2
+      exit 1
+    fi
+  '';
+
   appForDevice = device: rec {
     app = makeApp { inherit device; };
     app-with-logging = makeApp {
@@ -210,14 +229,26 @@ rec {
       release = false;
       rootFeatures = [ "default" "speculos" "extra_debug" ];
     };
+    # Keys from a fixed seed, for checking host SDKs against docs/test-vectors.md on speculos
+    app-with-test-vectors = makeApp {
+      inherit device;
+      release = false;
+      rootFeatures = [ "default" "test_vectors" ];
+    };
+    # Reports the peak stack depth in GET_VERSION, for ts-tests/memory-budget-tests.ts
+    app-with-memory-stats = makeApp {
+      inherit device;
+      rootFeatures = [ "default" "memory_stats" ];
+    };
 
-    memLimit = {
-      nanos = 4500;
-      nanosplus = 400000;
-      nanox = 400000;
-    }.${device} or (throw "Unknown target device: `${device}'");
+    # Shared with ts-tests/memory-budget-tests.ts. The flash is lower than what the device has, to
+    # leave room for the next release.
+    budget = memoryBudgets.${device} or (throw "Unknown target device: `${device}'");
+    memLimit = budget.stack;
+    flashLimit = budget.flash;
 
     stack-check = makeStackCheck { inherit memLimit rootCrate device; };
+    size-check = makeSizeCheck { inherit flashLimit rootCrate device; };
     stack-check-with-logging = makeStackCheck {
       inherit memLimit device;
       rootCrate = rootCrate-with-logging;
@@ -226,6 +257,7 @@ rec {
 
     rootCrate = app.rootCrate.build;
     rootCrate-with-logging = app-with-logging.rootCrate.build;
+    rootCrate-with-memory-stats = app-with-memory-stats.rootCrate.build;
 
     appExe = rootCrate + "/bin/" + appName;
 
@@ -286,6 +318,17 @@ rec {
       appExe = rootCrate-with-logging + "/bin/" + appName;
       variant = "-with-logging";
     };
+    # The peak stack of the memory_stats build while signing, and the flash of the release build
+    memory-budget-check = runTests {
+      inherit speculosCmd device;
+      appExe = rootCrate-with-memory-stats + "/bin/" + appName;
+      variant = "-memory-stats";
+      testEnv = {
+        DEVICE = device;
+        MEMORY_STATS = "1";
+        APP_ELF = appExe;
+      };
+    };
 
     appShell = pkgs.mkShell {
       packages = [ alamgu.ledgerctl loadApp alamgu.generic-cli pkgs.jq ];
@@ -0,0 +1,18 @@
+// Writes the fixture corpus as seeds for the intent_message fuzz target, see CONTRIBUTING.md:
+//
+//     cargo run --example fuzz_seeds -- ../rust-app/fuzz/corpus/intent_message
+
+use std::path::PathBuf;
+
+fn main() -> std::io::Result<()> {
+    let dir = PathBuf::from(
+        std::env::args()
+            .nth(1)
+            .expect("usage: fuzz_seeds <corpus directory>"),
+    );
+    std::fs::create_dir_all(&dir)?;
+    for (name, bytes) in iota_tx_fixtures::corpus() {
+        std::fs::write(dir.join(name), bytes)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,108 @@
+// The BCS encoding of the types of an IOTA transaction, as far as the fixtures need them. Follows
+// the layout which the parser of the app reads, see rust-app/interface/src/schemas.rs.
+
+pub trait Bcs {
+    fn encode(&self, out: &mut Vec<u8>);
+
+    fn to_bcs(&self) -> Vec<u8> {
+        let mut rv = Vec::new();
+        self.encode(&mut rv);
+        rv
+    }
+}
+
+pub fn uleb128(mut value: u64, out: &mut Vec<u8>) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+impl Bcs for bool {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.push(*self as u8);
+    }
+}
+
+impl Bcs for u8 {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.push(*self);
+    }
+}
+
+impl Bcs for u16 {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_le_bytes());
+    }
+}
+
+impl Bcs for u64 {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_le_bytes());
+    }
+}
+
+impl Bcs for [u8; 32] {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(self);
+    }
+}
+
+impl Bcs for str {
+    fn encode(&self, out: &mut Vec<u8>) {
+        uleb128(self.len() as u64, out);
+        out.extend_from_slice(self.as_bytes());
+    }
+}
+
+impl Bcs for String {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.as_str().encode(out);
+    }
+}
+
+impl<T: Bcs> Bcs for Vec<T> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        uleb128(self.len() as u64, out);
+        for item in self {
+            item.encode(out);
+        }
+    }
+}
+
+impl<T: Bcs> Bcs for Option<T> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        match self {
+            None => out.push(0),
+            Some(item) => {
+                out.push(1);
+                item.encode(out);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_uleb128() {
+        for (value, bytes) in [
+            (0, &[0][..]),
+            (0x7f, &[0x7f]),
+            (0x80, &[0x80, 0x01]),
+            (300, &[0xac, 0x02]),
+            (u32::MAX as u64, &[0xff, 0xff, 0xff, 0xff, 0x0f]),
+        ] {
+            let mut out = Vec::new();
+            uleb128(value, &mut out);
+            assert_eq!(out, bytes);
+        }
+    }
+}
@@ -0,0 +1,518 @@
+//! Builds valid IOTA transactions for the tests of the Ledger app, so that parser tests and fuzzing
+//! seeds are written as the transaction they stand for instead of a blob of hex.
+//!
+//! ```
+//! use iota_tx_fixtures::TransactionBuilder;
+//!
+//! let mut tx = TransactionBuilder::new([1; 32]);
+//! let coin = tx.split_coins(tx.gas_coin(), &[1_000_000_000]);
+//! tx.transfer_objects(&[coin], [2; 32]);
+//! let bytes = tx.intent_message();
+//! ```
+
+pub mod bcs;
+pub mod types;
+
+use crate::bcs::Bcs;
+use crate::types::*;
+
+// The objects and packages of the IOTA framework
+pub const IOTA_SYSTEM_PACKAGE_ID: ObjectId = short_id(3);
+pub const IOTA_SYSTEM_STATE_OBJECT_ID: ObjectId = short_id(5);
+pub const IOTA_SYSTEM_STATE_INITIAL_VERSION: u64 = 1;
+
+// As used by the default gas of the builder
+pub const DEFAULT_GAS_PRICE: u64 = 1000;
+pub const DEFAULT_GAS_BUDGET: u64 = 10_000_000;
+
+/// The type of IOTA coins, `0x2::iota::IOTA`, for the type arguments of calls generic over a coin.
+pub fn iota_coin_type() -> TypeTag {
+    TypeTag::Struct(Box::new(StructTag {
+        address: short_id(2),
+        module: "iota".to_string(),
+        name: "IOTA".to_string(),
+        type_params: vec![],
+    }))
+}
+
+/// An object ID like `0x5`, with all but the last byte zero.
+pub const fn short_id(last: u8) -> ObjectId {
+    let mut rv = [0; 32];
+    rv[31] = last;
+    rv
+}
+
+/// Builds a programmable transaction, adding an input for every value passed to its commands.
+#[derive(Clone, Debug)]
+pub struct TransactionBuilder {
+    data: TransactionData,
+}
+
+impl TransactionBuilder {
+    /// A transaction of the given sender, paying for gas with one object of theirs.
+    pub fn new(sender: Address) -> TransactionBuilder {
+        TransactionBuilder {
+            data: TransactionData {
+                inputs: Vec::new(),
+                commands: Vec::new(),
+                sender,
+                gas_data: GasData {
+                    payment: vec![ObjectRef {
+                        id: [0x12; 32],
+                        version: 7,
+                        digest: Digest([0xf2; 32]),
+                    }],
+                    owner: sender,
+                    price: DEFAULT_GAS_PRICE,
+                    budget: DEFAULT_GAS_BUDGET,
+                },
+                expiration: None,
+            },
+        }
+    }
+
+    pub fn gas_data(&mut self, gas_data: GasData) -> &mut Self {
+        self.data.gas_data = gas_data;
+        self
+    }
+
+    pub fn gas_budget(&mut self, price: u64, budget: u64) -> &mut Self {
+        self.data.gas_data.price = price;
+        self.data.gas_data.budget = budget;
+        self
+    }
+
+    pub fn expiration(&mut self, epoch: u64) -> &mut Self {
+        self.data.expiration = Some(epoch);
+        self
+    }
+
+    pub fn gas_coin(&self) -> Argument {
+        Argument::GasCoin
+    }
+
+    pub fn input(&mut self, input: CallArg) -> Argument {
+        self.data.inputs.push(input);
+        Argument::Input(self.data.inputs.len() as u16 - 1)
+    }
+
+    pub fn pure<T: Bcs + ?Sized>(&mut self, value: &T) -> Argument {
+        self.input(CallArg::Pure(value.to_bcs()))
+    }
+
+    pub fn owned_object(&mut self, object: ObjectRef) -> Argument {
+        self.input(CallArg::Object(ObjectArg::ImmOrOwnedObject(object)))
+    }
+
+    pub fn shared_object(
+        &mut self,
+        id: ObjectId,
+        initial_shared_version: u64,
+        mutable: bool,
+    ) -> Argument {
+        self.input(CallArg::Object(ObjectArg::SharedObject {
+            id,
+            initial_shared_version,
+            mutable,
+        }))
+    }
+
+    /// Add a command, returning its result.
+    pub fn command(&mut self, command: Command) -> Argument {
+        self.data.commands.push(command);
+        Argument::Result(self.data.commands.len() as u16 - 1)
+    }
+
+    /// Split coins of the given amounts off a coin. With more than one amount, the coins are the
+    /// nested results of the command.
+    pub fn split_coins(&mut self, coin: Argument, amounts: &[u64]) -> Argument {
+        let amounts = amounts.iter().map(|amount| self.pure(amount)).collect();
+        self.command(Command::SplitCoins(coin, amounts))
+    }
+
+    pub fn merge_coins(&mut self, coin: Argument, coins: &[Argument]) -> Argument {
+        self.command(Command::MergeCoins(coin, coins.to_vec()))
+    }
+
+    pub fn transfer_objects(&mut self, objects: &[Argument], recipient: Address) -> Argument {
+        let recipient = self.pure(&recipient);
+        self.command(Command::TransferObjects(objects.to_vec(), recipient))
+    }
+
+    pub fn move_call(
+        &mut self,
+        package: ObjectId,
+        module: &str,
+        function: &str,
+        type_arguments: Vec<TypeTag>,
+        arguments: &[Argument],
+    ) -> Argument {
+        self.command(Command::MoveCall(Box::new(MoveCall {
+            package,
+            module: module.to_string(),
+            function: function.to_string(),
+            type_arguments,
+            arguments: arguments.to_vec(),
+        })))
+    }
+
+    fn system_state(&mut self) -> Argument {
+        self.shared_object(
+            IOTA_SYSTEM_STATE_OBJECT_ID,
+            IOTA_SYSTEM_STATE_INITIAL_VERSION,
+            true,
+        )
+    }
+
+    /// Stake a coin with a validator, through `0x3::iota_system::request_add_stake`.
+    pub fn request_add_stake(&mut self, coin: Argument, validator: Address) -> Argument {
+        let system_state = self.system_state();
+        let validator = self.pure(&validator);
+        self.move_call(
+            IOTA_SYSTEM_PACKAGE_ID,
+            "iota_system",
+            "request_add_stake",
+            vec![],
+            &[system_state, coin, validator],
+        )
+    }
+
+    /// Unstake a StakedIota object, through `0x3::iota_system::request_withdraw_stake`.
+    pub fn request_withdraw_stake(&mut self, staked_iota: ObjectRef) -> Argument {
+        let system_state = self.system_state();
+        let staked_iota = self.owned_object(staked_iota);
+        self.move_call(
+            IOTA_SYSTEM_PACKAGE_ID,
+            "iota_system",
+            "request_withdraw_stake",
+            vec![],
+            &[system_state, staked_iota],
+        )
+    }
+
+    /// Unstake a TimelockedStakedIota object, through
+    /// `0x3::timelocked_staking::request_withdraw_stake`.
+    pub fn request_withdraw_timelocked_stake(
+        &mut self,
+        timelocked_staked_iota: ObjectRef,
+    ) -> Argument {
+        let system_state = self.system_state();
+        let timelocked_staked_iota = self.owned_object(timelocked_staked_iota);
+        self.move_call(
+            IOTA_SYSTEM_PACKAGE_ID,
+            "timelocked_staking",
+            "request_withdraw_stake",
+            vec![],
+            &[system_state, timelocked_staked_iota],
+        )
+    }
+
+    pub fn transaction_data(&self) -> &TransactionData {
+        &self.data
+    }
+
+    /// The BCS of the TransactionData.
+    pub fn build(&self) -> Vec<u8> {
+        self.data.to_bcs()
+    }
+
+    /// The BCS of the transaction with the intent of a transaction, which is what SIGN_TX signs.
+    pub fn intent_message(&self) -> Vec<u8> {
+        IntentMessage {
+            scope: 0,
+            version: 0,
+            app_id: 0,
+            value: self.data.clone(),
+        }
+        .to_bcs()
+    }
+}
+
+/// A transfer of some of the gas coin, as wallets send them.
+pub fn transfer(sender: Address, recipient: Address, amount: u64) -> TransactionBuilder {
+    let mut tx = TransactionBuilder::new(sender);
+    let coin = tx.split_coins(Argument::GasCoin, &[amount]);
+    tx.transfer_objects(&[coin], recipient);
+    tx
+}
+
+/// Staking some of the gas coin with a validator.
+pub fn stake(sender: Address, validator: Address, amount: u64) -> TransactionBuilder {
+    let mut tx = TransactionBuilder::new(sender);
+    let coin = tx.split_coins(Argument::GasCoin, &[amount]);
+    tx.request_add_stake(coin, validator);
+    tx
+}
+
+/// Unstaking a StakedIota object.
+pub fn unstake(sender: Address, staked_iota: ObjectRef) -> TransactionBuilder {
+    let mut tx = TransactionBuilder::new(sender);
+    tx.request_withdraw_stake(staked_iota);
+    tx
+}
+
+/// Named intent messages covering the commands above, for seeding the fuzzers.
+pub fn corpus() -> Vec<(&'static str, Vec<u8>)> {
+    let sender = [0x6f; 32];
+    let recipient = [0x4f; 32];
+    let staked_iota = ObjectRef {
+        id: [0x5a; 32],
+        version: 3,
+        digest: Digest([0xd1; 32]),
+    };
+
+    let mut split_many = TransactionBuilder::new(sender);
+    let coins = split_many.split_coins(Argument::GasCoin, &[1, 2, 3]);
+    let nested: Vec<Argument> = (0..3)
+        .map(|i| match coins {
+            Argument::Result(r) => Argument::NestedResult(r, i),
+            _ => unreachable!(),
+        })
+        .collect();
+    split_many.merge_coins(nested[0], &nested[1..]);
+    split_many.transfer_objects(&[nested[0]], recipient);
+
+    let mut generic_call = TransactionBuilder::new(sender);
+    let amount = generic_call.pure(&5u64);
+    generic_call.move_call(
+        short_id(2),
+        "coin",
+        "split",
+        vec![iota_coin_type()],
+        &[Argument::GasCoin, amount],
+    );
+
+    let lending_market = [0x1e; 32];
+    let mut deposit = TransactionBuilder::new(sender);
+    let coin = deposit.split_coins(Argument::GasCoin, &[1_000_000_000]);
+    let market = deposit.shared_object([0x3c; 32], 9, true);
+    deposit.move_call(
+        lending_market,
+        "lending",
+        "deposit",
+        vec![iota_coin_type()],
+        &[market, coin],
+    );
+
+    let mut withdraw = TransactionBuilder::new(sender);
+    let market = withdraw.shared_object([0x3c; 32], 9, true);
+    let amount = withdraw.pure(&500_000_000u64);
+    let coin = withdraw.move_call(
+        lending_market,
+        "lending",
+        "withdraw",
+        vec![iota_coin_type()],
+        &[market, amount],
+    );
+    withdraw.transfer_objects(&[coin], sender);
+
+    let amm = [0x5b; 32];
+    let usdc = TypeTag::Struct(Box::new(StructTag {
+        address: [0xc0; 32],
+        module: "usdc".to_string(),
+        name: "USDC".to_string(),
+        type_params: vec![],
+    }));
+    let mut add_liquidity = TransactionBuilder::new(sender);
+    let iota = add_liquidity.split_coins(Argument::GasCoin, &[2_000_000_000]);
+    let usdc_coin = add_liquidity.owned_object(ObjectRef {
+        id: [0xcc; 32],
+        version: 4,
+        digest: Digest([0xd2; 32]),
+    });
+    let other = add_liquidity.split_coins(usdc_coin, &[3_000_000]);
+    let pool = add_liquidity.shared_object([0x9b; 32], 11, true);
+    let share = add_liquidity.move_call(
+        amm,
+        "pool",
+        "add_liquidity",
+        vec![iota_coin_type(), usdc.clone()],
+        &[pool, iota, other],
+    );
+    add_liquidity.transfer_objects(&[share], sender);
+
+    let mut remove_liquidity = TransactionBuilder::new(sender);
+    let pool = remove_liquidity.shared_object([0x9b; 32], 11, true);
+    let share = remove_liquidity.owned_object(ObjectRef {
+        id: [0x5e; 32],
+        version: 6,
+        digest: Digest([0xd3; 32]),
+    });
+    let min_iota = remove_liquidity.pure(&1_900_000_000u64);
+    let min_usdc = remove_liquidity.pure(&2_900_000u64);
+    let coins = remove_liquidity.move_call(
+        amm,
+        "pool",
+        "remove_liquidity",
+        vec![iota_coin_type(), usdc.clone()],
+        &[pool, share, min_iota, min_usdc],
+    );
+    let both: Vec<Argument> = (0..2)
+        .map(|i| match coins {
+            Argument::Result(r) => Argument::NestedResult(r, i),
+            _ => unreachable!(),
+        })
+        .collect();
+    remove_liquidity.transfer_objects(&both, sender);
+
+    let mut send_objects = TransactionBuilder::new(sender);
+    let nft = send_objects.owned_object(ObjectRef {
+        id: [0xa1; 32],
+        version: 8,
+        digest: Digest([0xd4; 32]),
+    });
+    let coin = send_objects.split_coins(Argument::GasCoin, &[500_000_000]);
+    let ticket = send_objects.owned_object(ObjectRef {
+        id: [0xa2; 32],
+        version: 2,
+        digest: Digest([0xd5; 32]),
+    });
+    send_objects.transfer_objects(&[nft, coin, ticket], recipient);
+
+    // The coins of the division are kept by the sender, and the coin divided, with the last of
+    // them, is sent on
+    let mut divide = TransactionBuilder::new(sender);
+    let coin = divide.split_coins(Argument::GasCoin, &[3_000_000_000]);
+    let count = divide.pure(&3u64);
+    divide.move_call(
+        short_id(2),
+        "pay",
+        "divide_and_keep",
+        vec![iota_coin_type()],
+        &[coin, count],
+    );
+    divide.transfer_objects(&[coin], recipient);
+
+    // 1000 USDC, of 6 decimals, minted by its issuer for the recipient
+    let mut mint = TransactionBuilder::new(sender);
+    let treasury_cap = mint.owned_object(ObjectRef {
+        id: [0x7c; 32],
+        version: 5,
+        digest: Digest([0xd6; 32]),
+    });
+    let amount = mint.pure(&1_000_000_000u64);
+    let minted_for = mint.pure(&recipient);
+    mint.move_call(
+        short_id(2),
+        "coin",
+        "mint_and_transfer",
+        vec![usdc],
+        &[treasury_cap, amount, minted_for],
+    );
+
+    let mut unstake_timelocked = TransactionBuilder::new(sender);
+    unstake_timelocked.request_withdraw_timelocked_stake(ObjectRef {
+        id: [0x5b; 32],
+        version: 4,
+        digest: Digest([0xd7; 32]),
+    });
+
+    let mut expiring = transfer(sender, recipient, 1_000_000);
+    expiring.expiration(42);
+
+    vec![
+        ("transfer", transfer(sender, recipient, 1_000_000)),
+        ("transfer-expiring", expiring),
+        ("split-merge-transfer", split_many),
+        ("object-transfer", send_objects),
+        ("stake", stake(sender, [0x77; 32], 1_000_000_000)),
+        ("unstake", unstake(sender, staked_iota)),
+        ("unstake-timelocked", unstake_timelocked),
+        ("move-call", generic_call),
+        ("lending-deposit", deposit),
+        ("lending-withdraw", withdraw),
+        ("liquidity-add", add_liquidity),
+        ("liquidity-remove", remove_liquidity),
+        ("divide", divide),
+        ("mint", mint),
+    ]
+    .into_iter()
+    .map(|(name, tx)| (name, tx.intent_message()))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    fn address(s: &str) -> Address {
+        let mut rv = [0; 32];
+        rv.copy_from_slice(&hex(s));
+        rv
+    }
+
+    // TRANSFER_TXN of ts-tests/common.ts
+    #[test]
+    fn builds_the_transfer_of_the_functional_tests() {
+        let sender = address("6fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21");
+        let mut tx = transfer(
+            sender,
+            address("4f2370b2a4810ad6c8e1cfd92cc8c8818fef8f59e3a80cea17871f78d850ba4b"),
+            1_000_000,
+        );
+        tx.gas_data(GasData {
+            payment: vec![ObjectRef {
+                id: address("12a6d0c44edc630d2724b1f57fea4f93308b1d22164402c65778bd99379c4733"),
+                version: 7,
+                digest: Digest(address(
+                    "f2fd3c87b227f1015182fe4348ed680d7ed32bcd3269704252c03e1d0b13d30d",
+                )),
+            }],
+            owner: sender,
+            price: 1,
+            budget: 1036,
+        });
+        assert_eq!(
+            tx.intent_message(),
+            hex(concat!(
+                "000000000002000840420f000000000000204f2370b2a4810ad6c8e1cfd92cc8c8818fef8f59e3a8",
+                "0cea17871f78d850ba4b0202000101000001010200000101006fb21feead027da4873295affd6c4f",
+                "3618fe176fa2fbf3e7b5ef1d9463b31e210112a6d0c44edc630d2724b1f57fea4f93308b1d221644",
+                "02c65778bd99379c4733070000000000000020f2fd3c87b227f1015182fe4348ed680d7ed32bcd32",
+                "69704252c03e1d0b13d30d6fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463",
+                "b31e2101000000000000000c0400000000000000"
+            ))
+        );
+    }
+
+    #[test]
+    fn stakes_through_the_system_state() {
+        let tx = stake([1; 32], [2; 32], 5);
+        let data = tx.transaction_data();
+        assert_eq!(data.inputs.len(), 3);
+        assert_eq!(
+            data.inputs[1],
+            CallArg::Object(ObjectArg::SharedObject {
+                id: IOTA_SYSTEM_STATE_OBJECT_ID,
+                initial_shared_version: 1,
+                mutable: true,
+            })
+        );
+        match &data.commands[1] {
+            Command::MoveCall(call) => {
+                assert_eq!(call.function, "request_add_stake");
+                assert_eq!(
+                    call.arguments,
+                    [Argument::Input(1), Argument::Result(0), Argument::Input(2)]
+                );
+            }
+            c => panic!("not a move call: {:?}", c),
+        }
+    }
+
+    #[test]
+    fn corpus_names_are_unique() {
+        let corpus = corpus();
+        let mut names: Vec<_> = corpus.iter().map(|(name, _)| *name).collect();
+        names.sort_unstable();
+        names.dedup();
+        assert_eq!(names.len(), corpus.len());
+    }
+}
@@ -0,0 +1,283 @@
+use crate::bcs::{uleb128, Bcs};
+
+pub type Address = [u8; 32];
+pub type ObjectId = Address;
+
+/// The digest of an object, which BCS encodes with its length.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Digest(pub [u8; 32]);
+
+impl Bcs for Digest {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.0.to_vec().encode(out);
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ObjectRef {
+    pub id: ObjectId,
+    pub version: u64,
+    pub digest: Digest,
+}
+
+impl Bcs for ObjectRef {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.id.encode(out);
+        self.version.encode(out);
+        self.digest.encode(out);
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ObjectArg {
+    ImmOrOwnedObject(ObjectRef),
+    SharedObject {
+        id: ObjectId,
+        initial_shared_version: u64,
+        mutable: bool,
+    },
+    Receiving(ObjectRef),
+}
+
+impl Bcs for ObjectArg {
+    fn encode(&self, out: &mut Vec<u8>) {
+        match self {
+            ObjectArg::ImmOrOwnedObject(object) => {
+                out.push(0);
+                object.encode(out);
+            }
+            ObjectArg::SharedObject {
+                id,
+                initial_shared_version,
+                mutable,
+            } => {
+                out.push(1);
+                id.encode(out);
+                initial_shared_version.encode(out);
+                mutable.encode(out);
+            }
+            ObjectArg::Receiving(object) => {
+                out.push(2);
+                object.encode(out);
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CallArg {
+    /// The BCS of a value, like a `u64` amount or a recipient address.
+    Pure(Vec<u8>),
+    Object(ObjectArg),
+}
+
+impl Bcs for CallArg {
+    fn encode(&self, out: &mut Vec<u8>) {
+        match self {
+            CallArg::Pure(bytes) => {
+                out.push(0);
+                bytes.encode(out);
+            }
+            CallArg::Object(object) => {
+                out.push(1);
+                object.encode(out);
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Argument {
+    GasCoin,
+    Input(u16),
+    Result(u16),
+    NestedResult(u16, u16),
+}
+
+impl Bcs for Argument {
+    fn encode(&self, out: &mut Vec<u8>) {
+        match self {
+            Argument::GasCoin => out.push(0),
+            Argument::Input(i) => {
+                out.push(1);
+                i.encode(out);
+            }
+            Argument::Result(i) => {
+                out.push(2);
+                i.encode(out);
+            }
+            Argument::NestedResult(i, j) => {
+                out.push(3);
+                i.encode(out);
+                j.encode(out);
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StructTag {
+    pub address: Address,
+    pub module: String,
+    pub name: String,
+    pub type_params: Vec<TypeTag>,
+}
+
+impl Bcs for StructTag {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.address.encode(out);
+        self.module.encode(out);
+        self.name.encode(out);
+        self.type_params.encode(out);
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TypeTag {
+    Bool,
+    U8,
+    U64,
+    U128,
+    Address,
+    Signer,
+    Vector(Box<TypeTag>),
+    Struct(Box<StructTag>),
+    U16,
+    U32,
+    U256,
+}
+
+impl Bcs for TypeTag {
+    fn encode(&self, out: &mut Vec<u8>) {
+        match self {
+            TypeTag::Bool => out.push(0),
+            TypeTag::U8 => out.push(1),
+            TypeTag::U64 => out.push(2),
+            TypeTag::U128 => out.push(3),
+            TypeTag::Address => out.push(4),
+            TypeTag::Signer => out.push(5),
+            TypeTag::Vector(inner) => {
+                out.push(6);
+                inner.encode(out);
+            }
+            TypeTag::Struct(tag) => {
+                out.push(7);
+                tag.encode(out);
+            }
+            TypeTag::U16 => out.push(8),
+            TypeTag::U32 => out.push(9),
+            TypeTag::U256 => out.push(10),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MoveCall {
+    pub package: ObjectId,
+    pub module: String,
+    pub function: String,
+    pub type_arguments: Vec<TypeTag>,
+    pub arguments: Vec<Argument>,
+}
+
+impl Bcs for MoveCall {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.package.encode(out);
+        self.module.encode(out);
+        self.function.encode(out);
+        self.type_arguments.encode(out);
+        self.arguments.encode(out);
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Command {
+    MoveCall(Box<MoveCall>),
+    TransferObjects(Vec<Argument>, Argument),
+    SplitCoins(Argument, Vec<Argument>),
+    MergeCoins(Argument, Vec<Argument>),
+}
+
+impl Bcs for Command {
+    fn encode(&self, out: &mut Vec<u8>) {
+        match self {
+            Command::MoveCall(call) => {
+                out.push(0);
+                call.encode(out);
+            }
+            Command::TransferObjects(objects, recipient) => {
+                out.push(1);
+                objects.encode(out);
+                recipient.encode(out);
+            }
+            Command::SplitCoins(coin, amounts) => {
+                out.push(2);
+                coin.encode(out);
+                amounts.encode(out);
+            }
+            Command::MergeCoins(coin, coins) => {
+                out.push(3);
+                coin.encode(out);
+                coins.encode(out);
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GasData {
+    pub payment: Vec<ObjectRef>,
+    pub owner: Address,
+    pub price: u64,
+    pub budget: u64,
+}
+
+impl Bcs for GasData {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.payment.encode(out);
+        self.owner.encode(out);
+        self.price.encode(out);
+        self.budget.encode(out);
+    }
+}
+
+/// The V1 of TransactionData, with a programmable transaction as its kind.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransactionData {
+    pub inputs: Vec<CallArg>,
+    pub commands: Vec<Command>,
+    pub sender: Address,
+    pub gas_data: GasData,
+    pub expiration: Option<u64>,
+}
+
+impl Bcs for TransactionData {
+    fn encode(&self, out: &mut Vec<u8>) {
+        // TransactionData::V1, then TransactionKind::ProgrammableTransaction
+        out.push(0);
+        out.push(0);
+        self.inputs.encode(out);
+        self.commands.encode(out);
+        self.sender.encode(out);
+        self.gas_data.encode(out);
+        self.expiration.encode(out);
+    }
+}
+
+/// An intent message for a transaction, as SIGN_TX receives it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IntentMessage {
+    pub scope: u64,
+    pub version: u64,
+    pub app_id: u64,
+    pub value: TransactionData,
+}
+
+impl Bcs for IntentMessage {
+    fn encode(&self, out: &mut Vec<u8>) {
+        uleb128(self.scope, out);
+        uleb128(self.version, out);
+        uleb128(self.app_id, out);
+        self.value.encode(out);
+    }
+}
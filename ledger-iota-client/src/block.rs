@@ -0,0 +1,152 @@
+use crate::{Error, Transport};
+use core::convert::{TryFrom, TryInto};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+// The block protocol of docs/block-protocol.md, through which the app reads the parameters of a
+// command and returns its result.
+
+// The most data in one block, which leaves room for the hash and the APDU header
+pub const CHUNK_SIZE: usize = 180;
+
+// Instructions the host sends
+const START: u8 = 0;
+const GET_CHUNK_RESPONSE_SUCCESS: u8 = 1;
+const GET_CHUNK_RESPONSE_FAILURE: u8 = 2;
+const PUT_CHUNK_RESPONSE: u8 = 3;
+const RESULT_ACCUMULATING_RESPONSE: u8 = 4;
+
+// Instructions the app sends
+const RESULT_ACCUMULATING: u8 = 0;
+const RESULT_FINAL: u8 = 1;
+const GET_CHUNK: u8 = 2;
+const PUT_CHUNK: u8 = 3;
+
+const SW_OK: u16 = 0x9000;
+
+pub type Hash = [u8; 32];
+
+fn sha256(data: &[u8]) -> Hash {
+    Sha256::digest(data).into()
+}
+
+/// The blocks of a parameter, the first of them first. Every block starts with the hash of the
+/// next one, and the last with zeroes instead.
+pub fn blocks(param: &[u8]) -> Vec<Vec<u8>> {
+    let mut chunks: Vec<&[u8]> = param.chunks(CHUNK_SIZE).collect();
+    if chunks.is_empty() {
+        chunks.push(&[]);
+    }
+    let mut rv = Vec::with_capacity(chunks.len());
+    let mut next = [0; 32];
+    for chunk in chunks.iter().rev() {
+        let mut block = Vec::with_capacity(32 + chunk.len());
+        block.extend_from_slice(&next);
+        block.extend_from_slice(chunk);
+        next = sha256(&block);
+        rv.push(block);
+    }
+    rv.reverse();
+    rv
+}
+
+/// Send a command whose parameters are given in full, answering the requests of the app for their
+/// blocks until it returns its result.
+pub fn run<T: Transport>(
+    transport: &mut T,
+    cla: u8,
+    ins: u8,
+    p2: u8,
+    params: &[&[u8]],
+) -> Result<Vec<u8>, Error<T::Error>> {
+    let mut store: HashMap<Hash, Vec<u8>> = HashMap::new();
+    let mut start = Vec::with_capacity(32 * params.len());
+    for param in params {
+        let blocks = blocks(param);
+        start.extend_from_slice(&sha256(&blocks[0]));
+        for block in blocks {
+            store.insert(sha256(&block), block);
+        }
+    }
+
+    let mut result = Vec::new();
+    let mut message = start;
+    message.insert(0, START);
+    loop {
+        let response = exchange(transport, cla, ins, p2, &message)?;
+        let (&instruction, payload) = response
+            .split_first()
+            .ok_or(Error::Protocol("empty response"))?;
+        message = match instruction {
+            RESULT_ACCUMULATING => {
+                result.extend_from_slice(payload);
+                vec![RESULT_ACCUMULATING_RESPONSE]
+            }
+            RESULT_FINAL => {
+                result.extend_from_slice(payload);
+                return Ok(result);
+            }
+            GET_CHUNK => {
+                let hash: &Hash = payload
+                    .try_into()
+                    .map_err(|_| Error::Protocol("malformed chunk request"))?;
+                match store.get(hash) {
+                    Some(block) => {
+                        let mut rv = vec![GET_CHUNK_RESPONSE_SUCCESS];
+                        rv.extend_from_slice(block);
+                        rv
+                    }
+                    None => vec![GET_CHUNK_RESPONSE_FAILURE],
+                }
+            }
+            PUT_CHUNK => {
+                store.insert(sha256(payload), payload.to_vec());
+                vec![PUT_CHUNK_RESPONSE]
+            }
+            _ => return Err(Error::Protocol("unknown instruction")),
+        };
+    }
+}
+
+fn exchange<T: Transport>(
+    transport: &mut T,
+    cla: u8,
+    ins: u8,
+    p2: u8,
+    data: &[u8],
+) -> Result<Vec<u8>, Error<T::Error>> {
+    let lc = u8::try_from(data.len()).map_err(|_| Error::Protocol("APDU too long"))?;
+    let mut apdu = vec![cla, ins, 0, p2, lc];
+    apdu.extend_from_slice(data);
+    let mut response = transport.exchange(&apdu).map_err(Error::Transport)?;
+    if response.len() < 2 {
+        return Err(Error::Protocol("response without status word"));
+    }
+    let sw_bytes = response.split_off(response.len() - 2);
+    let sw = u16::from_be_bytes([sw_bytes[0], sw_bytes[1]]);
+    if sw != SW_OK {
+        return Err(Error::Status(sw));
+    }
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chains_the_blocks() {
+        let param = vec![7; 2 * CHUNK_SIZE + 1];
+        let blocks = blocks(&param);
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(blocks[0][..32], sha256(&blocks[1])[..]);
+        assert_eq!(blocks[1][..32], sha256(&blocks[2])[..]);
+        assert_eq!(blocks[2][..32], [0; 32]);
+        assert_eq!(blocks[2][32..], [7]);
+    }
+
+    #[test]
+    fn empty_parameters_have_one_block() {
+        assert_eq!(blocks(&[]), vec![vec![0; 32]]);
+    }
+}
@@ -0,0 +1,671 @@
+//! A host-side client for the IOTA Ledger app.
+//!
+//! Builds the APDUs of the commands in docs/apdu.md, runs the block protocol through which the app
+//! reads their parameters, and decodes the responses. The transport to the device is left to the
+//! caller, see [`Transport`].
+
+use core::convert::{Infallible, TryFrom, TryInto};
+use core::fmt;
+
+pub mod block;
+pub mod path;
+
+pub use path::Bip32Path;
+
+pub use ledger_iota_interface::{
+    Derivation, Ins, OperationKind, PatternKind, MAX_LABEL_LENGTH, SW_BAD_ULEB128, SW_TOO_COMPLEX,
+};
+
+const CLA: u8 = 0x00;
+
+/// Sends one APDU to the device and returns its response, the status word included.
+pub trait Transport {
+    type Error;
+
+    fn exchange(&mut self, apdu: &[u8]) -> Result<Vec<u8>, Self::Error>;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error<E = Infallible> {
+    Transport(E),
+    /// The app answered with a status word other than 0x9000, see docs/apdu.md.
+    Status(u16),
+    /// The app answered with something this client does not understand.
+    Protocol(&'static str),
+    InvalidPath,
+    InvalidLabel,
+}
+
+impl<E: fmt::Debug> fmt::Display for Error<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Transport(e) => write!(f, "transport error: {e:?}"),
+            Error::Status(sw) => write!(f, "the app returned status word 0x{sw:04x}"),
+            Error::Protocol(msg) => write!(f, "protocol error: {msg}"),
+            Error::InvalidPath => write!(f, "invalid derivation path"),
+            Error::InvalidLabel => write!(f, "invalid address book label"),
+        }
+    }
+}
+
+impl<E: fmt::Debug> std::error::Error for Error<E> {}
+
+impl<E> Error<E> {
+    // For the errors which cannot come from the transport
+    fn lift(e: Error) -> Error<E> {
+        match e {
+            Error::Transport(e) => match e {},
+            Error::Status(sw) => Error::Status(sw),
+            Error::Protocol(msg) => Error::Protocol(msg),
+            Error::InvalidPath => Error::InvalidPath,
+            Error::InvalidLabel => Error::InvalidLabel,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Version {
+    pub major: u8,
+    pub minor: u8,
+    pub patch: u8,
+    pub name: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PublicKey {
+    pub public_key: [u8; 32],
+    /// The BLAKE2b-256 of the public key.
+    pub address: [u8; 32],
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Signature(pub [u8; 64]);
+
+/// Which known-answer tests of SELF_TEST passed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SelfTest {
+    /// The BLAKE2b-256 that transactions are hashed with.
+    pub blake2b: bool,
+    /// Ed25519 signing and verifying, with the key of RFC 8032.
+    pub ed25519: bool,
+    /// Signing and verifying with a key derived from the seed of the device.
+    pub device_key: bool,
+}
+
+impl SelfTest {
+    pub fn passed(&self) -> bool {
+        self.blake2b && self.ed25519 && self.device_key
+    }
+}
+
+/// The largest transactions the app handles, as GET_LIMITS reports them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Limits {
+    /// The most bytes of a transaction SIGN_TX signs.
+    pub max_tx_size: u32,
+    /// The most bytes of a transaction SIGN_TX_CHUNKED signs, which depends on the device.
+    pub max_chunked_tx_size: u32,
+    /// The most inputs of a transaction which can be reviewed rather than blind signed.
+    pub max_inputs: u8,
+    /// The most commands of a transaction which can be reviewed rather than blind signed.
+    pub max_commands: u8,
+    /// The most amounts of a SplitCoins command which can be reviewed rather than blind signed.
+    pub max_split_amounts: u8,
+}
+
+/// What the last transaction signed does, as GET_RECEIPT reports it from what the app parsed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Receipt {
+    /// The BLAKE2b-256 of the intent message, which the signature is of.
+    pub hash: [u8; 32],
+    pub kind: OperationKind,
+    /// None for transactions which were blind signed, and so not parsed.
+    pub gas_budget: Option<u64>,
+    pub recipient: Option<[u8; 32]>,
+    /// The amounts the review showed, in the smallest unit of their coins.
+    pub amounts: Vec<u64>,
+}
+
+/// The Move calls the app clear signs, as GET_PATTERNS lists them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Patterns {
+    /// Bumped whenever the app changes which calls it clear signs.
+    pub version: u8,
+    pub patterns: Vec<Pattern>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Pattern {
+    pub kind: PatternKind,
+    /// None for calls clear signed in any package, which only apps before version 3 of the
+    /// patterns list. Since version 4, every pattern is listed with its package.
+    pub package: Option<[u8; 32]>,
+    pub module: String,
+    pub function: String,
+}
+
+pub struct Client<T> {
+    transport: T,
+    derivation: Derivation,
+}
+
+impl<T: Transport> Client<T> {
+    pub fn new(transport: T) -> Client<T> {
+        Client {
+            transport,
+            derivation: Derivation::Slip10,
+        }
+    }
+
+    /// Derive the keys of later commands with the given scheme instead of SLIP-0010.
+    pub fn with_derivation(mut self, derivation: Derivation) -> Client<T> {
+        self.derivation = derivation;
+        self
+    }
+
+    pub fn into_transport(self) -> T {
+        self.transport
+    }
+
+    pub fn get_version(&mut self) -> Result<Version, Error<T::Error>> {
+        let rv = block::run(&mut self.transport, CLA, Ins::GetVersion as u8, 0, &[])?;
+        match rv.as_slice() {
+            [major, minor, patch, name @ ..] => Ok(Version {
+                major: *major,
+                minor: *minor,
+                patch: *patch,
+                name: String::from_utf8(name.to_vec())
+                    .map_err(|_| Error::Protocol("app name is not UTF-8"))?,
+            }),
+            _ => Err(Error::Protocol("short version")),
+        }
+    }
+
+    /// The public key and address of a path, without a prompt unless the app is set to confirm
+    /// them.
+    pub fn get_pubkey(&mut self, path: &Bip32Path) -> Result<PublicKey, Error<T::Error>> {
+        self.pubkey_command(Ins::GetPubkey, path)
+    }
+
+    /// The public key and address of a path, once the user has checked the address on the device.
+    pub fn verify_address(&mut self, path: &Bip32Path) -> Result<PublicKey, Error<T::Error>> {
+        self.pubkey_command(Ins::VerifyAddress, path)
+    }
+
+    fn pubkey_command(&mut self, ins: Ins, path: &Bip32Path) -> Result<PublicKey, Error<T::Error>> {
+        let rv = self.run(ins, &[&path.encode()])?;
+        decode_pubkey(&rv).map_err(Error::lift)
+    }
+
+    /// Sign the BCS bytes of a transaction, once the user has approved it on the device.
+    pub fn sign_transaction(
+        &mut self,
+        path: &Bip32Path,
+        tx: &[u8],
+    ) -> Result<Signature, Error<T::Error>> {
+        let tx_size =
+            u32::try_from(tx.len()).map_err(|_| Error::Protocol("transaction too large"))?;
+        let mut param = Vec::with_capacity(4 + tx.len());
+        param.extend_from_slice(&tx_size.to_le_bytes());
+        param.extend_from_slice(tx);
+        let rv = self.run(Ins::Sign, &[&param, &path.encode()])?;
+        let signature = <[u8; 64]>::try_from(rv.as_slice())
+            .map_err(|_| Error::Protocol("signature is not 64 bytes"))?;
+        Ok(Signature(signature))
+    }
+
+    /// Save the address of a path to the address book of the app, with a label in printable
+    /// ASCII.
+    pub fn save_address(&mut self, path: &Bip32Path, label: &str) -> Result<(), Error<T::Error>> {
+        let param = encode_label(label).map_err(Error::lift)?;
+        self.run(Ins::SaveAddress, &[&path.encode(), &param])?;
+        Ok(())
+    }
+
+    /// Label a recipient for the rest of the session, with a label in printable ASCII. Reviews of
+    /// transfers to it show the label as a claim of the host, marked unverified, as the app cannot
+    /// check it.
+    pub fn label_recipient(
+        &mut self,
+        address: &[u8; 32],
+        label: &str,
+    ) -> Result<(), Error<T::Error>> {
+        let param = encode_label(label).map_err(Error::lift)?;
+        block::run(
+            &mut self.transport,
+            CLA,
+            Ins::LabelRecipient as u8,
+            0,
+            &[&address[..], &param],
+        )?;
+        Ok(())
+    }
+
+    /// Run the known-answer tests of the app, for checking a device before it is used to sign.
+    pub fn self_test(&mut self) -> Result<SelfTest, Error<T::Error>> {
+        let rv = block::run(&mut self.transport, CLA, Ins::SelfTest as u8, 0, &[])?;
+        let passed = |result: u8| match result {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(Error::Protocol("self-test result is neither 0 nor 1")),
+        };
+        match rv.as_slice() {
+            [blake2b, ed25519, device_key] => Ok(SelfTest {
+                blake2b: passed(*blake2b)?,
+                ed25519: passed(*ed25519)?,
+                device_key: passed(*device_key)?,
+            }),
+            _ => Err(Error::Protocol("self-test response is not 3 bytes")),
+        }
+    }
+
+    /// List the Move calls the app clear signs, for telling before signing a transaction whether
+    /// it will be reviewed or need blind signing.
+    pub fn get_patterns(&mut self) -> Result<Patterns, Error<T::Error>> {
+        let rv = block::run(&mut self.transport, CLA, Ins::GetPatterns as u8, 0, &[])?;
+        decode_patterns(&rv).map_err(Error::lift)
+    }
+
+    /// Get the largest transactions the app signs and reviews, for splitting or refusing larger
+    /// ones before sending them.
+    pub fn get_limits(&mut self) -> Result<Limits, Error<T::Error>> {
+        let rv = block::run(&mut self.transport, CLA, Ins::GetLimits as u8, 0, &[])?;
+        if rv.len() != 11 {
+            return Err(Error::Protocol("limits response is not 11 bytes"));
+        }
+        let u32_at = |i: usize| u32::from_le_bytes([rv[i], rv[i + 1], rv[i + 2], rv[i + 3]]);
+        Ok(Limits {
+            max_tx_size: u32_at(0),
+            max_chunked_tx_size: u32_at(4),
+            max_inputs: rv[8],
+            max_commands: rv[9],
+            max_split_amounts: rv[10],
+        })
+    }
+
+    /// Get what the last transaction signed does, as the user reviewed it, for keeping a record
+    /// of what was approved. The app answers with [`Error::Status`] if the last transaction was
+    /// not signed, or if there was none.
+    pub fn get_receipt(&mut self) -> Result<Receipt, Error<T::Error>> {
+        let rv = block::run(&mut self.transport, CLA, Ins::GetReceipt as u8, 0, &[])?;
+        decode_receipt(&rv).map_err(Error::lift)
+    }
+
+    fn run(&mut self, ins: Ins, params: &[&[u8]]) -> Result<Vec<u8>, Error<T::Error>> {
+        block::run(
+            &mut self.transport,
+            CLA,
+            ins as u8,
+            self.derivation.p2(),
+            params,
+        )
+    }
+}
+
+// A label as SAVE_ADDRESS and LABEL_RECIPIENT take it, after its length
+fn encode_label(label: &str) -> Result<Vec<u8>, Error> {
+    let valid = !label.is_empty()
+        && label.len() <= MAX_LABEL_LENGTH
+        && label.bytes().all(|b| b.is_ascii_graphic() || b == b' ');
+    if !valid {
+        return Err(Error::InvalidLabel);
+    }
+    let mut rv = vec![label.len() as u8];
+    rv.extend_from_slice(label.as_bytes());
+    Ok(rv)
+}
+
+/// Decode the response of GET_PUBKEY or VERIFY_ADDRESS.
+pub fn decode_pubkey(rv: &[u8]) -> Result<PublicKey, Error> {
+    if rv.len() != 66 || rv[0] != 32 || rv[33] != 32 {
+        return Err(Error::Protocol("malformed public key response"));
+    }
+    let mut public_key = [0; 32];
+    public_key.copy_from_slice(&rv[1..33]);
+    let mut address = [0; 32];
+    address.copy_from_slice(&rv[34..66]);
+    Ok(PublicKey {
+        public_key,
+        address,
+    })
+}
+
+/// Decode the response of GET_PATTERNS.
+pub fn decode_patterns(rv: &[u8]) -> Result<Patterns, Error> {
+    const MALFORMED: Error = Error::Protocol("malformed patterns response");
+    fn take<'a>(rv: &mut &'a [u8], n: usize) -> Result<&'a [u8], Error> {
+        if rv.len() < n {
+            return Err(MALFORMED);
+        }
+        let (head, tail) = rv.split_at(n);
+        *rv = tail;
+        Ok(head)
+    }
+    fn name(rv: &mut &[u8]) -> Result<String, Error> {
+        let len = take(rv, 1)?[0] as usize;
+        String::from_utf8(take(rv, len)?.to_vec()).map_err(|_| MALFORMED)
+    }
+
+    let mut rv = rv;
+    let header = take(&mut rv, 2)?;
+    let (version, count) = (header[0], header[1]);
+    let mut patterns = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        let kind = PatternKind::try_from(take(&mut rv, 1)?[0])
+            .map_err(|_| Error::Protocol("unknown pattern kind"))?;
+        // Before version 4, a byte before the package said whether there was one
+        let has_package = match version {
+            0..=3 => match take(&mut rv, 1)?[0] {
+                0 => false,
+                1 => true,
+                _ => return Err(MALFORMED),
+            },
+            _ => true,
+        };
+        let package = if has_package {
+            Some(<[u8; 32]>::try_from(take(&mut rv, 32)?).map_err(|_| MALFORMED)?)
+        } else {
+            None
+        };
+        patterns.push(Pattern {
+            kind,
+            package,
+            module: name(&mut rv)?,
+            function: name(&mut rv)?,
+        });
+    }
+    if !rv.is_empty() {
+        return Err(MALFORMED);
+    }
+    Ok(Patterns { version, patterns })
+}
+
+/// Decode the response of GET_RECEIPT.
+pub fn decode_receipt(rv: &[u8]) -> Result<Receipt, Error> {
+    const MALFORMED: Error = Error::Protocol("malformed receipt response");
+    if rv.len() < 42 {
+        return Err(MALFORMED);
+    }
+    let (head, mut rest) = rv.split_at(41);
+    let mut hash = [0; 32];
+    hash.copy_from_slice(&head[..32]);
+    let kind =
+        OperationKind::try_from(head[32]).map_err(|_| Error::Protocol("unknown operation kind"))?;
+    let mut gas_budget = [0; 8];
+    gas_budget.copy_from_slice(&head[33..41]);
+    let recipient = match rest[0] {
+        0 => {
+            rest = &rest[1..];
+            None
+        }
+        1 if rest.len() >= 33 => {
+            let mut recipient = [0; 32];
+            recipient.copy_from_slice(&rest[1..33]);
+            rest = &rest[33..];
+            Some(recipient)
+        }
+        _ => return Err(MALFORMED),
+    };
+    let (&count, amounts) = rest.split_first().ok_or(MALFORMED)?;
+    if amounts.len() != 8 * count as usize {
+        return Err(MALFORMED);
+    }
+    let amounts = amounts
+        .chunks(8)
+        .map(|amount| u64::from_le_bytes(amount.try_into().unwrap()))
+        .collect();
+    Ok(Receipt {
+        hash,
+        kind,
+        gas_budget: (kind != OperationKind::BlindSigned).then(|| u64::from_le_bytes(gas_budget)),
+        recipient,
+        amounts,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sha2::Digest;
+    use std::collections::VecDeque;
+
+    // Answers with canned responses, and keeps the APDUs it was sent
+    #[derive(Default)]
+    struct MockTransport {
+        sent: Vec<Vec<u8>>,
+        responses: VecDeque<Vec<u8>>,
+    }
+
+    impl Transport for MockTransport {
+        type Error = ();
+
+        fn exchange(&mut self, apdu: &[u8]) -> Result<Vec<u8>, ()> {
+            self.sent.push(apdu.to_vec());
+            self.responses.pop_front().ok_or(())
+        }
+    }
+
+    fn ok(data: &[u8]) -> Vec<u8> {
+        let mut rv = data.to_vec();
+        rv.extend_from_slice(&[0x90, 0x00]);
+        rv
+    }
+
+    #[test]
+    fn gets_the_version() {
+        let mut transport = MockTransport::default();
+        transport.responses.push_back(ok(b"\x01\x00\x02\x03iota"));
+        let mut client = Client::new(transport);
+        let version = client.get_version().unwrap();
+        assert_eq!(
+            version,
+            Version {
+                major: 0,
+                minor: 2,
+                patch: 3,
+                name: "iota".to_string()
+            }
+        );
+        assert_eq!(client.into_transport().sent, vec![vec![0, 0, 0, 0, 1, 0]]);
+    }
+
+    #[test]
+    fn answers_chunk_requests() {
+        let path = Bip32Path::iota(0, 0);
+        let block = block::blocks(&path.encode()).remove(0);
+        let hash: [u8; 32] = sha2::Sha256::digest(&block).into();
+
+        let mut response = vec![32];
+        response.extend_from_slice(&[1; 32]);
+        response.push(32);
+        response.extend_from_slice(&[2; 32]);
+
+        let mut transport = MockTransport::default();
+        let mut get_chunk = vec![2];
+        get_chunk.extend_from_slice(&hash);
+        transport.responses.push_back(ok(&get_chunk));
+        transport
+            .responses
+            .push_back(ok(&[&[1][..], &response].concat()));
+        let mut client = Client::new(transport).with_derivation(Derivation::Bip32Ed25519);
+
+        let rv = client.get_pubkey(&path).unwrap();
+        assert_eq!(rv.public_key, [1; 32]);
+        assert_eq!(rv.address, [2; 32]);
+
+        let sent = client.into_transport().sent;
+        assert_eq!(sent[0][..5], [0, 2, 0, 1, 33]);
+        assert_eq!(sent[0][5..], [&[0][..], &hash].concat()[..]);
+        assert_eq!(sent[1][5..], [&[1][..], &block].concat()[..]);
+    }
+
+    #[test]
+    fn runs_the_self_test() {
+        let mut transport = MockTransport::default();
+        // RESULT_FINAL, then the results
+        transport.responses.push_back(ok(&[1, 1, 1, 1]));
+        transport.responses.push_back(ok(&[1, 1, 0, 1]));
+        transport.responses.push_back(ok(&[1, 1, 1]));
+        let mut client = Client::new(transport);
+        assert!(client.self_test().unwrap().passed());
+        let rv = client.self_test().unwrap();
+        assert!(!rv.ed25519 && !rv.passed());
+        assert!(matches!(client.self_test(), Err(Error::Protocol(_))));
+        assert_eq!(client.into_transport().sent[0], vec![0, 6, 0, 0, 1, 0]);
+    }
+
+    #[test]
+    fn returns_status_words() {
+        let mut transport = MockTransport::default();
+        transport.responses.push_back(vec![0x69, 0x85]);
+        let mut client = Client::new(transport);
+        let rv = client.sign_transaction(&Bip32Path::iota(0, 0), &[0; 8]);
+        assert_eq!(rv, Err(Error::Status(0x6985)));
+    }
+
+    #[test]
+    fn rejects_invalid_labels() {
+        let mut client = Client::new(MockTransport::default());
+        for label in ["", "these are seventeen", "tab\there"] {
+            let rv = client.save_address(&Bip32Path::iota(0, 0), label);
+            assert_eq!(rv, Err(Error::InvalidLabel));
+            let rv = client.label_recipient(&[0x4f; 32], label);
+            assert_eq!(rv, Err(Error::InvalidLabel));
+        }
+    }
+
+    #[test]
+    fn labels_recipients_without_a_derivation() {
+        let mut transport = MockTransport::default();
+        transport.responses.push_back(ok(&[1]));
+        let mut client = Client::new(transport).with_derivation(Derivation::Bip32Ed25519);
+        client.label_recipient(&[0x4f; 32], "Exchange").unwrap();
+        let sent = client.into_transport().sent;
+        // START with the hashes of both parameters
+        assert_eq!(sent[0][..5], [0, 7, 0, 0, 65]);
+    }
+
+    #[test]
+    fn lists_patterns_across_results() {
+        let mut stake = b"\x08\x01".to_vec();
+        stake.extend_from_slice(&[3; 32]);
+        stake.extend_from_slice(b"\x0biota_system\x11request_add_stake");
+
+        let mut transport = MockTransport::default();
+        // RESULT_ACCUMULATING, then RESULT_FINAL
+        transport.responses.push_back(ok(
+            &[&[0, 1, 2][..], b"\x01\x00\x07lending\x07deposit"].concat()
+        ));
+        transport
+            .responses
+            .push_back(ok(&[&[1][..], &stake].concat()));
+        let mut client = Client::new(transport);
+        let rv = client.get_patterns().unwrap();
+        assert_eq!(rv.version, 1);
+        assert_eq!(
+            rv.patterns,
+            vec![
+                Pattern {
+                    kind: PatternKind::LendingDeposit,
+                    package: None,
+                    module: "lending".to_string(),
+                    function: "deposit".to_string(),
+                },
+                Pattern {
+                    kind: PatternKind::Stake,
+                    package: Some([3; 32]),
+                    module: "iota_system".to_string(),
+                    function: "request_add_stake".to_string(),
+                },
+            ]
+        );
+        let sent = client.into_transport().sent;
+        assert_eq!(sent[0], vec![0, 8, 0, 0, 1, 0]);
+        assert_eq!(sent[1], vec![0, 8, 0, 0, 1, 4]);
+        assert!(decode_patterns(&[1, 1]).is_err());
+        assert!(decode_patterns(&[1, 1, 0x0c, 0]).is_err());
+    }
+
+    #[test]
+    fn lists_packages_without_a_flag_since_version_4() {
+        let mut stake = b"\x08".to_vec();
+        stake.extend_from_slice(&[3; 32]);
+        stake.extend_from_slice(b"\x0biota_system\x11request_add_stake");
+        let rv = [&[4, 1][..], &stake].concat();
+        assert_eq!(
+            decode_patterns(&rv).unwrap(),
+            Patterns {
+                version: 4,
+                patterns: vec![Pattern {
+                    kind: PatternKind::Stake,
+                    package: Some([3; 32]),
+                    module: "iota_system".to_string(),
+                    function: "request_add_stake".to_string(),
+                }],
+            }
+        );
+        // The flag of older versions is read as the first byte of the package
+        let rv = [&[4, 1][..], b"\x08\x01", &stake[1..]].concat();
+        assert!(decode_patterns(&rv).is_err());
+    }
+
+    #[test]
+    fn gets_the_limits() {
+        let mut transport = MockTransport::default();
+        // RESULT_FINAL, then the limits
+        transport
+            .responses
+            .push_back(ok(&[1, 0, 0, 2, 0, 0, 0x10, 0, 0, 32, 8, 8]));
+        transport.responses.push_back(ok(&[1, 0, 0, 2, 0]));
+        let mut client = Client::new(transport);
+        assert_eq!(
+            client.get_limits().unwrap(),
+            Limits {
+                max_tx_size: 128 * 1024,
+                max_chunked_tx_size: 4096,
+                max_inputs: 32,
+                max_commands: 8,
+                max_split_amounts: 8,
+            }
+        );
+        assert!(matches!(client.get_limits(), Err(Error::Protocol(_))));
+        assert_eq!(client.into_transport().sent[0], vec![0, 9, 0, 0, 1, 0]);
+    }
+
+    #[test]
+    fn gets_the_receipt() {
+        let mut transfer = vec![1];
+        transfer.extend_from_slice(&[0xd7; 32]);
+        transfer.push(1);
+        transfer.extend_from_slice(&2_000_000u64.to_le_bytes());
+        transfer.push(1);
+        transfer.extend_from_slice(&[0x4f; 32]);
+        transfer.push(1);
+        transfer.extend_from_slice(&1_000_000u64.to_le_bytes());
+        let mut blind = vec![1];
+        blind.extend_from_slice(&[0xd8; 32]);
+        blind.extend_from_slice(&[0; 11]);
+
+        let mut transport = MockTransport::default();
+        transport.responses.push_back(ok(&transfer));
+        transport.responses.push_back(ok(&blind));
+        transport.responses.push_back(vec![0x69, 0x82]);
+        let mut client = Client::new(transport);
+        assert_eq!(
+            client.get_receipt().unwrap(),
+            Receipt {
+                hash: [0xd7; 32],
+                kind: OperationKind::Transfer,
+                gas_budget: Some(2_000_000),
+                recipient: Some([0x4f; 32]),
+                amounts: vec![1_000_000],
+            }
+        );
+        let rv = client.get_receipt().unwrap();
+        assert_eq!(rv.kind, OperationKind::BlindSigned);
+        assert_eq!((rv.gas_budget, rv.recipient), (None, None));
+        assert_eq!(client.get_receipt(), Err(Error::Status(0x6982)));
+        assert_eq!(client.into_transport().sent[0], vec![0, 10, 0, 0, 1, 0]);
+        assert!(decode_receipt(&transfer[1..transfer.len() - 1]).is_err());
+    }
+}
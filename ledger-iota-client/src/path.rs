@@ -0,0 +1,114 @@
+use crate::Error;
+use core::fmt;
+use core::str::FromStr;
+
+pub use ledger_iota_interface::HARDENED;
+
+// The most components the app accepts in a path
+pub const MAX_PATH_LENGTH: usize = 10;
+
+/// A BIP32 derivation path, like `44'/4218'/0'/0'/0'`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Bip32Path(Vec<u32>);
+
+impl Bip32Path {
+    pub fn new(components: Vec<u32>) -> Result<Bip32Path, Error> {
+        if components.is_empty() || components.len() > MAX_PATH_LENGTH {
+            return Err(Error::InvalidPath);
+        }
+        Ok(Bip32Path(components))
+    }
+
+    /// The path of the given account and address index on mainnet, as IOTA wallets derive them.
+    pub fn iota(account: u32, index: u32) -> Bip32Path {
+        Bip32Path(vec![
+            44 | HARDENED,
+            4218 | HARDENED,
+            account | HARDENED,
+            HARDENED,
+            index | HARDENED,
+        ])
+    }
+
+    pub fn components(&self) -> &[u32] {
+        &self.0
+    }
+
+    /// The path as the app reads it: the number of components, then each of them little endian.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut rv = Vec::with_capacity(1 + 4 * self.0.len());
+        rv.push(self.0.len() as u8);
+        for component in &self.0 {
+            rv.extend_from_slice(&component.to_le_bytes());
+        }
+        rv
+    }
+}
+
+impl FromStr for Bip32Path {
+    type Err = Error;
+
+    /// Parse a path like `m/44'/4218'/0'/0'/0'`, where the leading `m/` is optional.
+    fn from_str(s: &str) -> Result<Bip32Path, Error> {
+        let s = s.strip_prefix("m/").unwrap_or(s);
+        let components = s
+            .split('/')
+            .map(|c| {
+                let (number, hardened) = match c.strip_suffix('\'') {
+                    Some(number) => (number, HARDENED),
+                    None => (c, 0),
+                };
+                match number.parse::<u32>() {
+                    Ok(n) if n & HARDENED == 0 => Ok(n | hardened),
+                    _ => Err(Error::InvalidPath),
+                }
+            })
+            .collect::<Result<Vec<u32>, Error>>()?;
+        Bip32Path::new(components)
+    }
+}
+
+impl fmt::Display for Bip32Path {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "m")?;
+        for component in &self.0 {
+            if component & HARDENED != 0 {
+                write!(f, "/{}'", component & !HARDENED)?;
+            } else {
+                write!(f, "/{component}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_encodes_a_path() {
+        let path: Bip32Path = "44'/4218'/0'/0'/0'".parse().unwrap();
+        assert_eq!(path, Bip32Path::iota(0, 0));
+        assert_eq!(path.to_string(), "m/44'/4218'/0'/0'/0'");
+        assert_eq!(
+            path.encode()[..9],
+            [5, 0x2c, 0, 0, 0x80, 0x7a, 0x10, 0, 0x80]
+        );
+        assert_eq!(path.encode().len(), 21);
+    }
+
+    #[test]
+    fn rejects_invalid_paths() {
+        for s in [
+            "",
+            "m/",
+            "44'/x",
+            "44''",
+            "2147483648",
+            "0/0/0/0/0/0/0/0/0/0/0",
+        ] {
+            assert_eq!(s.parse::<Bip32Path>(), Err(Error::InvalidPath), "{s}");
+        }
+    }
+}
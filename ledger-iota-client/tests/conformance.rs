@@ -0,0 +1,426 @@
+//! Checks the app running in speculos against the APDU contract of `ledger_iota_interface::spec`.
+//!
+//! The cases are generated from the contract: every instruction with valid parameters and each of
+//! its derivations, with every P1 and P2 it does not accept, with a wrong CLA, and with each field
+//! of its parameters malformed, then an APDU continuing a command with a P2, and every INS which
+//! is not an instruction. Anything the contract does not say the app does, like a status word
+//! missing from it, a response of another layout, or accepting what it should reject, is reported
+//! at the end.
+//!
+//! Skipped unless SPECULOS_APDU names the APDU port of speculos, as in
+//! `SPECULOS_APDU=127.0.0.1:9999 cargo test --test conformance`. Prompts are approved through the
+//! automation of the REST API at SPECULOS_API, 127.0.0.1:5005 by default. QUIT_APP is left out, as
+//! the app is gone after it.
+
+use ledger_iota_client::block::{self, blocks};
+use ledger_iota_client::{Bip32Path, Error, Transport};
+use ledger_iota_interface::spec::{
+    self, Field, Framing, Instruction, Layout, CHUNKED_P1, CHUNKED_RESUME_RESPONSE, CLA, SW_OK,
+};
+use ledger_iota_interface::{Ins, P1_FIRST, P1_LAST, P1_NEXT, P1_RESUME, P2_BIP32_ED25519};
+use sha2::{Digest, Sha256};
+use std::convert::TryInto;
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+
+// The APDU port of speculos frames each APDU with its length, and each response with the length
+// of its data, which the status word follows
+struct Speculos(TcpStream);
+
+impl Transport for Speculos {
+    type Error = io::Error;
+
+    fn exchange(&mut self, apdu: &[u8]) -> io::Result<Vec<u8>> {
+        self.0.write_all(&(apdu.len() as u32).to_be_bytes())?;
+        self.0.write_all(apdu)?;
+        let mut len = [0; 4];
+        self.0.read_exact(&mut len)?;
+        let mut rv = vec![0; u32::from_be_bytes(len) as usize + 2];
+        self.0.read_exact(&mut rv)?;
+        Ok(rv)
+    }
+}
+
+impl Speculos {
+    // One APDU, returning its data and status word
+    fn send(&mut self, cla: u8, ins: u8, p1: u8, p2: u8, data: &[u8]) -> (Vec<u8>, u16) {
+        let mut apdu = vec![cla, ins, p1, p2, data.len() as u8];
+        apdu.extend_from_slice(data);
+        let mut rv = self.exchange(&apdu).expect("speculos went away");
+        let sw = rv.split_off(rv.len() - 2);
+        (rv, u16::from_be_bytes([sw[0], sw[1]]))
+    }
+}
+
+// What every rule leaves alone: the menus, the busy screens, and the progress while parsing
+const IGNORED_SCREENS: &[&str] = &[
+    "",
+    "Cancel",
+    "Working...",
+    "Quit",
+    "Version",
+    "IOTA",
+    "lOTA",
+    "Settings",
+    "About",
+    "Getting version",
+    "Verifying address",
+    "Exporting pubkey",
+    "Signing",
+    "Saving address",
+];
+
+// Approves everything: both buttons on Confirm, the right one on anything else
+fn accept_rules() -> String {
+    let press = |buttons: &[u8]| {
+        let presses: Vec<String> = buttons
+            .iter()
+            .map(|b| format!("[\"button\", {b}, true]"))
+            .chain(
+                buttons
+                    .iter()
+                    .rev()
+                    .map(|b| format!("[\"button\", {b}, false]")),
+            )
+            .collect();
+        presses.join(", ")
+    };
+    let mut rules: Vec<String> = IGNORED_SCREENS
+        .iter()
+        .map(|text| format!("{{\"text\": \"{text}\", \"actions\": []}}"))
+        .collect();
+    rules.push(r#"{"regexp": "^Parsing", "actions": []}"#.to_string());
+    rules.push(r#"{"regexp": "^\\d+\\.\\d+\\.\\d+$", "actions": []}"#.to_string());
+    for text in ["Confirm", " Confirm"] {
+        rules.push(format!(
+            "{{\"text\": \"{text}\", \"actions\": [{}]}}",
+            press(&[1, 2])
+        ));
+    }
+    rules.push(format!("{{\"actions\": [{}]}}", press(&[2])));
+    format!("{{\"version\": 1, \"rules\": [{}]}}", rules.join(", "))
+}
+
+fn set_automation(rules: &str) {
+    let api = std::env::var("SPECULOS_API").unwrap_or_else(|_| "127.0.0.1:5005".to_string());
+    let mut stream = TcpStream::connect(&api).expect("cannot reach the API of speculos");
+    write!(
+        stream,
+        "POST /automation HTTP/1.1\r\nHost: {api}\r\nContent-Type: application/json\r\n\
+         Content-Length: {}\r\nConnection: close\r\n\r\n{rules}",
+        rules.len()
+    )
+    .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    assert!(
+        response.starts_with("HTTP/1.0 200") || response.starts_with("HTTP/1.1 200"),
+        "speculos did not take the automation rules: {response}"
+    );
+}
+
+fn tx() -> Vec<u8> {
+    iota_tx_fixtures::transfer([0x6f; 32], [0x4f; 32], 1_000_000).intent_message()
+}
+
+// A valid value of a field, as the parameters of a command have it
+fn valid(layout: Layout) -> Vec<u8> {
+    match layout {
+        Layout::Bip32Path => Bip32Path::iota(0, 0).encode(),
+        Layout::SizePrefixed => {
+            let tx = tx();
+            [&(tx.len() as u32).to_le_bytes()[..], &tx[..]].concat()
+        }
+        Layout::Label => [&[11][..], &b"conformance"[..]].concat(),
+        Layout::U8 => vec![0],
+        Layout::U32Le => vec![0; 4],
+        Layout::Fixed(n) => vec![0; n],
+        Layout::LengthPrefixed(n) => [vec![n as u8], vec![0; n]].concat(),
+        Layout::Rest => vec![],
+    }
+}
+
+// Values of a field which the app has to reject
+fn malformed(layout: Layout) -> Vec<(&'static str, Vec<u8>)> {
+    match layout {
+        Layout::Bip32Path => vec![
+            ("an empty path", vec![0]),
+            (
+                "a path of 11 components",
+                [vec![11], vec![0x80; 44]].concat(),
+            ),
+            ("a truncated path", vec![5, 0x2c, 0, 0, 0x80]),
+        ],
+        Layout::SizePrefixed => {
+            let tx = tx();
+            vec![
+                (
+                    "a size past the end",
+                    [&(tx.len() as u32 + 1).to_le_bytes()[..], &tx[..]].concat(),
+                ),
+                ("a truncated size", vec![1, 0]),
+            ]
+        }
+        Layout::Label => vec![
+            ("a label of 17 bytes", [&[17][..], &[b'a'; 17][..]].concat()),
+            ("a label with a tab", [&[3][..], &b"a\tb"[..]].concat()),
+            ("a truncated label", vec![4, b'a']),
+        ],
+        _ => vec![],
+    }
+}
+
+fn sha256(data: &[u8]) -> [u8; 32] {
+    Sha256::digest(data).into()
+}
+
+// What the host answers a request for a block with, see docs/block-protocol.md
+const GET_CHUNK_RESPONSE_SUCCESS: u8 = 1;
+
+// The first APDU of the block protocol for some parameters
+fn start(params: &[Vec<u8>]) -> Vec<u8> {
+    let mut rv = vec![0];
+    for param in params {
+        rv.extend_from_slice(&sha256(&blocks(param)[0]));
+    }
+    rv
+}
+
+struct Harness {
+    speculos: Speculos,
+    findings: Vec<String>,
+}
+
+impl Harness {
+    fn flag(&mut self, what: String) {
+        eprintln!("undocumented: {what}");
+        self.findings.push(what);
+    }
+
+    // Every failure has to be with a status word of the contract
+    fn expect_rejected(&mut self, what: &str, sw: u16) {
+        if sw == SW_OK {
+            self.flag(format!("{what} is accepted"));
+        } else if !spec::is_documented(sw) {
+            self.flag(format!("{what} is rejected with 0x{sw:04x}"));
+        }
+    }
+
+    fn expect_layout(&mut self, what: &str, fields: &[Field], data: &[u8]) {
+        if !spec::matches(fields, data) {
+            self.flag(format!("{what} answers with {data:02x?}"));
+        }
+    }
+
+    fn params(instruction: &Instruction) -> Vec<Vec<u8>> {
+        instruction
+            .params
+            .iter()
+            .map(|fields| fields.iter().flat_map(|f| valid(f.layout)).collect())
+            .collect()
+    }
+
+    // The data of the APDU which starts the command
+    fn first_apdu(instruction: &Instruction, params: &[Vec<u8>]) -> Vec<u8> {
+        match instruction.framing {
+            Framing::Chunked => params[0].clone(),
+            _ => start(params),
+        }
+    }
+
+    // Runs a command the app has to carry out, approving its prompts, and returns its result
+    fn run(
+        &mut self,
+        instruction: &Instruction,
+        p2: u8,
+        params: &[Vec<u8>],
+    ) -> Result<Vec<u8>, u16> {
+        if instruction.prompts {
+            set_automation(&accept_rules());
+        }
+        let rv = match instruction.framing {
+            Framing::Block => {
+                let params: Vec<&[u8]> = params.iter().map(|p| p.as_slice()).collect();
+                match block::run(&mut self.speculos, CLA, instruction.ins as u8, p2, &params) {
+                    Ok(rv) => Ok(rv),
+                    Err(Error::Status(sw)) => Err(sw),
+                    Err(e) => panic!("{}: {e}", instruction.name),
+                }
+            }
+            Framing::Chunked => self.run_chunked(instruction, p2, &params[0]),
+            Framing::None => unreachable!(),
+        };
+        if instruction.prompts {
+            set_automation(r#"{"version": 1, "rules": []}"#);
+        }
+        rv
+    }
+
+    // The path, then the transaction in two halves, asking how much was received in between
+    fn run_chunked(
+        &mut self,
+        instruction: &Instruction,
+        p2: u8,
+        path: &[u8],
+    ) -> Result<Vec<u8>, u16> {
+        let ins = instruction.ins as u8;
+        let tx = tx();
+        let (head, tail) = tx.split_at(tx.len() / 2);
+        for (p1, p2, data) in [(P1_FIRST, p2, path), (P1_NEXT, 0, head)] {
+            match self.speculos.send(CLA, ins, p1, p2, data) {
+                (_, SW_OK) => {}
+                (_, sw) => return Err(sw),
+            }
+        }
+        let (received, sw) = self.speculos.send(CLA, ins, P1_RESUME, 0, &[]);
+        if sw != SW_OK {
+            return Err(sw);
+        }
+        self.expect_layout(
+            "P1_RESUME of SIGN_TX_CHUNKED",
+            CHUNKED_RESUME_RESPONSE,
+            &received,
+        );
+        if received.get(..4) != Some(&(head.len() as u32).to_le_bytes()[..]) {
+            self.flag(format!(
+                "P1_RESUME of SIGN_TX_CHUNKED counts {received:02x?} bytes"
+            ));
+        }
+        match self.speculos.send(CLA, ins, P1_LAST, 0, tail) {
+            (rv, SW_OK) => Ok(rv),
+            (_, sw) => Err(sw),
+        }
+    }
+
+    fn check_instruction(&mut self, instruction: &Instruction) {
+        let name = instruction.name;
+        let ins = instruction.ins as u8;
+        let params = Self::params(instruction);
+
+        for &p2 in instruction.p2 {
+            match self.run(instruction, p2, &params) {
+                Ok(rv) => {
+                    self.expect_layout(&format!("{name} with P2 {p2}"), instruction.response, &rv)
+                }
+                Err(sw) => self.flag(format!("{name} with P2 {p2} fails with 0x{sw:04x}")),
+            }
+        }
+
+        let first = Self::first_apdu(instruction, &params);
+        let (p1, p2) = (instruction.p1[0], instruction.p2[0]);
+        for bad_p1 in 0..=u8::MAX {
+            let other_apdu =
+                instruction.framing == Framing::Chunked && CHUNKED_P1.contains(&bad_p1);
+            if !instruction.p1.contains(&bad_p1) && !other_apdu {
+                let (_, sw) = self.speculos.send(CLA, ins, bad_p1, p2, &first);
+                self.expect_rejected(&format!("{name} with P1 {bad_p1}"), sw);
+            }
+        }
+        for bad_p2 in 0..=u8::MAX {
+            if !instruction.p2.contains(&bad_p2) {
+                let (_, sw) = self.speculos.send(CLA, ins, p1, bad_p2, &first);
+                self.expect_rejected(&format!("{name} with P2 {bad_p2}"), sw);
+            }
+        }
+        for bad_cla in [0x01, 0xe0, 0xff] {
+            let (_, sw) = self.speculos.send(bad_cla, ins, p1, p2, &first);
+            self.expect_rejected(&format!("{name} with CLA {bad_cla:#04x}"), sw);
+        }
+
+        for (i, fields) in instruction.params.iter().enumerate() {
+            for (j, field) in fields.iter().enumerate() {
+                for (what, value) in malformed(field.layout) {
+                    let mut params = params.clone();
+                    params[i] = fields
+                        .iter()
+                        .enumerate()
+                        .flat_map(|(k, f)| {
+                            if k == j {
+                                value.clone()
+                            } else {
+                                valid(f.layout)
+                            }
+                        })
+                        .collect();
+                    let what = format!("{name} with {what} as {}", field.name);
+                    match self.run(instruction, p2, &params) {
+                        Ok(_) => self.flag(format!("{what} is accepted")),
+                        Err(sw) => self.expect_rejected(&what, sw),
+                    }
+                }
+            }
+        }
+    }
+
+    fn check_block_continuation(&mut self) {
+        let instruction = spec::instruction(Ins::GetPubkey as u8).unwrap();
+        let ins = instruction.ins as u8;
+        let params = Self::params(instruction);
+        let (_, sw) = self.speculos.send(CLA, ins, 0, 0, &start(&params));
+        assert_eq!(sw, SW_OK);
+        // The block asked for, with the P2 which only the first APDU picks the derivation with
+        let block = [&[GET_CHUNK_RESPONSE_SUCCESS][..], &blocks(&params[0])[0][..]].concat();
+        let (_, sw) = self.speculos.send(CLA, ins, 0, P2_BIP32_ED25519, &block);
+        self.expect_rejected("GET_PUBKEY continued with P2 1", sw);
+    }
+
+    fn check_chunked_sequence(&mut self) {
+        let ins = Ins::SignChunked as u8;
+        // Nothing to resume or go on with yet
+        for p1 in [P1_RESUME, P1_NEXT, P1_LAST] {
+            let (_, sw) = self.speculos.send(CLA, ins, p1, 0, &[]);
+            self.expect_rejected(&format!("SIGN_TX_CHUNKED with P1 {p1} and no sequence"), sw);
+        }
+        // Only the first APDU picks the derivation
+        let path = Bip32Path::iota(0, 0).encode();
+        let (_, sw) = self.speculos.send(CLA, ins, P1_FIRST, 0, &path);
+        assert_eq!(sw, SW_OK);
+        for p1 in [P1_NEXT, P1_LAST, P1_RESUME] {
+            let (_, sw) = self.speculos.send(CLA, ins, p1, 1, &[]);
+            self.expect_rejected(&format!("SIGN_TX_CHUNKED with P1 {p1} and P2 1"), sw);
+        }
+    }
+}
+
+#[test]
+fn app_conforms_to_the_apdu_contract() {
+    let address = match std::env::var("SPECULOS_APDU") {
+        Ok(address) => address,
+        Err(_) => {
+            eprintln!("SPECULOS_APDU is not set, skipping");
+            return;
+        }
+    };
+    let mut harness = Harness {
+        speculos: Speculos(TcpStream::connect(address).expect("cannot reach speculos")),
+        findings: Vec::new(),
+    };
+
+    for instruction in spec::INSTRUCTIONS {
+        if instruction.framing != Framing::None {
+            harness.check_instruction(instruction);
+        }
+    }
+    harness.check_block_continuation();
+    harness.check_chunked_sequence();
+
+    for ins in 0..=u8::MAX {
+        if spec::instruction(ins).is_none() {
+            let (_, sw) = harness.speculos.send(CLA, ins, 0, 0, &start(&[]));
+            harness.expect_rejected(&format!("INS {ins:#04x}"), sw);
+        }
+    }
+
+    let version = harness
+        .run(spec::instruction(0).unwrap(), 0, &[])
+        .expect("GET_VERSION failed");
+    let [major, minor, patch]: [u8; 3] = version[..3].try_into().unwrap();
+    eprintln!("checked version {major}.{minor}.{patch} of the app");
+
+    assert!(
+        harness.findings.is_empty(),
+        "the app does {} things the contract does not say:\n{}",
+        harness.findings.len(),
+        harness.findings.join("\n")
+    );
+}
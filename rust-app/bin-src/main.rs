@@ -4,7 +4,10 @@
 #[cfg(not(target_family = "bolos"))]
 fn main() {}
 
+#[cfg(not(any(target_os = "stax", target_os = "flex")))]
 use iota_rebased::main_nanos::app_main;
+#[cfg(any(target_os = "stax", target_os = "flex"))]
+use iota_rebased::main_stax::app_main;
 
 ledger_device_sdk::set_panic!(ledger_device_sdk::exiting_panic);
 
@@ -1,3 +1,32 @@
+use std::process::Command;
+
 fn main() {
     println!("cargo:rerun-if-changed=script.ld");
+
+    // Both are shown in the About menu. Builds from a source tarball have no git repository to ask.
+    let git_hash = Command::new("git")
+        .args(["rev-parse", "--short=8", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|hash| hash.trim().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=GIT_HASH={git_hash}");
+    println!("cargo:rerun-if-changed=../.git/HEAD");
+
+    let sdk_version = std::fs::read_to_string("Cargo.lock")
+        .ok()
+        .and_then(|lock| {
+            let mut lines = lock.lines();
+            lines.find(|line| *line == "name = \"ledger_device_sdk\"")?;
+            let version = lines
+                .next()?
+                .strip_prefix("version = \"")?
+                .strip_suffix('"')?;
+            Some(version.to_string())
+        })
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=LEDGER_SDK_VERSION={sdk_version}");
+    println!("cargo:rerun-if-changed=Cargo.lock");
 }
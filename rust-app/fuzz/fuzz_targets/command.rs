@@ -0,0 +1,16 @@
+#![no_main]
+
+use iota_rebased::interface::CommandSchema;
+use iota_rebased::parser::IotaInterp;
+use iota_rebased_fuzz::{run, MemStream};
+use ledger_parser_combinators::async_parser::AsyncParser;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    // The parse is run to the end below, while data is still there
+    let mut input = unsafe { MemStream::new(data) };
+    let _ = run(<IotaInterp as AsyncParser<CommandSchema, _>>::parse(
+        &IotaInterp,
+        &mut input,
+    ));
+});
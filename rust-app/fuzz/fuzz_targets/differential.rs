@@ -0,0 +1,343 @@
+#![no_main]
+
+// Differential fuzzing of the parser against the BCS deserializer of the official IOTA types, see
+// CONTRIBUTING.md. Whatever the parser recognizes, and so clear signs, has to be a transaction the
+// reference deserializes as well, and the review has to show what the reference reads in it.
+//
+// The other way around, the parser rejects most valid transactions, which are then blind signed.
+// Only a valid transaction which it refuses to blind sign too, as malformed, is reported; that does
+// not fail the run, as nothing gets signed which should not be.
+
+use core::convert::TryInto;
+use iota_rebased::interface::{AppError, IntentMessage};
+use iota_rebased::parser::{take_rejection, tx_parser, Operation, TxSummary};
+use iota_rebased_fuzz::{run, MemStream};
+use iota_types::transaction::{
+    Argument, CallArg, Command, ObjectArg, ProgrammableTransaction, TransactionData,
+    TransactionDataAPI, TransactionExpiration, TransactionKind,
+};
+use ledger_parser_combinators::async_parser::AsyncParser;
+use libfuzzer_sys::fuzz_target;
+
+type Reference = shared_crypto::intent::IntentMessage<TransactionData>;
+
+// The summary of the parser if it recognized the transaction, and why it rejected it otherwise
+fn parse(data: &[u8]) -> (Option<TxSummary>, Option<AppError>) {
+    // The parse is run to the end here, while data is still there
+    let mut input = unsafe { MemStream::new(data) };
+    let parser = tx_parser::<MemStream>();
+    let summary = run(<_ as AsyncParser<IntentMessage, _>>::parse(
+        &parser, &mut input,
+    ));
+    // SIGN_TX rejects bytes after a recognized transaction, so the reference never sees them
+    (summary.filter(|_| input.remaining() == 0), take_rejection())
+}
+
+fn pure_amount(inputs: &[CallArg], argument: &Argument) -> u64 {
+    let amount: [u8; 8] = pure(inputs, argument)
+        .try_into()
+        .expect("recognized an amount which is not 8 bytes");
+    u64::from_le_bytes(amount)
+}
+
+// The amounts a call is given, which are its pure arguments of 8 bytes
+fn call_amounts(inputs: &[CallArg], arguments: &[Argument]) -> Vec<u64> {
+    arguments
+        .iter()
+        .filter(|argument| match argument {
+            Argument::Input(i) => matches!(
+                &inputs[*i as usize],
+                CallArg::Pure(bytes) if bytes.len() == 8
+            ),
+            _ => false,
+        })
+        .map(|argument| pure_amount(inputs, argument))
+        .collect()
+}
+
+// The amounts of the coins a call takes, each of which the parser only recognizes as split off for
+// the call alone
+fn coin_amounts(pt: &ProgrammableTransaction, arguments: &[Argument]) -> Vec<u64> {
+    arguments
+        .iter()
+        .filter_map(|argument| match argument {
+            Argument::Result(i) | Argument::NestedResult(i, 0) => match &pt.commands[*i as usize] {
+                Command::SplitCoins(_, amounts) => Some(pure_amount(&pt.inputs, &amounts[0])),
+                command => panic!("recognized a coin of command {:?}", command),
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+// Whether an object is the gas coin or a coin split off it, which are the objects known to be IOTA
+fn is_iota_coin(pt: &ProgrammableTransaction, object: &Argument) -> bool {
+    match object {
+        Argument::GasCoin => true,
+        Argument::Result(i) | Argument::NestedResult(i, _) => matches!(
+            &pt.commands[*i as usize],
+            Command::SplitCoins(Argument::GasCoin, _)
+        ),
+        _ => false,
+    }
+}
+
+// The bytes of the pure input an argument refers to
+fn pure<'a>(inputs: &'a [CallArg], argument: &Argument) -> &'a [u8] {
+    match argument {
+        Argument::Input(i) => match &inputs[*i as usize] {
+            CallArg::Pure(bytes) => bytes,
+            input => panic!(
+                "recognized an argument of input {:?}, which is not pure",
+                input
+            ),
+        },
+        argument => panic!("recognized argument {:?}, which is not an input", argument),
+    }
+}
+
+fn check_summary(summary: &TxSummary, reference: &Reference) {
+    let tx = &reference.value;
+    assert_eq!(summary.sender, tx.sender().to_inner(), "sender");
+
+    let gas = tx.gas_data();
+    assert_eq!(summary.gas.price, gas.price, "gas price");
+    assert_eq!(summary.gas.budget, gas.budget, "gas budget");
+    assert_eq!(summary.gas.object_count, gas.payment.len(), "gas objects");
+    if let Some((id, _, _)) = gas.payment.first() {
+        assert_eq!(summary.gas.object, id.into_bytes(), "gas object");
+    }
+
+    let expiration = match tx.expiration() {
+        TransactionExpiration::None => None,
+        TransactionExpiration::Epoch(epoch) => Some(*epoch),
+    };
+    assert_eq!(summary.expiration, expiration, "expiration");
+
+    let pt = match tx.kind() {
+        TransactionKind::ProgrammableTransaction(pt) => pt,
+        kind => panic!("recognized transaction kind {:?}", kind),
+    };
+    // The objects of the sender, in the exact state the review shows
+    let objects: Vec<_> = pt
+        .inputs
+        .iter()
+        .filter_map(|input| match input {
+            CallArg::Object(ObjectArg::ImmOrOwnedObject((id, version, digest))) => {
+                Some((id.into_bytes(), version.value(), *digest.inner()))
+            }
+            _ => None,
+        })
+        .collect();
+    assert_eq!(summary.objects.count, objects.len(), "objects");
+    for (object, (id, version, digest)) in summary.objects.first.iter().zip(&objects) {
+        assert_eq!(object.id, *id, "object");
+        assert_eq!(object.version, *version, "version of the object");
+        assert_eq!(object.digest, *digest, "digest of the object");
+    }
+
+    let mut total_amount: u128 = 0;
+    let mut amount_count = 0;
+    let mut recipients = Vec::new();
+    let mut transferred = Vec::new();
+    let mut gas_split_amount: Option<u64> = None;
+    let mut calls = Vec::new();
+    for command in &pt.commands {
+        match command {
+            Command::SplitCoins(coin, amounts) => {
+                for amount in amounts {
+                    let amount = pure_amount(&pt.inputs, amount);
+                    total_amount += amount as u128;
+                    amount_count += 1;
+                    if *coin == Argument::GasCoin {
+                        gas_split_amount = Some(gas_split_amount.unwrap_or(0) + amount);
+                    }
+                }
+            }
+            Command::TransferObjects(objects, recipient) => {
+                recipients.push(pure(&pt.inputs, recipient));
+                transferred.extend(objects);
+            }
+            Command::MoveCall(call) => calls.push(call),
+            command => panic!("recognized command {:?}", command),
+        }
+    }
+    assert_eq!(summary.commands.len(), pt.commands.len(), "commands");
+    match (&summary.call, &calls[..]) {
+        (None, []) => {}
+        (Some(name), [call]) => {
+            assert_eq!(
+                name.package,
+                call.package.into_bytes(),
+                "package of the call"
+            );
+            assert_eq!(name.module.as_str(), call.module.as_str(), "module");
+            assert_eq!(name.function.as_str(), call.function.as_str(), "function");
+            let pure_arguments = call
+                .arguments
+                .iter()
+                .filter(|argument| match argument {
+                    Argument::Input(i) => matches!(&pt.inputs[*i as usize], CallArg::Pure(_)),
+                    _ => false,
+                })
+                .count();
+            assert_eq!(name.arguments.len(), pure_arguments, "pure arguments");
+        }
+        (name, calls) => panic!("recognized {} calls, named {}", calls.len(), name.is_some()),
+    }
+
+    // What is minted is sent by the call, and not by a transfer, see below
+    let recipient = match &summary.operation {
+        Operation::Mint(_) => None,
+        operation => operation.recipient().map(|r| &r[..]),
+    };
+    assert_eq!(
+        recipients,
+        recipient.into_iter().collect::<Vec<_>>(),
+        "recipient"
+    );
+    match &summary.operation {
+        Operation::Transfer(transfer) => {
+            assert!(calls.is_empty(), "transfer with a call");
+            assert_eq!(transfer.total_amount as u128, total_amount, "total amount");
+            assert_eq!(transfer.amount_count, amount_count, "amounts");
+        }
+        Operation::ObjectTransfer(transfer) => {
+            assert!(calls.is_empty(), "transfer with a call");
+            assert_eq!(transfer.object_count, transferred.len(), "objects sent");
+            let iota_coin_count = transferred
+                .iter()
+                .filter(|object| is_iota_coin(pt, object))
+                .count();
+            assert_eq!(transfer.iota_coin_count, iota_coin_count, "IOTA coins sent");
+            // None once the gas coin itself is sent
+            let split_amount =
+                gas_split_amount.filter(|_| !transferred.contains(&&Argument::GasCoin));
+            assert_eq!(
+                transfer.split_amount, split_amount,
+                "amount split off the gas coin"
+            );
+        }
+        Operation::Lending(lending) => {
+            let [call] = calls[..] else {
+                panic!("lending with {} calls", calls.len());
+            };
+            assert_eq!(lending.package, call.package.into_bytes(), "package");
+            // The coin split off for the call, or the amount passed to it
+            let amount = if amount_count > 0 {
+                total_amount
+            } else {
+                let amounts = call_amounts(&pt.inputs, &call.arguments);
+                assert_eq!(amounts.len(), 1, "amounts of the call");
+                amounts[0] as u128
+            };
+            assert_eq!(lending.amount as u128, amount, "amount");
+        }
+        Operation::Liquidity(liquidity) => {
+            let [call] = calls[..] else {
+                panic!("liquidity with {} calls", calls.len());
+            };
+            assert_eq!(liquidity.package, call.package.into_bytes(), "package");
+            // The coins added, or the least accepted of each
+            let amounts = if amount_count > 0 {
+                coin_amounts(pt, &call.arguments)
+            } else {
+                call_amounts(&pt.inputs, &call.arguments)
+            };
+            assert_eq!(liquidity.amounts[..], amounts[..], "amounts");
+        }
+        Operation::Divide(divide) => {
+            let [call] = calls[..] else {
+                panic!("division with {} calls", calls.len());
+            };
+            assert_eq!(divide.package, call.package.into_bytes(), "package");
+            // The coin split off for the call, into the number of coins it is given
+            assert_eq!(
+                coin_amounts(pt, &call.arguments)[..],
+                [divide.amount],
+                "amount"
+            );
+            assert_eq!(
+                call_amounts(&pt.inputs, &call.arguments)[..],
+                [divide.count],
+                "count"
+            );
+        }
+        Operation::Stake(stake) => {
+            let [call] = calls[..] else {
+                panic!("stake with {} calls", calls.len());
+            };
+            assert_eq!(stake.package, call.package.into_bytes(), "package");
+            // The validator is the last argument, after the system state and the coin
+            let validator = call
+                .arguments
+                .last()
+                .map(|argument| pure(&pt.inputs, argument));
+            assert_eq!(validator, Some(&stake.validator[..]), "validator");
+            // The coin split off for the call
+            assert_eq!(u128::from(stake.amount), total_amount, "amount");
+        }
+        Operation::Mint(mint) => {
+            let [call] = calls[..] else {
+                panic!("mint with {} calls", calls.len());
+            };
+            assert_eq!(mint.package, call.package.into_bytes(), "package");
+            assert_eq!(
+                call_amounts(&pt.inputs, &call.arguments)[..],
+                [mint.amount],
+                "amount"
+            );
+            // The recipient is the last argument, after the treasury cap and the amount
+            let recipient = call
+                .arguments
+                .last()
+                .map(|argument| pure(&pt.inputs, argument));
+            assert_eq!(
+                recipient,
+                Some(&mint.recipient[..]),
+                "recipient of the mint"
+            );
+        }
+        Operation::Unstake(unstake) => {
+            let [call] = calls[..] else {
+                panic!("unstake with {} calls", calls.len());
+            };
+            assert_eq!(unstake.package, call.package.into_bytes(), "package");
+            // The stake is the one object of the sender the call is given, after the system state
+            let staked: Vec<_> = call
+                .arguments
+                .iter()
+                .filter_map(|argument| match argument {
+                    Argument::Input(i) => match &pt.inputs[*i as usize] {
+                        CallArg::Object(ObjectArg::ImmOrOwnedObject((id, _, _))) => {
+                            Some(id.into_bytes())
+                        }
+                        _ => None,
+                    },
+                    _ => None,
+                })
+                .collect();
+            assert_eq!(staked[..], [unstake.staked], "stake withdrawn");
+        }
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    let reference = bcs::from_bytes::<Reference>(data);
+    match (parse(data), reference) {
+        ((Some(summary), _), Ok(reference)) => check_summary(&summary, &reference),
+        ((Some(_), _), Err(e)) => {
+            panic!(
+                "recognized a transaction which the reference rejects: {}",
+                e
+            )
+        }
+        ((None, Some(AppError::BadUleb128)), Ok(_)) => {
+            eprintln!(
+                "rejected as malformed, where the reference deserializes it: {}",
+                data.iter().map(|b| format!("{b:02x}")).collect::<String>()
+            );
+        }
+        _ => {}
+    }
+});
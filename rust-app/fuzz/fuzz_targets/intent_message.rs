@@ -0,0 +1,16 @@
+#![no_main]
+
+use iota_rebased::interface::IntentMessage;
+use iota_rebased::parser::tx_parser;
+use iota_rebased_fuzz::{run, MemStream};
+use ledger_parser_combinators::async_parser::AsyncParser;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    // The parse is run to the end below, while data is still there
+    let mut input = unsafe { MemStream::new(data) };
+    let parser = tx_parser::<MemStream>();
+    let _ = run(<_ as AsyncParser<IntentMessage, _>>::parse(
+        &parser, &mut input,
+    ));
+});
@@ -0,0 +1,55 @@
+#![feature(type_alias_impl_trait)]
+#![feature(impl_trait_in_assoc_type)]
+
+use core::future::Future;
+use core::task::Poll;
+use iota_rebased::futures::poll_once;
+use ledger_parser_combinators::async_parser::{reject, Readable, TryFuture};
+
+/// Readable over the fuzzer input, standing in for the ByteStream of the block protocol.
+///
+/// Reading past the end rejects, just like running out of blocks does on the device.
+#[derive(Clone, Copy)]
+pub struct MemStream(&'static [u8]);
+
+impl MemStream {
+    /// # Safety
+    ///
+    /// The stream must not be used once `data` is gone. Parsers keep no reference to their input
+    /// past the end of the parse, so it is enough to [`run`] the parse before `data` is dropped.
+    pub unsafe fn new(data: &[u8]) -> MemStream {
+        MemStream(core::mem::transmute::<&[u8], &'static [u8]>(data))
+    }
+
+    /// The number of bytes not read yet.
+    pub fn remaining(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl Readable for MemStream {
+    type OutFut<'a, const N: usize> = impl 'a + Future<Output = [u8; N]>;
+    fn read<'a: 'b, 'b, const N: usize>(&'a mut self) -> Self::OutFut<'b, N> {
+        async move {
+            if self.0.len() < N {
+                return reject(0).await;
+            }
+            let (head, tail) = self.0.split_at(N);
+            self.0 = tail;
+            let mut rv = [0; N];
+            rv.copy_from_slice(head);
+            rv
+        }
+    }
+}
+
+/// Run a parse to the end, returning `None` if it was rejected.
+///
+/// The whole input is there already, so a single poll is all it takes, like for SIGN_TX_CHUNKED.
+pub fn run<F: Future>(parse: F) -> Option<F::Output> {
+    iota_rebased::parser::clear_rejection();
+    match poll_once(TryFuture(parse)) {
+        Poll::Ready(Some(rv)) => Some(rv),
+        _ => None,
+    }
+}
@@ -0,0 +1,216 @@
+//! The wire format of the IOTA Ledger app: the schemas of the parameters of its commands and of
+//! the transactions it signs, its instructions, and its status words, see docs/apdu.md.
+//!
+//! The app parses with these schemas, and host tooling can build and check APDUs against the same
+//! definitions. Builds without the device SDK on the host, and without the schemas, which need a
+//! nightly toolchain, with `default-features = false`.
+
+#![no_std]
+#![allow(incomplete_features)]
+#![cfg_attr(feature = "schemas", feature(adt_const_params))]
+
+#[cfg(target_family = "bolos")]
+use core::convert::TryFrom;
+#[cfg(target_family = "bolos")]
+use ledger_device_sdk::io::{ApduHeader, StatusWords, SyscallError};
+use num_enum::TryFromPrimitive;
+
+pub mod spec;
+
+#[cfg(feature = "schemas")]
+mod schemas;
+#[cfg(feature = "schemas")]
+pub use schemas::*;
+
+// Set in the components of a BIP32 path which use hardened derivation
+pub const HARDENED: u32 = 0x8000_0000;
+
+// The longest label of an address in the address book
+pub const MAX_LABEL_LENGTH: usize = 16;
+
+pub const IOTA_ADDRESS_LENGTH: usize = 32;
+
+// Rejects malformed ULEB128 lengths and enum tags, see docs/apdu.md
+pub const SW_BAD_ULEB128: u16 = 0xb001;
+// Rejects transactions with more inputs or commands than can be reviewed, see docs/apdu.md
+pub const SW_TOO_COMPLEX: u16 = 0xb002;
+
+/// Why a transaction was rejected.
+///
+/// Most of these are reported with the same status word for now, but they all say what went wrong
+/// in the logs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AppError {
+    // Well formed, but not a transaction which can be reviewed
+    UnsupportedTransactionData,
+    UnsupportedTransactionKind,
+    UnsupportedCallArg,
+    UnsupportedObjectArg,
+    UnsupportedCommand,
+    UnsupportedArgument,
+    UnsupportedExpiration,
+    UnsupportedTypeArgument,
+    // Not a transfer which can be reviewed
+    NotATransfer,
+    TooManyAddresses,
+    MultipleTransfers,
+    RecipientMismatch,
+    TooManyAmounts,
+    SplitOfNonAmount,
+    TooComplex,
+    // Not a Move call which can be reviewed
+    UnknownCall,
+    MultipleCalls,
+    CallArgumentMismatch,
+    // Malformed
+    AmountOverflow,
+    BadUleb128,
+}
+
+#[cfg(target_family = "bolos")]
+impl AppError {
+    pub const fn status_word(self) -> u16 {
+        match self {
+            AppError::TooComplex => SW_TOO_COMPLEX,
+            AppError::AmountOverflow => SyscallError::InvalidParameter as u16,
+            AppError::BadUleb128 => SW_BAD_ULEB128,
+            _ => SyscallError::NotSupported as u16,
+        }
+    }
+}
+
+// Version of the APDU interface described in docs/apdu.md, bumped when it changes.
+pub const API_VERSION: &str = "1";
+
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, TryFromPrimitive)]
+pub enum Ins {
+    GetVersion = 0,
+    VerifyAddress = 1,
+    GetPubkey = 2,
+    Sign = 3,
+    SignChunked = 4,
+    SaveAddress = 5,
+    SelfTest = 6,
+    LabelRecipient = 7,
+    GetPatterns = 8,
+    GetLimits = 9,
+    GetReceipt = 10,
+    Exit = 0xff,
+}
+
+/// What a Move call the app recognizes is reviewed as, as GET_PATTERNS lists it.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, TryFromPrimitive)]
+pub enum PatternKind {
+    LendingDeposit = 1,
+    LendingWithdraw = 2,
+    LendingBorrow = 3,
+    LendingRepay = 4,
+    AddLiquidity = 5,
+    RemoveLiquidity = 6,
+    Divide = 7,
+    Stake = 8,
+    Unstake = 9,
+    UnstakeTimelocked = 10,
+    Mint = 11,
+}
+
+/// What the last transaction signed did, as GET_RECEIPT reports it.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, TryFromPrimitive)]
+pub enum OperationKind {
+    BlindSigned = 0,
+    Transfer = 1,
+    ObjectTransfer = 2,
+    LendingDeposit = 3,
+    LendingWithdraw = 4,
+    LendingBorrow = 5,
+    LendingRepay = 6,
+    AddLiquidity = 7,
+    RemoveLiquidity = 8,
+    Divide = 9,
+    Stake = 10,
+    Unstake = 11,
+    UnstakeTimelocked = 12,
+    Mint = 13,
+}
+
+// P1 values for Ins::SignChunked
+pub const P1_FIRST: u8 = 0x00;
+pub const P1_NEXT: u8 = 0x01;
+pub const P1_LAST: u8 = 0x02;
+pub const P1_RESUME: u8 = 0x03;
+
+// P2 values for the commands which derive keys, and 0 for the others
+pub const P2_SLIP10: u8 = 0x00;
+pub const P2_BIP32_ED25519: u8 = 0x01;
+
+/// How keys are derived from the seed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Derivation {
+    /// SLIP-0010, as the IOTA software wallets derive keys
+    Slip10,
+    /// The BIP32-Ed25519 scheme of the Ledger SDK, for seeds used with it elsewhere
+    Bip32Ed25519,
+}
+
+impl Derivation {
+    pub fn from_p2(p2: u8) -> Option<Derivation> {
+        match p2 {
+            P2_SLIP10 => Some(Derivation::Slip10),
+            P2_BIP32_ED25519 => Some(Derivation::Bip32Ed25519),
+            _ => None,
+        }
+    }
+
+    pub fn p2(self) -> u8 {
+        match self {
+            Derivation::Slip10 => P2_SLIP10,
+            Derivation::Bip32Ed25519 => P2_BIP32_ED25519,
+        }
+    }
+
+    pub fn is_slip10(self) -> bool {
+        self == Derivation::Slip10
+    }
+}
+
+#[cfg(target_family = "bolos")]
+impl TryFrom<ApduHeader> for Ins {
+    type Error = StatusWords;
+    fn try_from(m: ApduHeader) -> Result<Ins, Self::Error> {
+        match m {
+            ApduHeader {
+                cla: 0,
+                ins: 4,
+                p1: P1_FIRST..=P1_RESUME,
+                p2: 0,
+            }
+            | ApduHeader {
+                cla: 0,
+                ins: 4,
+                p1: P1_FIRST,
+                p2: P2_BIP32_ED25519,
+            } => Ok(Ins::SignChunked),
+            // The commands which derive keys
+            ApduHeader {
+                cla: 0,
+                ins: ins @ (1 | 2 | 3 | 5),
+                p1: 0,
+                p2: P2_SLIP10 | P2_BIP32_ED25519,
+            }
+            | ApduHeader {
+                cla: 0,
+                ins,
+                p1: 0,
+                p2: 0,
+            } => Self::try_from(ins).map_err(|_| StatusWords::BadIns),
+            // A command there is, with a P1 or P2 it does not take
+            ApduHeader { cla: 0, ins, .. } if Self::try_from(ins).is_ok() => {
+                Err(StatusWords::BadP1P2)
+            }
+            _ => Err(StatusWords::BadIns),
+        }
+    }
+}
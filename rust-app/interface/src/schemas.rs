@@ -0,0 +1,81 @@
+// The schemas of the parameters of the commands and of the transactions, which the parsers of the
+// app interpret with ledger-parser-combinators.
+
+use crate::{IOTA_ADDRESS_LENGTH, MAX_LABEL_LENGTH};
+use ledger_parser_combinators::bcs::async_parser::{Vec, ULEB128};
+use ledger_parser_combinators::core_parsers::{Array, Byte, DArray, U16, U32, U64};
+use ledger_parser_combinators::endianness::Endianness;
+
+// Payload for a public key request
+pub type Bip32Key = DArray<Byte, U32<{ Endianness::Little }>, 10>;
+
+// Payload for saving an address to the address book
+pub type AddressLabel = DArray<Byte, Byte, MAX_LABEL_LENGTH>;
+
+pub type SignParameters = (IntentMessage, Bip32Key);
+
+// Iota Types
+pub type IntentMessage = (Intent, TransactionData);
+
+pub struct TransactionData;
+
+pub type TransactionDataV1 = (
+    TransactionKind,
+    IotaAddress,           // sender
+    GasData,               // gas_data
+    TransactionExpiration, // expiration
+);
+
+pub struct TransactionKind;
+
+pub struct ProgrammableTransaction;
+
+pub struct CommandSchema;
+pub struct ArgumentSchema;
+pub struct CallArgSchema;
+
+// ObjectID package, Identifier module, Identifier function, Vec<TypeTag> type_arguments,
+// Vec<Argument> arguments
+pub struct MoveCallSchema;
+// The name of a module, function or struct, as a ULEB128 length and ASCII bytes
+pub struct IdentifierSchema;
+pub struct TypeTagSchema;
+
+// Vec<ObjectRef, { usize::MAX }> payment, IotaAddress owner, Amount price, Amount budget
+pub struct GasData;
+
+pub struct TransactionExpiration;
+pub type EpochId = U64<{ Endianness::Little }>;
+
+pub type ObjectRef = (ObjectID, SequenceNumber, ObjectDigest);
+
+pub type SharedObject = (
+    ObjectID,       // id
+    SequenceNumber, // initial_shared_version
+    bool,           // mutable
+);
+
+pub type AccountAddress = IotaAddress;
+pub type ObjectID = AccountAddress;
+pub type SequenceNumber = U64LE;
+pub type ObjectDigest = SHA3_256_HASH;
+
+pub type IotaAddress = Array<Byte, IOTA_ADDRESS_LENGTH>;
+
+pub type Coins = Vec<ObjectRef, { usize::MAX }>;
+
+pub type Recipient = IotaAddress;
+
+pub type Amount = U64LE;
+
+pub type U64LE = U64<{ Endianness::Little }>;
+pub type U16LE = U16<{ Endianness::Little }>;
+
+pub type Intent = (IntentVersion, IntentScope, AppId);
+pub type IntentVersion = ULEB128;
+pub type IntentScope = ULEB128;
+pub type AppId = ULEB128;
+
+// TODO: confirm if 33 is indeed ok for all uses of SHA3_256_HASH
+#[allow(non_camel_case_types)]
+pub type SHA3_256_HASH = Array<Byte, 33>;
@@ -0,0 +1,362 @@
+//! The APDU contract of docs/apdu.md as data: which headers each instruction accepts, the layout
+//! of its parameters and of its response, and the status words the app may answer with.
+//!
+//! Host tooling checks the app against these, see the conformance test of ledger-iota-client, so
+//! a change of the contract has to be made here as well as in the app and in the docs.
+
+use crate::{
+    Ins, IOTA_ADDRESS_LENGTH, MAX_LABEL_LENGTH, P1_FIRST, P1_LAST, P1_NEXT, P1_RESUME,
+    P2_BIP32_ED25519, P2_SLIP10, SW_BAD_ULEB128, SW_TOO_COMPLEX,
+};
+
+pub const CLA: u8 = 0x00;
+
+pub const SW_OK: u16 = 0x9000;
+pub const SW_NOT_SUPPORTED: u16 = 0x6808;
+pub const SW_NOTHING_RECEIVED: u16 = 0x6982;
+pub const SW_ERROR: u16 = 0x6d00;
+pub const SW_CLA_OR_INS_NOT_SUPPORTED: u16 = 0x6e00;
+pub const SW_BAD_LEN: u16 = 0x6e01;
+pub const SW_BAD_P1P2: u16 = 0x6e02;
+
+pub struct StatusWord {
+    pub sw: u16,
+    pub name: &'static str,
+    pub description: &'static str,
+}
+
+/// Every status word the app answers with. Anything else is undocumented behaviour.
+pub const STATUS_WORDS: &[StatusWord] = &[
+    StatusWord {
+        sw: SW_NOT_SUPPORTED,
+        name: "SW_NOT_SUPPORTED",
+        description: "`INS` is disabled (Blind Signing)",
+    },
+    StatusWord {
+        sw: SW_NOTHING_RECEIVED,
+        name: "SW_NOTHING_RECEIVED",
+        description: "No input was received by the app",
+    },
+    StatusWord {
+        sw: SW_ERROR,
+        name: "SW_ERROR",
+        description: "Error has occurred due to bad input or user rejected",
+    },
+    StatusWord {
+        sw: SW_CLA_OR_INS_NOT_SUPPORTED,
+        name: "SW_CLA_OR_INS_NOT_SUPPORTED",
+        description: "No command exists for the `CLA` and `INS`",
+    },
+    StatusWord {
+        sw: SW_BAD_LEN,
+        name: "SW_BAD_LEN",
+        description: "Length mismatch in inputs",
+    },
+    StatusWord {
+        sw: SW_BAD_P1P2,
+        name: "SW_BAD_P1P2",
+        description: "`P1` or `P2` is not one the command takes",
+    },
+    StatusWord {
+        sw: SW_OK,
+        name: "SW_OK",
+        description: "Success, or continue if more input from client is expected",
+    },
+    StatusWord {
+        sw: SW_BAD_ULEB128,
+        name: "SW_BAD_ULEB128",
+        description: "Over-long, too large or truncated ULEB128 in a transaction",
+    },
+    StatusWord {
+        sw: SW_TOO_COMPLEX,
+        name: "SW_TOO_COMPLEX",
+        description: "Too many inputs or commands to review",
+    },
+];
+
+pub fn is_documented(sw: u16) -> bool {
+    STATUS_WORDS.iter().any(|s| s.sw == sw)
+}
+
+/// How one field of a parameter or of a response is laid out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Layout {
+    U8,
+    U32Le,
+    /// Exactly that many bytes
+    Fixed(usize),
+    /// A length byte which must be the given one, then that many bytes
+    LengthPrefixed(usize),
+    /// A length byte of at most 10, then that many components as u32 LE
+    Bip32Path,
+    /// A u32 LE size, then that many bytes
+    SizePrefixed,
+    /// A length byte of at most MAX_LABEL_LENGTH, then that many bytes of printable ASCII
+    Label,
+    /// Whatever is left, possibly nothing
+    Rest,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Field {
+    pub name: &'static str,
+    pub layout: Layout,
+}
+
+const fn field(name: &'static str, layout: Layout) -> Field {
+    Field { name, layout }
+}
+
+/// How the host sends the parameters of a command.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Framing {
+    /// Each parameter as a chain of blocks, see docs/block-protocol.md
+    Block,
+    /// The path in a `P1_FIRST` APDU, then the transaction in `P1_NEXT` APDUs and a `P1_LAST` one
+    Chunked,
+    /// No parameters, and no response either
+    None,
+}
+
+pub struct Instruction {
+    pub ins: Ins,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub framing: Framing,
+    /// The P1 values of the APDU which starts the command
+    pub p1: &'static [u8],
+    /// The P2 values of the APDU which starts the command, which pick the derivation if there is
+    /// more than one
+    pub p2: &'static [u8],
+    /// Whether the user has to approve the command on the device
+    pub prompts: bool,
+    pub params: &'static [&'static [Field]],
+    pub response: &'static [Field],
+}
+
+const P1_ZERO: &[u8] = &[0];
+const P2_ZERO: &[u8] = &[0];
+const P2_DERIVATION: &[u8] = &[P2_SLIP10, P2_BIP32_ED25519];
+
+// The P1 values of the APDUs of SIGN_TX_CHUNKED, of which only P1_FIRST starts a command
+pub const CHUNKED_P1: &[u8] = &[P1_FIRST, P1_NEXT, P1_LAST, P1_RESUME];
+
+// The response of P1_RESUME
+pub const CHUNKED_RESUME_RESPONSE: &[Field] = &[field("received", Layout::U32Le)];
+
+const PATH: &[Field] = &[field("bip32_path", Layout::Bip32Path)];
+
+const PUBKEY_RESPONSE: &[Field] = &[
+    field("public_key", Layout::LengthPrefixed(32)),
+    field("address", Layout::LengthPrefixed(32)),
+];
+
+const SIGNATURE: &[Field] = &[field("signature", Layout::Fixed(64))];
+
+pub const INSTRUCTIONS: &[Instruction] = &[
+    Instruction {
+        ins: Ins::GetVersion,
+        name: "GET_VERSION",
+        description: "Gets the app version in machine readable format (bytes)",
+        framing: Framing::Block,
+        p1: P1_ZERO,
+        p2: P2_ZERO,
+        prompts: false,
+        params: &[],
+        response: &[
+            field("major", Layout::U8),
+            field("minor", Layout::U8),
+            field("patch", Layout::U8),
+            field("name", Layout::Rest),
+        ],
+    },
+    Instruction {
+        ins: Ins::VerifyAddress,
+        name: "VERIFY_ADDRESS",
+        description: "Shows the Address on device for a BIP32 path",
+        framing: Framing::Block,
+        p1: P1_ZERO,
+        p2: P2_DERIVATION,
+        prompts: true,
+        params: &[PATH],
+        response: PUBKEY_RESPONSE,
+    },
+    Instruction {
+        ins: Ins::GetPubkey,
+        name: "GET_PUBKEY",
+        description: "Gets the Public Key and Address for a BIP32 path",
+        framing: Framing::Block,
+        p1: P1_ZERO,
+        p2: P2_DERIVATION,
+        prompts: false,
+        params: &[PATH],
+        response: PUBKEY_RESPONSE,
+    },
+    Instruction {
+        ins: Ins::Sign,
+        name: "SIGN_TX",
+        description: "Sign Transaction",
+        framing: Framing::Block,
+        p1: P1_ZERO,
+        p2: P2_DERIVATION,
+        prompts: true,
+        params: &[&[field("tx", Layout::SizePrefixed)], PATH],
+        response: SIGNATURE,
+    },
+    Instruction {
+        ins: Ins::SignChunked,
+        name: "SIGN_TX_CHUNKED",
+        description: "Sign Transaction, without the Block Protocol",
+        framing: Framing::Chunked,
+        p1: &[P1_FIRST],
+        p2: P2_DERIVATION,
+        prompts: true,
+        params: &[PATH],
+        response: SIGNATURE,
+    },
+    Instruction {
+        ins: Ins::SaveAddress,
+        name: "SAVE_ADDRESS",
+        description: "Saves the Address for a BIP32 path to the address book",
+        framing: Framing::Block,
+        p1: P1_ZERO,
+        p2: P2_DERIVATION,
+        prompts: true,
+        params: &[PATH, &[field("label", Layout::Label)]],
+        response: &[],
+    },
+    Instruction {
+        ins: Ins::SelfTest,
+        name: "SELF_TEST",
+        description: "Runs known-answer tests of the cryptography of the app",
+        framing: Framing::Block,
+        p1: P1_ZERO,
+        p2: P2_ZERO,
+        prompts: false,
+        params: &[],
+        response: &[
+            field("blake2b", Layout::U8),
+            field("ed25519", Layout::U8),
+            field("device_key", Layout::U8),
+        ],
+    },
+    Instruction {
+        ins: Ins::LabelRecipient,
+        name: "LABEL_RECIPIENT",
+        description: "Labels a recipient for the session, shown as unverified",
+        framing: Framing::Block,
+        p1: P1_ZERO,
+        p2: P2_ZERO,
+        prompts: false,
+        params: &[
+            &[field("address", Layout::Fixed(IOTA_ADDRESS_LENGTH))],
+            &[field("label", Layout::Label)],
+        ],
+        response: &[],
+    },
+    Instruction {
+        ins: Ins::GetPatterns,
+        name: "GET_PATTERNS",
+        description: "Lists the Move calls the app clear signs",
+        framing: Framing::Block,
+        p1: P1_ZERO,
+        p2: P2_ZERO,
+        prompts: false,
+        params: &[],
+        response: &[
+            field("version", Layout::U8),
+            field("count", Layout::U8),
+            field("patterns", Layout::Rest),
+        ],
+    },
+    Instruction {
+        ins: Ins::GetLimits,
+        name: "GET_LIMITS",
+        description: "Gets the largest transactions the app signs and reviews",
+        framing: Framing::Block,
+        p1: P1_ZERO,
+        p2: P2_ZERO,
+        prompts: false,
+        params: &[],
+        response: &[
+            field("max_tx_size", Layout::U32Le),
+            field("max_chunked_tx_size", Layout::U32Le),
+            field("max_inputs", Layout::U8),
+            field("max_commands", Layout::U8),
+            field("max_split_amounts", Layout::U8),
+        ],
+    },
+    Instruction {
+        ins: Ins::GetReceipt,
+        name: "GET_RECEIPT",
+        description: "Gets what the last transaction signed does, as the app parsed it",
+        framing: Framing::Block,
+        p1: P1_ZERO,
+        p2: P2_ZERO,
+        prompts: false,
+        params: &[],
+        response: &[
+            field("hash", Layout::Fixed(32)),
+            field("kind", Layout::U8),
+            field("gas_budget", Layout::Fixed(8)),
+            field("recipient_and_amounts", Layout::Rest),
+        ],
+    },
+    Instruction {
+        ins: Ins::Exit,
+        name: "QUIT_APP",
+        description: "Quits the app",
+        framing: Framing::None,
+        p1: P1_ZERO,
+        p2: P2_ZERO,
+        prompts: false,
+        params: &[],
+        response: &[],
+    },
+];
+
+pub fn instruction(ins: u8) -> Option<&'static Instruction> {
+    INSTRUCTIONS.iter().find(|i| i.ins as u8 == ins)
+}
+
+/// Whether data is laid out as the fields say, with nothing left over.
+pub fn matches(fields: &[Field], mut data: &[u8]) -> bool {
+    for field in fields {
+        let size = match field.layout {
+            Layout::U8 => 1,
+            Layout::U32Le => 4,
+            Layout::Fixed(n) => n,
+            Layout::LengthPrefixed(n) => match data.first() {
+                Some(&len) if len as usize == n => 1 + n,
+                _ => return false,
+            },
+            Layout::Bip32Path => match data.first() {
+                Some(&len) if len <= 10 => 1 + 4 * len as usize,
+                _ => return false,
+            },
+            Layout::SizePrefixed => match data.get(..4) {
+                Some(size) => 4 + u32::from_le_bytes([size[0], size[1], size[2], size[3]]) as usize,
+                None => return false,
+            },
+            Layout::Label => match data.first() {
+                Some(&len) if len as usize <= MAX_LABEL_LENGTH => {
+                    let label = match data.get(1..1 + len as usize) {
+                        Some(label) => label,
+                        None => return false,
+                    };
+                    if !label.iter().all(|b| b.is_ascii_graphic() || *b == b' ') {
+                        return false;
+                    }
+                    1 + len as usize
+                }
+                _ => return false,
+            },
+            Layout::Rest => data.len(),
+        };
+        match data.get(size..) {
+            Some(rest) => data = rest,
+            None => return false,
+        }
+    }
+    data.is_empty()
+}
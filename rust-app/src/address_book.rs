@@ -0,0 +1,139 @@
+use crate::interface::{IOTA_ADDRESS_LENGTH, MAX_LABEL_LENGTH};
+use crate::utils::ct_eq;
+use arrayvec::ArrayString;
+use ledger_device_sdk::nvm::{AtomicStorage, SingleStorage};
+use ledger_device_sdk::NVMData;
+
+// Recipients the user has saved, so transfers to them can be shown by name.
+
+#[cfg(target_os = "nanos")]
+const MAX_ENTRIES: usize = 8;
+#[cfg(not(target_os = "nanos"))]
+const MAX_ENTRIES: usize = 32;
+
+#[derive(Clone, Copy)]
+struct Entry {
+    address: [u8; IOTA_ADDRESS_LENGTH],
+    label: [u8; MAX_LABEL_LENGTH],
+    label_length: u8,
+}
+
+const EMPTY_ENTRY: Entry = Entry {
+    address: [0; IOTA_ADDRESS_LENGTH],
+    label: [0; MAX_LABEL_LENGTH],
+    label_length: 0,
+};
+
+#[derive(Clone, Copy)]
+struct AddressBook {
+    entries: [Entry; MAX_ENTRIES],
+    length: u8,
+}
+
+const EMPTY_BOOK: AddressBook = AddressBook {
+    entries: [EMPTY_ENTRY; MAX_ENTRIES],
+    length: 0,
+};
+
+#[link_section = ".nvm_data"]
+static mut ADDRESS_BOOK: NVMData<AtomicStorage<AddressBook>> =
+    NVMData::new(AtomicStorage::new(&EMPTY_BOOK));
+
+#[inline(never)]
+fn get() -> AddressBook {
+    let book = unsafe { ADDRESS_BOOK.get_mut() };
+    *book.get_ref()
+}
+
+// Where the address is in the book, if it is. Every entry is compared, in constant time, so that
+// how long a lookup takes does not tell the host which of the saved addresses it asked for.
+fn find(book: &AddressBook, address: &[u8; IOTA_ADDRESS_LENGTH]) -> Option<usize> {
+    let mut found = None;
+    for (i, entry) in book.entries[..book.length as usize].iter().enumerate() {
+        if ct_eq(&entry.address, address) {
+            found = Some(i);
+        }
+    }
+    found
+}
+
+fn label(entry: &Entry) -> Option<ArrayString<MAX_LABEL_LENGTH>> {
+    let label = core::str::from_utf8(&entry.label[..entry.label_length as usize]).ok()?;
+    ArrayString::from(label).ok()
+}
+
+/// The label the address was saved under, if any.
+#[inline(never)]
+pub fn lookup(address: &[u8; IOTA_ADDRESS_LENGTH]) -> Option<ArrayString<MAX_LABEL_LENGTH>> {
+    let book = get();
+    label(&book.entries[find(&book, address)?])
+}
+
+// The bytes at either end which a look-alike shares with the address it imitates. Addresses with
+// the first and last 4 hex characters of one chosen take some 2^32 tries to find, which is cheap.
+const SIMILAR_END_LENGTH: usize = 2;
+
+/// The label of a saved address which the address is not, but looks like at a glance, sharing
+/// the characters at both ends with it. Sends to such a look-alike, which a scammer had sent dust
+/// from so that it shows up in the history of the user, are how addresses get poisoned.
+#[inline(never)]
+pub fn lookup_similar(
+    address: &[u8; IOTA_ADDRESS_LENGTH],
+) -> Option<ArrayString<MAX_LABEL_LENGTH>> {
+    let book = get();
+    if find(&book, address).is_some() {
+        return None;
+    }
+    // Every entry is compared, as in find
+    let end = IOTA_ADDRESS_LENGTH - SIMILAR_END_LENGTH;
+    let mut found = None;
+    for (i, entry) in book.entries[..book.length as usize].iter().enumerate() {
+        if ct_eq(
+            &entry.address[..SIMILAR_END_LENGTH],
+            &address[..SIMILAR_END_LENGTH],
+        ) & ct_eq(&entry.address[end..], &address[end..])
+        {
+            found = Some(i);
+        }
+    }
+    label(&book.entries[found?])
+}
+
+/// Save an address, replacing the label if it was saved before. Once the book is full the oldest
+/// entry is dropped to make room.
+#[inline(never)]
+pub fn save(address: &[u8; IOTA_ADDRESS_LENGTH], label: &str) -> Option<()> {
+    if label.len() > MAX_LABEL_LENGTH {
+        return None;
+    }
+    let mut entry = Entry {
+        address: *address,
+        label: [0; MAX_LABEL_LENGTH],
+        label_length: label.len() as u8,
+    };
+    entry.label[..label.len()].copy_from_slice(label.as_bytes());
+
+    let mut book = get();
+    let length = book.length as usize;
+    match find(&book, address) {
+        Some(i) => book.entries[i] = entry,
+        None if length < MAX_ENTRIES => {
+            book.entries[length] = entry;
+            book.length += 1;
+        }
+        None => {
+            book.entries.rotate_left(1);
+            book.entries[MAX_ENTRIES - 1] = entry;
+        }
+    }
+    let storage = unsafe { ADDRESS_BOOK.get_mut() };
+    storage.update(&book);
+    Some(())
+}
+
+/// Forget all saved addresses.
+#[inline(never)]
+pub fn clear() {
+    let storage = unsafe { ADDRESS_BOOK.get_mut() };
+    storage.update(&EMPTY_BOOK);
+}
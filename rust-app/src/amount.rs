@@ -0,0 +1,73 @@
+// Splitting amounts in nanos into whole IOTA and the digits after the decimal mark. Does not
+// depend on the device, so that it is tested on the host too, see the property tests below.
+
+use arrayvec::ArrayString;
+
+// Nanos per IOTA, as a power of ten
+pub const DECIMALS: u32 = 9;
+
+pub fn get_amount_in_decimals(amount: u64) -> (u64, ArrayString<12>) {
+    let factor_pow = DECIMALS;
+    let factor = u64::pow(10, factor_pow);
+    let quotient = amount / factor;
+    let remainder = amount % factor;
+    let mut remainder_str: ArrayString<12> = ArrayString::new();
+    {
+        // Make a string for the remainder, containing at lease one zero
+        // So 1 IOTA will be displayed as "1.0"
+        let mut rem = remainder;
+        for i in 0..factor_pow {
+            let f = u64::pow(10, factor_pow - i - 1);
+            let r = rem / f;
+            let _ = remainder_str.try_push(char::from(b'0' + r as u8));
+            rem %= f;
+            if rem == 0 {
+                break;
+            }
+        }
+    }
+    (quotient, remainder_str)
+}
+
+#[cfg(all(not(target_family = "bolos"), test))]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    const FACTOR: u64 = 1_000_000_000;
+
+    // Amounts near the places where the output changes shape, besides any amount at all
+    fn amounts() -> impl Strategy<Value = u64> {
+        prop_oneof![
+            any::<u64>(),
+            Just(0u64),
+            Just(u64::MAX),
+            (0..=u64::MAX / FACTOR).prop_map(|q| q * FACTOR),
+            (1..=u64::MAX / FACTOR).prop_map(|q| q * FACTOR - 1),
+            (0..=u64::MAX / FACTOR - 1).prop_map(|q| q * FACTOR + 1),
+            (0..DECIMALS).prop_map(|p| 10u64.pow(p)),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn round_trips(amount in amounts()) {
+            let (quotient, remainder) = get_amount_in_decimals(amount);
+            let digits = remainder.len() as u32;
+            prop_assert!((1..=DECIMALS).contains(&digits), "{:?}", remainder);
+            prop_assert!(remainder.bytes().all(|b| b.is_ascii_digit()), "{:?}", remainder);
+            // Only "0" ends in a zero, any other trailing zeroes are left out
+            prop_assert!(
+                remainder.as_str() == "0" || !remainder.ends_with('0'),
+                "{:?}",
+                remainder
+            );
+
+            let fraction: u64 = remainder.parse().unwrap();
+            let nanos = fraction * 10u64.pow(DECIMALS - digits);
+            prop_assert!(nanos < FACTOR);
+            let total = quotient.checked_mul(FACTOR).and_then(|q| q.checked_add(nanos));
+            prop_assert_eq!(total, Some(amount));
+        }
+    }
+}
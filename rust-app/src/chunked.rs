@@ -0,0 +1,154 @@
+use crate::implementation::{refuse, sign_txn, take_sign_rejection};
+use crate::interface::{Derivation, P1_FIRST, P1_LAST, P1_NEXT, P1_RESUME};
+use crate::settings::Settings;
+use crate::utils::poll_once;
+use arrayvec::ArrayVec;
+use core::future::Future;
+use core::task::Poll;
+use ledger_device_sdk::io::{Comm, Reply, StatusWords, SyscallError};
+use ledger_parser_combinators::async_parser::{Readable, TryFuture};
+use zeroize::Zeroize;
+
+// Ledger-standard chunked signing, for hosts which do not speak the block protocol.
+//
+// P1_FIRST carries the BIP32 path, encoded as for the block protocol, and picks the derivation
+// with its P2, like the other commands which derive keys do. P1_NEXT and P1_LAST carry
+// consecutive pieces of the transaction. The review starts once P1_LAST is received, and the
+// signature is returned in the response to that APDU.
+//
+// P1_RESUME answers with how much of the transaction has been received, so that a host which lost
+// the connection in the middle of it can go on from there instead of starting over.
+
+// Leave enough room for the rest of the app on the smallest device.
+#[cfg(target_os = "nanos")]
+pub const MAX_CHUNKED_TX_SIZE: usize = 768;
+#[cfg(not(target_os = "nanos"))]
+pub const MAX_CHUNKED_TX_SIZE: usize = 4096;
+
+const MAX_PATH_SIZE: usize = 1 + 4 * 10;
+
+struct ChunkedState {
+    path: ArrayVec<u8, MAX_PATH_SIZE>,
+    txn: ArrayVec<u8, MAX_CHUNKED_TX_SIZE>,
+    derivation: Derivation,
+    started: bool,
+}
+
+static mut STATE: ChunkedState = ChunkedState {
+    path: ArrayVec::new_const(),
+    txn: ArrayVec::new_const(),
+    derivation: Derivation::Slip10,
+    started: false,
+};
+
+/// Readable over a buffer which has been fully received already.
+///
+/// Reading past the end rejects, just like running out of blocks does in the block protocol.
+#[derive(Clone, Copy)]
+pub struct SliceStream(&'static [u8]);
+
+impl Readable for SliceStream {
+    type OutFut<'a, const N: usize> = impl 'a + Future<Output = [u8; N]>;
+    fn read<'a: 'b, 'b, const N: usize>(&'a mut self) -> Self::OutFut<'b, N> {
+        async move {
+            if self.0.len() < N {
+                return refuse(StatusWords::BadLen as u16).await;
+            }
+            let (head, tail) = self.0.split_at(N);
+            self.0 = tail;
+            let mut rv = [0; N];
+            rv.copy_from_slice(head);
+            rv
+        }
+    }
+}
+
+fn reset(state: &mut ChunkedState) {
+    state.path.zeroize();
+    state.txn.clear();
+    state.derivation = Derivation::Slip10;
+    state.started = false;
+}
+
+pub fn clear() {
+    reset(unsafe { &mut STATE });
+}
+
+// Store the data of one chunk, returning whether it was the last one.
+fn receive_chunk(
+    state: &mut ChunkedState,
+    p1: u8,
+    p2: u8,
+    data: &[u8],
+) -> Result<bool, StatusWords> {
+    match p1 {
+        P1_FIRST => {
+            reset(state);
+            state.derivation = Derivation::from_p2(p2).ok_or(StatusWords::BadP1P2)?;
+            state
+                .path
+                .try_extend_from_slice(data)
+                .map_err(|_| StatusWords::BadLen)?;
+            state.started = true;
+            Ok(false)
+        }
+        P1_NEXT | P1_LAST if state.started => {
+            state
+                .txn
+                .try_extend_from_slice(data)
+                .map_err(|_| StatusWords::BadLen)?;
+            Ok(p1 == P1_LAST)
+        }
+        _ => Err(StatusWords::BadP1P2),
+    }
+}
+
+/// Handle one Ins::SignChunked APDU, leaving the response data in `comm` on success.
+#[inline(never)]
+pub fn handle_chunk(comm: &mut Comm, settings: Settings) -> Result<(), Reply> {
+    let (p1, p2) = {
+        let metadata = comm.get_apdu_metadata();
+        (metadata.p1, metadata.p2)
+    };
+    if p1 == P1_RESUME {
+        // Leaves the sequence as it is, whether or not there is one
+        let state = unsafe { &STATE };
+        if !state.started {
+            return Err(StatusWords::BadP1P2.into());
+        }
+        comm.append(&(state.txn.len() as u32).to_le_bytes());
+        return Ok(());
+    }
+    let last = comm
+        .get_data()
+        .and_then(|data| receive_chunk(unsafe { &mut STATE }, p1, p2, data));
+    let rv = match last {
+        Ok(false) => return Ok(()),
+        Ok(true) => {
+            let state = unsafe { &STATE };
+            trace!("Chunked sign: {} bytes received", state.txn.len());
+            // Everything has been received, so the only way the flow can stop short of a
+            // signature is by being rejected, which the host is told the reason of. Only the
+            // parsers of the path reject without saying why, and always over a bad parameter.
+            match poll_once(TryFuture(sign_txn(
+                SliceStream(state.txn.as_slice()),
+                state.txn.len(),
+                SliceStream(state.path.as_slice()),
+                settings,
+                state.derivation,
+            ))) {
+                Poll::Ready(Some(sig)) => {
+                    comm.append(&sig);
+                    Ok(())
+                }
+                _ => Err(Reply(
+                    take_sign_rejection().unwrap_or(SyscallError::InvalidParameter as u16),
+                )),
+            }
+        }
+        Err(sw) => Err(sw.into()),
+    };
+    // Stop after the last chunk or at the first error, the host has to start again from P1_FIRST.
+    clear();
+    rv
+}
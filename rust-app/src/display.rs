@@ -0,0 +1,1156 @@
+#[cfg(feature = "address_book")]
+use crate::address_book;
+use crate::amount::get_amount_in_decimals;
+use crate::interface::{HARDENED, IOTA_ADDRESS_LENGTH};
+#[cfg(feature = "liquidity")]
+use crate::parser::LiquiditySummary;
+use crate::parser::{
+    CallName, CoinType, DivideSummary, IotaAddressRaw, MintSummary, ObjectIdRaw,
+    ObjectTransferSummary, Operation, OwnedObject, PureValue, StructTag, TransferSummary,
+    TxSummary, UnstakeSummary, MAX_COMMANDS, MAX_OWNED_OBJECTS,
+};
+#[cfg(feature = "lending")]
+use crate::patterns::LendingAction;
+#[cfg(feature = "liquidity")]
+use crate::patterns::LiquidityAction;
+use crate::patterns::{self, StakedObject};
+use crate::session_labels;
+use crate::settings::{Setting, Settings};
+use crate::utils::{
+    final_accept_prompt, scroller, scroller_paginated, want_details, warning_screen,
+};
+use arrayvec::ArrayString;
+use arrayvec::ArrayVec;
+use core::fmt::Write;
+use ledger_crypto_helpers::common::HexSlice;
+use ledger_crypto_helpers::hasher::HexHash;
+
+// The account and address index of a path of the form m/44'/coin'/account'/change'/index'
+pub fn write_account_index(w: &mut impl Write, path: &[u32]) -> core::fmt::Result {
+    if let Some(account) = path.get(2) {
+        write!(w, "#{}", account & !HARDENED)?;
+    }
+    if let Some(index) = path.get(4) {
+        write!(w, ", Index #{}", index & !HARDENED)?;
+    }
+    Ok(())
+}
+
+pub fn write_bip32_path(w: &mut impl Write, path: &[u32]) -> core::fmt::Result {
+    write!(w, "m")?;
+    for component in path {
+        if component & HARDENED != 0 {
+            write!(w, "/{}'", component & !HARDENED)?;
+        } else {
+            write!(w, "/{component}")?;
+        }
+    }
+    Ok(())
+}
+
+// Amounts are in nanos, which are shown as is if the user prefers them to IOTA
+fn write_amount(w: &mut impl Write, amount: u64) -> core::fmt::Result {
+    if Settings.is_enabled(Setting::BaseUnits) {
+        write!(w, "{amount} nanos")
+    } else {
+        let (quotient, remainder_str) = get_amount_in_decimals(amount);
+        let separator = if Settings.is_enabled(Setting::DecimalComma) {
+            ','
+        } else {
+            '.'
+        };
+        write!(w, "IOTA ")?;
+        write_grouped(w, quotient)?;
+        write!(w, "{separator}{}", remainder_str.as_str())
+    }
+}
+
+// With a space between each group of three digits, so that large amounts can be read at a glance
+fn write_grouped(w: &mut impl Write, n: u64) -> core::fmt::Result {
+    if n >= 1000 {
+        write_grouped(w, n / 1000)?;
+        write!(w, " {:03}", n % 1000)
+    } else {
+        write!(w, "{n}")
+    }
+}
+
+// Only the first and last 8 characters of the address, if the user prefers that
+fn write_review_address(w: &mut impl Write, address: &IotaAddressRaw) -> core::fmt::Result {
+    if Settings.is_enabled(Setting::TruncatedAddresses) {
+        write!(
+            w,
+            "0x{}...{}",
+            HexSlice(&address[..4]),
+            HexSlice(&address[IOTA_ADDRESS_LENGTH - 4..])
+        )
+    } else {
+        write!(w, "0x{}", HexSlice(address))
+    }
+}
+
+// The full version of an address shown truncated before, if asked for
+#[cold]
+#[inline(never)]
+fn show_full_address(title: &str, address: &IotaAddressRaw) -> Option<()> {
+    if want_details(&["Show Full Address?"]) {
+        scroller_paginated(title, |w| Ok(write!(w, "0x{}", HexSlice(address))?))
+    } else {
+        Some(())
+    }
+}
+
+// Only IOTA is known to have 9 decimals, so other coins are shown in their smallest unit
+fn write_coin_amount(w: &mut impl Write, coin_type: &CoinType, amount: u64) -> core::fmt::Result {
+    match coin_type {
+        CoinType::Iota => write_amount(w, amount),
+        CoinType::Other(tag) => write!(w, "{amount} base units of {}", tag.name),
+    }
+}
+
+fn write_coin_type(w: &mut impl Write, tag: &StructTag) -> core::fmt::Result {
+    write!(
+        w,
+        "0x{}::{}::{}",
+        HexSlice(&tag.address),
+        tag.module,
+        tag.name
+    )
+}
+
+// An ID without its leading zeros, like 0x2
+fn write_short_id(w: &mut impl Write, id: &ObjectIdRaw) -> core::fmt::Result {
+    match id.iter().position(|b| *b != 0) {
+        Some(first) => {
+            write!(w, "0x{:x}", id[first])?;
+            write!(w, "{}", HexSlice(&id[first + 1..]))
+        }
+        None => write!(w, "0x0"),
+    }
+}
+
+// A package by its name if it is a well-known one, see patterns.rs, and by its ID otherwise
+fn write_package(w: &mut impl Write, package: &ObjectIdRaw) -> core::fmt::Result {
+    match patterns::package_name(package) {
+        Some(name) => {
+            write!(w, "{name} (")?;
+            write_short_id(w, package)?;
+            write!(w, ")")
+        }
+        None => write!(w, "0x{}", HexSlice(package)),
+    }
+}
+
+// Like "u64 1000" or "bool true", with the type the call takes the argument as
+fn write_pure_value(w: &mut impl Write, value: &PureValue) -> core::fmt::Result {
+    match value {
+        PureValue::Bool(b) => write!(w, "bool {b}"),
+        PureValue::U8(n) => write!(w, "u8 {n}"),
+        PureValue::U16(n) => write!(w, "u16 {n}"),
+        PureValue::U64(n) => write!(w, "u64 {n}"),
+        PureValue::U128(bytes) => write!(w, "u128 {}", u128::from_le_bytes(*bytes)),
+        PureValue::Address(address) => write!(w, "address 0x{}", HexSlice(address)),
+    }
+}
+
+// Like "IOTA System (0x3) iota_system::request_add_stake, given address 0x7777...", with the pure
+// arguments of the call in order
+fn write_call(w: &mut impl Write, call: &CallName) -> core::fmt::Result {
+    write_package(w, &call.package)?;
+    write!(w, " {}::{}", call.module, call.function)?;
+    for (i, value) in call.arguments.iter().enumerate() {
+        write!(w, "{}", if i == 0 { ", given " } else { ", " })?;
+        write_pure_value(w, value)?;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "lending")]
+fn lending_verb(action: LendingAction) -> &'static str {
+    match action {
+        LendingAction::Deposit => "Deposit",
+        LendingAction::Withdraw => "Withdraw",
+        LendingAction::Borrow => "Borrow",
+        LendingAction::Repay => "Repay",
+    }
+}
+
+// The coins of a pool are told apart by a letter, in the order of the pool
+#[cfg(feature = "liquidity")]
+const COIN_TYPE_HEADERS: [&str; 2] = ["Coin Type A", "Coin Type B"];
+#[cfg(feature = "liquidity")]
+const AMOUNT_HEADERS: [&str; 2] = ["Amount A", "Amount B"];
+#[cfg(feature = "liquidity")]
+const MINIMUM_HEADERS: [&str; 2] = ["Minimum A", "Minimum B"];
+
+// The coins an operation moves, in order, with their amounts
+fn coins(operation: &Operation) -> ArrayVec<(&CoinType, u64), 2> {
+    let mut rv = ArrayVec::new();
+    match operation {
+        Operation::Transfer(transfer) => rv.push((&CoinType::Iota, transfer.total_amount)),
+        Operation::ObjectTransfer(transfer) => {
+            if let Some(amount) = transfer.split_amount {
+                rv.push((&CoinType::Iota, amount));
+            }
+        }
+        #[cfg(feature = "lending")]
+        Operation::Lending(lending) => rv.push((&lending.coin_type, lending.amount)),
+        Operation::Divide(divide) => rv.push((&divide.coin_type, divide.amount)),
+        Operation::Stake(stake) => rv.push((&CoinType::Iota, stake.amount)),
+        Operation::Mint(mint) => rv.push((&mint.coin_type, mint.amount)),
+        // Whatever the stake and its rewards amount to, which the transaction does not say
+        Operation::Unstake(_) => {}
+        #[cfg(feature = "liquidity")]
+        Operation::Liquidity(liquidity) => {
+            for (coin_type, amount) in liquidity.coin_types.iter().zip(liquidity.amounts) {
+                rv.push((coin_type, amount));
+            }
+        }
+    }
+    rv
+}
+
+// Like "Known: StakeCorp (0x9ab1...)", by its label if the validator is in the address book, and
+// with the address in full either way, like the package, as the validator is who the user trusts
+// with the stake
+fn write_validator(w: &mut impl Write, validator: &IotaAddressRaw) -> core::fmt::Result {
+    #[cfg(feature = "address_book")]
+    if let Some(label) = address_book::lookup(validator) {
+        return write!(w, "Known: {label} (0x{})", HexSlice(validator));
+    }
+    write!(w, "0x{}", HexSlice(validator))
+}
+
+// What an unstake withdraws, which is only known once it is executed, as rewards accrue each epoch
+const UNSTAKE_WITHDRAWN: &str = "All accrued rewards, along with the stake";
+
+// Like "Withdraw stake 0x5a5a...5a5a"
+fn write_unstake(w: &mut impl Write, unstake: &UnstakeSummary) -> core::fmt::Result {
+    match unstake.object {
+        StakedObject::StakedIota => write!(w, "Withdraw stake 0x{}", HexSlice(&unstake.staked)),
+        StakedObject::TimelockedStakedIota => write!(
+            w,
+            "Withdraw timelocked stake 0x{}, of which the principal stays timelocked",
+            HexSlice(&unstake.staked)
+        ),
+    }
+}
+
+// Like "Split IOTA 1.0 into 3 coins of IOTA 0.333333333, one of them with IOTA 0.000000001 more"
+fn write_division(w: &mut impl Write, divide: &DivideSummary) -> core::fmt::Result {
+    write!(w, "Split ")?;
+    write_coin_amount(w, &divide.coin_type, divide.amount)?;
+    write!(w, " into {} coins of ", divide.count)?;
+    write_coin_amount(w, &divide.coin_type, divide.piece())?;
+    if divide.remainder() > 0 {
+        write!(w, ", one of them with ")?;
+        write_coin_amount(w, &divide.coin_type, divide.remainder())?;
+        write!(w, " more")?;
+    }
+    Ok(())
+}
+
+fn write_count(w: &mut impl Write, count: usize, noun: &str) -> core::fmt::Result {
+    match count {
+        1 => write!(w, "1 {noun}"),
+        n => write!(w, "{n} {noun}s"),
+    }
+}
+
+// Like "3 objects (2 Coin<IOTA>, 1 other)", as the type of the others is not in the transaction
+fn write_object_counts(w: &mut impl Write, transfer: &ObjectTransferSummary) -> core::fmt::Result {
+    write_count(w, transfer.object_count, "object")?;
+    let other_count = transfer.object_count - transfer.iota_coin_count;
+    match (transfer.iota_coin_count, other_count) {
+        (0, other) => write!(w, " ({other} other)"),
+        (iota, 0) => write!(w, " ({iota} Coin<IOTA>)"),
+        (iota, other) => write!(w, " ({iota} Coin<IOTA>, {other} other)"),
+    }
+}
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+// Standard Base64, with padding
+#[cold]
+#[inline(never)]
+fn write_base64(w: &mut impl Write, bytes: &[u8]) -> core::fmt::Result {
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let indices = [
+            b[0] >> 2,
+            (b[0] & 0x03) << 4 | b[1] >> 4,
+            (b[1] & 0x0f) << 2 | b[2] >> 6,
+            b[2] & 0x3f,
+        ];
+        for (i, index) in indices.iter().enumerate() {
+            if i <= chunk.len() {
+                w.write_char(BASE64_ALPHABET[*index as usize] as char)?;
+            } else {
+                w.write_char('=')?;
+            }
+        }
+    }
+    Ok(())
+}
+
+const BASE58_ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+// Base58 with the alphabet of Bitcoin, which is how wallets and explorers show object digests
+#[cold]
+#[inline(never)]
+fn write_base58(w: &mut impl Write, bytes: &[u8; 32]) -> core::fmt::Result {
+    // The digits, least significant first, of which 32 bytes take at most 44
+    let mut digits = [0u8; 44];
+    let mut len = 0;
+    for byte in bytes {
+        let mut carry = *byte as u32;
+        for digit in digits[..len].iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits[len] = (carry % 58) as u8;
+            len += 1;
+            carry /= 58;
+        }
+    }
+    // Each leading zero byte is a leading '1', as the number alone would lose them
+    for _ in bytes.iter().take_while(|b| **b == 0) {
+        w.write_char('1')?;
+    }
+    for digit in digits[..len].iter().rev() {
+        w.write_char(BASE58_ALPHABET[*digit as usize] as char)?;
+    }
+    Ok(())
+}
+
+// The exact state of an object, so that it can be matched with the records of the signer
+fn write_owned_object(w: &mut impl Write, object: &OwnedObject) -> core::fmt::Result {
+    write!(
+        w,
+        "0x{}, Version {}, Digest ",
+        HexSlice(&object.id),
+        object.version
+    )?;
+    write_base58(w, &object.digest)
+}
+
+/// The hash in groups of four characters, with a marker before every fourth group so that a
+/// mismatch can be located when comparing with the host, e.g. "1:fc2b ce70 e1cb 980a 2:6d49 ...".
+fn write_hash_groups(w: &mut impl Write, hash: &[u8; 32]) -> core::fmt::Result {
+    let mut encoded: ArrayString<64> = ArrayString::new();
+    if Settings.is_enabled(Setting::Base64Hashes) {
+        write_base64(&mut encoded, hash)?;
+    } else {
+        write!(encoded, "{}", HexSlice(hash))?;
+    }
+    for (i, group) in encoded.as_bytes().chunks(4).enumerate() {
+        if i > 0 {
+            write!(w, " ")?;
+        }
+        if i % 4 == 0 {
+            write!(w, "{}:", i / 4 + 1)?;
+        }
+        // Both encodings are ASCII, so the groups are always valid
+        w.write_str(core::str::from_utf8(group).map_err(|_| core::fmt::Error)?)?;
+    }
+    Ok(())
+}
+
+#[derive(Clone, Copy)]
+enum ReviewField {
+    Transfer,
+    ObjectTransfer,
+    #[cfg(feature = "lending")]
+    Lending,
+    #[cfg(feature = "liquidity")]
+    Liquidity,
+    Divide,
+    Stake,
+    Mint,
+    Unstake,
+    From,
+    FullFrom,
+    // Which of the accounts of the user signs, as the address alone does not say
+    Account,
+    Command(usize),
+    // Each object input of the sender, with its version and digest
+    Object(usize),
+    // The package of a call, and each coin it is for which is not IOTA
+    Package,
+    Validator,
+    CoinType(usize),
+    To,
+    FullTo,
+    // The label of LABEL_RECIPIENT, which nothing checks
+    ClaimedName,
+    #[cfg(feature = "address_book")]
+    Recipient,
+    Amount(usize),
+    // What an unstake withdraws, in place of the amount, which the transaction does not have
+    Withdrawn,
+    GasObject,
+    GasPrice,
+    MaxGas,
+    Expiration,
+}
+
+// The most fields a review has: the operation, From, FullFrom and Account, each command and object
+// with expert mode, the package and two coin types, the four of the recipient, two amounts, and
+// the gas object, gas price, maximum gas and expiration. Liquidity, in builds which have it, has
+// all of those which a stake, with its validator, does not.
+pub(crate) const MAX_REVIEW_FIELDS: usize = MAX_COMMANDS + MAX_OWNED_OBJECTS + 17;
+
+// Shown along with the fields of a review: the warnings of a mint, of a burn address and of a
+// similar address, and the hash
+pub(crate) const MAX_REVIEW_EXTRAS: usize = 4;
+
+// The NBGL review has its own navigation, and its scroller only fails when it runs out of room.
+const CAN_GO_BACK: bool = cfg!(not(any(target_os = "stax", target_os = "flex")));
+
+#[inline(never)]
+fn show_review_field(summary: &TxSummary, path: &[u32], field: ReviewField) -> Option<()> {
+    match field {
+        // An overview, so the details which follow have some context
+        ReviewField::Transfer => match &summary.operation {
+            Operation::Transfer(transfer) => scroller_paginated("Transfer", |w| {
+                write_count(w, 1, "recipient")?;
+                write!(w, ", ")?;
+                write_count(w, transfer.amount_count, "amount")?;
+                write!(w, ", gas ")?;
+                Ok(write_amount(w, summary.gas.budget)?)
+            }),
+            _ => Some(()),
+        },
+        ReviewField::ObjectTransfer => match &summary.operation {
+            Operation::ObjectTransfer(transfer) => scroller_paginated("Transfer", |w| {
+                write!(w, "Transferring ")?;
+                Ok(write_object_counts(w, transfer)?)
+            }),
+            _ => Some(()),
+        },
+        #[cfg(feature = "lending")]
+        ReviewField::Lending => match &summary.operation {
+            Operation::Lending(lending) => scroller_paginated("Lending", |w| {
+                write!(w, "{} ", lending_verb(lending.action))?;
+                write_coin_amount(w, &lending.coin_type, lending.amount)?;
+                if lending.action == LendingAction::Deposit {
+                    write!(w, " as collateral")?;
+                }
+                Ok(())
+            }),
+            _ => Some(()),
+        },
+        #[cfg(feature = "liquidity")]
+        ReviewField::Liquidity => match &summary.operation {
+            Operation::Liquidity(liquidity) => scroller_paginated("Liquidity", |w| {
+                match liquidity.action {
+                    LiquidityAction::Add => write!(w, "Add ")?,
+                    LiquidityAction::Remove => write!(w, "Remove for at least ")?,
+                }
+                write_coin_amount(w, &liquidity.coin_types[0], liquidity.amounts[0])?;
+                write!(w, " and ")?;
+                Ok(write_coin_amount(
+                    w,
+                    &liquidity.coin_types[1],
+                    liquidity.amounts[1],
+                )?)
+            }),
+            _ => Some(()),
+        },
+        ReviewField::Divide => match &summary.operation {
+            Operation::Divide(divide) => {
+                scroller_paginated("Divide", |w| Ok(write_division(w, divide)?))
+            }
+            _ => Some(()),
+        },
+        ReviewField::Stake => match &summary.operation {
+            Operation::Stake(stake) => {
+                scroller_paginated("Stake", |w| Ok(write_amount(w, stake.amount)?))
+            }
+            _ => Some(()),
+        },
+        ReviewField::Mint => match &summary.operation {
+            Operation::Mint(mint) => scroller_paginated("Mint", |w| {
+                write!(w, "Mint ")?;
+                Ok(write_coin_amount(w, &mint.coin_type, mint.amount)?)
+            }),
+            _ => Some(()),
+        },
+        ReviewField::Unstake => match &summary.operation {
+            Operation::Unstake(unstake) => {
+                scroller_paginated("Unstake", |w| Ok(write_unstake(w, unstake)?))
+            }
+            _ => Some(()),
+        },
+        ReviewField::From => {
+            scroller_paginated("From", |w| Ok(write_review_address(w, &summary.sender)?))
+        }
+        ReviewField::FullFrom => show_full_address("From", &summary.sender),
+        ReviewField::Account => scroller("Account", |w| Ok(write_account_index(w, path)?)),
+        ReviewField::Command(i) => match (summary.commands[i], &summary.call) {
+            // There is only ever one call, so it is the one of the summary
+            ("MoveCall", Some(call)) => scroller_paginated("Command", |w| {
+                write!(w, "{}/{}: ", i + 1, summary.commands.len())?;
+                Ok(write_call(w, call)?)
+            }),
+            (name, _) => scroller("Command", |w| {
+                Ok(write!(w, "{}/{}: {}", i + 1, summary.commands.len(), name)?)
+            }),
+        },
+        // Objects past the first are only counted, like the gas objects
+        ReviewField::Object(i) => match summary.objects.first.get(i) {
+            Some(object) => scroller_paginated("Object", |w| {
+                write!(w, "{}/{}: ", i + 1, summary.objects.count)?;
+                write_owned_object(w, object)?;
+                let shown = summary.objects.first.len();
+                if i + 1 == shown && summary.objects.count > shown {
+                    write!(w, " and {} more", summary.objects.count - shown)?;
+                }
+                Ok(())
+            }),
+            None => Some(()),
+        },
+        // Always in full or by name, as the package is what the user trusts with the call
+        ReviewField::Package => match summary.operation.package() {
+            Some(package) => scroller_paginated("Package", |w| Ok(write_package(w, package)?)),
+            None => Some(()),
+        },
+        ReviewField::Validator => match &summary.operation {
+            Operation::Stake(stake) => {
+                scroller_paginated("Validator", |w| Ok(write_validator(w, &stake.validator)?))
+            }
+            _ => Some(()),
+        },
+        ReviewField::CoinType(i) => {
+            let header = match &summary.operation {
+                #[cfg(feature = "liquidity")]
+                Operation::Liquidity(_) => COIN_TYPE_HEADERS[i],
+                _ => "Coin Type",
+            };
+            match coins(&summary.operation).get(i) {
+                Some((CoinType::Other(tag), _)) => {
+                    scroller_paginated(header, |w| Ok(write_coin_type(w, tag)?))
+                }
+                _ => Some(()),
+            }
+        }
+        ReviewField::To => match summary.operation.recipient() {
+            Some(recipient) => {
+                scroller_paginated("To", |w| Ok(write_review_address(w, recipient)?))
+            }
+            None => Some(()),
+        },
+        ReviewField::FullTo => match summary.operation.recipient() {
+            Some(recipient) => show_full_address("To", recipient),
+            None => Some(()),
+        },
+        ReviewField::ClaimedName => match summary.operation.recipient() {
+            Some(recipient) => match session_labels::lookup(recipient) {
+                Some(label) => {
+                    scroller_paginated("Claimed Name", |w| Ok(write!(w, "{label} (unverified)")?))
+                }
+                None => Some(()),
+            },
+            None => Some(()),
+        },
+        #[cfg(feature = "address_book")]
+        ReviewField::Recipient => match summary.operation.recipient().map(address_book::lookup) {
+            Some(Some(label)) => scroller("Recipient", |w| Ok(write!(w, "Known: {label}")?)),
+            Some(None) => scroller("Recipient", |w| Ok(write!(w, "Not in address book")?)),
+            None => Some(()),
+        },
+        ReviewField::Amount(i) => {
+            let header = match &summary.operation {
+                #[cfg(feature = "liquidity")]
+                Operation::Liquidity(LiquiditySummary {
+                    action: LiquidityAction::Remove,
+                    ..
+                }) => MINIMUM_HEADERS[i],
+                #[cfg(feature = "liquidity")]
+                Operation::Liquidity(_) => AMOUNT_HEADERS[i],
+                _ => "Amount",
+            };
+            match coins(&summary.operation).get(i) {
+                Some((coin_type, amount)) => {
+                    scroller_paginated(header, |w| Ok(write_coin_amount(w, coin_type, *amount)?))
+                }
+                None => Some(()),
+            }
+        }
+        ReviewField::Withdrawn => match &summary.operation {
+            Operation::Unstake(_) => {
+                scroller_paginated("Withdraw", |w| Ok(write!(w, "{UNSTAKE_WITHDRAWN}")?))
+            }
+            _ => Some(()),
+        },
+        ReviewField::GasObject => match summary.gas.object_count {
+            1 => scroller_paginated("Gas Object", |w| {
+                Ok(write!(w, "0x{}", HexSlice(&summary.gas.object))?)
+            }),
+            n => scroller_paginated("Gas Objects", |w| {
+                Ok(write!(
+                    w,
+                    "0x{} and {} more",
+                    HexSlice(&summary.gas.object),
+                    n - 1
+                )?)
+            }),
+        },
+        ReviewField::GasPrice => scroller("Gas Price", |w| Ok(write!(w, "{}", summary.gas.price)?)),
+        ReviewField::MaxGas => scroller("Max Gas", |w| Ok(write_amount(w, summary.gas.budget)?)),
+        ReviewField::Expiration => match summary.expiration {
+            None => scroller("Expiration", |w| Ok(write!(w, "None")?)),
+            Some(epoch) => scroller("Expiration", |w| Ok(write!(w, "Epoch {epoch}")?)),
+        },
+    }
+}
+
+// With the LimitReview setting, the fields shown before asking to go on
+const REVIEW_LIMIT: usize = 8;
+
+// Declining rejects the transaction, as it cannot be signed without being reviewed fully
+#[cold]
+#[inline(never)]
+fn want_more_fields(remaining: usize) -> bool {
+    let mut prompt: ArrayString<24> = ArrayString::new();
+    if write!(prompt, "Show {remaining} more items?").is_err() {
+        return false;
+    }
+    want_details(&[prompt.as_str()])
+}
+
+#[cold]
+#[inline(never)]
+fn warn_unrecoverable() -> Option<()> {
+    warning_screen("Burn Address", "Funds will be unrecoverable")?;
+    scroller("WARNING", |w| Ok(write!(w, "Funds will be unrecoverable")?))
+}
+
+#[cold]
+#[inline(never)]
+fn warn_mint() -> Option<()> {
+    warning_screen("Mint", "New coins will be created")?;
+    scroller("WARNING", |w| {
+        Ok(write!(
+            w,
+            "New coins will be created, adding to the supply"
+        )?)
+    })
+}
+
+// Shown before the review, as the review itself would show the recipient as not in the book
+#[cfg(feature = "address_book")]
+#[cold]
+#[inline(never)]
+fn warn_similar_address(label: &str) -> Option<()> {
+    warning_screen(
+        "Similar Address",
+        "The recipient looks like a saved address, but is not it",
+    )?;
+    scroller_paginated("WARNING", |w| {
+        Ok(write!(
+            w,
+            "Similar to saved address '{label}', possible poisoning"
+        )?)
+    })
+}
+
+// The fields of the review of `summary`, in the order they are shown
+fn review_fields(
+    summary: &TxSummary,
+    path: &[u32],
+    expert: bool,
+    truncated: bool,
+) -> ArrayVec<ReviewField, MAX_REVIEW_FIELDS> {
+    let mut fields: ArrayVec<ReviewField, MAX_REVIEW_FIELDS> = ArrayVec::new();
+    match &summary.operation {
+        Operation::Transfer(_) => fields.push(ReviewField::Transfer),
+        Operation::ObjectTransfer(_) => fields.push(ReviewField::ObjectTransfer),
+        #[cfg(feature = "lending")]
+        Operation::Lending(_) => fields.push(ReviewField::Lending),
+        #[cfg(feature = "liquidity")]
+        Operation::Liquidity(_) => fields.push(ReviewField::Liquidity),
+        Operation::Divide(_) => fields.push(ReviewField::Divide),
+        Operation::Stake(_) => fields.push(ReviewField::Stake),
+        Operation::Mint(_) => fields.push(ReviewField::Mint),
+        Operation::Unstake(_) => fields.push(ReviewField::Unstake),
+    }
+    fields.push(ReviewField::From);
+    if truncated {
+        fields.push(ReviewField::FullFrom);
+    }
+    if path.len() > 2 {
+        fields.push(ReviewField::Account);
+    }
+    if expert {
+        for i in 0..summary.commands.len() {
+            fields.push(ReviewField::Command(i));
+        }
+        for i in 0..summary.objects.first.len() {
+            fields.push(ReviewField::Object(i));
+        }
+    }
+    let coins = coins(&summary.operation);
+    if let Operation::Stake(_) = summary.operation {
+        fields.push(ReviewField::Validator);
+    }
+    if summary.operation.package().is_some() {
+        fields.push(ReviewField::Package);
+        for (i, (coin_type, _)) in coins.iter().enumerate() {
+            if **coin_type != CoinType::Iota {
+                fields.push(ReviewField::CoinType(i));
+            }
+        }
+    }
+    if summary.operation.recipient().is_some() {
+        fields.push(ReviewField::To);
+        if truncated {
+            fields.push(ReviewField::FullTo);
+        }
+        fields.push(ReviewField::ClaimedName);
+        #[cfg(feature = "address_book")]
+        fields.push(ReviewField::Recipient);
+    }
+    for i in 0..coins.len() {
+        fields.push(ReviewField::Amount(i));
+    }
+    if let Operation::Unstake(_) = summary.operation {
+        fields.push(ReviewField::Withdrawn);
+    }
+    if expert && summary.gas.object_count > 0 {
+        fields.push(ReviewField::GasObject);
+    }
+    if expert {
+        fields.push(ReviewField::GasPrice);
+    }
+    fields.push(ReviewField::MaxGas);
+    if expert {
+        fields.push(ReviewField::Expiration);
+    }
+    fields
+}
+
+/// Show each field of a known transaction signed with the key for `path`, except for the final
+/// prompt.
+///
+/// Going back from the first page of a field shows the previous field again, going back from the
+/// first field rejects.
+#[inline(never)]
+pub fn review_transaction(summary: &TxSummary, path: &[u32], settings: Settings) -> Option<()> {
+    let expert = cfg!(feature = "expert_screens") && settings.is_enabled(Setting::ExpertMode);
+    let truncated = settings.is_enabled(Setting::TruncatedAddresses);
+    let fields = review_fields(summary, path, expert, truncated);
+
+    // Minting is only ever done by the issuer of a coin, so anyone else asked to sign it is warned
+    if let Operation::Mint(_) = summary.operation {
+        warn_mint()?;
+    }
+    // Before the review, as sends to a burn address are almost always a mistake
+    if summary
+        .operation
+        .recipient()
+        .map_or(false, patterns::is_burn_address)
+    {
+        warn_unrecoverable()?;
+    }
+    #[cfg(feature = "address_book")]
+    if let Some(label) = summary
+        .operation
+        .recipient()
+        .and_then(address_book::lookup_similar)
+    {
+        warn_similar_address(&label)?;
+    }
+
+    // Only offered on BAGL, see Setting::LimitReview
+    let limited = cfg!(not(any(target_os = "stax", target_os = "flex")))
+        && settings.is_enabled(Setting::LimitReview);
+    let mut more_confirmed = false;
+    let mut i = 0;
+    while i < fields.len() {
+        if limited && i == REVIEW_LIMIT && !more_confirmed {
+            if !want_more_fields(fields.len() - i) {
+                return None;
+            }
+            more_confirmed = true;
+        }
+        match show_review_field(summary, path, fields[i]) {
+            Some(()) => i += 1,
+            None if CAN_GO_BACK && i > 0 => i -= 1,
+            None => return None,
+        }
+    }
+    Some(())
+}
+
+// Like "To 0x4f23...ba4b", short enough for a line of the final prompt
+fn write_recap_recipient(w: &mut impl Write, recipient: &IotaAddressRaw) -> core::fmt::Result {
+    write!(
+        w,
+        "To 0x{}...{}",
+        HexSlice(&recipient[..2]),
+        HexSlice(&recipient[IOTA_ADDRESS_LENGTH - 2..])
+    )
+}
+
+// A line of the recap with an amount, long enough for the longest one, which is of gas, at the
+// largest budget: "Gas IOTA 18 446 744 073.709551615", or "Gas 18446744073709551615 nanos"
+type RecapAmount = ArrayString<36>;
+
+fn write_recap_amount(w: &mut impl Write, coin_type: &CoinType, amount: u64) -> core::fmt::Result {
+    match coin_type {
+        CoinType::Iota => write_amount(w, amount),
+        // The coin type does not fit, it was shown before
+        CoinType::Other(_) => write!(w, "{} base units", amount),
+    }
+}
+
+fn write_recap_gas(w: &mut impl Write, budget: u64) -> core::fmt::Result {
+    write!(w, "Gas ")?;
+    write_amount(w, budget)
+}
+
+/// The final prompt, with a short recap of the transaction so that the last thing seen before
+/// approving is the whole picture.
+#[inline(never)]
+pub fn final_recap(summary: &TxSummary) -> Option<()> {
+    let mut what: ArrayString<32> = ArrayString::new();
+    // What the operation is of, for the transfers of objects, the divisions, the recipients of
+    // what is minted and the rewards unstakes withdraw
+    let mut detail: ArrayString<40> = ArrayString::new();
+    let mut amounts: [RecapAmount; 2] = [ArrayString::new(), ArrayString::new()];
+    let mut gas: RecapAmount = ArrayString::new();
+    match &summary.operation {
+        Operation::Transfer(TransferSummary { recipient, .. })
+        | Operation::ObjectTransfer(ObjectTransferSummary { recipient, .. }) => {
+            write_recap_recipient(&mut what, recipient).ok()?;
+        }
+        #[cfg(feature = "lending")]
+        Operation::Lending(lending) => {
+            write!(what, "{}", lending_verb(lending.action)).ok()?;
+        }
+        #[cfg(feature = "liquidity")]
+        Operation::Liquidity(liquidity) => match liquidity.action {
+            LiquidityAction::Add => write!(what, "Add Liquidity").ok()?,
+            LiquidityAction::Remove => write!(what, "Remove Liquidity").ok()?,
+        },
+        Operation::Divide(divide) => {
+            write!(what, "Divide").ok()?;
+            write!(detail, "Into {} coins", divide.count).ok()?;
+        }
+        Operation::Stake(_) => write!(what, "Stake").ok()?,
+        Operation::Mint(MintSummary { recipient, .. }) => {
+            write!(what, "Mint").ok()?;
+            write_recap_recipient(&mut detail, recipient).ok()?;
+        }
+        Operation::Unstake(unstake) => {
+            write!(what, "Unstake").ok()?;
+            if unstake.object == StakedObject::TimelockedStakedIota {
+                write!(detail, "Timelocked, ").ok()?;
+            }
+            write!(detail, "with all rewards").ok()?;
+        }
+    }
+    let coins = coins(&summary.operation);
+    for ((coin_type, amount), line) in coins.iter().zip(amounts.iter_mut()) {
+        write_recap_amount(line, coin_type, *amount).ok()?;
+    }
+    write_recap_gas(&mut gas, summary.gas.budget).ok()?;
+    let mut lines: ArrayVec<&str, 5> = ArrayVec::new();
+    lines.push("Sign Transaction?");
+    lines.push(what.as_str());
+    if let Operation::ObjectTransfer(transfer) = &summary.operation {
+        write_object_counts(&mut detail, transfer).ok()?;
+    }
+    if !detail.is_empty() {
+        lines.push(detail.as_str());
+    }
+    for line in &amounts[..coins.len()] {
+        lines.push(line.as_str());
+    }
+    lines.push(gas.as_str());
+    final_accept_prompt(&lines)
+}
+
+pub fn show_hash(hash: &HexHash<32>) -> Option<()> {
+    scroller("Transaction Hash", |w| Ok(write_hash_groups(w, &hash.0)?))
+}
+
+#[cfg(all(target_family = "bolos", test))]
+mod tests {
+    use super::*;
+    use crate::parser::{GasSummary, ObjectsSummary};
+    use ledger_device_sdk::assert_eq_err as assert_eq;
+    use ledger_device_sdk::testing::TestType;
+    use testmacro::test_item as test;
+
+    fn grouped(n: u64) -> ArrayString<32> {
+        let mut s = ArrayString::new();
+        let _ = write_grouped(&mut s, n);
+        s
+    }
+
+    fn package(id: &ObjectIdRaw) -> ArrayString<80> {
+        let mut s = ArrayString::new();
+        let _ = write_package(&mut s, id);
+        s
+    }
+
+    #[test]
+    fn known_packages_by_name() {
+        let mut id = [0; 32];
+        id[31] = 3;
+        assert_eq!(package(&id).as_str(), "IOTA System (0x3)");
+        // Not the framework, though it ends like it
+        id[0] = 0x10;
+        id[31] = 2;
+        assert_eq!(
+            package(&id).as_str(),
+            "0x1000000000000000000000000000000000000000000000000000000000000002"
+        );
+    }
+
+    fn pure_value(value: PureValue) -> ArrayString<80> {
+        let mut s = ArrayString::new();
+        let _ = write_pure_value(&mut s, &value);
+        s
+    }
+
+    #[test]
+    fn pure_values_by_type() {
+        assert_eq!(pure_value(PureValue::Bool(true)).as_str(), "bool true");
+        assert_eq!(pure_value(PureValue::U8(255)).as_str(), "u8 255");
+        assert_eq!(pure_value(PureValue::U16(513)).as_str(), "u16 513");
+        assert_eq!(pure_value(PureValue::U64(5)).as_str(), "u64 5");
+        assert_eq!(
+            pure_value(PureValue::U128([0xff; 16])).as_str(),
+            "u128 340282366920938463463374607431768211455"
+        );
+        let mut two_pow_64 = [0; 16];
+        two_pow_64[8] = 1;
+        assert_eq!(
+            pure_value(PureValue::U128(two_pow_64)).as_str(),
+            "u128 18446744073709551616"
+        );
+    }
+
+    fn owned_object(digest: [u8; 32]) -> ArrayString<160> {
+        let object = OwnedObject {
+            id: [0xab; 32],
+            version: 7,
+            digest,
+        };
+        let mut s = ArrayString::new();
+        let _ = write_owned_object(&mut s, &object);
+        s
+    }
+
+    #[test]
+    fn objects_with_version_and_digest() {
+        let mut digest = [0; 32];
+        for (i, b) in digest.iter_mut().enumerate() {
+            *b = i as u8;
+        }
+        assert_eq!(
+            owned_object(digest).as_str(),
+            "0xabababababababababababababababababababababababababababababababab, Version 7, \
+             Digest 1thX6LZfHDZZKUs92febYZhYRcXddmzfzF2NvTkPNE"
+        );
+        assert_eq!(
+            owned_object([0xff; 32]).as_str(),
+            "0xabababababababababababababababababababababababababababababababab, Version 7, \
+             Digest JEKNVnkbo3jma5nREBBJCDoXFVeKkD56V3xKrvRmWxFG"
+        );
+        // Leading zero bytes are kept
+        assert_eq!(
+            owned_object([0; 32]).as_str(),
+            "0xabababababababababababababababababababababababababababababababab, Version 7, \
+             Digest 11111111111111111111111111111111"
+        );
+    }
+
+    fn object_counts(object_count: usize, iota_coin_count: usize) -> ArrayString<48> {
+        let transfer = ObjectTransferSummary {
+            recipient: [1; 32],
+            object_count,
+            iota_coin_count,
+            split_amount: None,
+        };
+        let mut s = ArrayString::new();
+        let _ = write_object_counts(&mut s, &transfer);
+        s
+    }
+
+    #[test]
+    fn objects_counted_by_kind() {
+        assert_eq!(
+            object_counts(3, 2).as_str(),
+            "3 objects (2 Coin<IOTA>, 1 other)"
+        );
+        assert_eq!(object_counts(1, 0).as_str(), "1 object (1 other)");
+        assert_eq!(object_counts(2, 2).as_str(), "2 objects (2 Coin<IOTA>)");
+    }
+
+    fn division(amount: u64, count: u64) -> ArrayString<128> {
+        let divide = DivideSummary {
+            package: patterns::IOTA_FRAMEWORK_ADDRESS,
+            coin_type: CoinType::Iota,
+            amount,
+            count,
+            recipient: [0x6f; 32],
+        };
+        let mut s = ArrayString::new();
+        let _ = write_division(&mut s, &divide);
+        s
+    }
+
+    #[test]
+    fn division_with_and_without_remainder() {
+        assert_eq!(
+            division(3_000_000_000, 3).as_str(),
+            "Split IOTA 3.0 into 3 coins of IOTA 1.0"
+        );
+        assert_eq!(
+            division(10_000_000_000, 3).as_str(),
+            "Split IOTA 10.0 into 3 coins of IOTA 3.333333333, one of them with IOTA 0.000000001 more"
+        );
+    }
+
+    #[test]
+    fn validator_not_in_the_address_book() {
+        let mut s: ArrayString<80> = ArrayString::new();
+        let _ = write_validator(&mut s, &[0x77; 32]);
+        assert_eq!(
+            s.as_str(),
+            "0x7777777777777777777777777777777777777777777777777777777777777777"
+        );
+    }
+
+    fn usdc() -> CoinType {
+        CoinType::Other(StructTag {
+            address: [0x5d; 32],
+            module: ArrayString::from("usdc").unwrap(),
+            name: ArrayString::from("USDC").unwrap(),
+        })
+    }
+
+    #[test]
+    fn worst_case_expert_review_fits() {
+        let object = OwnedObject {
+            id: [0xa1; 32],
+            version: 4,
+            digest: [0xd1; 32],
+        };
+        #[cfg(feature = "liquidity")]
+        let operation = Operation::Liquidity(LiquiditySummary {
+            action: LiquidityAction::Add,
+            package: [0x5b; 32],
+            coin_types: [usdc(), usdc()],
+            amounts: [u64::MAX, u64::MAX],
+            recipient: Some([0x22; 32]),
+        });
+        // Without pools, a mint has the most, with one coin type and one amount
+        #[cfg(not(feature = "liquidity"))]
+        let operation = Operation::Mint(MintSummary {
+            package: patterns::IOTA_FRAMEWORK_ADDRESS,
+            coin_type: usdc(),
+            amount: u64::MAX,
+            recipient: [0x22; 32],
+        });
+        let summary = TxSummary {
+            sender: [0x11; 32],
+            operation,
+            commands: (0..MAX_COMMANDS).map(|_| "MoveCall").collect(),
+            call: None,
+            objects: ObjectsSummary {
+                first: (0..MAX_OWNED_OBJECTS).map(|_| object).collect(),
+                count: MAX_OWNED_OBJECTS + 3,
+            },
+            gas: GasSummary {
+                object: [0x33; 32],
+                object_count: 2,
+                price: u64::MAX,
+                budget: u64::MAX,
+            },
+            expiration: Some(u64::MAX),
+        };
+        let path = [44 | HARDENED, 4218 | HARDENED, HARDENED, 0, 7];
+        let fields = review_fields(&summary, &path, true, true);
+        // All but the address book one, which is only there with the address book, and the coin
+        // type and amount only liquidity has a second of
+        let fewer = usize::from(!cfg!(feature = "address_book"))
+            + 2 * usize::from(!cfg!(feature = "liquidity"));
+        assert_eq!(fields.len(), MAX_REVIEW_FIELDS - fewer);
+    }
+
+    #[test]
+    fn recap_fits_the_largest_amounts() {
+        let mut settings = Settings;
+        let base_units = settings.is_enabled(Setting::BaseUnits);
+        for enabled in [false, true] {
+            settings.set_enabled(Setting::BaseUnits, enabled);
+            let mut gas = RecapAmount::new();
+            assert_eq!(write_recap_gas(&mut gas, u64::MAX).is_ok(), true);
+            for coin_type in [CoinType::Iota, usdc()] {
+                let mut line = RecapAmount::new();
+                assert_eq!(
+                    write_recap_amount(&mut line, &coin_type, u64::MAX).is_ok(),
+                    true
+                );
+            }
+        }
+        settings.set_enabled(Setting::BaseUnits, base_units);
+    }
+
+    #[test]
+    fn amount_zero() {
+        let (quotient, remainder) = get_amount_in_decimals(0);
+        assert_eq!(quotient, 0);
+        assert_eq!(remainder.as_str(), "0");
+        assert_eq!(grouped(quotient).as_str(), "0");
+    }
+
+    #[test]
+    fn amount_exact_iota() {
+        let (quotient, remainder) = get_amount_in_decimals(1_000_000_000);
+        assert_eq!(quotient, 1);
+        assert_eq!(remainder.as_str(), "0");
+        let (quotient, remainder) = get_amount_in_decimals(1_234_000_000_000);
+        assert_eq!(quotient, 1234);
+        assert_eq!(remainder.as_str(), "0");
+    }
+
+    #[test]
+    fn amount_fraction() {
+        let (quotient, remainder) = get_amount_in_decimals(1_036);
+        assert_eq!(quotient, 0);
+        assert_eq!(remainder.as_str(), "000001036");
+        let (quotient, remainder) = get_amount_in_decimals(1_234_567_500_000_000);
+        assert_eq!(quotient, 1_234_567);
+        assert_eq!(remainder.as_str(), "5");
+    }
+
+    #[test]
+    fn amount_max() {
+        let (quotient, remainder) = get_amount_in_decimals(u64::MAX);
+        assert_eq!(quotient, 18_446_744_073);
+        assert_eq!(remainder.as_str(), "709551615");
+        assert_eq!(grouped(quotient).as_str(), "18 446 744 073");
+    }
+
+    #[test]
+    fn grouping() {
+        assert_eq!(grouped(999).as_str(), "999");
+        assert_eq!(grouped(1_000).as_str(), "1 000");
+        assert_eq!(grouped(1_234_567).as_str(), "1 234 567");
+        assert_eq!(grouped(1_000_001).as_str(), "1 000 001");
+        assert_eq!(grouped(u64::MAX).as_str(), "18 446 744 073 709 551 615");
+    }
+}
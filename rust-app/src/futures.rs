@@ -0,0 +1,39 @@
+// Futures for driving the parser, which do not depend on the device and so build on the host too,
+// e.g. for fuzzing the parser.
+
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+use pin_project::pin_project;
+#[pin_project]
+pub struct NoinlineFut<F: Future>(#[pin] pub F);
+
+impl<F: Future> Future for NoinlineFut<F> {
+    type Output = F::Output;
+    #[inline(never)]
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        #[cfg(all(target_family = "bolos", feature = "memory_stats"))]
+        crate::memory_stats::sample();
+        self.project().0.poll(cx)
+    }
+}
+
+fn noop_raw_waker() -> RawWaker {
+    fn no_op(_: *const ()) {}
+    fn clone(_: *const ()) -> RawWaker {
+        noop_raw_waker()
+    }
+    static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, no_op, no_op, no_op);
+    RawWaker::new(core::ptr::null(), &VTABLE)
+}
+
+/// Poll a future once, outside of the block protocol driver.
+///
+/// Only useful for futures which never wait on the host, e.g. ones reading from a buffer which
+/// has been received already.
+pub fn poll_once<F: Future>(fut: F) -> Poll<F::Output> {
+    let waker = unsafe { Waker::from_raw(noop_raw_waker()) };
+    let mut cx = Context::from_waker(&waker);
+    let fut = core::pin::pin!(fut);
+    fut.poll(&mut cx)
+}
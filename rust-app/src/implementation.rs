@@ -1,33 +1,49 @@
-use crate::interface::{
-    Amount, ArgumentSchema, Bip32Key, CallArgSchema, CommandSchema, EpochId, GasData, Ins, Intent,
-    IntentMessage, ObjectRef, ProgrammableTransaction, Recipient, SharedObject, TransactionData,
-    TransactionDataV1, TransactionExpiration, TransactionKind, IOTA_ADDRESS_LENGTH, U16LE,
+#[cfg(feature = "address_book")]
+use crate::address_book;
+use crate::display::{
+    final_recap, review_transaction, show_hash, write_account_index, write_bip32_path,
+};
+use crate::interface::{AppError, Bip32Key, Derivation, Ins, IOTA_ADDRESS_LENGTH};
+use crate::key_cache::{self, with_public_keys_cached};
+use crate::parser::{self, tx_parser, IotaAddressRaw};
+use crate::path::{bip_path_parser, has_high_index, is_bip_prefix_valid, STANDARD_PATH_LENGTH};
+use crate::progress;
+use crate::receipt;
+use crate::session_labels;
+use crate::settings::{Setting, Settings};
+use crate::signature_counter;
+#[cfg(feature = "test_vectors")]
+use crate::test_vectors::eddsa_sign;
+use crate::utils::{
+    final_accept_prompt, scroller, scroller_paginated, warning_screen, NoinlineFut,
 };
-use crate::settings::Settings;
-use crate::utils::{scroller, scroller_paginated, NoinlineFut};
 use alamgu_async_block::{ByteStream, HostIO};
-use arrayvec::ArrayString;
 use arrayvec::ArrayVec;
 use core::fmt::Write;
 use ledger_crypto_helpers::common::{try_option, Address, HexSlice};
-use ledger_crypto_helpers::eddsa::{ed25519_public_key_bytes, eddsa_sign, with_public_keys};
+use ledger_crypto_helpers::eddsa::ed25519_public_key_bytes;
+#[cfg(not(feature = "test_vectors"))]
+use ledger_crypto_helpers::eddsa::eddsa_sign;
 use ledger_crypto_helpers::hasher::{Blake2b, Hasher, HexHash};
-use ledger_device_sdk::io::{StatusWords, SyscallError};
-use ledger_log::trace;
+use ledger_device_sdk::io::{Comm, StatusWords, SyscallError};
 use ledger_parser_combinators::async_parser::{
-    reject, reject_on, AsyncParser, HasOutput, Readable, TryFuture,
+    reject, AsyncParser, HasOutput, Readable, TryFuture,
 };
-use ledger_parser_combinators::bcs::async_parser::{Vec, ULEB128};
-use ledger_parser_combinators::interp::{Action, DefaultInterp, SubInterp};
-use ledger_prompts_ui::{final_accept_prompt, ScrollerError};
+use ledger_parser_combinators::interp::{DefaultInterp, SubInterp};
 
 use core::convert::TryFrom;
 use core::future::Future;
+use zeroize::Zeroizing;
 
-type IotaAddressRaw = [u8; IOTA_ADDRESS_LENGTH];
-
+#[derive(Clone)]
 pub struct IotaPubKeyAddress(ledger_device_sdk::ecc::ECPublicKey<65, 'E'>, IotaAddressRaw);
 
+impl IotaPubKeyAddress {
+    pub fn public_key(&self) -> &ledger_device_sdk::ecc::ECPublicKey<65, 'E'> {
+        &self.0
+    }
+}
+
 impl Address<IotaPubKeyAddress, ledger_device_sdk::ecc::ECPublicKey<65, 'E'>>
     for IotaPubKeyAddress
 {
@@ -55,39 +71,80 @@ pub type BipParserImplT =
     impl AsyncParser<Bip32Key, ByteStream> + HasOutput<Bip32Key, Output = ArrayVec<u32, 10>>;
 pub const BIP_PATH_PARSER: BipParserImplT = SubInterp(DefaultInterp);
 
-// Need a path of length 5, as make_bip32_path panics with smaller paths
-pub const BIP32_TESTNET_PREFIX: [u32; 5] =
-    ledger_device_sdk::ecc::make_bip32_path(b"m/44'/1'/123'/0'/0'");
-pub const BIP32_IOTA_PREFIX: [u32; 5] =
-    ledger_device_sdk::ecc::make_bip32_path(b"m/44'/4218'/123'/0'/0'");
+/// Reject a path of another depth, unless expert mode is enabled and the user accepts it, so that
+/// a host cannot quietly use keys from unexpected places of the tree.
+async fn check_path_depth(path: &[u32], settings: Settings) {
+    if path.len() == STANDARD_PATH_LENGTH {
+        return;
+    }
+    if !settings.is_enabled(Setting::ExpertMode) {
+        refuse::<()>(SyscallError::InvalidParameter as u16).await;
+    }
+    if warn_path_depth(path).is_none() {
+        refuse::<()>(StatusWords::UserCancelled as u16).await;
+    }
+}
+
+#[cold]
+#[inline(never)]
+fn warn_path_depth(path: &[u32]) -> Option<()> {
+    scroller("WARNING", |w| Ok(write!(w, "Unusual derivation path")?))?;
+    scroller_paginated("Path", |w| Ok(write_bip32_path(w, path)?))?;
+    final_accept_prompt(&["Use This Path?"])
+}
+
+/// Have the user accept a path with an unusually high account or index, as a host might pick one
+/// to make the funds hard to find again.
+async fn check_path_indices(path: &[u32]) {
+    if has_high_index(path) && warn_path_indices(path).is_none() {
+        refuse::<()>(StatusWords::UserCancelled as u16).await;
+    }
+}
 
-fn is_bip_prefix_valid(path: &[u32]) -> bool {
-    path.starts_with(&BIP32_TESTNET_PREFIX[0..2]) || path.starts_with(&BIP32_IOTA_PREFIX[0..2])
+#[cold]
+#[inline(never)]
+fn warn_path_indices(path: &[u32]) -> Option<()> {
+    scroller("WARNING", |w| {
+        Ok(write!(w, "Unusually high account or index")?)
+    })?;
+    scroller("Account", |w| Ok(write_account_index(w, path)?))?;
+    final_accept_prompt(&["Use This Path?"])
 }
 
-pub async fn get_address_apdu(io: HostIO, prompt: bool) {
+pub async fn get_address_apdu(
+    io: HostIO,
+    prompt: bool,
+    settings: Settings,
+    derivation: Derivation,
+) {
     let input = match io.get_params::<1>() {
         Some(v) => v,
         None => reject(SyscallError::InvalidParameter as u16).await,
     };
 
-    let path = BIP_PATH_PARSER.parse(&mut input[0].clone()).await;
+    let path = Zeroizing::new(BIP_PATH_PARSER.parse(&mut input[0].clone()).await);
 
     if !is_bip_prefix_valid(&path) {
         reject::<()>(SyscallError::InvalidParameter as u16).await;
     }
+    check_path_depth(&path, settings).await;
+    check_path_indices(&path).await;
 
     let mut rv = ArrayVec::<u8, 220>::new();
 
-    if with_public_keys(&path, true, |key, address: &IotaPubKeyAddress| {
+    if with_public_keys_cached(&path, derivation, |address| {
         try_option(|| -> Option<()> {
             if prompt {
                 scroller("Provide Public Key", |_w| Ok(()))?;
+                if path.len() > 2 {
+                    scroller("Account", |w| Ok(write_account_index(w, &path)?))?;
+                }
+                scroller_paginated("Path", |w| Ok(write_bip32_path(w, &path)?))?;
                 scroller_paginated("Address", |w| Ok(write!(w, "{address}")?))?;
                 final_accept_prompt(&[])?;
             }
 
-            let key_bytes = ed25519_public_key_bytes(key);
+            let key_bytes = ed25519_public_key_bytes(address.public_key());
 
             rv.try_push(u8::try_from(key_bytes.len()).ok()?).ok()?;
             rv.try_extend_from_slice(key_bytes).ok()?;
@@ -107,592 +164,227 @@ pub async fn get_address_apdu(io: HostIO, prompt: bool) {
     io.result_final(&rv).await;
 }
 
-pub enum CallArg {
-    RecipientAddress(IotaAddressRaw),
-    Amount(u64),
-    OtherPure,
-    ObjectArg,
-}
-
-impl HasOutput<CallArgSchema> for DefaultInterp {
-    type Output = CallArg;
-}
+#[cfg(feature = "address_book")]
+pub async fn save_address_apdu(io: HostIO, settings: Settings, derivation: Derivation) {
+    use crate::interface::AddressLabel;
 
-impl<BS: Clone + Readable> AsyncParser<CallArgSchema, BS> for DefaultInterp {
-    type State<'c> = impl Future<Output = Self::Output> + 'c where BS: 'c;
-    fn parse<'a: 'c, 'b: 'c, 'c>(&'b self, input: &'a mut BS) -> Self::State<'c> {
-        async move {
-            let enum_variant =
-                <DefaultInterp as AsyncParser<ULEB128, BS>>::parse(&DefaultInterp, input).await;
-            match enum_variant {
-                0 => {
-                    let length =
-                        <DefaultInterp as AsyncParser<ULEB128, BS>>::parse(&DefaultInterp, input)
-                            .await;
-                    trace!("CallArgSchema: Pure: length: {}", length);
-                    match length {
-                        8 => CallArg::Amount(
-                            <DefaultInterp as AsyncParser<Amount, BS>>::parse(
-                                &DefaultInterp,
-                                input,
-                            )
-                            .await,
-                        ),
-                        32 => CallArg::RecipientAddress(
-                            <DefaultInterp as AsyncParser<Recipient, BS>>::parse(
-                                &DefaultInterp,
-                                input,
-                            )
-                            .await,
-                        ),
-                        _ => {
-                            for _ in 0..length {
-                                let _: [u8; 1] = input.read().await;
-                            }
-                            CallArg::OtherPure
-                        }
-                    }
-                }
-                1 => {
-                    let enum_variant =
-                        <DefaultInterp as AsyncParser<ULEB128, BS>>::parse(&DefaultInterp, input)
-                            .await;
-                    match enum_variant {
-                        0 => {
-                            trace!("CallArgSchema: ObjectArg: ImmOrOwnedObject");
-                            object_ref_parser().parse(input).await;
-                        }
-                        1 => {
-                            trace!("CallArgSchema: ObjectArg: SharedObject");
-                            <(DefaultInterp, DefaultInterp, DefaultInterp) as AsyncParser<
-                                SharedObject,
-                                BS,
-                            >>::parse(
-                                &(DefaultInterp, DefaultInterp, DefaultInterp), input
-                            )
-                            .await;
-                        }
-                        _ => {
-                            reject_on(
-                                core::file!(),
-                                core::line!(),
-                                SyscallError::NotSupported as u16,
-                            )
-                            .await
-                        }
-                    }
-                    CallArg::ObjectArg
-                }
-                _ => {
-                    trace!("CallArgSchema: Unknown enum: {}", enum_variant);
-                    reject_on(
-                        core::file!(),
-                        core::line!(),
-                        SyscallError::NotSupported as u16,
-                    )
-                    .await
-                }
-            }
-        }
-    }
-}
+    let input = match io.get_params::<2>() {
+        Some(v) => v,
+        None => reject(SyscallError::InvalidParameter as u16).await,
+    };
 
-pub const TRANSFER_OBJECT_ARRAY_LENGTH: usize = 1;
-pub const SPLIT_COIN_ARRAY_LENGTH: usize = 8;
+    let path = Zeroizing::new(BIP_PATH_PARSER.parse(&mut input[0].clone()).await);
 
-pub enum Command {
-    TransferObject(ArrayVec<Argument, TRANSFER_OBJECT_ARRAY_LENGTH>, Argument),
-    SplitCoins(Argument, ArrayVec<Argument, SPLIT_COIN_ARRAY_LENGTH>),
-}
+    if !is_bip_prefix_valid(&path) {
+        reject::<()>(SyscallError::InvalidParameter as u16).await;
+    }
+    check_path_depth(&path, settings).await;
+    check_path_indices(&path).await;
 
-impl HasOutput<CommandSchema> for DefaultInterp {
-    type Output = Command;
-}
+    let label_bytes = <SubInterp<DefaultInterp> as AsyncParser<AddressLabel, ByteStream>>::parse(
+        &SubInterp(DefaultInterp),
+        &mut input[1].clone(),
+    )
+    .await;
+    let label = match core::str::from_utf8(&label_bytes) {
+        Ok(label)
+            if !label.is_empty() && label.bytes().all(|b| b.is_ascii_graphic() || b == b' ') =>
+        {
+            label
+        }
+        _ => reject(SyscallError::InvalidParameter as u16).await,
+    };
 
-impl<BS: Clone + Readable> AsyncParser<CommandSchema, BS> for DefaultInterp {
-    type State<'c> = impl Future<Output = Self::Output> + 'c where BS: 'c;
-    fn parse<'a: 'c, 'b: 'c, 'c>(&'b self, input: &'a mut BS) -> Self::State<'c> {
-        async move {
-            let enum_variant =
-                <DefaultInterp as AsyncParser<ULEB128, BS>>::parse(&DefaultInterp, input).await;
-            match enum_variant {
-                1 => {
-                    trace!("CommandSchema: TransferObject");
-                    let v1 = <SubInterp<DefaultInterp> as AsyncParser<
-                        Vec<ArgumentSchema, TRANSFER_OBJECT_ARRAY_LENGTH>,
-                        BS,
-                    >>::parse(&SubInterp(DefaultInterp), input)
-                    .await;
-                    let v2 = <DefaultInterp as AsyncParser<ArgumentSchema, BS>>::parse(
-                        &DefaultInterp,
-                        input,
-                    )
-                    .await;
-                    Command::TransferObject(v1, v2)
-                }
-                2 => {
-                    trace!("CommandSchema: SplitCoins");
-                    let v1 = <DefaultInterp as AsyncParser<ArgumentSchema, BS>>::parse(
-                        &DefaultInterp,
-                        input,
-                    )
-                    .await;
-                    let v2 = <SubInterp<DefaultInterp> as AsyncParser<
-                        Vec<ArgumentSchema, SPLIT_COIN_ARRAY_LENGTH>,
-                        BS,
-                    >>::parse(&SubInterp(DefaultInterp), input)
-                    .await;
-                    Command::SplitCoins(v1, v2)
-                }
-                _ => {
-                    trace!("CommandSchema: Unknown enum: {}", enum_variant);
-                    reject_on(
-                        core::file!(),
-                        core::line!(),
-                        SyscallError::NotSupported as u16,
-                    )
-                    .await
-                }
+    if with_public_keys_cached(&path, derivation, |address| {
+        try_option(|| -> Option<()> {
+            scroller("Save Address", |_w| Ok(()))?;
+            if path.len() > 2 {
+                scroller("Account", |w| Ok(write_account_index(w, &path)?))?;
             }
-        }
+            scroller_paginated("Address", |w| Ok(write!(w, "{address}")?))?;
+            scroller("Label", |w| Ok(write!(w, "{label}")?))?;
+            final_accept_prompt(&["Save Address?"])?;
+            address_book::save(
+                <&IotaAddressRaw>::try_from(address.get_binary_address()).ok()?,
+                label,
+            )
+        }())
+    })
+    .is_err()
+    {
+        reject::<()>(StatusWords::UserCancelled as u16).await;
     }
-}
 
-pub enum Argument {
-    GasCoin,
-    Input(u16),
-    Result(u16),
-    NestedResult(u16, u16),
+    io.result_final(&[]).await;
 }
 
-impl HasOutput<ArgumentSchema> for DefaultInterp {
-    type Output = Argument;
-}
+// Without a prompt, as the label is only ever shown marked as unverified, see session_labels.rs
+pub async fn label_recipient_apdu(io: HostIO) {
+    use crate::interface::{AddressLabel, IotaAddress};
 
-impl<BS: Clone + Readable> AsyncParser<ArgumentSchema, BS> for DefaultInterp {
-    type State<'c> = impl Future<Output = Self::Output> + 'c where BS: 'c;
-    fn parse<'a: 'c, 'b: 'c, 'c>(&'b self, input: &'a mut BS) -> Self::State<'c> {
-        async move {
-            let enum_variant =
-                <DefaultInterp as AsyncParser<ULEB128, BS>>::parse(&DefaultInterp, input).await;
-            match enum_variant {
-                0 => {
-                    trace!("ArgumentSchema: GasCoin");
-                    Argument::GasCoin
-                }
-                1 => {
-                    trace!("ArgumentSchema: Input");
-                    Argument::Input(
-                        <DefaultInterp as AsyncParser<U16LE, BS>>::parse(&DefaultInterp, input)
-                            .await,
-                    )
-                }
-                2 => {
-                    trace!("ArgumentSchema: Result");
-                    Argument::Result(
-                        <DefaultInterp as AsyncParser<U16LE, BS>>::parse(&DefaultInterp, input)
-                            .await,
-                    )
-                }
-                3 => {
-                    trace!("ArgumentSchema: NestedResult");
-                    Argument::NestedResult(
-                        <DefaultInterp as AsyncParser<U16LE, BS>>::parse(&DefaultInterp, input)
-                            .await,
-                        <DefaultInterp as AsyncParser<U16LE, BS>>::parse(&DefaultInterp, input)
-                            .await,
-                    )
-                }
-                _ => {
-                    reject_on(
-                        core::file!(),
-                        core::line!(),
-                        SyscallError::NotSupported as u16,
-                    )
-                    .await
-                }
-            }
+    let input = match io.get_params::<2>() {
+        Some(v) => v,
+        None => reject(SyscallError::InvalidParameter as u16).await,
+    };
+
+    let address = <DefaultInterp as AsyncParser<IotaAddress, ByteStream>>::parse(
+        &DefaultInterp,
+        &mut input[0].clone(),
+    )
+    .await;
+    let label_bytes = <SubInterp<DefaultInterp> as AsyncParser<AddressLabel, ByteStream>>::parse(
+        &SubInterp(DefaultInterp),
+        &mut input[1].clone(),
+    )
+    .await;
+    let label = match core::str::from_utf8(&label_bytes) {
+        Ok(label)
+            if !label.is_empty() && label.bytes().all(|b| b.is_ascii_graphic() || b == b' ') =>
+        {
+            label
         }
-    }
-}
+        _ => reject(SyscallError::InvalidParameter as u16).await,
+    };
 
-impl<const PROMPT: bool> HasOutput<ProgrammableTransaction<PROMPT>>
-    for ProgrammableTransaction<PROMPT>
-{
-    type Output = ();
+    if session_labels::set(&address, label).is_none() {
+        reject::<()>(SyscallError::InvalidParameter as u16).await;
+    }
+    io.result_final(&[]).await;
 }
 
-impl<BS: Clone + Readable, const PROMPT: bool> AsyncParser<ProgrammableTransaction<PROMPT>, BS>
-    for ProgrammableTransaction<PROMPT>
-{
-    type State<'c> = impl Future<Output = Self::Output> + 'c where BS: 'c;
-    fn parse<'a: 'c, 'b: 'c, 'c>(&'b self, input: &'a mut BS) -> Self::State<'c> {
-        async move {
-            let mut recipient = None;
-            let mut recipient_index = None;
-            let mut amounts: ArrayVec<(u64, u32), SPLIT_COIN_ARRAY_LENGTH> = ArrayVec::new();
-
-            // Handle inputs
-            {
-                let length =
-                    <DefaultInterp as AsyncParser<ULEB128, BS>>::parse(&DefaultInterp, input).await;
-
-                trace!("ProgrammableTransaction: Inputs: {}", length);
-                for i in 0..length {
-                    let arg = <DefaultInterp as AsyncParser<CallArgSchema, BS>>::parse(
-                        &DefaultInterp,
-                        input,
-                    )
-                    .await;
-                    match arg {
-                        CallArg::RecipientAddress(addr) => match recipient {
-                            None => {
-                                recipient = Some(addr);
-                                recipient_index = Some(i);
-                            }
-                            // Reject on multiple RecipientAddress(s)
-                            _ => {
-                                reject_on(
-                                    core::file!(),
-                                    core::line!(),
-                                    SyscallError::NotSupported as u16,
-                                )
-                                .await
-                            }
-                        },
-                        CallArg::Amount(amt) =>
-                        {
-                            #[allow(clippy::single_match)]
-                            match amounts.try_push((amt, i)) {
-                                Err(_) => {
-                                    reject_on(
-                                        core::file!(),
-                                        core::line!(),
-                                        SyscallError::NotSupported as u16,
-                                    )
-                                    .await
-                                }
-                                _ => {}
-                            }
-                        }
-                        _ => {}
-                    }
-                }
-            }
-
-            if recipient_index.is_none() || amounts.is_empty() {
-                reject_on::<()>(
-                    core::file!(),
-                    core::line!(),
-                    SyscallError::NotSupported as u16,
-                )
-                .await;
-            }
+// Lists PATTERNS, a few to each result, as the whole list is bigger than one
+pub async fn get_patterns_apdu(io: HostIO) {
+    use crate::patterns::{entries, PATTERNS_VERSION};
 
-            let mut verified_recipient = false;
-            let mut total_amount: u64 = 0;
-            // Handle commands
-            {
-                let length =
-                    <DefaultInterp as AsyncParser<ULEB128, BS>>::parse(&DefaultInterp, input).await;
-                trace!("ProgrammableTransaction: Commands: {}", length);
-                for _ in 0..length {
-                    let c = <DefaultInterp as AsyncParser<CommandSchema, BS>>::parse(
-                        &DefaultInterp,
-                        input,
-                    )
-                    .await;
-                    match c {
-                        Command::TransferObject(_nested_results, recipient_input) => {
-                            if verified_recipient {
-                                // Reject more than one TransferObject(s)
-                                reject_on::<()>(
-                                    core::file!(),
-                                    core::line!(),
-                                    SyscallError::NotSupported as u16,
-                                )
-                                .await;
-                            }
-                            match recipient_input {
-                                Argument::Input(inp_index) => {
-                                    if Some(inp_index as u32) != recipient_index {
-                                        trace!("TransferObject recipient mismatch");
-                                        reject_on::<()>(
-                                            core::file!(),
-                                            core::line!(),
-                                            SyscallError::NotSupported as u16,
-                                        )
-                                        .await;
-                                    }
-                                    verified_recipient = true;
-                                }
-                                _ => {
-                                    reject_on(
-                                        core::file!(),
-                                        core::line!(),
-                                        SyscallError::NotSupported as u16,
-                                    )
-                                    .await
-                                }
-                            }
-                        }
-                        Command::SplitCoins(coin, input_indices) => {
-                            match coin {
-                                Argument::GasCoin => {}
-                                _ => {
-                                    reject_on(
-                                        core::file!(),
-                                        core::line!(),
-                                        SyscallError::NotSupported as u16,
-                                    )
-                                    .await
-                                }
-                            }
-                            for arg in &input_indices {
-                                match arg {
-                                    Argument::Input(inp_index) => {
-                                        for (amt, ix) in &amounts {
-                                            if *ix == (*inp_index as u32) {
-                                                match total_amount.checked_add(*amt) {
-                                                    Some(t) => total_amount = t,
-                                                    None => {
-                                                        reject_on(
-                                                            core::file!(),
-                                                            core::line!(),
-                                                            SyscallError::InvalidParameter as u16,
-                                                        )
-                                                        .await
-                                                    }
-                                                }
-                                            }
-                                        }
-                                    }
-                                    _ => {
-                                        reject_on(
-                                            core::file!(),
-                                            core::line!(),
-                                            SyscallError::NotSupported as u16,
-                                        )
-                                        .await
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-
-            if !verified_recipient {
-                reject_on::<()>(
-                    core::file!(),
-                    core::line!(),
-                    SyscallError::NotSupported as u16,
-                )
-                .await;
-            }
-
-            if PROMPT
-                && Option::<()>::is_none(
-                    &try {
-                        scroller_paginated("To", |w| {
-                            Ok(write!(
-                                w,
-                                "0x{}",
-                                HexSlice(&recipient.ok_or(ScrollerError)?)
-                            )?)
-                        })?;
-
-                        let (quotient, remainder_str) = get_amount_in_decimals(total_amount);
-                        scroller_paginated("Amount", |w| {
-                            Ok(write!(w, "IOTA {quotient}.{}", remainder_str.as_str())?)
-                        })?;
-                    },
-                )
-            {
-                reject::<()>(StatusWords::UserCancelled as u16).await;
-            }
+    let mut rv = ArrayVec::<u8, 220>::new();
+    let _ = rv.try_push(PATTERNS_VERSION);
+    let _ = rv.try_push(entries().count() as u8);
+    for (pattern, package) in entries() {
+        let mut entry = ArrayVec::<u8, 220>::new();
+        if pattern.encode(package, &mut entry).is_none() {
+            reject::<()>(SyscallError::Unspecified as u16).await;
+        }
+        if rv.try_extend_from_slice(&entry).is_err() {
+            io.result_accumulating(&rv).await;
+            rv.clear();
+            let _ = rv.try_extend_from_slice(&entry);
         }
     }
+    io.result_final(&rv).await;
 }
 
-impl<const PROMPT: bool> HasOutput<TransactionKind<PROMPT>> for TransactionKind<PROMPT> {
-    type Output = ();
+// The limits past which sign_txn refuses a transaction, or can only blind sign it, so that hosts
+// can split or refuse transactions before sending them
+pub async fn get_limits_apdu(io: HostIO) {
+    use crate::chunked::MAX_CHUNKED_TX_SIZE;
+    use crate::parser::{MAX_COMMANDS, MAX_INPUTS, SPLIT_COIN_ARRAY_LENGTH};
+
+    let mut rv = ArrayVec::<u8, 11>::new();
+    let _ = rv.try_extend_from_slice(&(MAX_TX_SIZE as u32).to_le_bytes());
+    let _ = rv.try_extend_from_slice(&(MAX_CHUNKED_TX_SIZE as u32).to_le_bytes());
+    let _ = rv.try_push(MAX_INPUTS as u8);
+    let _ = rv.try_push(MAX_COMMANDS as u8);
+    let _ = rv.try_push(SPLIT_COIN_ARRAY_LENGTH as u8);
+    io.result_final(&rv).await;
 }
 
-impl<BS: Clone + Readable, const PROMPT: bool> AsyncParser<TransactionKind<PROMPT>, BS>
-    for TransactionKind<PROMPT>
-{
-    type State<'c> = impl Future<Output = Self::Output> + 'c where BS: 'c;
-    fn parse<'a: 'c, 'b: 'c, 'c>(&'b self, input: &'a mut BS) -> Self::State<'c> {
-        async move {
-            let enum_variant =
-                <DefaultInterp as AsyncParser<ULEB128, BS>>::parse(&DefaultInterp, input).await;
-            match enum_variant {
-                0 => {
-                    trace!("TransactionKind: ProgrammableTransaction");
-                    <ProgrammableTransaction<PROMPT> as AsyncParser<
-                        ProgrammableTransaction<PROMPT>,
-                        BS,
-                    >>::parse(&ProgrammableTransaction::<PROMPT>, input)
-                    .await;
-                }
-                _ => {
-                    trace!("TransactionKind: {}", enum_variant);
-                    reject_on(
-                        core::file!(),
-                        core::line!(),
-                        SyscallError::NotSupported as u16,
-                    )
-                    .await
-                }
-            }
-        }
+// Without a prompt, as it only tells the host what the user approved, see receipt.rs
+pub async fn get_receipt_apdu(io: HostIO) {
+    let mut rv = ArrayVec::<u8, 100>::new();
+    if receipt::encode(&mut rv).is_none() {
+        reject::<()>(StatusWords::NothingReceived as u16).await;
     }
+    io.result_final(&rv).await;
 }
 
-fn get_amount_in_decimals(amount: u64) -> (u64, ArrayString<12>) {
-    let factor_pow = 9;
-    let factor = u64::pow(10, factor_pow);
-    let quotient = amount / factor;
-    let remainder = amount % factor;
-    let mut remainder_str: ArrayString<12> = ArrayString::new();
-    {
-        // Make a string for the remainder, containing at lease one zero
-        // So 1 IOTA will be displayed as "1.0"
-        let mut rem = remainder;
-        for i in 0..factor_pow {
-            let f = u64::pow(10, factor_pow - i - 1);
-            let r = rem / f;
-            let _ = remainder_str.try_push(char::from(b'0' + r as u8));
-            rem %= f;
-            if rem == 0 {
-                break;
-            }
-        }
-    }
-    (quotient, remainder_str)
+struct TxnHash {
+    hasher: Blake2b,
+    consumed: usize,
 }
 
-impl HasOutput<TransactionExpiration> for DefaultInterp {
-    type Output = ();
-}
+// The hash of what has been read of the transaction being signed so far
+static mut TXN_HASH: Option<TxnHash> = None;
+
+/// Hashes everything read through it, so that the transaction is hashed while it is parsed
+/// instead of being streamed from the host once more.
+#[derive(Clone)]
+struct HashingStream<BS>(BS);
 
-impl<BS: Clone + Readable> AsyncParser<TransactionExpiration, BS> for DefaultInterp {
-    type State<'c> = impl Future<Output = Self::Output> + 'c where BS: 'c;
-    fn parse<'a: 'c, 'b: 'c, 'c>(&'b self, input: &'a mut BS) -> Self::State<'c> {
+impl<BS: Readable> Readable for HashingStream<BS> {
+    type OutFut<'a, const N: usize> = impl 'a + Future<Output = [u8; N]>;
+    fn read<'a: 'b, 'b, const N: usize>(&'a mut self) -> Self::OutFut<'b, N> {
         async move {
-            let enum_variant =
-                <DefaultInterp as AsyncParser<ULEB128, BS>>::parse(&DefaultInterp, input).await;
-            match enum_variant {
-                0 => {
-                    trace!("TransactionExpiration: None");
-                }
-                1 => {
-                    trace!("TransactionExpiration: Epoch");
-                    <DefaultInterp as AsyncParser<EpochId, BS>>::parse(&DefaultInterp, input).await;
-                }
-                _ => {
-                    reject_on(
-                        core::file!(),
-                        core::line!(),
-                        SyscallError::NotSupported as u16,
-                    )
-                    .await
-                }
+            let bytes: [u8; N] = self.0.read().await;
+            if let Some(state) = unsafe { TXN_HASH.as_mut() } {
+                state.hasher.update(&bytes);
+                state.consumed += N;
             }
+            bytes
         }
     }
 }
 
-const fn gas_data_parser<BS: Clone + Readable, const PROMPT: bool>(
-) -> impl AsyncParser<GasData<PROMPT>, BS> + HasOutput<GasData<PROMPT>, Output = ()> {
-    Action(
-        (
-            SubInterp(object_ref_parser()),
-            DefaultInterp,
-            DefaultInterp,
-            DefaultInterp,
-        ),
-        |(_, _sender, _gas_price, gas_budget): (_, _, u64, u64)| {
-            // Gas price is per gas amount. Gas budget is total, reflecting the amount of gas *
-            // gas price. We only care about the total, not the price or amount in isolation , so we
-            // just ignore that field.
-            //
-            // C.F. https://github.com/MystenLabs/sui/pull/8676
-            if PROMPT {
-                let (quotient, remainder_str) = get_amount_in_decimals(gas_budget);
-                scroller("Max Gas", |w| {
-                    Ok(write!(w, "IOTA {}.{}", quotient, remainder_str.as_str())?)
-                })?
-            }
-            Some(())
-        },
-    )
-}
-
-const fn object_ref_parser<BS: Readable>(
-) -> impl AsyncParser<ObjectRef, BS> + HasOutput<ObjectRef, Output = ()> {
-    Action((DefaultInterp, DefaultInterp, DefaultInterp), |_| Some(()))
+fn start_hash() {
+    unsafe {
+        TXN_HASH = Some(TxnHash {
+            hasher: Hasher::new(),
+            consumed: 0,
+        })
+    };
 }
 
-const fn intent_parser<BS: Readable>(
-) -> impl AsyncParser<Intent, BS> + HasOutput<Intent, Output = ()> {
-    Action((DefaultInterp, DefaultInterp, DefaultInterp), |_| {
-        trace!("Intent Ok");
-        Some(())
-    })
-}
+// Bytes read at once when hashing what the parse left, which has to fit on the stack
+#[cfg(target_os = "nanos")]
+const HASH_CHUNK_SIZE: usize = 128;
+#[cfg(not(target_os = "nanos"))]
+const HASH_CHUNK_SIZE: usize = 256;
 
-const fn transaction_data_v1_parser<BS: Clone + Readable, const PROMPT: bool>(
-) -> impl AsyncParser<TransactionDataV1<PROMPT>, BS> + HasOutput<TransactionDataV1<PROMPT>, Output = ()>
-{
-    Action(
-        (
-            TransactionKind::<PROMPT>,
-            DefaultInterp,
-            gas_data_parser::<_, PROMPT>(),
-            DefaultInterp,
-        ),
-        |_| Some(()),
-    )
+// Read N bytes if that bit is set in `remaining`, where N is a power of two below HASH_CHUNK_SIZE
+async fn skip_bit<BS: Readable, const N: usize>(txn: &mut BS, remaining: usize) {
+    if (remaining % HASH_CHUNK_SIZE) & N != 0 {
+        let _: [u8; N] = txn.read().await;
+    }
 }
 
-impl<const PROMPT: bool> HasOutput<TransactionData<PROMPT>> for TransactionData<PROMPT> {
-    type Output = ();
+fn hashed_length() -> usize {
+    unsafe { TXN_HASH.as_ref() }.map_or(0, |state| state.consumed)
 }
 
-impl<BS: Clone + Readable, const PROMPT: bool> AsyncParser<TransactionData<PROMPT>, BS>
-    for TransactionData<PROMPT>
-{
-    type State<'c> = impl Future<Output = Self::Output> + 'c where BS: 'c;
-    fn parse<'a: 'c, 'b: 'c, 'c>(&'b self, input: &'a mut BS) -> Self::State<'c> {
-        async move {
-            let enum_variant =
-                <DefaultInterp as AsyncParser<ULEB128, BS>>::parse(&DefaultInterp, input).await;
-            match enum_variant {
-                0 => {
-                    trace!("TransactionData: V1");
-                    transaction_data_v1_parser::<_, PROMPT>().parse(input).await;
-                }
-                _ => {
-                    reject_on(
-                        core::file!(),
-                        core::line!(),
-                        SyscallError::NotSupported as u16,
-                    )
-                    .await
-                }
-            }
-        }
+/// Hash whatever the parse left of the `length` bytes of the transaction.
+///
+/// A parse which read past them reviewed bytes which would not be signed, so that rejects, as does
+/// a host which sends more than `length` bytes.
+async fn finish_hash<BS: Readable>(mut txn: HashingStream<BS>, length: usize) -> HexHash<32> {
+    let consumed = hashed_length();
+    if consumed > length {
+        refuse::<()>(SyscallError::InvalidParameter as u16).await;
     }
+    let remaining = length - consumed;
+    for _ in 0..remaining / HASH_CHUNK_SIZE {
+        let _: [u8; HASH_CHUNK_SIZE] = txn.read().await;
+    }
+    // The rest takes one read per bit set in it, instead of one per byte
+    skip_bit::<_, 128>(&mut txn, remaining).await;
+    skip_bit::<_, 64>(&mut txn, remaining).await;
+    skip_bit::<_, 32>(&mut txn, remaining).await;
+    skip_bit::<_, 16>(&mut txn, remaining).await;
+    skip_bit::<_, 8>(&mut txn, remaining).await;
+    skip_bit::<_, 4>(&mut txn, remaining).await;
+    skip_bit::<_, 2>(&mut txn, remaining).await;
+    skip_bit::<_, 1>(&mut txn, remaining).await;
+    let hash = match unsafe { TXN_HASH.take() } {
+        Some(mut state) => state.hasher.finalize(),
+        None => refuse(SyscallError::Unspecified as u16).await,
+    };
+    // Running out of input rejects, which is what should happen here
+    let extra: Option<[u8; 1]> = TryFuture(txn.0.read()).await;
+    if extra.is_some() {
+        refuse::<()>(SyscallError::InvalidParameter as u16).await;
+    }
+    hash
 }
 
-const fn tx_parser<BS: Clone + Readable, const PROMPT: bool>(
-) -> impl AsyncParser<IntentMessage<PROMPT>, BS> + HasOutput<IntentMessage<PROMPT>, Output = ()> {
-    Action((intent_parser(), TransactionData::<PROMPT>), |_| Some(()))
-}
-
-pub async fn sign_apdu(io: HostIO, settings: Settings) {
+pub async fn sign_apdu(io: HostIO, settings: Settings, derivation: Derivation) {
     let mut input = match io.get_params::<2>() {
         Some(v) => v,
         None => reject(SyscallError::InvalidParameter as u16).await,
@@ -701,109 +393,210 @@ pub async fn sign_apdu(io: HostIO, settings: Settings) {
     // Read length, and move input[0] by one byte
     let length = usize::from_le_bytes(input[0].read().await);
 
-    let known_txn = {
-        let mut txn = input[0].clone();
-        NoinlineFut(async move {
-            trace!("Beginning check parse");
-            TryFuture(tx_parser::<_, false>().parse(&mut txn))
-                .await
-                .is_some()
-        })
-        .await
-    };
+    let sig = NoinlineFut(sign_txn(
+        input[0].clone(),
+        length,
+        input[1].clone(),
+        settings,
+        derivation,
+    ))
+    .await;
+    io.result_final(&sig).await;
+}
 
-    if known_txn {
-        if scroller("Transfer", |w| Ok(write!(w, "IOTA")?)).is_none() {
-            reject::<()>(StatusWords::UserCancelled as u16).await;
-        };
-        {
-            let mut bs = input[1].clone();
-            NoinlineFut(async move {
-                let path = BIP_PATH_PARSER.parse(&mut bs).await;
-                if !is_bip_prefix_valid(&path) {
-                    reject::<()>(SyscallError::InvalidParameter as u16).await;
-                }
-                if with_public_keys(&path, true, |_, address: &IotaPubKeyAddress| {
-                    try_option(|| -> Option<()> {
-                        scroller_paginated("From", |w| Ok(write!(w, "{address}")?))?;
-                        Some(())
-                    }())
-                })
-                .ok()
-                .is_none()
-                {
-                    reject::<()>(StatusWords::UserCancelled as u16).await;
-                }
-            })
-            .await
-        };
+// The status word sign_txn last rejected with. The block protocol tells the host by itself, the
+// chunked flow polls sign_txn outside of it and has to reply with this instead.
+static mut SIGN_REJECTION: Option<u16> = None;
 
-        {
-            let mut txn = input[0].clone();
-            NoinlineFut(async move {
-                trace!("Beginning parse");
-                tx_parser::<_, true>().parse(&mut txn).await;
-            })
-            .await
-        };
-
-        if final_accept_prompt(&["Sign Transaction?"]).is_none() {
-            reject::<()>(StatusWords::UserCancelled as u16).await;
-        };
-    } else if settings.get() == 0 {
-        scroller("WARNING", |w| {
-            Ok(write!(
-                w,
-                "Transaction not recognized, enable blind signing to sign unknown transactions"
-            )?)
-        });
-        reject::<()>(SyscallError::NotSupported as u16).await;
-    } else if scroller("WARNING", |w| Ok(write!(w, "Transaction not recognized")?)).is_none() {
-        reject::<()>(StatusWords::UserCancelled as u16).await;
+/// Reject with `sw`, remembering it for [`take_sign_rejection`].
+pub(crate) async fn refuse<T>(sw: u16) -> T {
+    reject(note_sign_rejection(sw)).await
+}
+
+// Kept out of line, like parser::note_rejection
+#[cold]
+#[inline(never)]
+fn note_sign_rejection(sw: u16) -> u16 {
+    unsafe { SIGN_REJECTION = Some(sw) };
+    sw
+}
+
+/// The status word the last sign_txn was rejected with, if it was rejected with [`refuse`].
+pub fn take_sign_rejection() -> Option<u16> {
+    unsafe { SIGN_REJECTION.take() }
+}
+
+// The largest transaction the network accepts, anything larger could never be executed
+const MAX_TX_SIZE: usize = 128 * 1024;
+
+/// Review and sign the `length` bytes of `txn` with the key `derivation` gives for the path in
+/// `bip_path`.
+///
+/// Shared by the block protocol flow and the chunked flow, which only differ in where the
+/// transaction and path bytes come from.
+pub async fn sign_txn<BS: Clone + Readable>(
+    txn: BS,
+    length: usize,
+    mut bip_path: BS,
+    mut settings: Settings,
+    derivation: Derivation,
+) -> ArrayVec<u8, 64> {
+    receipt::clear();
+    unsafe { SIGN_REJECTION = None };
+    // What is bound to fail is rejected before anything is shown, so that nothing gets approved
+    // only to not be signed in the end.
+    if length > MAX_TX_SIZE {
+        refuse::<()>(StatusWords::BadLen as u16).await;
+    }
+    let path = NoinlineFut(async {
+        let path = Zeroizing::new(bip_path_parser().parse(&mut bip_path).await);
+        if !is_bip_prefix_valid(&path) {
+            refuse::<()>(SyscallError::InvalidParameter as u16).await;
+        }
+        check_path_depth(&path, settings).await;
+        check_path_indices(&path).await;
+        path
+    })
+    .await;
+
+    // A single pass both recognizes the transaction and collects what to review.
+    start_hash();
+    parser::clear_rejection();
+    let mut txn = HashingStream(txn);
+    let parsed = NoinlineFut(async {
+        trace!("Beginning parse");
+        TryFuture(tx_parser().parse(&mut txn)).await
+    })
+    .await;
+    progress::clear();
+    let rejection = parser::take_rejection();
+    if rejection == Some(AppError::BadUleb128) {
+        refuse::<()>(AppError::BadUleb128.status_word()).await;
+    }
+    // Bytes after a recognized transaction would be signed without being reviewed
+    if parsed.is_some() && hashed_length() != length {
+        refuse::<()>(SyscallError::InvalidParameter as u16).await;
+    }
+    let hash = NoinlineFut(finish_hash(txn, length)).await;
+    let known_txn = parsed.is_some();
+    receipt::prepare(&hash.0, parsed.as_ref());
+
+    if let Some(mut summary) = parsed {
+        summary.sender = NoinlineFut(async {
+            let sender = with_public_keys_cached(&path, derivation, |address| {
+                try_option(<IotaAddressRaw>::try_from(address.get_binary_address()).ok())
+            });
+            match sender {
+                Ok(sender) => sender,
+                Err(_) => refuse(SyscallError::InvalidParameter as u16).await,
+            }
+        })
+        .await;
+
+        if review_transaction(&summary, &path, settings).is_none() {
+            refuse::<()>(StatusWords::UserCancelled as u16).await;
+        }
+        // For the audit trail of those who need to record exactly what was signed
+        if settings.is_enabled(Setting::ShowHash) && show_hash(&hash).is_none() {
+            refuse::<()>(StatusWords::UserCancelled as u16).await;
+        }
+        if final_recap(&summary).is_none() {
+            refuse::<()>(StatusWords::UserCancelled as u16).await;
+        }
+    } else if !settings.blind_signing_allowed() {
+        refuse::<()>(refuse_blind_sign(rejection)).await;
+    } else if warn_blind_sign().is_none() {
+        refuse::<()>(StatusWords::UserCancelled as u16).await;
     }
 
     // By the time we get here, we've approved and just need to do the signature.
     NoinlineFut(async move {
-        let mut hasher: Blake2b = Hasher::new();
+        if !known_txn && accept_blind_sign(&hash).is_none() {
+            refuse::<()>(StatusWords::UserCancelled as u16).await;
+        }
+        // Like the path, zeroed once dropped instead of staying in RAM until overwritten
+        if let Some(sig) = eddsa_sign(&path, derivation.is_slip10(), &hash.0)
+            .ok()
+            .map(|sig| Zeroizing::new(sig.0))
         {
-            let mut txn = input[0].clone();
-            const CHUNK_SIZE: usize = 128;
-            let (chunks, rem) = (length / CHUNK_SIZE, length % CHUNK_SIZE);
-            for _ in 0..chunks {
-                let b: [u8; CHUNK_SIZE] = txn.read().await;
-                hasher.update(&b);
+            if !signature_verifies(&path, derivation, &sig, &hash.0) {
+                refuse::<()>(SyscallError::Unspecified as u16).await;
             }
-            for _ in 0..rem {
-                let b: [u8; 1] = txn.read().await;
-                hasher.update(&b);
+            if !known_txn {
+                settings.blind_signing_used();
+            }
+            signature_counter::increment();
+            receipt::signed();
+            let mut rv = ArrayVec::new();
+            if rv.try_extend_from_slice(&sig[0..]).is_ok() {
+                return rv;
             }
         }
-        let hash: HexHash<32> = hasher.finalize();
-        if !known_txn {
-            if scroller("Transaction Hash", |w| Ok(write!(w, "0x{hash}")?)).is_none() {
-                reject::<()>(StatusWords::UserCancelled as u16).await;
-            };
-            if final_accept_prompt(&["Blind Sign Transaction?"]).is_none() {
-                reject::<()>(StatusWords::UserCancelled as u16).await;
-            };
-        }
-        let path = BIP_PATH_PARSER.parse(&mut input[1].clone()).await;
-        if !is_bip_prefix_valid(&path) {
-            reject::<()>(SyscallError::InvalidParameter as u16).await;
-        }
-        if let Some(sig) = { eddsa_sign(&path, true, &hash.0).ok() } {
-            io.result_final(&sig.0[0..]).await;
-        } else {
-            reject::<()>(SyscallError::Unspecified as u16).await;
-        }
+        refuse(SyscallError::Unspecified as u16).await
     })
     .await
 }
 
+// A fault while signing could give out a signature which is invalid, or worse, one which leaks the
+// private key, so it is checked against the public key before being returned.
+#[inline(never)]
+fn signature_verifies(path: &[u32], derivation: Derivation, sig: &[u8; 64], hash: &[u8]) -> bool {
+    with_public_keys_cached(path, derivation, |address| {
+        Ok(address
+            .public_key()
+            .verify((&sig[..], sig.len() as u32), hash))
+    })
+    .unwrap_or(false)
+}
+
+// Blind signing is the exception, so what only it shows is kept out of the way of known
+// transactions.
+
+// Tell why a transaction which cannot be reviewed is not signed, and with which status word
+#[cold]
+#[inline(never)]
+fn refuse_blind_sign(rejection: Option<AppError>) -> u16 {
+    if rejection == Some(AppError::TooComplex) {
+        scroller("WARNING", |w| {
+            Ok(write!(
+                w,
+                "Transaction too large to review, enable blind signing to sign it"
+            )?)
+        });
+        return AppError::TooComplex.status_word();
+    }
+    scroller("WARNING", |w| {
+        Ok(write!(
+            w,
+            "Transaction not recognized, enable blind signing to sign unknown transactions"
+        )?)
+    });
+    SyscallError::NotSupported as u16
+}
+
+#[cold]
+#[inline(never)]
+fn warn_blind_sign() -> Option<()> {
+    warning_screen("Blind Sign", "This transaction cannot be reviewed")?;
+    scroller("WARNING", |w| Ok(write!(w, "Transaction not recognized")?))
+}
+
+#[cold]
+#[inline(never)]
+fn accept_blind_sign(hash: &HexHash<32>) -> Option<()> {
+    show_hash(hash)?;
+    final_accept_prompt(&["Blind Sign Transaction?"])
+}
+
 pub type APDUsFuture = impl Future<Output = ()>;
 
 #[inline(never)]
-pub fn handle_apdu_async(io: HostIO, ins: Ins, settings: Settings) -> APDUsFuture {
+pub fn handle_apdu_async(
+    io: HostIO,
+    ins: Ins,
+    settings: Settings,
+    derivation: Derivation,
+) -> APDUsFuture {
     trace!("Constructing future");
     async move {
         trace!("Dispatching");
@@ -815,19 +608,62 @@ pub fn handle_apdu_async(io: HostIO, ins: Ins, settings: Settings) -> APDUsFutur
                 let _ = rv.try_push(env!("CARGO_PKG_VERSION_MINOR").parse().unwrap());
                 let _ = rv.try_push(env!("CARGO_PKG_VERSION_PATCH").parse().unwrap());
                 let _ = rv.try_extend_from_slice(APP_NAME.as_bytes());
+                #[cfg(feature = "memory_stats")]
+                let _ = rv.try_extend_from_slice(&crate::memory_stats::counters());
                 io.result_final(&rv).await;
             }
             Ins::VerifyAddress => {
-                NoinlineFut(get_address_apdu(io, true)).await;
+                NoinlineFut(get_address_apdu(io, true, settings, derivation)).await;
             }
             Ins::GetPubkey => {
-                NoinlineFut(get_address_apdu(io, false)).await;
+                let prompt = settings.is_enabled(Setting::ConfirmPubkey);
+                NoinlineFut(get_address_apdu(io, prompt, settings, derivation)).await;
+            }
+            Ins::SaveAddress => {
+                #[cfg(feature = "address_book")]
+                NoinlineFut(save_address_apdu(io, settings, derivation)).await;
+                #[cfg(not(feature = "address_book"))]
+                reject::<()>(SyscallError::NotSupported as u16).await;
             }
             Ins::Sign => {
                 trace!("Handling sign");
-                NoinlineFut(sign_apdu(io, settings)).await;
+                NoinlineFut(sign_apdu(io, settings, derivation)).await;
+            }
+            Ins::SelfTest => {
+                NoinlineFut(crate::self_test::self_test_apdu(io)).await;
+            }
+            Ins::LabelRecipient => {
+                NoinlineFut(label_recipient_apdu(io)).await;
+            }
+            Ins::GetPatterns => {
+                NoinlineFut(get_patterns_apdu(io)).await;
+            }
+            Ins::GetLimits => {
+                NoinlineFut(get_limits_apdu(io)).await;
+            }
+            Ins::GetReceipt => {
+                NoinlineFut(get_receipt_apdu(io)).await;
+            }
+            // Chunked signing does not use the block protocol, see chunked.rs
+            Ins::SignChunked => reject::<()>(StatusWords::BadIns as u16).await,
+            Ins::Exit => {
+                key_cache::clear();
+                ledger_device_sdk::exit_app(0)
             }
-            Ins::Exit => ledger_device_sdk::exit_app(0),
         }
     }
 }
+
+// The HostToLedger instruction which starts a command, see docs/block-protocol.md
+const BLOCK_START: u8 = 0;
+
+/// Whether the APDU in `comm` continues a command of the block protocol, rather than starting one.
+pub fn continues_command(comm: &Comm) -> bool {
+    matches!(comm.get_data(), Ok([cmd, ..]) if *cmd != BLOCK_START)
+}
+
+/// Whether the APDU in `comm` continues a command of the block protocol with a P2 other than 0,
+/// which only the APDU starting a command sets.
+pub fn sets_p2_midway(comm: &Comm) -> bool {
+    continues_command(comm) && comm.get_apdu_metadata().p2 != 0
+}
@@ -1,100 +1,3 @@
-use core::convert::TryFrom;
-use ledger_device_sdk::io::{ApduHeader, StatusWords};
-use ledger_parser_combinators::bcs::async_parser::{Vec, ULEB128};
-use ledger_parser_combinators::core_parsers::{Array, Byte, DArray, U16, U32, U64};
-use ledger_parser_combinators::endianness::Endianness;
-use num_enum::TryFromPrimitive;
-
-// Payload for a public key request
-pub type Bip32Key = DArray<Byte, U32<{ Endianness::Little }>, 10>;
-
-pub type SignParameters = (IntentMessage<true>, Bip32Key);
-
-// Iota Types
-pub type IntentMessage<const PROMPT: bool> = (Intent, TransactionData<PROMPT>);
-
-pub struct TransactionData<const PROMPT: bool>;
-
-pub type TransactionDataV1<const PROMPT: bool> = (
-    TransactionKind<PROMPT>,
-    IotaAddress,           // sender
-    GasData<PROMPT>,       // gas_data
-    TransactionExpiration, // expiration
-);
-
-pub struct TransactionKind<const PROMPT: bool>;
-
-pub struct ProgrammableTransaction<const PROMPT: bool>;
-
-pub struct CommandSchema;
-pub struct ArgumentSchema;
-pub struct CallArgSchema;
-
-pub type GasData<const PROMPT: bool> = (
-    Vec<ObjectRef, { usize::MAX }>, // payment
-    IotaAddress,                    // owner
-    Amount,                         // price
-    Amount,                         // budget
-);
-
-pub struct TransactionExpiration;
-pub type EpochId = U64<{ Endianness::Little }>;
-
-pub type ObjectRef = (ObjectID, SequenceNumber, ObjectDigest);
-
-pub type SharedObject = (
-    ObjectID,       // id
-    SequenceNumber, // initial_shared_version
-    bool,           // mutable
-);
-
-pub type AccountAddress = IotaAddress;
-pub type ObjectID = AccountAddress;
-pub type SequenceNumber = U64LE;
-pub type ObjectDigest = SHA3_256_HASH;
-
-pub const IOTA_ADDRESS_LENGTH: usize = 32;
-pub type IotaAddress = Array<Byte, IOTA_ADDRESS_LENGTH>;
-
-pub type Coins = Vec<ObjectRef, { usize::MAX }>;
-
-pub type Recipient = IotaAddress;
-
-pub type Amount = U64LE;
-
-pub type U64LE = U64<{ Endianness::Little }>;
-pub type U16LE = U16<{ Endianness::Little }>;
-
-pub type Intent = (IntentVersion, IntentScope, AppId);
-pub type IntentVersion = ULEB128;
-pub type IntentScope = ULEB128;
-pub type AppId = ULEB128;
-
-// TODO: confirm if 33 is indeed ok for all uses of SHA3_256_HASH
-#[allow(non_camel_case_types)]
-pub type SHA3_256_HASH = Array<Byte, 33>;
-
-#[repr(u8)]
-#[derive(Debug, TryFromPrimitive)]
-pub enum Ins {
-    GetVersion = 0,
-    VerifyAddress = 1,
-    GetPubkey = 2,
-    Sign = 3,
-    Exit = 0xff,
-}
-
-impl TryFrom<ApduHeader> for Ins {
-    type Error = StatusWords;
-    fn try_from(m: ApduHeader) -> Result<Ins, Self::Error> {
-        match m {
-            ApduHeader {
-                cla: 0,
-                ins,
-                p1: 0,
-                p2: 0,
-            } => Self::try_from(ins).map_err(|_| StatusWords::BadIns),
-            _ => Err(StatusWords::BadIns),
-        }
-    }
-}
+// The schemas, instructions and status words are defined once in ledger-iota-interface, which host
+// tooling uses too, see interface/.
+pub use ledger_iota_interface::*;
@@ -0,0 +1,55 @@
+use crate::implementation::IotaPubKeyAddress;
+use crate::interface::Derivation;
+#[cfg(feature = "test_vectors")]
+use crate::test_vectors::with_public_keys;
+use crate::utils::ct_eq;
+use arrayvec::ArrayVec;
+use core::convert::TryFrom;
+use ledger_crypto_helpers::common::CryptographyError;
+#[cfg(not(feature = "test_vectors"))]
+use ledger_crypto_helpers::eddsa::with_public_keys;
+
+// Only the public key and the address are cached; private key material is never kept around
+// between APDUs, and is still derived on demand for every signature.
+struct CachedKey {
+    path: ArrayVec<u32, 10>,
+    derivation: Derivation,
+    address: IotaPubKeyAddress,
+}
+
+// RAM only, so the cache never survives the app being closed.
+static mut KEY_CACHE: Option<CachedKey> = None;
+
+/// Like `with_public_keys`, but reuses the key derived for the previous request if it was for the
+/// same path and derivation. Requesting another drops the previous entry before deriving the new
+/// key.
+#[inline(never)]
+pub fn with_public_keys_cached<V>(
+    path: &[u32],
+    derivation: Derivation,
+    f: impl FnOnce(&IotaPubKeyAddress) -> Result<V, CryptographyError>,
+) -> Result<V, CryptographyError> {
+    let cache = unsafe { &mut KEY_CACHE };
+    if let Some(entry) = cache {
+        if entry.derivation == derivation && ct_eq(&entry.path, path) {
+            return f(&entry.address);
+        }
+    }
+    *cache = None;
+    let slip10 = derivation.is_slip10();
+    with_public_keys(path, slip10, |_, address: &IotaPubKeyAddress| {
+        let rv = f(address);
+        if let Ok(cached_path) = ArrayVec::try_from(path) {
+            *cache = Some(CachedKey {
+                path: cached_path,
+                derivation,
+                address: address.clone(),
+            });
+        }
+        rv
+    })
+}
+
+pub fn clear() {
+    unsafe { KEY_CACHE = None };
+}
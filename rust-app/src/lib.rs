@@ -8,7 +8,7 @@
 #![feature(try_blocks)]
 #![cfg_attr(all(target_family = "bolos", test), no_main)]
 #![cfg_attr(target_family = "bolos", feature(custom_test_frameworks))]
-#![reexport_test_harness_main = "test_main"]
+#![cfg_attr(target_family = "bolos", reexport_test_harness_main = "test_main")]
 #![cfg_attr(
     target_family = "bolos",
     test_runner(ledger_device_sdk::testing::sdk_test_runner)
@@ -17,6 +17,27 @@
 #![cfg_attr(all(not(version("1.65"))), feature(generic_associated_types))]
 #![cfg_attr(version("1.71"), feature(impl_trait_in_assoc_type))]
 
+// Tracing of the parser and the main loop, which is only compiled in with the extra_debug feature
+// so that release builds pay for it neither in flash nor in cycles. Defined before the modules so
+// that all of them can use it.
+#[cfg(feature = "extra_debug")]
+macro_rules! trace {
+    ($($arg:tt)*) => { ledger_log::trace!($($arg)*) };
+}
+#[cfg(not(feature = "extra_debug"))]
+macro_rules! trace {
+    // Still type checks the arguments, which are never formatted
+    ($($arg:tt)*) => {
+        if false {
+            let _ = format_args!($($arg)*);
+        }
+    };
+}
+
+// For the property tests, which run on the host
+#[cfg(all(not(target_family = "bolos"), test))]
+extern crate std;
+
 #[cfg(feature = "pending_review_screen")]
 mod pending;
 
@@ -33,18 +54,73 @@ pub mod interface;
 #[cfg(target_family = "bolos")]
 pub mod utils;
 
+// The parser builds on the host too, so that it can be fuzzed, see fuzz/
+pub mod futures;
+
+pub mod parser;
+
+pub mod patterns;
+
+pub mod amount;
+
+pub mod path;
+
+#[cfg(target_family = "bolos")]
+pub mod display;
+
 #[cfg(target_family = "bolos")]
 pub mod implementation;
 
 #[cfg(target_family = "bolos")]
+pub mod chunked;
+
+#[cfg(target_family = "bolos")]
+pub mod key_cache;
+
+#[cfg(all(target_family = "bolos", feature = "address_book"))]
+pub mod address_book;
+
+#[cfg(target_family = "bolos")]
+pub mod session_labels;
+
+#[cfg(target_family = "bolos")]
+pub mod receipt;
+
+pub mod progress;
+
+#[cfg(target_family = "bolos")]
+pub mod signature_counter;
+
+#[cfg(target_family = "bolos")]
+pub mod self_test;
+
+#[cfg(all(target_family = "bolos", feature = "memory_stats"))]
+pub mod memory_stats;
+
+#[cfg(all(target_family = "bolos", feature = "test_vectors"))]
+pub mod test_vectors;
+
+#[cfg(all(
+    target_family = "bolos",
+    not(any(target_os = "stax", target_os = "flex"))
+))]
 pub mod menu;
 
+#[cfg(any(target_os = "stax", target_os = "flex"))]
+pub mod ui_nbgl;
+
 #[cfg(target_family = "bolos")]
 pub mod settings;
 
-#[cfg(target_family = "bolos")]
+#[cfg(all(
+    target_family = "bolos",
+    not(any(target_os = "stax", target_os = "flex"))
+))]
 pub mod main_nanos;
 
+#[cfg(any(target_os = "stax", target_os = "flex"))]
+pub mod main_stax;
+
 #[cfg(all(target_family = "bolos", test))]
 use core::panic::PanicInfo;
 /// In case of runtime problems, return an internal error and exit the app
@@ -1,13 +1,17 @@
-use crate::implementation::{handle_apdu_async, APDUsFuture};
-use crate::interface::Ins;
-use crate::menu::{BusyMenu, DoCancel, DoExitApp, IdleMenu, IdleMenuWithSettings};
+use crate::chunked;
+use crate::implementation::{continues_command, handle_apdu_async, sets_p2_midway, APDUsFuture};
+use crate::interface::{Derivation, Ins};
+use crate::key_cache;
+use crate::menu::{busy_label, BusyMenu, DoCancel, DoExitApp, IdleMenu, IdleMenuWithSettings};
+use crate::progress;
 use crate::settings::Settings;
+use crate::utils::SingleThreaded;
 
 use alamgu_async_block::{poll_apdu_handlers, HostIO, HostIOState};
 
-use ledger_device_sdk::io;
+use ledger_device_sdk::io::{self, StatusWords};
 use ledger_device_sdk::uxapp::{UxEvent, BOLOS_UX_OK};
-use ledger_log::{info, trace};
+use ledger_log::info;
 use ledger_prompts_ui::{handle_menu_button_event, show_menu};
 
 use core::cell::RefCell;
@@ -16,6 +20,8 @@ use pin_cell::{PinCell, PinMut};
 
 #[allow(dead_code)]
 pub fn app_main() {
+    #[cfg(feature = "memory_stats")]
+    crate::memory_stats::init();
     let comm: SingleThreaded<RefCell<io::Comm>> = SingleThreaded(RefCell::new(io::Comm::new()));
 
     let hostio_state: SingleThreaded<RefCell<HostIOState>> =
@@ -35,7 +41,17 @@ pub fn app_main() {
         idle_menu: IdleMenu::AppMain,
         settings: Settings,
     };
+    idle_menu.settings.migrate();
     let mut busy_menu = BusyMenu::Working;
+    // The command the user cancelled from the busy menu, or which timed out. The host is still
+    // waiting to send the next APDU of it, which has to be answered with an error instead.
+    let mut cancelled: Option<Ins> = None;
+    // The command of the pending future, if any.
+    let mut pending: Option<Ins> = None;
+    // Ticks since the last APDU or button press. Only counts while busy, as prompts run their own
+    // event loop and do not return here until they are done. warning_screen counts its own, the
+    // prompts of ledger_prompts_ui wait for the user however long it takes.
+    let mut busy_ticks: u32 = 0;
 
     info!("IOTA {}", env!("CARGO_PKG_VERSION"));
     info!(
@@ -57,14 +73,64 @@ pub fn app_main() {
         // Wait for either a specific button push to exit the app
         // or an APDU command
         let evt = comm.borrow_mut().next_event::<Ins>();
+        if !matches!(evt, io::Event::Ticker) {
+            busy_ticks = 0;
+        }
+        // Any other command starts afresh, only the dropped one is left hanging
+        if let io::Event::Command(ins) = &evt {
+            if cancelled != Some(*ins) || !continues_command(&comm.borrow()) {
+                cancelled = None;
+            }
+        }
+        // Another command while one is pending, or the same one started over, means the host has
+        // given up on the pending one, which is dropped along with everything it left behind before
+        // the new one is handled. Its last APDU has been answered already, so there is nothing left
+        // to answer.
+        if let (io::Event::Command(ins), Some(old)) = (&evt, pending) {
+            if *ins != old || !continues_command(&comm.borrow()) {
+                info!("Dropping the pending command for a new one");
+                drop_pending(*states, &mut busy_menu);
+                pending = None;
+            }
+        }
         match evt {
+            io::Event::Command(_) if cancelled.is_some() => {
+                trace!("Rejecting command after cancel");
+                cancelled = None;
+                comm.borrow_mut().reply(StatusWords::UserCancelled);
+                menu(states.borrow(), &idle_menu, &busy_menu);
+            }
+            // SIGN_TX_CHUNKED is not of the block protocol, its APDUs are checked by chunked.rs
+            io::Event::Command(ins)
+                if ins != Ins::SignChunked && sets_p2_midway(&comm.borrow()) =>
+            {
+                trace!("Rejecting P2 of an APDU continuing a command");
+                comm.borrow_mut().reply(StatusWords::BadP1P2);
+                menu(states.borrow(), &idle_menu, &busy_menu);
+            }
+            io::Event::Command(Ins::SignChunked) => {
+                trace!("Chunked sign command received");
+                let rv = chunked::handle_chunk(&mut comm.borrow_mut(), idle_menu.settings);
+                match rv {
+                    Ok(()) => comm.borrow_mut().reply_ok(),
+                    Err(sw) => comm.borrow_mut().reply(sw),
+                }
+                menu(states.borrow(), &idle_menu, &busy_menu);
+            }
             io::Event::Command(ins) => {
                 trace!("Command received");
+                // Only used when a command starts, the block protocol APDUs which follow it leave P2
+                // at 0.
+                let derivation = Derivation::from_p2(comm.borrow().get_apdu_metadata().p2)
+                    .unwrap_or(Derivation::Slip10);
+                if states.borrow().is_none() {
+                    progress::set_instruction(busy_label(&ins));
+                }
                 let poll_rv = poll_apdu_handlers(
                     PinMut::as_mut(&mut states.0.borrow_mut()),
                     ins,
                     *hostio,
-                    |io, ins| handle_apdu_async(io, ins, idle_menu.settings),
+                    |io, ins| handle_apdu_async(io, ins, idle_menu.settings, derivation),
                 );
                 match poll_rv {
                     Ok(()) => {
@@ -77,9 +143,12 @@ pub fn app_main() {
                         comm.borrow_mut().reply(sw);
                     }
                 };
+                pending = states.borrow().as_ref().map(|_| ins);
                 // Reset BusyMenu if we are done handling APDU
                 if states.borrow().is_none() {
                     busy_menu = BusyMenu::Working;
+                    progress::clear();
+                    progress::clear_instruction();
                 }
                 menu(states.borrow(), &idle_menu, &busy_menu);
                 trace!("Command done");
@@ -90,13 +159,15 @@ pub fn app_main() {
                     true => {
                         if let Some(DoExitApp) = handle_menu_button_event(&mut idle_menu, btn) {
                             info!("Exiting app at user direction via root menu");
+                            key_cache::clear();
                             ledger_device_sdk::exit_app(0)
                         }
                     }
                     _ => {
                         if let Some(DoCancel) = handle_menu_button_event(&mut busy_menu, btn) {
                             info!("Resetting at user direction via busy menu");
-                            PinMut::as_mut(&mut states.borrow_mut()).set(None);
+                            drop_pending(*states, &mut busy_menu);
+                            cancelled = pending.take();
                         }
                     }
                 };
@@ -104,6 +175,15 @@ pub fn app_main() {
                 trace!("Button done");
             }
             io::Event::Ticker => {
+                if states.borrow().is_some() {
+                    busy_ticks += 1;
+                    if idle_menu.settings.busy_timed_out(busy_ticks) {
+                        info!("Resetting after the host went quiet");
+                        drop_pending(*states, &mut busy_menu);
+                        cancelled = pending.take();
+                        menu(states.borrow(), &idle_menu, &busy_menu);
+                    }
+                }
                 if UxEvent::Event.request() != BOLOS_UX_OK {
                     UxEvent::block();
                     // Redisplay application menu here
@@ -115,19 +195,12 @@ pub fn app_main() {
     }
 }
 
-// We are single-threaded in fact, albeit with nontrivial code flow. We don't need to worry about
-// full atomicity of the below globals.
-struct SingleThreaded<T>(T);
-unsafe impl<T> Send for SingleThreaded<T> {}
-unsafe impl<T> Sync for SingleThreaded<T> {}
-impl<T> core::ops::Deref for SingleThreaded<T> {
-    type Target = T;
-    fn deref(&self) -> &T {
-        &self.0
-    }
-}
-impl<T> core::ops::DerefMut for SingleThreaded<T> {
-    fn deref_mut(&mut self) -> &mut T {
-        &mut self.0
-    }
+// Drop the pending command, and everything it left behind, so that the next one starts afresh
+fn drop_pending(states: Pin<&PinCell<Option<APDUsFuture>>>, busy_menu: &mut BusyMenu) {
+    PinMut::as_mut(&mut states.borrow_mut()).set(None);
+    *busy_menu = BusyMenu::Working;
+    progress::clear();
+    progress::clear_instruction();
+    chunked::clear();
+    key_cache::clear();
 }
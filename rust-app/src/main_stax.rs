@@ -0,0 +1,195 @@
+use crate::chunked;
+use crate::implementation::{continues_command, handle_apdu_async, sets_p2_midway, APDUsFuture};
+use crate::interface::{Derivation, Ins};
+use crate::key_cache;
+use crate::settings::Settings;
+use crate::ui_nbgl::{reset_review, APP_ICON};
+use crate::utils::SingleThreaded;
+
+use alamgu_async_block::{poll_apdu_handlers, HostIO, HostIOState};
+
+use ledger_device_sdk::io::{self, StatusWords};
+use ledger_device_sdk::nbgl::NbglHome;
+use ledger_device_sdk::uxapp::{UxEvent, BOLOS_UX_OK};
+use ledger_log::info;
+
+use core::cell::RefCell;
+use core::pin::Pin;
+use pin_cell::{PinCell, PinMut};
+
+// In the order of crate::settings::Setting
+const SETTINGS_STRINGS: [[&str; 2]; 9] = [
+    [
+        "Blind Signing",
+        "Allow signing transactions which cannot be reviewed",
+    ],
+    [
+        "Expert Mode",
+        "Show gas price, expiration, gas objects, commands and object versions, and allow unusual paths",
+    ],
+    ["Amounts in nanos", "Show amounts in nanos instead of IOTA"],
+    [
+        "Blind Sign Once",
+        "Allow blind signing for the next transaction only",
+    ],
+    [
+        "Truncated addresses",
+        "Show the first and last characters of addresses, followed by the full address",
+    ],
+    [
+        "Confirm public key",
+        "Ask before sharing a public key with the host",
+    ],
+    [
+        "Show hash",
+        "Show the transaction hash for transactions which can be reviewed too",
+    ],
+    ["Base64 hashes", "Show hashes in Base64 instead of hex"],
+    [
+        "Decimal comma",
+        "Separate the decimals of amounts with a comma instead of a period",
+    ],
+];
+
+#[allow(dead_code)]
+pub fn app_main() {
+    #[cfg(feature = "memory_stats")]
+    crate::memory_stats::init();
+    let comm: SingleThreaded<RefCell<io::Comm>> = SingleThreaded(RefCell::new(io::Comm::new()));
+
+    let hostio_state: SingleThreaded<RefCell<HostIOState>> =
+        SingleThreaded(RefCell::new(HostIOState::new(unsafe {
+            core::mem::transmute(&comm.0)
+        })));
+    let hostio: SingleThreaded<HostIO> =
+        SingleThreaded(HostIO(unsafe { core::mem::transmute(&hostio_state.0) }));
+    let states_backing: SingleThreaded<PinCell<Option<APDUsFuture>>> =
+        SingleThreaded(PinCell::new(None));
+    let states: SingleThreaded<Pin<&PinCell<Option<APDUsFuture>>>> =
+        SingleThreaded(Pin::static_ref(unsafe {
+            core::mem::transmute(&states_backing.0)
+        }));
+
+    let mut settings = Settings;
+    settings.migrate();
+
+    // The command which timed out. The host is still waiting to send the next APDU of it, which has
+    // to be answered with an error instead.
+    let mut cancelled: Option<Ins> = None;
+    // The command of the pending future, if any.
+    let mut pending: Option<Ins> = None;
+    // Ticks since the last APDU, while in the middle of a command. The NBGL prompts run the event
+    // loop of the SDK, so those wait for the user however long it takes.
+    let mut busy_ticks: u32 = 0;
+
+    info!("IOTA {}", env!("CARGO_PKG_VERSION"));
+
+    loop {
+        // While idle, the home screen runs the event loop until the host sends a command.
+        let evt = if states.borrow().is_none() {
+            let mut comm = comm.borrow_mut();
+            NbglHome::new(Some(&mut *comm))
+                .app_name("IOTA")
+                .info_contents(env!("CARGO_PKG_VERSION"), env!("CARGO_PKG_AUTHORS"))
+                .glyph(&APP_ICON)
+                .settings(settings.nbgl_storage(), &SETTINGS_STRINGS)
+                .show::<Ins>()
+        } else {
+            comm.borrow_mut().next_event::<Ins>()
+        };
+        if !matches!(evt, io::Event::Ticker) {
+            busy_ticks = 0;
+        }
+        // Any other command starts afresh, only the dropped one is left hanging
+        if let io::Event::Command(ins) = &evt {
+            if cancelled != Some(*ins) || !continues_command(&comm.borrow()) {
+                cancelled = None;
+            }
+        }
+        // Another command while one is pending, or the same one started over, means the host has
+        // given up on the pending one, which is dropped along with everything it left behind before
+        // the new one is handled. Its last APDU has been answered already, so there is nothing left
+        // to answer.
+        if let (io::Event::Command(ins), Some(old)) = (&evt, pending) {
+            if *ins != old || !continues_command(&comm.borrow()) {
+                info!("Dropping the pending command for a new one");
+                drop_pending(*states);
+                pending = None;
+            }
+        }
+        match evt {
+            io::Event::Command(_) if cancelled.is_some() => {
+                trace!("Rejecting command after timeout");
+                cancelled = None;
+                comm.borrow_mut().reply(StatusWords::UserCancelled);
+            }
+            // SIGN_TX_CHUNKED is not of the block protocol, its APDUs are checked by chunked.rs
+            io::Event::Command(ins)
+                if ins != Ins::SignChunked && sets_p2_midway(&comm.borrow()) =>
+            {
+                trace!("Rejecting P2 of an APDU continuing a command");
+                comm.borrow_mut().reply(StatusWords::BadP1P2);
+            }
+            io::Event::Command(Ins::SignChunked) => {
+                trace!("Chunked sign command received");
+                let rv = chunked::handle_chunk(&mut comm.borrow_mut(), settings);
+                reset_review();
+                match rv {
+                    Ok(()) => comm.borrow_mut().reply_ok(),
+                    Err(sw) => comm.borrow_mut().reply(sw),
+                }
+            }
+            io::Event::Command(ins) => {
+                trace!("Command received");
+                // Only used when a command starts, the block protocol APDUs which follow it leave P2
+                // at 0.
+                let derivation = Derivation::from_p2(comm.borrow().get_apdu_metadata().p2)
+                    .unwrap_or(Derivation::Slip10);
+                let poll_rv = poll_apdu_handlers(
+                    PinMut::as_mut(&mut states.0.borrow_mut()),
+                    ins,
+                    *hostio,
+                    |io, ins| handle_apdu_async(io, ins, settings, derivation),
+                );
+                match poll_rv {
+                    Ok(()) => {
+                        trace!("APDU accepted; sending response");
+                        comm.borrow_mut().reply_ok();
+                        trace!("Replied");
+                    }
+                    Err(sw) => {
+                        PinMut::as_mut(&mut states.0.borrow_mut()).set(None);
+                        comm.borrow_mut().reply(sw);
+                    }
+                };
+                pending = states.borrow().as_ref().map(|_| ins);
+                if states.borrow().is_none() {
+                    reset_review();
+                }
+                trace!("Command done");
+            }
+            io::Event::Ticker => {
+                if states.borrow().is_some() {
+                    busy_ticks += 1;
+                    if settings.busy_timed_out(busy_ticks) {
+                        info!("Resetting after the host went quiet");
+                        drop_pending(*states);
+                        cancelled = pending.take();
+                    }
+                }
+                if UxEvent::Event.request() != BOLOS_UX_OK {
+                    UxEvent::block();
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+// Drop the pending command, and everything it left behind, so that the next one starts afresh
+fn drop_pending(states: Pin<&PinCell<Option<APDUsFuture>>>) {
+    PinMut::as_mut(&mut states.borrow_mut()).set(None);
+    reset_review();
+    chunked::clear();
+    key_cache::clear();
+}
@@ -0,0 +1,48 @@
+use crate::implementation::APDUsFuture;
+
+// How much memory parsing takes, for builds with the memory_stats feature, so that the headroom
+// left for new parsers can be measured instead of guessed.
+//
+// The stack is sampled each time a NoinlineFut is polled, which is how the deeper parts of the
+// parser are entered, so the peak depth is a lower bound of the real one.
+
+// Where the stack was when the app started, which depths are measured from
+static mut STACK_BASE: usize = 0;
+
+// The deepest the stack has been sampled at since
+static mut STACK_LOWEST: usize = usize::MAX;
+
+#[inline(always)]
+fn stack_pointer() -> usize {
+    let marker = 0u8;
+    core::hint::black_box(&marker) as *const u8 as usize
+}
+
+pub fn init() {
+    let sp = stack_pointer();
+    unsafe {
+        STACK_BASE = sp;
+        STACK_LOWEST = sp;
+    }
+}
+
+#[inline(always)]
+pub fn sample() {
+    let sp = stack_pointer();
+    unsafe {
+        if sp < STACK_LOWEST {
+            STACK_LOWEST = sp;
+        }
+    }
+}
+
+/// The peak stack depth in bytes, followed by the size of the state of a command in progress,
+/// which is where the parser keeps what it has collected, both as u32 LE.
+pub fn counters() -> [u8; 8] {
+    let depth = unsafe { STACK_BASE.saturating_sub(STACK_LOWEST) } as u32;
+    let state = core::mem::size_of::<Option<APDUsFuture>>() as u32;
+    let mut rv = [0; 8];
+    rv[..4].copy_from_slice(&depth.to_le_bytes());
+    rv[4..].copy_from_slice(&state.to_le_bytes());
+    rv
+}
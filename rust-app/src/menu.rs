@@ -1,9 +1,15 @@
-use crate::settings::Settings;
+#[cfg(feature = "address_book")]
+use crate::address_book;
+use crate::interface::{Ins, API_VERSION};
+use crate::progress;
+use crate::settings::{Setting, Settings, TIMEOUT_OPTIONS};
+use crate::signature_counter;
 use include_gif::include_gif;
 use ledger_device_sdk::ui::bagls::Icon;
 use ledger_device_sdk::ui::bitmaps::Glyph;
 use ledger_prompts_ui::{
-    Menu, MenuLabelBottom, MenuLabelTop, BACK_ICON, MENU_ICON_X, MENU_ICON_Y, SETTINGS_ICON,
+    final_accept_prompt, Menu, MenuLabelBottom, MenuLabelTop, BACK_ICON, MENU_ICON_X, MENU_ICON_Y,
+    SETTINGS_ICON,
 };
 
 pub const APP_ICON_GLYPH: Glyph = Glyph::from_include(include_gif!("iota-small.gif"));
@@ -12,6 +18,9 @@ pub const APP_ICON: Icon = Icon::from(&APP_ICON_GLYPH)
     .set_x(MENU_ICON_X)
     .set_y(MENU_ICON_Y);
 
+// Without the expert screens, expert mode would have nothing to show
+const EXPERT_SCREENS: bool = cfg!(feature = "expert_screens");
+
 pub struct IdleMenuWithSettings {
     pub idle_menu: IdleMenu,
     pub settings: Settings,
@@ -21,12 +30,33 @@ pub enum IdleMenu {
     AppMain,
     ShowVersion,
     Settings(Option<SettingsSubMenu>),
+    About(Option<AboutSubMenu>),
     Exit,
 }
 
+#[derive(Clone, Copy)]
 pub enum SettingsSubMenu {
-    EnableBlindSigning,
-    DisableBlindSigning,
+    BlindSigning,
+    BlindSigningOnce,
+    ExpertMode,
+    Units,
+    Addresses,
+    Timeout,
+    ConfirmPubkey,
+    ShowHash,
+    HashFormat,
+    DecimalMark,
+    LimitReview,
+    Reset,
+    Back,
+}
+
+pub enum AboutSubMenu {
+    AppName,
+    ApiVersion,
+    SdkVersion,
+    Commit,
+    Signatures,
     Back,
 }
 
@@ -44,34 +74,136 @@ impl Menu for IdleMenuWithSettings {
             IdleMenu::AppMain => self.idle_menu = IdleMenu::Exit,
             IdleMenu::ShowVersion => self.idle_menu = IdleMenu::AppMain,
             IdleMenu::Settings(None) => self.idle_menu = IdleMenu::ShowVersion,
+            IdleMenu::Settings(Some(SettingsSubMenu::BlindSigning)) => {
+                self.idle_menu = IdleMenu::Settings(Some(SettingsSubMenu::Back))
+            }
+            IdleMenu::Settings(Some(SettingsSubMenu::BlindSigningOnce)) => {
+                self.idle_menu = IdleMenu::Settings(Some(SettingsSubMenu::BlindSigning))
+            }
+            IdleMenu::Settings(Some(SettingsSubMenu::ExpertMode)) => {
+                self.idle_menu = IdleMenu::Settings(Some(SettingsSubMenu::BlindSigningOnce))
+            }
+            IdleMenu::Settings(Some(SettingsSubMenu::Units)) if !EXPERT_SCREENS => {
+                self.idle_menu = IdleMenu::Settings(Some(SettingsSubMenu::BlindSigningOnce))
+            }
+            IdleMenu::Settings(Some(SettingsSubMenu::Units)) => {
+                self.idle_menu = IdleMenu::Settings(Some(SettingsSubMenu::ExpertMode))
+            }
+            IdleMenu::Settings(Some(SettingsSubMenu::Addresses)) => {
+                self.idle_menu = IdleMenu::Settings(Some(SettingsSubMenu::Units))
+            }
+            IdleMenu::Settings(Some(SettingsSubMenu::Timeout)) => {
+                self.idle_menu = IdleMenu::Settings(Some(SettingsSubMenu::Addresses))
+            }
+            IdleMenu::Settings(Some(SettingsSubMenu::ConfirmPubkey)) => {
+                self.idle_menu = IdleMenu::Settings(Some(SettingsSubMenu::Timeout))
+            }
+            IdleMenu::Settings(Some(SettingsSubMenu::ShowHash)) => {
+                self.idle_menu = IdleMenu::Settings(Some(SettingsSubMenu::ConfirmPubkey))
+            }
+            IdleMenu::Settings(Some(SettingsSubMenu::HashFormat)) => {
+                self.idle_menu = IdleMenu::Settings(Some(SettingsSubMenu::ShowHash))
+            }
+            IdleMenu::Settings(Some(SettingsSubMenu::DecimalMark)) => {
+                self.idle_menu = IdleMenu::Settings(Some(SettingsSubMenu::HashFormat))
+            }
+            IdleMenu::Settings(Some(SettingsSubMenu::LimitReview)) => {
+                self.idle_menu = IdleMenu::Settings(Some(SettingsSubMenu::DecimalMark))
+            }
+            IdleMenu::Settings(Some(SettingsSubMenu::Reset)) => {
+                self.idle_menu = IdleMenu::Settings(Some(SettingsSubMenu::LimitReview))
+            }
             IdleMenu::Settings(Some(SettingsSubMenu::Back)) => {
-                if self.settings.get() == 1 {
-                    self.idle_menu = IdleMenu::Settings(Some(SettingsSubMenu::DisableBlindSigning))
-                } else {
-                    self.idle_menu = IdleMenu::Settings(Some(SettingsSubMenu::EnableBlindSigning))
-                }
+                self.idle_menu = IdleMenu::Settings(Some(SettingsSubMenu::Reset))
             }
-            IdleMenu::Settings(Some(_)) => {
-                self.idle_menu = IdleMenu::Settings(Some(SettingsSubMenu::Back))
+            IdleMenu::About(None) => self.idle_menu = IdleMenu::Settings(None),
+            IdleMenu::About(Some(AboutSubMenu::AppName)) => {
+                self.idle_menu = IdleMenu::About(Some(AboutSubMenu::Back))
+            }
+            IdleMenu::About(Some(AboutSubMenu::ApiVersion)) => {
+                self.idle_menu = IdleMenu::About(Some(AboutSubMenu::AppName))
+            }
+            IdleMenu::About(Some(AboutSubMenu::SdkVersion)) => {
+                self.idle_menu = IdleMenu::About(Some(AboutSubMenu::ApiVersion))
+            }
+            IdleMenu::About(Some(AboutSubMenu::Commit)) => {
+                self.idle_menu = IdleMenu::About(Some(AboutSubMenu::SdkVersion))
+            }
+            IdleMenu::About(Some(AboutSubMenu::Signatures)) => {
+                self.idle_menu = IdleMenu::About(Some(AboutSubMenu::Commit))
+            }
+            IdleMenu::About(Some(AboutSubMenu::Back)) => {
+                self.idle_menu = IdleMenu::About(Some(AboutSubMenu::Signatures))
             }
-            IdleMenu::Exit => self.idle_menu = IdleMenu::Settings(None),
+            IdleMenu::Exit => self.idle_menu = IdleMenu::About(None),
         };
     }
     fn move_right(&mut self) {
         match self.idle_menu {
             IdleMenu::AppMain => self.idle_menu = IdleMenu::ShowVersion,
             IdleMenu::ShowVersion => self.idle_menu = IdleMenu::Settings(None),
-            IdleMenu::Settings(None) => self.idle_menu = IdleMenu::Exit,
-            IdleMenu::Settings(Some(SettingsSubMenu::Back)) => {
-                if self.settings.get() == 1 {
-                    self.idle_menu = IdleMenu::Settings(Some(SettingsSubMenu::DisableBlindSigning))
-                } else {
-                    self.idle_menu = IdleMenu::Settings(Some(SettingsSubMenu::EnableBlindSigning))
-                }
+            IdleMenu::Settings(None) => self.idle_menu = IdleMenu::About(None),
+            IdleMenu::Settings(Some(SettingsSubMenu::BlindSigning)) => {
+                self.idle_menu = IdleMenu::Settings(Some(SettingsSubMenu::BlindSigningOnce))
+            }
+            IdleMenu::Settings(Some(SettingsSubMenu::BlindSigningOnce)) if !EXPERT_SCREENS => {
+                self.idle_menu = IdleMenu::Settings(Some(SettingsSubMenu::Units))
+            }
+            IdleMenu::Settings(Some(SettingsSubMenu::BlindSigningOnce)) => {
+                self.idle_menu = IdleMenu::Settings(Some(SettingsSubMenu::ExpertMode))
+            }
+            IdleMenu::Settings(Some(SettingsSubMenu::ExpertMode)) => {
+                self.idle_menu = IdleMenu::Settings(Some(SettingsSubMenu::Units))
+            }
+            IdleMenu::Settings(Some(SettingsSubMenu::Units)) => {
+                self.idle_menu = IdleMenu::Settings(Some(SettingsSubMenu::Addresses))
+            }
+            IdleMenu::Settings(Some(SettingsSubMenu::Addresses)) => {
+                self.idle_menu = IdleMenu::Settings(Some(SettingsSubMenu::Timeout))
+            }
+            IdleMenu::Settings(Some(SettingsSubMenu::Timeout)) => {
+                self.idle_menu = IdleMenu::Settings(Some(SettingsSubMenu::ConfirmPubkey))
             }
-            IdleMenu::Settings(Some(_)) => {
+            IdleMenu::Settings(Some(SettingsSubMenu::ConfirmPubkey)) => {
+                self.idle_menu = IdleMenu::Settings(Some(SettingsSubMenu::ShowHash))
+            }
+            IdleMenu::Settings(Some(SettingsSubMenu::ShowHash)) => {
+                self.idle_menu = IdleMenu::Settings(Some(SettingsSubMenu::HashFormat))
+            }
+            IdleMenu::Settings(Some(SettingsSubMenu::HashFormat)) => {
+                self.idle_menu = IdleMenu::Settings(Some(SettingsSubMenu::DecimalMark))
+            }
+            IdleMenu::Settings(Some(SettingsSubMenu::DecimalMark)) => {
+                self.idle_menu = IdleMenu::Settings(Some(SettingsSubMenu::LimitReview))
+            }
+            IdleMenu::Settings(Some(SettingsSubMenu::LimitReview)) => {
+                self.idle_menu = IdleMenu::Settings(Some(SettingsSubMenu::Reset))
+            }
+            IdleMenu::Settings(Some(SettingsSubMenu::Reset)) => {
                 self.idle_menu = IdleMenu::Settings(Some(SettingsSubMenu::Back))
             }
+            IdleMenu::Settings(Some(SettingsSubMenu::Back)) => {
+                self.idle_menu = IdleMenu::Settings(Some(SettingsSubMenu::BlindSigning))
+            }
+            IdleMenu::About(None) => self.idle_menu = IdleMenu::Exit,
+            IdleMenu::About(Some(AboutSubMenu::AppName)) => {
+                self.idle_menu = IdleMenu::About(Some(AboutSubMenu::ApiVersion))
+            }
+            IdleMenu::About(Some(AboutSubMenu::ApiVersion)) => {
+                self.idle_menu = IdleMenu::About(Some(AboutSubMenu::SdkVersion))
+            }
+            IdleMenu::About(Some(AboutSubMenu::SdkVersion)) => {
+                self.idle_menu = IdleMenu::About(Some(AboutSubMenu::Commit))
+            }
+            IdleMenu::About(Some(AboutSubMenu::Commit)) => {
+                self.idle_menu = IdleMenu::About(Some(AboutSubMenu::Signatures))
+            }
+            IdleMenu::About(Some(AboutSubMenu::Signatures)) => {
+                self.idle_menu = IdleMenu::About(Some(AboutSubMenu::Back))
+            }
+            IdleMenu::About(Some(AboutSubMenu::Back)) => {
+                self.idle_menu = IdleMenu::About(Some(AboutSubMenu::AppName))
+            }
             IdleMenu::Exit => self.idle_menu = IdleMenu::AppMain,
         };
     }
@@ -81,27 +213,30 @@ impl Menu for IdleMenuWithSettings {
             IdleMenu::AppMain => None,
             IdleMenu::ShowVersion => None,
             IdleMenu::Settings(None) => {
-                if self.settings.get() == 1 {
-                    self.idle_menu = IdleMenu::Settings(Some(SettingsSubMenu::DisableBlindSigning))
-                } else {
-                    self.idle_menu = IdleMenu::Settings(Some(SettingsSubMenu::EnableBlindSigning))
-                };
+                self.idle_menu = IdleMenu::Settings(Some(SettingsSubMenu::BlindSigning));
                 None
             }
-            IdleMenu::Settings(Some(SettingsSubMenu::EnableBlindSigning)) => {
-                self.settings.set(&1);
-                self.idle_menu = IdleMenu::Settings(Some(SettingsSubMenu::DisableBlindSigning));
+            IdleMenu::Settings(Some(SettingsSubMenu::Back)) => {
+                self.idle_menu = IdleMenu::Settings(None);
                 None
             }
-            IdleMenu::Settings(Some(SettingsSubMenu::DisableBlindSigning)) => {
-                self.settings.set(&0);
-                self.idle_menu = IdleMenu::Settings(Some(SettingsSubMenu::EnableBlindSigning));
+            // The labels are too short to explain what a setting risks, so each change is
+            // confirmed after a short explanation.
+            IdleMenu::Settings(Some(entry)) => {
+                if final_accept_prompt(entry.help()).is_some() {
+                    self.change(entry);
+                }
                 None
             }
-            IdleMenu::Settings(Some(SettingsSubMenu::Back)) => {
-                self.idle_menu = IdleMenu::Settings(None);
+            IdleMenu::About(None) => {
+                self.idle_menu = IdleMenu::About(Some(AboutSubMenu::AppName));
+                None
+            }
+            IdleMenu::About(Some(AboutSubMenu::Back)) => {
+                self.idle_menu = IdleMenu::About(None);
                 None
             }
+            IdleMenu::About(Some(_)) => None,
             IdleMenu::Exit => Some(DoExitApp),
         }
     }
@@ -129,17 +264,91 @@ impl Menu for IdleMenuWithSettings {
                     bold: true,
                 },
             ),
-            IdleMenu::Settings(Some(SettingsSubMenu::EnableBlindSigning)) => (
+            IdleMenu::Settings(Some(SettingsSubMenu::BlindSigning)) => (
                 MenuLabelTop::Text("Blind Signing"),
+                self.enabled_label(Setting::BlindSigning),
+            ),
+            IdleMenu::Settings(Some(SettingsSubMenu::BlindSigningOnce)) => (
+                MenuLabelTop::Text("Blind Sign Once"),
+                self.enabled_label(Setting::BlindSigningOnce),
+            ),
+            IdleMenu::Settings(Some(SettingsSubMenu::ExpertMode)) => (
+                MenuLabelTop::Text("Expert Mode"),
+                self.enabled_label(Setting::ExpertMode),
+            ),
+            IdleMenu::Settings(Some(SettingsSubMenu::Units)) => (
+                MenuLabelTop::Text("Amount Unit"),
                 MenuLabelBottom {
-                    text: "Disabled",
+                    text: if self.settings.is_enabled(Setting::BaseUnits) {
+                        "nanos"
+                    } else {
+                        "IOTA"
+                    },
                     bold: false,
                 },
             ),
-            IdleMenu::Settings(Some(SettingsSubMenu::DisableBlindSigning)) => (
-                MenuLabelTop::Text("Blind Signing"),
+            IdleMenu::Settings(Some(SettingsSubMenu::Addresses)) => (
+                MenuLabelTop::Text("Addresses"),
+                MenuLabelBottom {
+                    text: if self.settings.is_enabled(Setting::TruncatedAddresses) {
+                        "Truncated"
+                    } else {
+                        "Full"
+                    },
+                    bold: false,
+                },
+            ),
+            IdleMenu::Settings(Some(SettingsSubMenu::Timeout)) => (
+                MenuLabelTop::Text("Timeout"),
                 MenuLabelBottom {
-                    text: "Enabled",
+                    text: match self.settings.timeout_minutes() {
+                        None => "Off",
+                        Some(1) => "1 minute",
+                        Some(5) => "5 minutes",
+                        Some(15) => "15 minutes",
+                        Some(_) => "Custom",
+                    },
+                    bold: false,
+                },
+            ),
+            IdleMenu::Settings(Some(SettingsSubMenu::ConfirmPubkey)) => (
+                MenuLabelTop::Text("Confirm Pubkey"),
+                self.enabled_label(Setting::ConfirmPubkey),
+            ),
+            IdleMenu::Settings(Some(SettingsSubMenu::ShowHash)) => (
+                MenuLabelTop::Text("Show Hash"),
+                self.enabled_label(Setting::ShowHash),
+            ),
+            IdleMenu::Settings(Some(SettingsSubMenu::HashFormat)) => (
+                MenuLabelTop::Text("Hash Format"),
+                MenuLabelBottom {
+                    text: if self.settings.is_enabled(Setting::Base64Hashes) {
+                        "Base64"
+                    } else {
+                        "Hex"
+                    },
+                    bold: false,
+                },
+            ),
+            IdleMenu::Settings(Some(SettingsSubMenu::DecimalMark)) => (
+                MenuLabelTop::Text("Decimal Mark"),
+                MenuLabelBottom {
+                    text: if self.settings.is_enabled(Setting::DecimalComma) {
+                        "Comma"
+                    } else {
+                        "Period"
+                    },
+                    bold: false,
+                },
+            ),
+            IdleMenu::Settings(Some(SettingsSubMenu::LimitReview)) => (
+                MenuLabelTop::Text("Limit Reviews"),
+                self.enabled_label(Setting::LimitReview),
+            ),
+            IdleMenu::Settings(Some(SettingsSubMenu::Reset)) => (
+                MenuLabelTop::Text("Reset Settings"),
+                MenuLabelBottom {
+                    text: "",
                     bold: false,
                 },
             ),
@@ -150,6 +359,55 @@ impl Menu for IdleMenuWithSettings {
                     bold: true,
                 },
             ),
+            IdleMenu::About(None) => (
+                MenuLabelTop::Text("About"),
+                MenuLabelBottom {
+                    text: "",
+                    bold: false,
+                },
+            ),
+            IdleMenu::About(Some(AboutSubMenu::AppName)) => (
+                MenuLabelTop::Text("App"),
+                MenuLabelBottom {
+                    text: "IOTA Rebased",
+                    bold: false,
+                },
+            ),
+            IdleMenu::About(Some(AboutSubMenu::ApiVersion)) => (
+                MenuLabelTop::Text("API Version"),
+                MenuLabelBottom {
+                    text: API_VERSION,
+                    bold: false,
+                },
+            ),
+            IdleMenu::About(Some(AboutSubMenu::SdkVersion)) => (
+                MenuLabelTop::Text("SDK Version"),
+                MenuLabelBottom {
+                    text: env!("LEDGER_SDK_VERSION"),
+                    bold: false,
+                },
+            ),
+            IdleMenu::About(Some(AboutSubMenu::Commit)) => (
+                MenuLabelTop::Text("Commit"),
+                MenuLabelBottom {
+                    text: env!("GIT_HASH"),
+                    bold: false,
+                },
+            ),
+            IdleMenu::About(Some(AboutSubMenu::Signatures)) => (
+                MenuLabelTop::Text("Signatures"),
+                MenuLabelBottom {
+                    text: signature_counter::label(),
+                    bold: false,
+                },
+            ),
+            IdleMenu::About(Some(AboutSubMenu::Back)) => (
+                MenuLabelTop::Icon(&BACK_ICON),
+                MenuLabelBottom {
+                    text: "Back",
+                    bold: true,
+                },
+            ),
             IdleMenu::Exit => (
                 MenuLabelTop::Icon(&ledger_prompts_ui::DASHBOARD_ICON),
                 MenuLabelBottom {
@@ -161,6 +419,98 @@ impl Menu for IdleMenuWithSettings {
     }
 }
 
+impl SettingsSubMenu {
+    fn help(&self) -> &'static [&'static str] {
+        match self {
+            SettingsSubMenu::BlindSigning => &["Sign what cannot", "be reviewed,", "trusting host"],
+            SettingsSubMenu::BlindSigningOnce => {
+                &["Blind signing", "for the next", "transaction only"]
+            }
+            SettingsSubMenu::ExpertMode => &["Also show gas,", "expiration and", "commands"],
+            SettingsSubMenu::Units => &["Show amounts in", "nanos, 10^-9", "of an IOTA"],
+            SettingsSubMenu::Addresses => &["Show short", "addresses, full", "on request"],
+            SettingsSubMenu::Timeout => &["Cancel commands", "the host leaves", "waiting"],
+            SettingsSubMenu::ConfirmPubkey => &["Ask before", "sharing public", "keys"],
+            SettingsSubMenu::ShowHash => &["Also show hash", "of reviewed", "transactions"],
+            SettingsSubMenu::HashFormat => &["Show hashes in", "Base64 to match", "other tools"],
+            SettingsSubMenu::DecimalMark => &["Write amounts", "as 1,5 instead", "of 1.5"],
+            SettingsSubMenu::LimitReview => &["Ask to go on", "after 8 items", "of a review"],
+            SettingsSubMenu::Reset if cfg!(feature = "address_book") => {
+                &["Restore defaults", "and clear the", "address book"]
+            }
+            SettingsSubMenu::Reset => &["Restore all", "settings to", "their defaults"],
+            SettingsSubMenu::Back => &[],
+        }
+    }
+}
+
+impl IdleMenuWithSettings {
+    fn change(&mut self, entry: SettingsSubMenu) {
+        match entry {
+            SettingsSubMenu::BlindSigning => self.toggle(Setting::BlindSigning),
+            SettingsSubMenu::BlindSigningOnce => self.toggle(Setting::BlindSigningOnce),
+            SettingsSubMenu::ExpertMode => self.toggle(Setting::ExpertMode),
+            SettingsSubMenu::Units => self.toggle(Setting::BaseUnits),
+            SettingsSubMenu::Addresses => self.toggle(Setting::TruncatedAddresses),
+            SettingsSubMenu::Timeout => self.next_timeout(),
+            SettingsSubMenu::ConfirmPubkey => self.toggle(Setting::ConfirmPubkey),
+            SettingsSubMenu::ShowHash => self.toggle(Setting::ShowHash),
+            SettingsSubMenu::HashFormat => self.toggle(Setting::Base64Hashes),
+            SettingsSubMenu::DecimalMark => self.toggle(Setting::DecimalComma),
+            SettingsSubMenu::LimitReview => self.toggle(Setting::LimitReview),
+            SettingsSubMenu::Reset => {
+                self.settings.reset();
+                #[cfg(feature = "address_book")]
+                address_book::clear();
+            }
+            SettingsSubMenu::Back => {}
+        }
+    }
+
+    fn toggle(&mut self, setting: Setting) {
+        let enabled = self.settings.is_enabled(setting);
+        self.settings.set_enabled(setting, !enabled);
+    }
+
+    // Cycle through TIMEOUT_OPTIONS
+    fn next_timeout(&mut self) {
+        let current = self.settings.timeout_minutes().unwrap_or(0);
+        let next = TIMEOUT_OPTIONS
+            .iter()
+            .position(|&m| m == current)
+            .map_or(0, |i| (i + 1) % TIMEOUT_OPTIONS.len());
+        self.settings.set_timeout_minutes(TIMEOUT_OPTIONS[next]);
+    }
+
+    fn enabled_label<'a>(&self, setting: Setting) -> MenuLabelBottom<'a> {
+        MenuLabelBottom {
+            text: if self.settings.is_enabled(setting) {
+                "Enabled"
+            } else {
+                "Disabled"
+            },
+            bold: false,
+        }
+    }
+}
+
+/// What the busy menu shows while handling `ins`.
+pub fn busy_label(ins: &Ins) -> &'static str {
+    match ins {
+        Ins::GetVersion => "Getting version",
+        Ins::VerifyAddress => "Verifying address",
+        Ins::GetPubkey => "Exporting pubkey",
+        Ins::Sign | Ins::SignChunked => "Signing",
+        Ins::SaveAddress => "Saving address",
+        Ins::SelfTest => "Self-testing",
+        Ins::LabelRecipient => "Labelling recipient",
+        Ins::GetPatterns => "Listing patterns",
+        Ins::GetLimits => "Getting limits",
+        Ins::GetReceipt => "Getting receipt",
+        Ins::Exit => "Exiting",
+    }
+}
+
 pub struct DoCancel;
 
 impl Menu for BusyMenu {
@@ -182,9 +532,9 @@ impl Menu for BusyMenu {
     fn label<'a>(&self) -> (MenuLabelTop<'a>, MenuLabelBottom<'a>) {
         match self {
             BusyMenu::Working => (
-                MenuLabelTop::Text("Working..."),
+                MenuLabelTop::Text(progress::instruction()),
                 MenuLabelBottom {
-                    text: "",
+                    text: progress::label(),
                     bold: false,
                 },
             ),
@@ -0,0 +1,2416 @@
+use crate::futures::NoinlineFut;
+use crate::interface::{
+    Amount, AppError, ArgumentSchema, CallArgSchema, CommandSchema, EpochId, GasData,
+    IdentifierSchema, Intent, IntentMessage, IotaAddress, MoveCallSchema, ObjectRef,
+    ProgrammableTransaction, Recipient, SharedObject, TransactionData, TransactionDataV1,
+    TransactionExpiration, TransactionKind, TypeTagSchema, IOTA_ADDRESS_LENGTH, U16LE,
+};
+#[cfg(feature = "lending")]
+use crate::patterns::LendingAction;
+#[cfg(feature = "liquidity")]
+use crate::patterns::LiquidityAction;
+use crate::patterns::{
+    self, CallKind, PureType, StakedObject, IOTA_COIN_MODULE, IOTA_COIN_NAME,
+    IOTA_FRAMEWORK_ADDRESS,
+};
+use crate::progress;
+use arrayvec::{ArrayString, ArrayVec};
+use ledger_parser_combinators::async_parser::{reject, AsyncParser, HasOutput, Readable};
+use ledger_parser_combinators::bcs::async_parser::{Vec, ULEB128};
+use ledger_parser_combinators::interp::{Action, DefaultInterp, SubInterp};
+
+use core::future::Future;
+
+pub type IotaAddressRaw = [u8; IOTA_ADDRESS_LENGTH];
+pub type ObjectIdRaw = [u8; IOTA_ADDRESS_LENGTH];
+
+/// Interpretation of the schemas of ledger-iota-interface, the IOTA counterpart of DefaultInterp.
+///
+/// A type of this crate, as the schemas are defined in another one and the parsers of a schema
+/// have to be implemented next to either the schema or the interpretation.
+pub struct IotaInterp;
+
+/// Parser for the ULEB128 lengths and enum tags of BCS, which only accepts the canonical encoding
+/// of a u32: at most 5 bytes, no trailing zero bytes, and nothing above u32::MAX.
+///
+/// Anything else rejects right away, instead of turning into an odd length or tag further into the
+/// transaction. A malformed transaction should not be signed at all, so this is not left to fall
+/// back to blind signing.
+pub struct CanonicalULEB128;
+
+// Why the parse of the transaction being signed was last rejected, as some rejections have to be
+// reported even though the parse falls back to blind signing
+static mut PARSE_REJECTION: Option<AppError> = None;
+
+/// Reject with the status word for `e`, remembering why.
+pub async fn reject_with<T>(e: AppError) -> T {
+    reject(note_rejection(e)).await
+}
+
+// Kept out of line, as each instance of reject_with would carry a copy of it otherwise
+#[cold]
+#[inline(never)]
+fn note_rejection(e: AppError) -> u16 {
+    trace!("Rejecting: {:?}", e);
+    unsafe { PARSE_REJECTION = Some(e) };
+    #[cfg(target_family = "bolos")]
+    {
+        e.status_word()
+    }
+    // Host builds only tell rejections apart by the error, they have no SDK for status words
+    #[cfg(not(target_family = "bolos"))]
+    {
+        0
+    }
+}
+
+/// Forget why the last parse was rejected, before starting another.
+pub fn clear_rejection() {
+    unsafe { PARSE_REJECTION = None };
+}
+
+/// Why the last parse was rejected, if it was rejected with [`reject_with`].
+pub fn take_rejection() -> Option<AppError> {
+    unsafe { PARSE_REJECTION.take() }
+}
+
+impl HasOutput<ULEB128> for CanonicalULEB128 {
+    type Output = u32;
+}
+
+impl<BS: Readable> AsyncParser<ULEB128, BS> for CanonicalULEB128 {
+    type State<'c> = impl Future<Output = Self::Output> + 'c where BS: 'c;
+    fn parse<'a: 'c, 'b: 'c, 'c>(&'b self, input: &'a mut BS) -> Self::State<'c> {
+        async move {
+            let mut value: u32 = 0;
+            for i in 0..5 {
+                let [byte]: [u8; 1] = input.read().await;
+                let bits = (byte & 0x7f) as u32;
+                // The fifth byte only has room for the top 4 bits of a u32
+                if i == 4 && bits > 0x0f {
+                    return reject_with(AppError::BadUleb128).await;
+                }
+                value |= bits << (7 * i);
+                if byte & 0x80 == 0 {
+                    // A zero last byte could have been left out
+                    if i > 0 && byte == 0 {
+                        return reject_with(AppError::BadUleb128).await;
+                    }
+                    return value;
+                }
+            }
+            reject_with(AppError::BadUleb128).await
+        }
+    }
+}
+
+pub enum CallArg {
+    Address(IotaAddressRaw),
+    Amount(u64),
+    SmallPure(SmallPure),
+    OtherPure,
+    OwnedObject(OwnedObject),
+    SharedObject,
+}
+
+/// An object of the sender, or an immutable one, in the exact state the transaction uses.
+#[derive(Clone, Copy)]
+pub struct OwnedObject {
+    pub id: ObjectIdRaw,
+    pub version: u64,
+    pub digest: [u8; 32],
+}
+
+/// A pure input of 1, 2 or 16 bytes, which is a bool, a u8, a u16 or a u128 depending on the call
+/// it is given to.
+#[derive(Clone, Copy)]
+pub struct SmallPure {
+    pub bytes: [u8; 16],
+    pub length: usize,
+}
+
+impl HasOutput<CallArgSchema> for IotaInterp {
+    type Output = CallArg;
+}
+
+impl<BS: Clone + Readable> AsyncParser<CallArgSchema, BS> for IotaInterp {
+    type State<'c> = impl Future<Output = Self::Output> + 'c where BS: 'c;
+    fn parse<'a: 'c, 'b: 'c, 'c>(&'b self, input: &'a mut BS) -> Self::State<'c> {
+        async move {
+            let enum_variant =
+                <CanonicalULEB128 as AsyncParser<ULEB128, BS>>::parse(&CanonicalULEB128, input)
+                    .await;
+            match enum_variant {
+                0 => {
+                    let length = <CanonicalULEB128 as AsyncParser<ULEB128, BS>>::parse(
+                        &CanonicalULEB128,
+                        input,
+                    )
+                    .await;
+                    trace!("CallArgSchema: Pure: length: {}", length);
+                    match length {
+                        8 => CallArg::Amount(
+                            <DefaultInterp as AsyncParser<Amount, BS>>::parse(
+                                &DefaultInterp,
+                                input,
+                            )
+                            .await,
+                        ),
+                        // Could be the recipient, which is up to the commands using it
+                        32 => CallArg::Address(
+                            <DefaultInterp as AsyncParser<Recipient, BS>>::parse(
+                                &DefaultInterp,
+                                input,
+                            )
+                            .await,
+                        ),
+                        1 | 2 | 16 => {
+                            let mut bytes = [0; 16];
+                            for byte in &mut bytes[..length as usize] {
+                                let [b]: [u8; 1] = input.read().await;
+                                *byte = b;
+                            }
+                            CallArg::SmallPure(SmallPure {
+                                bytes,
+                                length: length as usize,
+                            })
+                        }
+                        _ => {
+                            for _ in 0..length {
+                                let _: [u8; 1] = input.read().await;
+                            }
+                            CallArg::OtherPure
+                        }
+                    }
+                }
+                1 => {
+                    let enum_variant = <CanonicalULEB128 as AsyncParser<ULEB128, BS>>::parse(
+                        &CanonicalULEB128,
+                        input,
+                    )
+                    .await;
+                    match enum_variant {
+                        0 => {
+                            trace!("CallArgSchema: ObjectArg: ImmOrOwnedObject");
+                            CallArg::OwnedObject(object_ref_parser().parse(input).await)
+                        }
+                        1 => {
+                            trace!("CallArgSchema: ObjectArg: SharedObject");
+                            <(DefaultInterp, DefaultInterp, DefaultInterp) as AsyncParser<
+                                SharedObject,
+                                BS,
+                            >>::parse(
+                                &(DefaultInterp, DefaultInterp, DefaultInterp), input
+                            )
+                            .await;
+                            CallArg::SharedObject
+                        }
+                        _ => reject_with(AppError::UnsupportedObjectArg).await,
+                    }
+                }
+                _ => {
+                    trace!("CallArgSchema: Unknown enum: {}", enum_variant);
+                    reject_with(AppError::UnsupportedCallArg).await
+                }
+            }
+        }
+    }
+}
+
+// The longest names of modules, functions and structs which are kept. Longer ones are never those
+// of a known call, see patterns.rs.
+pub const MAX_IDENTIFIER_LENGTH: usize = 32;
+pub type Identifier = ArrayString<MAX_IDENTIFIER_LENGTH>;
+
+impl HasOutput<IdentifierSchema> for IotaInterp {
+    type Output = Option<Identifier>;
+}
+
+impl<BS: Clone + Readable> AsyncParser<IdentifierSchema, BS> for IotaInterp {
+    type State<'c> = impl Future<Output = Self::Output> + 'c where BS: 'c;
+    fn parse<'a: 'c, 'b: 'c, 'c>(&'b self, input: &'a mut BS) -> Self::State<'c> {
+        async move {
+            let length =
+                <CanonicalULEB128 as AsyncParser<ULEB128, BS>>::parse(&CanonicalULEB128, input)
+                    .await;
+            let mut rv = Some(Identifier::new());
+            for _ in 0..length {
+                let [byte]: [u8; 1] = input.read().await;
+                // Identifiers are ASCII, so anything else is not the name of a known call either
+                let kept = rv.as_mut().map_or(false, |name| {
+                    byte.is_ascii() && name.try_push(byte as char).is_ok()
+                });
+                if !kept {
+                    rv = None;
+                }
+            }
+            rv
+        }
+    }
+}
+
+/// A struct type without type parameters, like the type of a coin.
+#[derive(Clone, PartialEq, Eq)]
+pub struct StructTag {
+    pub address: IotaAddressRaw,
+    pub module: Identifier,
+    pub name: Identifier,
+}
+
+impl StructTag {
+    pub fn is_iota(&self) -> bool {
+        self.address == IOTA_FRAMEWORK_ADDRESS
+            && self.module.as_str() == IOTA_COIN_MODULE
+            && self.name.as_str() == IOTA_COIN_NAME
+    }
+}
+
+// The most type tags one type argument may nest, vectors and type parameters of structs included
+const MAX_NESTED_TYPE_TAGS: u32 = 16;
+
+impl HasOutput<TypeTagSchema> for IotaInterp {
+    type Output = Option<StructTag>;
+}
+
+// The struct the type tag is, if it is one without type parameters. Anything nested in it is
+// parsed one tag after the other, instead of recursively, so that it takes no more stack.
+impl<BS: Clone + Readable> AsyncParser<TypeTagSchema, BS> for IotaInterp {
+    type State<'c> = impl Future<Output = Self::Output> + 'c where BS: 'c;
+    fn parse<'a: 'c, 'b: 'c, 'c>(&'b self, input: &'a mut BS) -> Self::State<'c> {
+        async move {
+            let mut rv = None;
+            let mut pending: u32 = 1;
+            let mut parsed: u32 = 0;
+            while pending > 0 {
+                pending -= 1;
+                parsed += 1;
+                if parsed > MAX_NESTED_TYPE_TAGS {
+                    return reject_with(AppError::UnsupportedTypeArgument).await;
+                }
+                let enum_variant =
+                    <CanonicalULEB128 as AsyncParser<ULEB128, BS>>::parse(&CanonicalULEB128, input)
+                        .await;
+                match enum_variant {
+                    // bool, u8, u64, u128, address, signer, u16, u32 and u256
+                    0..=5 | 8..=10 => {}
+                    6 => {
+                        trace!("TypeTagSchema: Vector");
+                        pending += 1;
+                    }
+                    7 => {
+                        trace!("TypeTagSchema: Struct");
+                        let address = <DefaultInterp as AsyncParser<IotaAddress, BS>>::parse(
+                            &DefaultInterp,
+                            input,
+                        )
+                        .await;
+                        let module = <IotaInterp as AsyncParser<IdentifierSchema, BS>>::parse(
+                            &IotaInterp,
+                            input,
+                        )
+                        .await;
+                        let name = <IotaInterp as AsyncParser<IdentifierSchema, BS>>::parse(
+                            &IotaInterp,
+                            input,
+                        )
+                        .await;
+                        let type_params = <CanonicalULEB128 as AsyncParser<ULEB128, BS>>::parse(
+                            &CanonicalULEB128,
+                            input,
+                        )
+                        .await;
+                        if type_params > MAX_NESTED_TYPE_TAGS {
+                            return reject_with(AppError::UnsupportedTypeArgument).await;
+                        }
+                        if let (1, 0, Some(module), Some(name)) =
+                            (parsed, type_params, module, name)
+                        {
+                            rv = Some(StructTag {
+                                address,
+                                module,
+                                name,
+                            });
+                        }
+                        pending += type_params;
+                    }
+                    _ => {
+                        trace!("TypeTagSchema: Unknown enum: {}", enum_variant);
+                        return reject_with(AppError::UnsupportedTypeArgument).await;
+                    }
+                }
+            }
+            rv
+        }
+    }
+}
+
+// Calls with more type arguments or arguments are not those of a known call, see patterns.rs
+pub const MAX_TYPE_ARGUMENTS: usize = 2;
+pub const MAX_CALL_ARGUMENTS: usize = 10;
+// The most pure arguments of a known call, see patterns.rs
+pub const MAX_PURE_ARGUMENTS: usize = 4;
+
+pub struct MoveCall {
+    pub package: ObjectIdRaw,
+    // None if too long to be known
+    pub module: Option<Identifier>,
+    pub function: Option<Identifier>,
+    pub type_arguments: ArrayVec<Option<StructTag>, MAX_TYPE_ARGUMENTS>,
+    pub arguments: ArrayVec<Argument, MAX_CALL_ARGUMENTS>,
+}
+
+/// What a call is of, for the review to name it.
+#[derive(Clone)]
+pub struct CallName {
+    pub package: ObjectIdRaw,
+    pub module: Identifier,
+    pub function: Identifier,
+    // Its pure arguments, in order, as the types it takes them as read them
+    pub arguments: ArrayVec<PureValue, MAX_PURE_ARGUMENTS>,
+}
+
+/// The value of a pure argument of a call, by the type of the argument.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PureValue {
+    Bool(bool),
+    U8(u8),
+    U16(u16),
+    U64(u64),
+    // In the bytes of BCS, little-endian, which are not aligned like a u128 would be
+    U128([u8; 16]),
+    Address(IotaAddressRaw),
+}
+
+impl HasOutput<MoveCallSchema> for IotaInterp {
+    type Output = MoveCall;
+}
+
+impl<BS: Clone + Readable> AsyncParser<MoveCallSchema, BS> for IotaInterp {
+    type State<'c> = impl Future<Output = Self::Output> + 'c where BS: 'c;
+    fn parse<'a: 'c, 'b: 'c, 'c>(&'b self, input: &'a mut BS) -> Self::State<'c> {
+        async move {
+            let package =
+                <DefaultInterp as AsyncParser<IotaAddress, BS>>::parse(&DefaultInterp, input).await;
+            let module =
+                <IotaInterp as AsyncParser<IdentifierSchema, BS>>::parse(&IotaInterp, input).await;
+            let function =
+                <IotaInterp as AsyncParser<IdentifierSchema, BS>>::parse(&IotaInterp, input).await;
+            let type_arguments = <SubInterp<IotaInterp> as AsyncParser<
+                Vec<TypeTagSchema, MAX_TYPE_ARGUMENTS>,
+                BS,
+            >>::parse(&SubInterp(IotaInterp), input)
+            .await;
+            let arguments = <SubInterp<IotaInterp> as AsyncParser<
+                Vec<ArgumentSchema, MAX_CALL_ARGUMENTS>,
+                BS,
+            >>::parse(&SubInterp(IotaInterp), input)
+            .await;
+            MoveCall {
+                package,
+                module,
+                function,
+                type_arguments,
+                arguments,
+            }
+        }
+    }
+}
+
+// The objects one transfer sends, which the review counts, at least both coins a pool gives back
+pub const TRANSFER_OBJECT_ARRAY_LENGTH: usize = 8;
+pub const SPLIT_COIN_ARRAY_LENGTH: usize = 8;
+// Pure inputs of 32 and 8 bytes, one of which has to be the recipient, and some of which have to
+// be amounts
+pub const MAX_ADDRESS_INPUTS: usize = 8;
+pub const MAX_AMOUNT_INPUTS: usize = 16;
+// Pure inputs of 1, 2 and 16 bytes, past which any more are not kept, and so are not taken by a
+// known call
+pub const MAX_SMALL_PURE_INPUTS: usize = 8;
+// Object inputs of the sender shown with their version and digest, the first of them if there are
+// more
+pub const MAX_OWNED_OBJECTS: usize = 4;
+// Transactions with more inputs or commands are too complex to be reviewed, and are only parsed as
+// far as the count. Both limits are documented in docs/apdu.md, and reported by GET_LIMITS.
+pub const MAX_INPUTS: usize = 32;
+pub const MAX_COMMANDS: usize = 8;
+
+pub enum Command {
+    MoveCall(MoveCall),
+    TransferObject(ArrayVec<Argument, TRANSFER_OBJECT_ARRAY_LENGTH>, Argument),
+    SplitCoins(Argument, ArrayVec<Argument, SPLIT_COIN_ARRAY_LENGTH>),
+}
+
+impl Command {
+    fn name(&self) -> &'static str {
+        match self {
+            Command::MoveCall(..) => "MoveCall",
+            Command::TransferObject(..) => "TransferObjects",
+            Command::SplitCoins(..) => "SplitCoins",
+        }
+    }
+}
+
+// Pure inputs which could be the recipient or an amount, with their index, and which inputs are
+// objects
+#[derive(Default)]
+struct TransferInputs {
+    addresses: ArrayVec<(IotaAddressRaw, u32), MAX_ADDRESS_INPUTS>,
+    amounts: ArrayVec<(u64, u32), MAX_AMOUNT_INPUTS>,
+    small_pures: ArrayVec<(SmallPure, u32), MAX_SMALL_PURE_INPUTS>,
+    // Bit i for input i, which fits as there are at most MAX_INPUTS of them
+    objects: u32,
+    // The first of the objects of the sender, for the expert review and the calls taking one, and
+    // how many there are
+    owned_objects: ArrayVec<(OwnedObject, u32), MAX_OWNED_OBJECTS>,
+    owned_object_count: usize,
+}
+
+impl TransferInputs {
+    const fn new() -> Self {
+        TransferInputs {
+            addresses: ArrayVec::new_const(),
+            amounts: ArrayVec::new_const(),
+            small_pures: ArrayVec::new_const(),
+            objects: 0,
+            owned_objects: ArrayVec::new_const(),
+            owned_object_count: 0,
+        }
+    }
+
+    fn clear(&mut self) {
+        self.addresses.clear();
+        self.amounts.clear();
+        self.small_pures.clear();
+        self.objects = 0;
+        self.owned_objects.clear();
+        self.owned_object_count = 0;
+    }
+
+    fn address(&self, index: u16) -> Option<IotaAddressRaw> {
+        self.addresses
+            .iter()
+            .find(|(_, ix)| *ix == index as u32)
+            .map(|(address, _)| *address)
+    }
+
+    fn amount(&self, index: u16) -> Option<u64> {
+        self.amounts
+            .iter()
+            .find(|(_, ix)| *ix == index as u32)
+            .map(|(amount, _)| *amount)
+    }
+
+    /// The object of the sender at `index`, if it is one of the first few.
+    fn owned_object(&self, index: u16) -> Option<&OwnedObject> {
+        self.owned_objects
+            .iter()
+            .find(|(_, ix)| *ix == index as u32)
+            .map(|(object, _)| object)
+    }
+
+    /// The pure input at `index` read as a value of `ty`, if it is one.
+    fn pure(&self, index: u16, ty: PureType) -> Option<PureValue> {
+        let small = || {
+            self.small_pures
+                .iter()
+                .find(|(pure, ix)| *ix == index as u32 && pure.length == ty.size())
+                .map(|(pure, _)| pure.bytes)
+        };
+        match ty {
+            PureType::U64 => self.amount(index).map(PureValue::U64),
+            PureType::Address => self.address(index).map(PureValue::Address),
+            // Any other byte is not a bool, and the call would abort on it
+            PureType::Bool => match small()?[0] {
+                0 => Some(PureValue::Bool(false)),
+                1 => Some(PureValue::Bool(true)),
+                _ => None,
+            },
+            PureType::U8 => small().map(|bytes| PureValue::U8(bytes[0])),
+            PureType::U16 => {
+                small().map(|bytes| PureValue::U16(u16::from_le_bytes([bytes[0], bytes[1]])))
+            }
+            PureType::U128 => small().map(PureValue::U128),
+        }
+    }
+
+    fn is_object(&self, index: u16) -> bool {
+        (index as usize) < MAX_INPUTS && self.objects & (1 << index) != 0
+    }
+}
+
+// The inputs of the transaction being parsed. They are collected here rather than in the parser
+// future, which lives on the stack, and cleared at the start of each parse.
+static mut TRANSFER_INPUTS: TransferInputs = TransferInputs::new();
+
+#[derive(Clone, PartialEq, Eq)]
+pub enum CoinType {
+    Iota,
+    Other(StructTag),
+}
+
+// The Move call of a transaction, as checked against its pattern
+struct CallCheck {
+    index: u16,
+    name: CallName,
+    kind: CallKind,
+    // One for each type argument
+    coin_types: ArrayVec<CoinType, MAX_TYPE_ARGUMENTS>,
+    // The coins the call takes, or the amounts it is given, depending on the kind, in the order of
+    // the coin types
+    coins: ArrayVec<Argument, MAX_TYPE_ARGUMENTS>,
+    amounts: ArrayVec<u64, MAX_TYPE_ARGUMENTS>,
+    // How many coins the call splits a coin into, for the kinds which take it
+    count: Option<u64>,
+    // The validator staked with, or the recipient of what is minted, for the kinds which take it
+    address: Option<IotaAddressRaw>,
+    // The object of the sender the call takes, for the kinds which show it
+    owned_object: Option<OwnedObject>,
+}
+
+impl CallCheck {
+    /// Check a call against the pattern for it, see patterns.rs.
+    ///
+    /// Each argument has to be an object, or what the pattern says the call takes: coins, which
+    /// are the results of other commands, or amounts. Anything else, like an address, could
+    /// change what the call does without the review showing it.
+    fn new(index: u16, call: &MoveCall, inputs: &TransferInputs) -> Result<CallCheck, AppError> {
+        let (Some(module), Some(function)) = (&call.module, &call.function) else {
+            return Err(AppError::UnknownCall);
+        };
+        let Some(pattern) = patterns::find(&call.package, module, function) else {
+            return Err(AppError::UnknownCall);
+        };
+        let kind = pattern.kind;
+        if call.type_arguments.len() != kind.coin_types() {
+            return Err(AppError::UnsupportedTypeArgument);
+        }
+        let mut coin_types = ArrayVec::new();
+        for tag in &call.type_arguments {
+            match tag {
+                Some(tag) if tag.is_iota() => coin_types.push(CoinType::Iota),
+                Some(tag) => coin_types.push(CoinType::Other(tag.clone())),
+                None => return Err(AppError::UnsupportedTypeArgument),
+            }
+        }
+        if kind.takes_iota() {
+            coin_types.push(CoinType::Iota);
+        }
+        let mut rv = CallCheck {
+            index,
+            name: CallName {
+                package: call.package,
+                module: *module,
+                function: *function,
+                arguments: ArrayVec::new(),
+            },
+            kind,
+            coin_types,
+            coins: ArrayVec::new(),
+            amounts: ArrayVec::new(),
+            count: None,
+            address: None,
+            owned_object: None,
+        };
+        for argument in &call.arguments {
+            match argument {
+                Argument::Input(i) if inputs.is_object(*i) => {
+                    // The first object of the sender, as the shared ones come without an ID.
+                    // Only the first few objects are kept, so one past them is not found.
+                    if kind.takes_owned_object() && rv.owned_object.is_none() {
+                        rv.owned_object = inputs.owned_object(*i).copied();
+                    }
+                }
+                // Read as the type the call takes it as, see patterns.rs
+                Argument::Input(i) => {
+                    let Some(ty) = kind.pure_types().get(rv.name.arguments.len()) else {
+                        return Err(AppError::CallArgumentMismatch);
+                    };
+                    let value = inputs.pure(*i, *ty).ok_or(AppError::CallArgumentMismatch)?;
+                    rv.name.arguments.push(value);
+                    match value {
+                        PureValue::U64(amount) if rv.amounts.len() < kind.amounts() => {
+                            rv.amounts.push(amount)
+                        }
+                        PureValue::U64(count) if kind.takes_count() && rv.count.is_none() => {
+                            rv.count = Some(count)
+                        }
+                        PureValue::Address(address) if rv.address.is_none() => {
+                            rv.address = Some(address)
+                        }
+                        PureValue::U64(_) | PureValue::Address(_) => {
+                            return Err(AppError::CallArgumentMismatch)
+                        }
+                        // Options of the call, which change nothing the review shows but for
+                        // the expert review of its command
+                        _ => {}
+                    }
+                }
+                Argument::Result(_) | Argument::NestedResult(..)
+                    if rv.coins.len() < kind.coins() =>
+                {
+                    rv.coins.push(*argument);
+                }
+                _ => return Err(AppError::CallArgumentMismatch),
+            }
+        }
+        if rv.coins.len() != kind.coins()
+            || rv.amounts.len() != kind.amounts()
+            || rv.count.is_some() != kind.takes_count()
+            || rv.address.is_some() != kind.takes_address()
+            || rv.owned_object.is_some() != kind.takes_owned_object()
+        {
+            return Err(AppError::CallArgumentMismatch);
+        }
+        Ok(rv)
+    }
+}
+
+// Whether an argument is the result of the command at `index`, or its first result
+fn is_result_of(argument: Argument, index: u16) -> bool {
+    argument == Argument::Result(index) || argument == Argument::NestedResult(index, 0)
+}
+
+// Whether an argument is any of the results of the command at `index`
+fn is_any_result_of(argument: Argument, index: u16) -> bool {
+    match argument {
+        Argument::Result(i) | Argument::NestedResult(i, _) => i == index,
+        _ => false,
+    }
+}
+
+// Calls take at most a coin of each of their coin types
+const MAX_SPLIT_COINS: usize = MAX_TYPE_ARGUMENTS;
+
+// A coin split off by a SplitCoins command
+#[derive(Clone, Copy)]
+struct SplitCoin {
+    index: u16,
+    // Off the gas coin, and so IOTA, rather than off a coin object of some type
+    from_gas: bool,
+    amount: u64,
+}
+
+// What an object sent without a call is known to be
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum TransferredObject {
+    GasCoin,
+    // A coin split off the gas coin
+    IotaCoin,
+    // An object input, or a coin split off one, of a type the transaction does not tell
+    Other,
+}
+
+// What the commands of a transaction have been checked to do so far
+#[derive(Default)]
+struct CommandCheck {
+    recipient: Option<IotaAddressRaw>,
+    // The objects sent to the recipient
+    transferred: ArrayVec<Argument, TRANSFER_OBJECT_ARRAY_LENGTH>,
+    total_amount: u64,
+    amount_count: usize,
+    // The number of SplitCoins commands, how many of them split a coin object rather than the gas
+    // coin, and the first few of them
+    splits: usize,
+    object_splits: usize,
+    split_coins: ArrayVec<SplitCoin, MAX_SPLIT_COINS>,
+    // Bit i for a SplitCoins command at index i off the gas coin, and for one off a coin object,
+    // which fits as there are at most MAX_COMMANDS of them
+    gas_split_commands: u32,
+    object_split_commands: u32,
+    // What all of the splits off the gas coin amount to
+    gas_split_amount: u64,
+    call: Option<CallCheck>,
+}
+
+impl CommandCheck {
+    /// Check the command at `index` against the inputs of the transaction.
+    ///
+    /// The recipient is whichever address input the transfer goes to, as a 32 byte input could
+    /// just as well be an object ID or some other data.
+    ///
+    /// Not async, so that the parser future does not keep the command around while a rejection
+    /// is awaited.
+    fn command(
+        &mut self,
+        index: u16,
+        c: &Command,
+        inputs: &TransferInputs,
+    ) -> Result<(), AppError> {
+        match c {
+            Command::MoveCall(call) => {
+                if self.call.is_some() {
+                    return Err(AppError::MultipleCalls);
+                }
+                self.call = Some(CallCheck::new(index, call, inputs)?);
+            }
+            Command::TransferObject(nested_results, recipient_input) => {
+                // Reject more than one TransferObject(s)
+                if self.recipient.is_some() {
+                    return Err(AppError::MultipleTransfers);
+                }
+                let Argument::Input(inp_index) = recipient_input else {
+                    return Err(AppError::UnsupportedArgument);
+                };
+                match inputs.address(*inp_index) {
+                    Some(address) => self.recipient = Some(address),
+                    None => return Err(AppError::RecipientMismatch),
+                }
+                self.transferred = nested_results.clone();
+            }
+            Command::SplitCoins(coin, input_indices) => {
+                // Only a call can tell the type of a coin object, see finish
+                let from_gas = match coin {
+                    Argument::GasCoin => true,
+                    Argument::Input(i) if inputs.is_object(*i) => false,
+                    _ => return Err(AppError::UnsupportedArgument),
+                };
+                self.splits += 1;
+                if from_gas {
+                    self.gas_split_commands |= 1 << index;
+                } else {
+                    self.object_splits += 1;
+                    self.object_split_commands |= 1 << index;
+                }
+                // Each reference splits off a coin of its own, so an amount input which is
+                // referenced more than once is counted once for each reference.
+                let mut split_amount: u64 = 0;
+                for arg in input_indices {
+                    let Argument::Input(inp_index) = arg else {
+                        return Err(AppError::UnsupportedArgument);
+                    };
+                    let Some(amt) = inputs.amount(*inp_index) else {
+                        return Err(AppError::SplitOfNonAmount);
+                    };
+                    self.amount_count += 1;
+                    self.total_amount = self
+                        .total_amount
+                        .checked_add(amt)
+                        .ok_or(AppError::AmountOverflow)?;
+                    split_amount = split_amount.saturating_add(amt);
+                }
+                if from_gas {
+                    self.gas_split_amount = self.gas_split_amount.saturating_add(split_amount);
+                }
+                // Any more than a call takes cannot be what it takes, see coin_amounts
+                let _ = self.split_coins.try_push(SplitCoin {
+                    index,
+                    from_gas,
+                    amount: split_amount,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// The amounts of the coins a call takes, each of which has to be split off for it alone, with
+    /// nothing else split off.
+    fn coin_amounts(&self, call: &CallCheck) -> Result<ArrayVec<u64, MAX_SPLIT_COINS>, AppError> {
+        if self.splits != call.coins.len() || self.amount_count != call.coins.len() {
+            return Err(AppError::CallArgumentMismatch);
+        }
+        let mut rv = ArrayVec::new();
+        let mut used: u32 = 0;
+        for (coin, coin_type) in call.coins.iter().zip(&call.coin_types) {
+            let Some(position) = self
+                .split_coins
+                .iter()
+                .position(|split| is_result_of(*coin, split.index))
+            else {
+                return Err(AppError::CallArgumentMismatch);
+            };
+            let split = self.split_coins[position];
+            // The gas coin is IOTA, and the call checks that a coin object is of the type it takes
+            if used & (1 << position) != 0 || (split.from_gas && *coin_type != CoinType::Iota) {
+                return Err(AppError::CallArgumentMismatch);
+            }
+            used |= 1 << position;
+            rv.push(split.amount);
+        }
+        Ok(rv)
+    }
+
+    /// What an object sent without a call is, as far as the transaction tells.
+    ///
+    /// Only the gas coin and the coins split off it are known to be IOTA, as an object input does
+    /// not come with its type, and a coin split off one is of whatever type it is.
+    fn transferred_object(
+        &self,
+        object: Argument,
+        inputs: &TransferInputs,
+    ) -> Result<TransferredObject, AppError> {
+        match object {
+            Argument::GasCoin => Ok(TransferredObject::GasCoin),
+            Argument::Input(i) if inputs.is_object(i) => Ok(TransferredObject::Other),
+            Argument::Result(i) | Argument::NestedResult(i, _) if i < MAX_COMMANDS as u16 => {
+                if self.gas_split_commands & (1 << i) != 0 {
+                    Ok(TransferredObject::IotaCoin)
+                } else if self.object_split_commands & (1 << i) != 0 {
+                    Ok(TransferredObject::Other)
+                } else {
+                    Err(AppError::UnsupportedArgument)
+                }
+            }
+            _ => Err(AppError::UnsupportedArgument),
+        }
+    }
+
+    /// What the call is of, if there is one.
+    fn call_name(&self) -> Option<CallName> {
+        self.call.as_ref().map(|call| call.name.clone())
+    }
+
+    /// What the commands do, once all of them have been checked.
+    fn finish(mut self, inputs: &TransferInputs) -> Result<Operation, AppError> {
+        let Some(call) = self.call.take() else {
+            let Some(recipient) = self.recipient else {
+                return Err(AppError::NotATransfer);
+            };
+            if self.transferred.is_empty() {
+                return Err(AppError::NotATransfer);
+            }
+            let mut iota_coin_count = 0;
+            let mut gas_coin = false;
+            for object in &self.transferred {
+                match self.transferred_object(*object, inputs)? {
+                    TransferredObject::GasCoin => {
+                        iota_coin_count += 1;
+                        gas_coin = true;
+                    }
+                    TransferredObject::IotaCoin => iota_coin_count += 1,
+                    TransferredObject::Other => {}
+                }
+            }
+            // Amounts split off the gas coin, and nothing else
+            if iota_coin_count == self.transferred.len() && !gas_coin {
+                if inputs.amounts.is_empty() {
+                    return Err(AppError::NotATransfer);
+                }
+                // Amounts of a coin object could be of any coin, not IOTA
+                if self.object_splits > 0 {
+                    return Err(AppError::UnsupportedArgument);
+                }
+                return Ok(Operation::Transfer(TransferSummary {
+                    recipient,
+                    total_amount: self.total_amount,
+                    amount_count: self.amount_count,
+                }));
+            }
+            // With the gas coin itself sent, all of it goes but what gas takes, which is not known
+            // until the transaction runs
+            let split_amount = if gas_coin || self.gas_split_commands == 0 {
+                None
+            } else {
+                Some(self.gas_split_amount)
+            };
+            return Ok(Operation::ObjectTransfer(ObjectTransferSummary {
+                recipient,
+                object_count: self.transferred.len(),
+                iota_coin_count,
+                split_amount,
+            }));
+        };
+        // What the call returns may be sent on, to the recipient shown, but nothing else. A
+        // division returns nothing, and the coin divided, which keeps the remainder, is sent on
+        // instead.
+        if self.recipient.is_some()
+            && (self.transferred.is_empty()
+                || !self.transferred.iter().all(|object| match call.kind {
+                    CallKind::Divide => call.coins.contains(object),
+                    _ => is_any_result_of(*object, call.index),
+                }))
+        {
+            return Err(AppError::CallArgumentMismatch);
+        }
+        let amounts = if call.coins.is_empty() {
+            if self.splits != 0 {
+                return Err(AppError::CallArgumentMismatch);
+            }
+            call.amounts.clone()
+        } else {
+            self.coin_amounts(&call)?
+        };
+        match call.kind {
+            #[cfg(feature = "lending")]
+            CallKind::Lending(action) => {
+                let (Some(coin_type), Some(amount)) =
+                    (call.coin_types.into_iter().next(), amounts.first())
+                else {
+                    return Err(AppError::CallArgumentMismatch);
+                };
+                Ok(Operation::Lending(LendingSummary {
+                    action,
+                    package: call.name.package,
+                    coin_type,
+                    amount: *amount,
+                    recipient: self.recipient,
+                }))
+            }
+            CallKind::Divide => {
+                // The coin divided is split off for the call, and the transaction cannot be run
+                // unless it is sent on
+                let (Some(coin_type), Some(amount), Some(count), Some(recipient)) = (
+                    call.coin_types.into_iter().next(),
+                    amounts.first(),
+                    call.count,
+                    self.recipient,
+                ) else {
+                    return Err(AppError::CallArgumentMismatch);
+                };
+                // The call aborts otherwise, and the review would have no amount to show
+                if count == 0 || count > *amount {
+                    return Err(AppError::CallArgumentMismatch);
+                }
+                Ok(Operation::Divide(DivideSummary {
+                    package: call.name.package,
+                    coin_type,
+                    amount: *amount,
+                    count,
+                    recipient,
+                }))
+            }
+            CallKind::Stake => {
+                // The stake goes to the sender, and the call returns nothing to send
+                let (Some(validator), Some(amount), None) =
+                    (call.address, amounts.first(), self.recipient)
+                else {
+                    return Err(AppError::CallArgumentMismatch);
+                };
+                Ok(Operation::Stake(StakeSummary {
+                    package: call.name.package,
+                    validator,
+                    amount: *amount,
+                }))
+            }
+            CallKind::Unstake(object) => {
+                // What is withdrawn goes to the sender, and the call returns nothing to send
+                let (Some(staked), None) = (call.owned_object, self.recipient) else {
+                    return Err(AppError::CallArgumentMismatch);
+                };
+                Ok(Operation::Unstake(UnstakeSummary {
+                    package: call.name.package,
+                    object,
+                    staked: staked.id,
+                }))
+            }
+            CallKind::Mint => {
+                // What is minted goes to the recipient given to the call, which returns nothing
+                let (Some(coin_type), Some(amount), Some(recipient), None) = (
+                    call.coin_types.into_iter().next(),
+                    amounts.first(),
+                    call.address,
+                    self.recipient,
+                ) else {
+                    return Err(AppError::CallArgumentMismatch);
+                };
+                Ok(Operation::Mint(MintSummary {
+                    package: call.name.package,
+                    coin_type,
+                    amount: *amount,
+                    recipient,
+                }))
+            }
+            #[cfg(feature = "liquidity")]
+            CallKind::Liquidity(action) => {
+                let (Ok(coin_types), Ok(amounts)) =
+                    (call.coin_types.into_inner(), amounts.into_inner())
+                else {
+                    return Err(AppError::CallArgumentMismatch);
+                };
+                Ok(Operation::Liquidity(LiquiditySummary {
+                    action,
+                    package: call.name.package,
+                    coin_types,
+                    amounts,
+                    recipient: self.recipient,
+                }))
+            }
+        }
+    }
+}
+
+impl HasOutput<CommandSchema> for IotaInterp {
+    type Output = Command;
+}
+
+impl<BS: Clone + Readable> AsyncParser<CommandSchema, BS> for IotaInterp {
+    type State<'c> = impl Future<Output = Self::Output> + 'c where BS: 'c;
+    fn parse<'a: 'c, 'b: 'c, 'c>(&'b self, input: &'a mut BS) -> Self::State<'c> {
+        async move {
+            let enum_variant =
+                <CanonicalULEB128 as AsyncParser<ULEB128, BS>>::parse(&CanonicalULEB128, input)
+                    .await;
+            match enum_variant {
+                0 => {
+                    trace!("CommandSchema: MoveCall");
+                    Command::MoveCall(
+                        NoinlineFut(<IotaInterp as AsyncParser<MoveCallSchema, BS>>::parse(
+                            &IotaInterp,
+                            input,
+                        ))
+                        .await,
+                    )
+                }
+                1 => {
+                    trace!("CommandSchema: TransferObject");
+                    let v1 = <SubInterp<IotaInterp> as AsyncParser<
+                        Vec<ArgumentSchema, TRANSFER_OBJECT_ARRAY_LENGTH>,
+                        BS,
+                    >>::parse(&SubInterp(IotaInterp), input)
+                    .await;
+                    let v2 =
+                        <IotaInterp as AsyncParser<ArgumentSchema, BS>>::parse(&IotaInterp, input)
+                            .await;
+                    Command::TransferObject(v1, v2)
+                }
+                2 => {
+                    trace!("CommandSchema: SplitCoins");
+                    let v1 =
+                        <IotaInterp as AsyncParser<ArgumentSchema, BS>>::parse(&IotaInterp, input)
+                            .await;
+                    let v2 = <SubInterp<IotaInterp> as AsyncParser<
+                        Vec<ArgumentSchema, SPLIT_COIN_ARRAY_LENGTH>,
+                        BS,
+                    >>::parse(&SubInterp(IotaInterp), input)
+                    .await;
+                    Command::SplitCoins(v1, v2)
+                }
+                _ => {
+                    trace!("CommandSchema: Unknown enum: {}", enum_variant);
+                    reject_with(AppError::UnsupportedCommand).await
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Argument {
+    GasCoin,
+    Input(u16),
+    Result(u16),
+    NestedResult(u16, u16),
+}
+
+impl HasOutput<ArgumentSchema> for IotaInterp {
+    type Output = Argument;
+}
+
+impl<BS: Clone + Readable> AsyncParser<ArgumentSchema, BS> for IotaInterp {
+    type State<'c> = impl Future<Output = Self::Output> + 'c where BS: 'c;
+    fn parse<'a: 'c, 'b: 'c, 'c>(&'b self, input: &'a mut BS) -> Self::State<'c> {
+        async move {
+            let enum_variant =
+                <CanonicalULEB128 as AsyncParser<ULEB128, BS>>::parse(&CanonicalULEB128, input)
+                    .await;
+            match enum_variant {
+                0 => {
+                    trace!("ArgumentSchema: GasCoin");
+                    Argument::GasCoin
+                }
+                1 => {
+                    trace!("ArgumentSchema: Input");
+                    Argument::Input(
+                        <DefaultInterp as AsyncParser<U16LE, BS>>::parse(&DefaultInterp, input)
+                            .await,
+                    )
+                }
+                2 => {
+                    trace!("ArgumentSchema: Result");
+                    Argument::Result(
+                        <DefaultInterp as AsyncParser<U16LE, BS>>::parse(&DefaultInterp, input)
+                            .await,
+                    )
+                }
+                3 => {
+                    trace!("ArgumentSchema: NestedResult");
+                    Argument::NestedResult(
+                        <DefaultInterp as AsyncParser<U16LE, BS>>::parse(&DefaultInterp, input)
+                            .await,
+                        <DefaultInterp as AsyncParser<U16LE, BS>>::parse(&DefaultInterp, input)
+                            .await,
+                    )
+                }
+                _ => reject_with(AppError::UnsupportedArgument).await,
+            }
+        }
+    }
+}
+
+impl HasOutput<ProgrammableTransaction> for IotaInterp {
+    type Output = ProgrammableSummary;
+}
+
+impl<BS: Clone + Readable> AsyncParser<ProgrammableTransaction, BS> for IotaInterp {
+    type State<'c> = impl Future<Output = Self::Output> + 'c where BS: 'c;
+    fn parse<'a: 'c, 'b: 'c, 'c>(&'b self, input: &'a mut BS) -> Self::State<'c> {
+        async move {
+            let inputs = unsafe { &mut TRANSFER_INPUTS };
+            inputs.clear();
+
+            // Handle inputs
+            {
+                let length =
+                    <CanonicalULEB128 as AsyncParser<ULEB128, BS>>::parse(&CanonicalULEB128, input)
+                        .await;
+
+                trace!("ProgrammableTransaction: Inputs: {}", length);
+                if length as usize > MAX_INPUTS {
+                    reject_with::<()>(AppError::TooComplex).await;
+                }
+                for i in 0..length {
+                    progress::set("Parsing input", i + 1, length);
+                    let arg = NoinlineFut(<IotaInterp as AsyncParser<CallArgSchema, BS>>::parse(
+                        &IotaInterp,
+                        input,
+                    ))
+                    .await;
+                    match arg {
+                        CallArg::Address(addr) => {
+                            if inputs.addresses.try_push((addr, i)).is_err() {
+                                reject_with::<()>(AppError::TooManyAddresses).await;
+                            }
+                        }
+                        CallArg::Amount(amt) => {
+                            if inputs.amounts.try_push((amt, i)).is_err() {
+                                reject_with::<()>(AppError::TooManyAmounts).await;
+                            }
+                        }
+                        CallArg::OwnedObject(object) => {
+                            inputs.objects |= 1 << i;
+                            inputs.owned_object_count += 1;
+                            let _ = inputs.owned_objects.try_push((object, i));
+                        }
+                        CallArg::SmallPure(pure) => {
+                            let _ = inputs.small_pures.try_push((pure, i));
+                        }
+                        CallArg::SharedObject => inputs.objects |= 1 << i,
+                        CallArg::OtherPure => {}
+                    }
+                }
+            }
+
+            let mut check = CommandCheck::default();
+            let mut commands = ArrayVec::new();
+            // Handle commands
+            {
+                let length =
+                    <CanonicalULEB128 as AsyncParser<ULEB128, BS>>::parse(&CanonicalULEB128, input)
+                        .await;
+                trace!("ProgrammableTransaction: Commands: {}", length);
+                if length as usize > MAX_COMMANDS {
+                    reject_with::<()>(AppError::TooComplex).await;
+                }
+                for i in 0..length {
+                    progress::set("Parsing command", i + 1, length);
+                    let checked = {
+                        let c = NoinlineFut(<IotaInterp as AsyncParser<CommandSchema, BS>>::parse(
+                            &IotaInterp,
+                            input,
+                        ))
+                        .await;
+                        // Cannot fail, as the number of commands was checked above
+                        let _ = commands.try_push(c.name());
+                        check.command(i as u16, &c, inputs)
+                    };
+                    if let Err(e) = checked {
+                        reject_with::<()>(e).await;
+                    }
+                }
+            }
+
+            let call = check.call_name();
+            match check.finish(inputs) {
+                Ok(operation) => ProgrammableSummary {
+                    operation,
+                    commands,
+                    call,
+                    objects: ObjectsSummary {
+                        first: inputs
+                            .owned_objects
+                            .iter()
+                            .map(|(object, _)| *object)
+                            .collect(),
+                        count: inputs.owned_object_count,
+                    },
+                },
+                Err(e) => reject_with(e).await,
+            }
+        }
+    }
+}
+
+impl HasOutput<TransactionKind> for IotaInterp {
+    type Output = ProgrammableSummary;
+}
+
+impl<BS: Clone + Readable> AsyncParser<TransactionKind, BS> for IotaInterp {
+    type State<'c> = impl Future<Output = Self::Output> + 'c where BS: 'c;
+    fn parse<'a: 'c, 'b: 'c, 'c>(&'b self, input: &'a mut BS) -> Self::State<'c> {
+        async move {
+            let enum_variant =
+                <CanonicalULEB128 as AsyncParser<ULEB128, BS>>::parse(&CanonicalULEB128, input)
+                    .await;
+            match enum_variant {
+                0 => {
+                    trace!("TransactionKind: ProgrammableTransaction");
+                    NoinlineFut(
+                        <IotaInterp as AsyncParser<ProgrammableTransaction, BS>>::parse(
+                            &IotaInterp,
+                            input,
+                        ),
+                    )
+                    .await
+                }
+                _ => {
+                    trace!("TransactionKind: {}", enum_variant);
+                    reject_with(AppError::UnsupportedTransactionKind).await
+                }
+            }
+        }
+    }
+}
+impl HasOutput<TransactionExpiration> for IotaInterp {
+    type Output = Option<u64>;
+}
+
+impl<BS: Clone + Readable> AsyncParser<TransactionExpiration, BS> for IotaInterp {
+    type State<'c> = impl Future<Output = Self::Output> + 'c where BS: 'c;
+    fn parse<'a: 'c, 'b: 'c, 'c>(&'b self, input: &'a mut BS) -> Self::State<'c> {
+        async move {
+            let enum_variant =
+                <CanonicalULEB128 as AsyncParser<ULEB128, BS>>::parse(&CanonicalULEB128, input)
+                    .await;
+            match enum_variant {
+                0 => {
+                    trace!("TransactionExpiration: None");
+                    None
+                }
+                1 => {
+                    trace!("TransactionExpiration: Epoch");
+                    Some(
+                        <DefaultInterp as AsyncParser<EpochId, BS>>::parse(&DefaultInterp, input)
+                            .await,
+                    )
+                }
+                _ => reject_with(AppError::UnsupportedExpiration).await,
+            }
+        }
+    }
+}
+
+impl HasOutput<GasData> for IotaInterp {
+    type Output = GasSummary;
+}
+
+impl<BS: Clone + Readable> AsyncParser<GasData, BS> for IotaInterp {
+    type State<'c> = impl Future<Output = Self::Output> + 'c where BS: 'c;
+    fn parse<'a: 'c, 'b: 'c, 'c>(&'b self, input: &'a mut BS) -> Self::State<'c> {
+        async move {
+            let mut object = [0; IOTA_ADDRESS_LENGTH];
+            let object_count =
+                <CanonicalULEB128 as AsyncParser<ULEB128, BS>>::parse(&CanonicalULEB128, input)
+                    .await;
+            for i in 0..object_count {
+                let id = NoinlineFut(gas_object_ref_parser().parse(input)).await;
+                if i == 0 {
+                    object = id;
+                }
+            }
+            let _owner =
+                <DefaultInterp as AsyncParser<IotaAddress, BS>>::parse(&DefaultInterp, input).await;
+            // Gas price is per gas amount. Gas budget is total, reflecting the amount of gas *
+            // gas price. We only care about the total, not the price or amount in isolation , so we
+            // just ignore that field, unless asked for it with the expert mode.
+            //
+            // C.F. https://github.com/MystenLabs/sui/pull/8676
+            let price =
+                <DefaultInterp as AsyncParser<Amount, BS>>::parse(&DefaultInterp, input).await;
+            let budget =
+                <DefaultInterp as AsyncParser<Amount, BS>>::parse(&DefaultInterp, input).await;
+            GasSummary {
+                object,
+                object_count: object_count as usize,
+                price,
+                budget,
+            }
+        }
+    }
+}
+
+const fn gas_object_ref_parser<BS: Readable>(
+) -> impl AsyncParser<ObjectRef, BS> + HasOutput<ObjectRef, Output = ObjectIdRaw> {
+    Action(
+        (DefaultInterp, DefaultInterp, DefaultInterp),
+        |(id, _, _): (ObjectIdRaw, _, _)| Some(id),
+    )
+}
+
+// The digest is a BCS vector, of which the first byte is the length, 32
+const fn object_ref_parser<BS: Readable>(
+) -> impl AsyncParser<ObjectRef, BS> + HasOutput<ObjectRef, Output = OwnedObject> {
+    Action(
+        (DefaultInterp, DefaultInterp, DefaultInterp),
+        |(id, version, digest): (ObjectIdRaw, u64, [u8; 33])| {
+            let mut rv = OwnedObject {
+                id,
+                version,
+                digest: [0; 32],
+            };
+            rv.digest.copy_from_slice(&digest[1..]);
+            Some(rv)
+        },
+    )
+}
+
+// The review shows a transaction, so only the intent of a transaction of IOTA is clear signed: scope
+// TransactionData, version V0 and app IOTA. Anything else is left to blind signing.
+const fn intent_parser<BS: Readable>(
+) -> impl AsyncParser<Intent, BS> + HasOutput<Intent, Output = ()> {
+    Action(
+        (CanonicalULEB128, CanonicalULEB128, CanonicalULEB128),
+        |intent: (u32, u32, u32)| {
+            if intent != (0, 0, 0) {
+                trace!("Intent not of a transaction");
+                return None;
+            }
+            trace!("Intent Ok");
+            Some(())
+        },
+    )
+}
+
+const fn transaction_data_v1_parser<BS: Clone + Readable>(
+) -> impl AsyncParser<TransactionDataV1, BS> + HasOutput<TransactionDataV1, Output = TxSummary> {
+    Action(
+        (IotaInterp, DefaultInterp, IotaInterp, IotaInterp),
+        |(programmable, sender, gas, expiration): (ProgrammableSummary, IotaAddressRaw, _, _)| {
+            Some(TxSummary {
+                sender,
+                operation: programmable.operation,
+                commands: programmable.commands,
+                call: programmable.call,
+                objects: programmable.objects,
+                gas,
+                expiration,
+            })
+        },
+    )
+}
+
+impl HasOutput<TransactionData> for IotaInterp {
+    type Output = TxSummary;
+}
+
+impl<BS: Clone + Readable> AsyncParser<TransactionData, BS> for IotaInterp {
+    type State<'c> = impl Future<Output = Self::Output> + 'c where BS: 'c;
+    fn parse<'a: 'c, 'b: 'c, 'c>(&'b self, input: &'a mut BS) -> Self::State<'c> {
+        async move {
+            let enum_variant =
+                <CanonicalULEB128 as AsyncParser<ULEB128, BS>>::parse(&CanonicalULEB128, input)
+                    .await;
+            match enum_variant {
+                0 => {
+                    trace!("TransactionData: V1");
+                    transaction_data_v1_parser::<_>().parse(input).await
+                }
+                _ => reject_with(AppError::UnsupportedTransactionData).await,
+            }
+        }
+    }
+}
+
+pub const fn tx_parser<BS: Clone + Readable>(
+) -> impl AsyncParser<IntentMessage, BS> + HasOutput<IntentMessage, Output = TxSummary> {
+    Action((intent_parser(), IotaInterp), |(_, summary)| Some(summary))
+}
+
+// What a transfer does
+pub struct TransferSummary {
+    pub recipient: IotaAddressRaw,
+    pub total_amount: u64,
+    pub amount_count: usize,
+}
+
+// What a transfer of objects, rather than of amounts alone, does
+pub struct ObjectTransferSummary {
+    pub recipient: IotaAddressRaw,
+    pub object_count: usize,
+    // The gas coin and coins split off it, of all the objects
+    pub iota_coin_count: usize,
+    // What is split off the gas coin for the transfer, unless the gas coin itself is sent
+    pub split_amount: Option<u64>,
+}
+
+// What a call of a lending market does
+#[cfg(feature = "lending")]
+pub struct LendingSummary {
+    pub action: LendingAction,
+    // The package of the market, which is what the user has to trust
+    pub package: ObjectIdRaw,
+    pub coin_type: CoinType,
+    pub amount: u64,
+    // Where what the call returns is sent, if anywhere
+    pub recipient: Option<IotaAddressRaw>,
+}
+
+// What a call of a pool of an AMM does, with both of its coins in the order of the pool
+#[cfg(feature = "liquidity")]
+pub struct LiquiditySummary {
+    pub action: LiquidityAction,
+    // The package of the pool, which is what the user has to trust
+    pub package: ObjectIdRaw,
+    pub coin_types: [CoinType; 2],
+    // The amounts of the coins added, or the least of them accepted for the share removed
+    pub amounts: [u64; 2],
+    // Where what the call returns is sent, if anywhere
+    pub recipient: Option<IotaAddressRaw>,
+}
+
+// What a call splitting a coin into coins of equal value does
+pub struct DivideSummary {
+    pub package: ObjectIdRaw,
+    pub coin_type: CoinType,
+    // Of the coin divided, which is split off for the call
+    pub amount: u64,
+    pub count: u64,
+    // Where the coin divided is sent, with the remainder, as the sender keeps the other coins
+    pub recipient: IotaAddressRaw,
+}
+
+impl DivideSummary {
+    /// The amount of each of the coins, of which the coin divided keeps the remainder too.
+    pub fn piece(&self) -> u64 {
+        self.amount / self.count
+    }
+
+    pub fn remainder(&self) -> u64 {
+        self.amount % self.count
+    }
+}
+
+// What staking with a validator does
+pub struct StakeSummary {
+    pub package: ObjectIdRaw,
+    pub validator: IotaAddressRaw,
+    // Of the coin split off for the call
+    pub amount: u64,
+}
+
+// What withdrawing a stake does
+pub struct UnstakeSummary {
+    pub package: ObjectIdRaw,
+    pub object: StakedObject,
+    // The ID of the object of the stake
+    pub staked: ObjectIdRaw,
+}
+
+// What minting a coin with its treasury cap does
+pub struct MintSummary {
+    pub package: ObjectIdRaw,
+    pub coin_type: CoinType,
+    pub amount: u64,
+    pub recipient: IotaAddressRaw,
+}
+
+pub enum Operation {
+    Transfer(TransferSummary),
+    ObjectTransfer(ObjectTransferSummary),
+    #[cfg(feature = "lending")]
+    Lending(LendingSummary),
+    #[cfg(feature = "liquidity")]
+    Liquidity(LiquiditySummary),
+    Divide(DivideSummary),
+    Stake(StakeSummary),
+    Mint(MintSummary),
+    Unstake(UnstakeSummary),
+}
+
+impl Operation {
+    pub fn recipient(&self) -> Option<&IotaAddressRaw> {
+        match self {
+            Operation::Transfer(transfer) => Some(&transfer.recipient),
+            Operation::ObjectTransfer(transfer) => Some(&transfer.recipient),
+            #[cfg(feature = "lending")]
+            Operation::Lending(lending) => lending.recipient.as_ref(),
+            #[cfg(feature = "liquidity")]
+            Operation::Liquidity(liquidity) => liquidity.recipient.as_ref(),
+            Operation::Divide(divide) => Some(&divide.recipient),
+            Operation::Stake(_) | Operation::Unstake(_) => None,
+            Operation::Mint(mint) => Some(&mint.recipient),
+        }
+    }
+
+    /// The package called, for operations which are a call.
+    pub fn package(&self) -> Option<&ObjectIdRaw> {
+        match self {
+            Operation::Transfer(_) | Operation::ObjectTransfer(_) => None,
+            #[cfg(feature = "lending")]
+            Operation::Lending(lending) => Some(&lending.package),
+            #[cfg(feature = "liquidity")]
+            Operation::Liquidity(liquidity) => Some(&liquidity.package),
+            Operation::Divide(divide) => Some(&divide.package),
+            Operation::Stake(stake) => Some(&stake.package),
+            Operation::Mint(mint) => Some(&mint.package),
+            Operation::Unstake(unstake) => Some(&unstake.package),
+        }
+    }
+}
+
+pub struct ProgrammableSummary {
+    pub operation: Operation,
+    pub commands: ArrayVec<&'static str, MAX_COMMANDS>,
+    pub call: Option<CallName>,
+    pub objects: ObjectsSummary,
+}
+
+// The object inputs of the sender
+pub struct ObjectsSummary {
+    pub first: ArrayVec<OwnedObject, MAX_OWNED_OBJECTS>,
+    pub count: usize,
+}
+
+pub struct GasSummary {
+    // The first of the objects paying for gas
+    pub object: ObjectIdRaw,
+    pub object_count: usize,
+    pub price: u64,
+    pub budget: u64,
+}
+
+// What a known transaction does, as parsed and without regard for how it is shown, so that the
+// review can go back to earlier fields once parsing is done.
+pub struct TxSummary {
+    // As found in the transaction, the review shows the address of the signing key instead
+    pub sender: IotaAddressRaw,
+    pub operation: Operation,
+    pub commands: ArrayVec<&'static str, MAX_COMMANDS>,
+    // The one Move call among the commands, if there is one
+    pub call: Option<CallName>,
+    pub objects: ObjectsSummary,
+    pub gas: GasSummary,
+    pub expiration: Option<u64>,
+}
+
+#[cfg(all(target_family = "bolos", test))]
+mod tests {
+    use super::*;
+    use ledger_device_sdk::assert_eq_err as assert_eq;
+    use ledger_device_sdk::testing::TestType;
+    use testmacro::test_item as test;
+
+    fn split_coins(inputs: &[u16]) -> Command {
+        Command::SplitCoins(
+            Argument::GasCoin,
+            inputs.iter().map(|i| Argument::Input(*i)).collect(),
+        )
+    }
+
+    fn transfer_to(input: u16) -> Command {
+        Command::TransferObject(
+            core::iter::once(Argument::NestedResult(0, 0)).collect(),
+            Argument::Input(input),
+        )
+    }
+
+    // Input 0 is an address, and inputs 1 and 2 are amounts
+    fn inputs(amounts: &[u64]) -> TransferInputs {
+        let mut inputs = TransferInputs::default();
+        inputs.addresses.push(([1; IOTA_ADDRESS_LENGTH], 0));
+        for (i, amount) in amounts.iter().enumerate() {
+            inputs.amounts.push((*amount, i as u32 + 1));
+        }
+        inputs
+    }
+
+    fn iota() -> StructTag {
+        StructTag {
+            address: IOTA_FRAMEWORK_ADDRESS,
+            module: Identifier::from(IOTA_COIN_MODULE).unwrap(),
+            name: Identifier::from(IOTA_COIN_NAME).unwrap(),
+        }
+    }
+
+    // A call of the lending market of package 0x1e1e..., for IOTA
+    #[cfg(feature = "lending")]
+    fn lending_call(function: &str, arguments: &[Argument]) -> Command {
+        Command::MoveCall(MoveCall {
+            package: [0x1e; IOTA_ADDRESS_LENGTH],
+            module: Identifier::from("lending").ok(),
+            function: Identifier::from(function).ok(),
+            type_arguments: core::iter::once(Some(iota())).collect(),
+            arguments: arguments.iter().copied().collect(),
+        })
+    }
+
+    #[cfg(feature = "lending")]
+    fn lending_of(check: CommandCheck, inputs: &TransferInputs) -> Option<LendingSummary> {
+        match check.finish(inputs) {
+            Ok(Operation::Lending(lending)) => Some(lending),
+            _ => None,
+        }
+    }
+
+    // The inputs above, with the market as input 5
+    fn market_inputs(amounts: &[u64]) -> TransferInputs {
+        let mut inputs = inputs(amounts);
+        inputs.objects |= 1 << 5;
+        inputs
+    }
+
+    #[test]
+    fn small_pure_inputs_by_type() {
+        let mut inputs = inputs(&[5]);
+        let mut bytes = [0; 16];
+        bytes[0] = 1;
+        bytes[1] = 2;
+        for (length, index) in [(1, 3), (2, 4), (16, 6)] {
+            inputs
+                .small_pures
+                .push((SmallPure { bytes, length }, index));
+        }
+        assert_eq!(inputs.pure(3, PureType::Bool), Some(PureValue::Bool(true)));
+        assert_eq!(inputs.pure(3, PureType::U8), Some(PureValue::U8(1)));
+        assert_eq!(inputs.pure(4, PureType::U16), Some(PureValue::U16(0x201)));
+        assert_eq!(inputs.pure(6, PureType::U128), Some(PureValue::U128(bytes)));
+        assert_eq!(inputs.pure(1, PureType::U64), Some(PureValue::U64(5)));
+        // Not of the length of the type
+        assert_eq!(inputs.pure(4, PureType::Bool), None);
+        assert_eq!(inputs.pure(3, PureType::U128), None);
+        assert_eq!(inputs.pure(1, PureType::U16), None);
+        // Not a bool
+        inputs.small_pures[0].0.bytes[0] = 2;
+        assert_eq!(inputs.pure(3, PureType::Bool), None);
+    }
+
+    #[cfg(feature = "lending")]
+    #[test]
+    fn deposits_a_split_coin() {
+        let mut check = CommandCheck::default();
+        let inputs = market_inputs(&[5]);
+        assert_eq!(check.command(0, &split_coins(&[1]), &inputs).is_ok(), true);
+        let deposit = lending_call("deposit", &[Argument::Input(5), Argument::Result(0)]);
+        assert_eq!(check.command(1, &deposit, &inputs).is_ok(), true);
+        let name = check.call_name();
+        assert_eq!(
+            name.map(
+                |name| name.module.as_str() == "lending" && name.function.as_str() == "deposit"
+            ),
+            Some(true)
+        );
+        let lending = lending_of(check, &inputs);
+        assert_eq!(lending.is_some(), true);
+        let lending = lending.unwrap();
+        assert_eq!(lending.action, LendingAction::Deposit);
+        assert_eq!(lending.amount, 5);
+        assert_eq!(lending.package, [0x1e; IOTA_ADDRESS_LENGTH]);
+        assert_eq!(lending.recipient, None);
+    }
+
+    #[cfg(feature = "lending")]
+    #[test]
+    fn withdraws_an_amount_to_the_recipient() {
+        let mut check = CommandCheck::default();
+        let inputs = market_inputs(&[5]);
+        let withdraw = lending_call("withdraw", &[Argument::Input(5), Argument::Input(1)]);
+        assert_eq!(check.command(0, &withdraw, &inputs).is_ok(), true);
+        let transfer = Command::TransferObject(
+            core::iter::once(Argument::Result(0)).collect(),
+            Argument::Input(0),
+        );
+        assert_eq!(check.command(1, &transfer, &inputs).is_ok(), true);
+        let lending = lending_of(check, &inputs);
+        assert_eq!(lending.is_some(), true);
+        let lending = lending.unwrap();
+        assert_eq!(lending.action, LendingAction::Withdraw);
+        assert_eq!(lending.amount, 5);
+        assert_eq!(lending.recipient, Some([1; IOTA_ADDRESS_LENGTH]));
+    }
+
+    #[cfg(feature = "lending")]
+    #[test]
+    fn call_with_other_arguments() {
+        let inputs = market_inputs(&[5]);
+        // An address, which could be who the withdrawal is for
+        let withdraw = lending_call("withdraw", &[Argument::Input(5), Argument::Input(0)]);
+        assert_eq!(
+            CommandCheck::default().command(0, &withdraw, &inputs).err(),
+            Some(AppError::CallArgumentMismatch)
+        );
+        // Two amounts
+        let withdraw = lending_call(
+            "withdraw",
+            &[Argument::Input(1), Argument::Input(2), Argument::Input(5)],
+        );
+        let inputs = market_inputs(&[5, 7]);
+        assert_eq!(
+            CommandCheck::default().command(0, &withdraw, &inputs).err(),
+            Some(AppError::CallArgumentMismatch)
+        );
+        // The gas coin itself
+        let deposit = lending_call("deposit", &[Argument::Input(5), Argument::GasCoin]);
+        assert_eq!(
+            CommandCheck::default().command(0, &deposit, &inputs).err(),
+            Some(AppError::CallArgumentMismatch)
+        );
+    }
+
+    #[cfg(feature = "lending")]
+    #[test]
+    fn unknown_and_repeated_calls() {
+        let inputs = market_inputs(&[5]);
+        let liquidate = lending_call("liquidate", &[Argument::Input(5), Argument::Input(1)]);
+        assert_eq!(
+            CommandCheck::default()
+                .command(0, &liquidate, &inputs)
+                .err(),
+            Some(AppError::UnknownCall)
+        );
+        let mut check = CommandCheck::default();
+        let borrow = lending_call("borrow", &[Argument::Input(5), Argument::Input(1)]);
+        assert_eq!(check.command(0, &borrow, &inputs).is_ok(), true);
+        assert_eq!(
+            check.command(1, &borrow, &inputs).err(),
+            Some(AppError::MultipleCalls)
+        );
+    }
+
+    #[cfg(feature = "lending")]
+    #[test]
+    fn deposit_with_the_coin_sent_elsewhere() {
+        let mut check = CommandCheck::default();
+        let inputs = market_inputs(&[5, 7]);
+        assert_eq!(
+            check.command(0, &split_coins(&[1, 2]), &inputs).is_ok(),
+            true
+        );
+        let deposit = lending_call(
+            "deposit",
+            &[Argument::Input(5), Argument::NestedResult(0, 1)],
+        );
+        assert_eq!(check.command(1, &deposit, &inputs).is_ok(), true);
+        // The other coin split off goes to the recipient, which the review would not show
+        assert_eq!(check.command(2, &transfer_to(0), &inputs).is_ok(), true);
+        assert_eq!(
+            check.finish(&inputs).err(),
+            Some(AppError::CallArgumentMismatch)
+        );
+    }
+
+    // A coin of package 0xc0c0..., as the framework only has IOTA
+    fn other_coin() -> StructTag {
+        StructTag {
+            address: [0xc0; IOTA_ADDRESS_LENGTH],
+            module: Identifier::from("usdc").unwrap(),
+            name: Identifier::from("USDC").unwrap(),
+        }
+    }
+
+    // A call of the pool of IOTA and the coin above, of package 0x5b5b...
+    #[cfg(feature = "liquidity")]
+    fn pool_call(function: &str, arguments: &[Argument]) -> Command {
+        Command::MoveCall(MoveCall {
+            package: [0x5b; IOTA_ADDRESS_LENGTH],
+            module: Identifier::from("pool").ok(),
+            function: Identifier::from(function).ok(),
+            type_arguments: [Some(iota()), Some(other_coin())].iter().cloned().collect(),
+            arguments: arguments.iter().copied().collect(),
+        })
+    }
+
+    #[cfg(feature = "liquidity")]
+    fn liquidity_of(check: CommandCheck, inputs: &TransferInputs) -> Option<LiquiditySummary> {
+        match check.finish(inputs) {
+            Ok(Operation::Liquidity(liquidity)) => Some(liquidity),
+            _ => None,
+        }
+    }
+
+    // The inputs above, with the pool as input 5 and a coin object of the sender as input 6
+    fn pool_inputs(amounts: &[u64]) -> TransferInputs {
+        let mut inputs = market_inputs(amounts);
+        inputs.objects |= 1 << 6;
+        inputs
+    }
+
+    fn split_coin_object(amount: u16) -> Command {
+        Command::SplitCoins(
+            Argument::Input(6),
+            core::iter::once(Argument::Input(amount)).collect(),
+        )
+    }
+
+    #[cfg(feature = "liquidity")]
+    #[test]
+    fn adds_a_coin_of_each_type() {
+        let mut check = CommandCheck::default();
+        let inputs = pool_inputs(&[5, 7]);
+        assert_eq!(check.command(0, &split_coins(&[1]), &inputs).is_ok(), true);
+        assert_eq!(
+            check.command(1, &split_coin_object(2), &inputs).is_ok(),
+            true
+        );
+        let add = pool_call(
+            "add_liquidity",
+            &[Argument::Input(5), Argument::Result(0), Argument::Result(1)],
+        );
+        assert_eq!(check.command(2, &add, &inputs).is_ok(), true);
+        let transfer = Command::TransferObject(
+            core::iter::once(Argument::Result(2)).collect(),
+            Argument::Input(0),
+        );
+        assert_eq!(check.command(3, &transfer, &inputs).is_ok(), true);
+        let liquidity = liquidity_of(check, &inputs);
+        assert_eq!(liquidity.is_some(), true);
+        let liquidity = liquidity.unwrap();
+        assert_eq!(liquidity.action, LiquidityAction::Add);
+        assert_eq!(liquidity.amounts, [5, 7]);
+        assert_eq!(liquidity.coin_types[0] == CoinType::Iota, true);
+        assert_eq!(
+            liquidity.coin_types[1] == CoinType::Other(other_coin()),
+            true
+        );
+        assert_eq!(liquidity.package, [0x5b; IOTA_ADDRESS_LENGTH]);
+        assert_eq!(liquidity.recipient, Some([1; IOTA_ADDRESS_LENGTH]));
+    }
+
+    #[cfg(feature = "liquidity")]
+    #[test]
+    fn adds_the_gas_coin_as_the_other_coin() {
+        let mut check = CommandCheck::default();
+        let inputs = pool_inputs(&[5, 7]);
+        assert_eq!(check.command(0, &split_coins(&[1]), &inputs).is_ok(), true);
+        assert_eq!(
+            check.command(1, &split_coin_object(2), &inputs).is_ok(),
+            true
+        );
+        let add = pool_call(
+            "add_liquidity",
+            &[Argument::Input(5), Argument::Result(1), Argument::Result(0)],
+        );
+        assert_eq!(check.command(2, &add, &inputs).is_ok(), true);
+        assert_eq!(
+            check.finish(&inputs).err(),
+            Some(AppError::CallArgumentMismatch)
+        );
+    }
+
+    #[cfg(feature = "liquidity")]
+    #[test]
+    fn adds_the_same_coin_twice() {
+        let mut check = CommandCheck::default();
+        let inputs = pool_inputs(&[5, 7]);
+        assert_eq!(check.command(0, &split_coins(&[1]), &inputs).is_ok(), true);
+        assert_eq!(
+            check.command(1, &split_coin_object(2), &inputs).is_ok(),
+            true
+        );
+        let add = pool_call(
+            "add_liquidity",
+            &[
+                Argument::Input(5),
+                Argument::Result(0),
+                Argument::NestedResult(0, 0),
+            ],
+        );
+        assert_eq!(check.command(2, &add, &inputs).is_ok(), true);
+        assert_eq!(
+            check.finish(&inputs).err(),
+            Some(AppError::CallArgumentMismatch)
+        );
+    }
+
+    #[cfg(feature = "liquidity")]
+    #[test]
+    fn removes_for_the_least_amounts() {
+        let mut check = CommandCheck::default();
+        let inputs = pool_inputs(&[5, 7]);
+        // Input 6 is the share of the pool
+        let remove = pool_call(
+            "remove_liquidity",
+            &[
+                Argument::Input(5),
+                Argument::Input(6),
+                Argument::Input(1),
+                Argument::Input(2),
+            ],
+        );
+        assert_eq!(check.command(0, &remove, &inputs).is_ok(), true);
+        let transfer = Command::TransferObject(
+            [Argument::NestedResult(0, 0), Argument::NestedResult(0, 1)]
+                .iter()
+                .copied()
+                .collect(),
+            Argument::Input(0),
+        );
+        assert_eq!(check.command(1, &transfer, &inputs).is_ok(), true);
+        let liquidity = liquidity_of(check, &inputs);
+        assert_eq!(liquidity.is_some(), true);
+        let liquidity = liquidity.unwrap();
+        assert_eq!(liquidity.action, LiquidityAction::Remove);
+        assert_eq!(liquidity.amounts, [5, 7]);
+    }
+
+    #[cfg(feature = "liquidity")]
+    #[test]
+    fn pool_call_with_one_coin_type() {
+        let inputs = pool_inputs(&[5, 7]);
+        let remove = Command::MoveCall(MoveCall {
+            package: [0x5b; IOTA_ADDRESS_LENGTH],
+            module: Identifier::from("pool").ok(),
+            function: Identifier::from("remove_liquidity").ok(),
+            type_arguments: core::iter::once(Some(iota())).collect(),
+            arguments: [Argument::Input(5), Argument::Input(1), Argument::Input(2)]
+                .iter()
+                .copied()
+                .collect(),
+        });
+        assert_eq!(
+            CommandCheck::default().command(0, &remove, &inputs).err(),
+            Some(AppError::UnsupportedTypeArgument)
+        );
+    }
+
+    #[test]
+    fn transfer_of_a_split_coin_object() {
+        let mut check = CommandCheck::default();
+        let inputs = pool_inputs(&[5]);
+        assert_eq!(
+            check.command(0, &split_coin_object(1), &inputs).is_ok(),
+            true
+        );
+        assert_eq!(check.command(1, &transfer_to(0), &inputs).is_ok(), true);
+        // Of a type the transaction does not tell, and so of no amount shown
+        let transfer = object_transfer_of(check, &inputs);
+        assert_eq!(transfer.is_some(), true);
+        let transfer = transfer.unwrap();
+        assert_eq!(transfer.object_count, 1);
+        assert_eq!(transfer.iota_coin_count, 0);
+        assert_eq!(transfer.split_amount, None);
+    }
+
+    // A call of 0x2::pay::divide_and_keep, for IOTA
+    fn divide_call(arguments: &[Argument]) -> Command {
+        Command::MoveCall(MoveCall {
+            package: IOTA_FRAMEWORK_ADDRESS,
+            module: Identifier::from("pay").ok(),
+            function: Identifier::from("divide_and_keep").ok(),
+            type_arguments: core::iter::once(Some(iota())).collect(),
+            arguments: arguments.iter().copied().collect(),
+        })
+    }
+
+    fn divide_of(check: CommandCheck, inputs: &TransferInputs) -> Option<DivideSummary> {
+        match check.finish(inputs) {
+            Ok(Operation::Divide(divide)) => Some(divide),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn divides_a_split_coin() {
+        let mut check = CommandCheck::default();
+        let inputs = inputs(&[10, 3]);
+        assert_eq!(check.command(0, &split_coins(&[1]), &inputs).is_ok(), true);
+        let divide = divide_call(&[Argument::Result(0), Argument::Input(2)]);
+        assert_eq!(check.command(1, &divide, &inputs).is_ok(), true);
+        assert_eq!(
+            check
+                .command(2, &transfer_of(&[Argument::Result(0)]), &inputs)
+                .is_ok(),
+            true
+        );
+        let divide = divide_of(check, &inputs);
+        assert_eq!(divide.is_some(), true);
+        let divide = divide.unwrap();
+        assert_eq!(divide.package, IOTA_FRAMEWORK_ADDRESS);
+        assert_eq!(divide.coin_type == CoinType::Iota, true);
+        assert_eq!(divide.amount, 10);
+        assert_eq!(divide.count, 3);
+        assert_eq!(divide.piece(), 3);
+        assert_eq!(divide.remainder(), 1);
+        assert_eq!(divide.recipient, [1; IOTA_ADDRESS_LENGTH]);
+    }
+
+    #[test]
+    fn divides_only_a_coin_sent_on() {
+        // Left over at the end, which a coin split off cannot be
+        let mut check = CommandCheck::default();
+        let inputs = inputs(&[10, 3]);
+        assert_eq!(check.command(0, &split_coins(&[1]), &inputs).is_ok(), true);
+        let divide = divide_call(&[Argument::Result(0), Argument::Input(2)]);
+        assert_eq!(check.command(1, &divide, &inputs).is_ok(), true);
+        assert_eq!(
+            check.finish(&inputs).err(),
+            Some(AppError::CallArgumentMismatch)
+        );
+        // The division returns nothing to send in its place
+        let mut check = CommandCheck::default();
+        assert_eq!(check.command(0, &split_coins(&[1]), &inputs).is_ok(), true);
+        assert_eq!(check.command(1, &divide, &inputs).is_ok(), true);
+        assert_eq!(
+            check
+                .command(2, &transfer_of(&[Argument::Result(1)]), &inputs)
+                .is_ok(),
+            true
+        );
+        assert_eq!(
+            check.finish(&inputs).err(),
+            Some(AppError::CallArgumentMismatch)
+        );
+    }
+
+    #[test]
+    fn divides_into_no_more_coins_than_there_is_value() {
+        for count in [0, 11] {
+            let mut check = CommandCheck::default();
+            let inputs = inputs(&[10, count]);
+            assert_eq!(check.command(0, &split_coins(&[1]), &inputs).is_ok(), true);
+            let divide = divide_call(&[Argument::Result(0), Argument::Input(2)]);
+            assert_eq!(check.command(1, &divide, &inputs).is_ok(), true);
+            let transfer = transfer_of(&[Argument::Result(0)]);
+            assert_eq!(check.command(2, &transfer, &inputs).is_ok(), true);
+            assert_eq!(
+                check.finish(&inputs).err(),
+                Some(AppError::CallArgumentMismatch)
+            );
+        }
+        // Without a count
+        let inputs = inputs(&[10]);
+        let divide = divide_call(&[Argument::Result(0)]);
+        assert_eq!(
+            CommandCheck::default().command(1, &divide, &inputs).err(),
+            Some(AppError::CallArgumentMismatch)
+        );
+    }
+
+    // A call of 0x3::iota_system::request_add_stake, with the system state as input 5 and the
+    // validator as input 0
+    fn stake_call(arguments: &[Argument]) -> Command {
+        Command::MoveCall(MoveCall {
+            package: patterns::IOTA_SYSTEM_ADDRESS,
+            module: Identifier::from("iota_system").ok(),
+            function: Identifier::from("request_add_stake").ok(),
+            type_arguments: ArrayVec::new(),
+            arguments: arguments.iter().copied().collect(),
+        })
+    }
+
+    fn stake_of(check: CommandCheck, inputs: &TransferInputs) -> Option<StakeSummary> {
+        match check.finish(inputs) {
+            Ok(Operation::Stake(stake)) => Some(stake),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn stakes_a_split_coin() {
+        let mut check = CommandCheck::default();
+        let inputs = market_inputs(&[5]);
+        assert_eq!(check.command(0, &split_coins(&[1]), &inputs).is_ok(), true);
+        let stake = stake_call(&[Argument::Input(5), Argument::Result(0), Argument::Input(0)]);
+        assert_eq!(check.command(1, &stake, &inputs).is_ok(), true);
+        let stake = stake_of(check, &inputs);
+        assert_eq!(stake.is_some(), true);
+        let stake = stake.unwrap();
+        assert_eq!(stake.package, patterns::IOTA_SYSTEM_ADDRESS);
+        assert_eq!(stake.validator, [1; IOTA_ADDRESS_LENGTH]);
+        assert_eq!(stake.amount, 5);
+    }
+
+    #[test]
+    fn stake_with_anything_else() {
+        // Along with another split, which would go elsewhere
+        let mut check = CommandCheck::default();
+        let inputs = market_inputs(&[5]);
+        assert_eq!(
+            check.command(0, &split_coins(&[1, 1]), &inputs).is_ok(),
+            true
+        );
+        let stake = stake_call(&[
+            Argument::Input(5),
+            Argument::NestedResult(0, 0),
+            Argument::Input(0),
+        ]);
+        assert_eq!(check.command(1, &stake, &inputs).is_ok(), true);
+        assert_eq!(
+            check.finish(&inputs).err(),
+            Some(AppError::CallArgumentMismatch)
+        );
+        // Without a validator
+        let mut check = CommandCheck::default();
+        assert_eq!(check.command(0, &split_coins(&[1]), &inputs).is_ok(), true);
+        let stake = stake_call(&[Argument::Input(5), Argument::Result(0)]);
+        assert_eq!(
+            check.command(1, &stake, &inputs).err(),
+            Some(AppError::CallArgumentMismatch)
+        );
+        // Of the gas coin itself, which only a transfer can take by value
+        let stake = stake_call(&[Argument::Input(5), Argument::GasCoin, Argument::Input(0)]);
+        assert_eq!(
+            CommandCheck::default().command(0, &stake, &inputs).err(),
+            Some(AppError::CallArgumentMismatch)
+        );
+    }
+
+    // A call withdrawing a stake of `module` of 0x3, with the system state as input 5
+    fn unstake_call(module: &str, arguments: &[Argument]) -> Command {
+        Command::MoveCall(MoveCall {
+            package: patterns::IOTA_SYSTEM_ADDRESS,
+            module: Identifier::from(module).ok(),
+            function: Identifier::from("request_withdraw_stake").ok(),
+            type_arguments: ArrayVec::new(),
+            arguments: arguments.iter().copied().collect(),
+        })
+    }
+
+    // The inputs of the market, with a stake of the sender as input 6
+    fn stake_inputs() -> TransferInputs {
+        let mut inputs = market_inputs(&[]);
+        inputs.objects |= 1 << 6;
+        let stake = OwnedObject {
+            id: [0x5a; IOTA_ADDRESS_LENGTH],
+            version: 3,
+            digest: [0; 32],
+        };
+        inputs.owned_objects.push((stake, 6));
+        inputs.owned_object_count = 1;
+        inputs
+    }
+
+    #[test]
+    fn unstakes_both_kinds_of_stake() {
+        for (module, object) in [
+            ("iota_system", StakedObject::StakedIota),
+            ("timelocked_staking", StakedObject::TimelockedStakedIota),
+        ] {
+            let mut check = CommandCheck::default();
+            let inputs = stake_inputs();
+            let unstake = unstake_call(module, &[Argument::Input(5), Argument::Input(6)]);
+            assert_eq!(check.command(0, &unstake, &inputs).is_ok(), true);
+            let unstake = match check.finish(&inputs) {
+                Ok(Operation::Unstake(unstake)) => Some(unstake),
+                _ => None,
+            };
+            assert_eq!(unstake.is_some(), true);
+            let unstake = unstake.unwrap();
+            assert_eq!(unstake.object, object);
+            assert_eq!(unstake.staked, [0x5a; IOTA_ADDRESS_LENGTH]);
+        }
+    }
+
+    #[test]
+    fn unstake_of_no_object_of_the_sender() {
+        let inputs = stake_inputs();
+        // The system state alone, which is shared
+        let unstake = unstake_call("iota_system", &[Argument::Input(5)]);
+        assert_eq!(
+            CommandCheck::default().command(0, &unstake, &inputs).err(),
+            Some(AppError::CallArgumentMismatch)
+        );
+        let unstake = unstake_call("iota_system", &[Argument::Input(5), Argument::Input(1)]);
+        assert_eq!(
+            CommandCheck::default().command(0, &unstake, &inputs).err(),
+            Some(AppError::CallArgumentMismatch)
+        );
+    }
+
+    // A call of 0x2::coin::mint_and_transfer for the coin above, with the treasury cap as input 5
+    fn mint_call(arguments: &[Argument]) -> Command {
+        Command::MoveCall(MoveCall {
+            package: IOTA_FRAMEWORK_ADDRESS,
+            module: Identifier::from("coin").ok(),
+            function: Identifier::from("mint_and_transfer").ok(),
+            type_arguments: core::iter::once(Some(other_coin())).collect(),
+            arguments: arguments.iter().copied().collect(),
+        })
+    }
+
+    #[test]
+    fn mints_an_amount_for_the_recipient() {
+        let mut check = CommandCheck::default();
+        let inputs = market_inputs(&[7]);
+        let mint = mint_call(&[Argument::Input(5), Argument::Input(1), Argument::Input(0)]);
+        assert_eq!(check.command(0, &mint, &inputs).is_ok(), true);
+        // Both pure arguments, for the expert review of the command
+        assert_eq!(
+            check.call_name().map(|name| name.arguments[..]
+                == [
+                    PureValue::U64(7),
+                    PureValue::Address([1; IOTA_ADDRESS_LENGTH])
+                ]),
+            Some(true)
+        );
+        let mint = match check.finish(&inputs) {
+            Ok(Operation::Mint(mint)) => Some(mint),
+            _ => None,
+        };
+        assert_eq!(mint.is_some(), true);
+        let mint = mint.unwrap();
+        assert_eq!(mint.coin_type == CoinType::Other(other_coin()), true);
+        assert_eq!(mint.amount, 7);
+        assert_eq!(mint.recipient, [1; IOTA_ADDRESS_LENGTH]);
+    }
+
+    #[test]
+    fn mint_without_an_amount_or_recipient() {
+        let inputs = market_inputs(&[7]);
+        for arguments in [
+            &[Argument::Input(5), Argument::Input(1)][..],
+            &[Argument::Input(5), Argument::Input(0)][..],
+            &[Argument::Input(5), Argument::Input(0), Argument::Input(1)][..],
+        ] {
+            assert_eq!(
+                CommandCheck::default()
+                    .command(0, &mint_call(arguments), &inputs)
+                    .err(),
+                Some(AppError::CallArgumentMismatch)
+            );
+        }
+    }
+
+    fn transfer_of(objects: &[Argument]) -> Command {
+        Command::TransferObject(objects.iter().copied().collect(), Argument::Input(0))
+    }
+
+    fn object_transfer_of(
+        check: CommandCheck,
+        inputs: &TransferInputs,
+    ) -> Option<ObjectTransferSummary> {
+        match check.finish(inputs) {
+            Ok(Operation::ObjectTransfer(transfer)) => Some(transfer),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn transfers_split_coins_as_amounts() {
+        let mut check = CommandCheck::default();
+        let inputs = inputs(&[5, 7]);
+        assert_eq!(
+            check.command(0, &split_coins(&[1, 2]), &inputs).is_ok(),
+            true
+        );
+        let coins = [Argument::NestedResult(0, 0), Argument::NestedResult(0, 1)];
+        assert_eq!(
+            check.command(1, &transfer_of(&coins), &inputs).is_ok(),
+            true
+        );
+        let transfer = match check.finish(&inputs) {
+            Ok(Operation::Transfer(transfer)) => Some(transfer),
+            _ => None,
+        };
+        assert_eq!(transfer.is_some(), true);
+        let transfer = transfer.unwrap();
+        assert_eq!(transfer.total_amount, 12);
+        assert_eq!(transfer.amount_count, 2);
+    }
+
+    #[test]
+    fn transfers_objects_along_with_a_split_coin() {
+        let mut check = CommandCheck::default();
+        let inputs = pool_inputs(&[5]);
+        assert_eq!(check.command(0, &split_coins(&[1]), &inputs).is_ok(), true);
+        let objects = [Argument::Input(5), Argument::Result(0), Argument::Input(6)];
+        assert_eq!(
+            check.command(1, &transfer_of(&objects), &inputs).is_ok(),
+            true
+        );
+        let transfer = object_transfer_of(check, &inputs);
+        assert_eq!(transfer.is_some(), true);
+        let transfer = transfer.unwrap();
+        assert_eq!(transfer.recipient, [1; IOTA_ADDRESS_LENGTH]);
+        assert_eq!(transfer.object_count, 3);
+        assert_eq!(transfer.iota_coin_count, 1);
+        assert_eq!(transfer.split_amount, Some(5));
+    }
+
+    #[test]
+    fn transfers_the_gas_coin() {
+        let mut check = CommandCheck::default();
+        let inputs = pool_inputs(&[5]);
+        assert_eq!(check.command(0, &split_coins(&[1]), &inputs).is_ok(), true);
+        let objects = [Argument::GasCoin, Argument::Result(0)];
+        assert_eq!(
+            check.command(1, &transfer_of(&objects), &inputs).is_ok(),
+            true
+        );
+        // All of the gas coin goes, so the amount split off it says nothing
+        let transfer = object_transfer_of(check, &inputs);
+        assert_eq!(transfer.is_some(), true);
+        let transfer = transfer.unwrap();
+        assert_eq!(transfer.object_count, 2);
+        assert_eq!(transfer.iota_coin_count, 2);
+        assert_eq!(transfer.split_amount, None);
+    }
+
+    #[test]
+    fn transfer_of_what_is_not_an_object() {
+        let inputs = pool_inputs(&[5]);
+        // An amount, the result of a command which is no split, and nothing at all
+        for objects in [
+            &[Argument::Input(1)][..],
+            &[Argument::Result(0)][..],
+            &[Argument::Input(6), Argument::Result(3)][..],
+            &[][..],
+        ] {
+            let mut check = CommandCheck::default();
+            assert_eq!(
+                check.command(0, &transfer_of(objects), &inputs).is_ok(),
+                true
+            );
+            assert_eq!(check.finish(&inputs).is_err(), true);
+        }
+    }
+
+    #[test]
+    fn split_coins_counts_each_reference() {
+        let mut check = CommandCheck::default();
+        let inputs = inputs(&[5, 7]);
+        assert_eq!(
+            check.command(0, &split_coins(&[1, 2]), &inputs).is_ok(),
+            true
+        );
+        assert_eq!(
+            check.command(0, &split_coins(&[1, 1]), &inputs).is_ok(),
+            true
+        );
+        assert_eq!(check.amount_count, 4);
+        assert_eq!(check.total_amount, 22);
+    }
+
+    #[test]
+    fn split_coins_of_other_input() {
+        let mut check = CommandCheck::default();
+        let inputs = inputs(&[5]);
+        assert_eq!(check.command(0, &split_coins(&[0]), &inputs).is_err(), true);
+        assert_eq!(check.command(0, &split_coins(&[3]), &inputs).is_err(), true);
+    }
+
+    #[test]
+    fn split_coins_overflow() {
+        let mut check = CommandCheck::default();
+        let inputs = inputs(&[u64::MAX]);
+        assert_eq!(check.command(0, &split_coins(&[1]), &inputs).is_ok(), true);
+        assert_eq!(check.command(0, &split_coins(&[1]), &inputs).is_err(), true);
+    }
+
+    #[test]
+    fn recipient_is_the_transferred_to_address() {
+        let mut check = CommandCheck::default();
+        let mut inputs = inputs(&[5]);
+        // An object ID, which is never transferred to
+        inputs.addresses.push(([2; IOTA_ADDRESS_LENGTH], 2));
+        assert_eq!(check.command(0, &transfer_to(0), &inputs).is_ok(), true);
+        assert_eq!(check.recipient, Some([1; IOTA_ADDRESS_LENGTH]));
+        // Only one transfer
+        assert_eq!(check.command(0, &transfer_to(2), &inputs).is_err(), true);
+    }
+
+    #[test]
+    fn transfer_to_non_address() {
+        let mut check = CommandCheck::default();
+        let inputs = inputs(&[5]);
+        assert_eq!(check.command(0, &transfer_to(1), &inputs).is_err(), true);
+        assert_eq!(check.recipient, None);
+    }
+}
@@ -0,0 +1,184 @@
+// Which derivation paths the app derives keys for. Does not depend on the device, so that the
+// checks are tested on the host too, see the tests below; the prompts for unusual paths are in
+// implementation.rs.
+
+use crate::interface::{Bip32Key, HARDENED};
+use arrayvec::ArrayVec;
+use ledger_parser_combinators::async_parser::{AsyncParser, HasOutput, Readable};
+use ledger_parser_combinators::interp::{DefaultInterp, SubInterp};
+
+pub const fn bip_path_parser<BS: Readable>(
+) -> impl AsyncParser<Bip32Key, BS> + HasOutput<Bip32Key, Output = ArrayVec<u32, 10>> {
+    SubInterp(DefaultInterp)
+}
+
+// The purpose and coin type of the paths, m/44'/1' on the testnet and m/44'/4218' of IOTA
+pub const BIP32_TESTNET_PREFIX: [u32; 2] = [44 | HARDENED, 1 | HARDENED];
+pub const BIP32_IOTA_PREFIX: [u32; 2] = [44 | HARDENED, 4218 | HARDENED];
+
+// The purpose and coin type are hardened in both prefixes. The account has to be hardened as well,
+// as no wallet derives it otherwise, so a host asking for that is buggy or worse.
+pub fn is_bip_prefix_valid(path: &[u32]) -> bool {
+    (path.starts_with(&BIP32_TESTNET_PREFIX) || path.starts_with(&BIP32_IOTA_PREFIX))
+        && path.get(2).map_or(true, |account| account & HARDENED != 0)
+}
+
+// The depth of the paths of IOTA wallets, m/44'/coin'/account'/change'/index'
+pub const STANDARD_PATH_LENGTH: usize = 5;
+
+// Wallets find funds by scanning accounts and indices upwards from 0, so funds sent to a much
+// higher one are as good as lost to the user.
+const MAX_USUAL_INDEX: u32 = 100;
+
+pub fn has_high_index(path: &[u32]) -> bool {
+    path.get(2)
+        .into_iter()
+        .chain(path.get(4))
+        .any(|component| component & !HARDENED > MAX_USUAL_INDEX)
+}
+
+#[cfg(all(not(target_family = "bolos"), test))]
+mod tests {
+    use super::*;
+    use crate::futures::poll_once;
+    use core::future::Future;
+    use core::task::Poll;
+    use ledger_parser_combinators::async_parser::{reject, TryFuture};
+    use std::vec::Vec;
+
+    const H: u32 = HARDENED;
+
+    // The bytes of a parameter, standing in for the ByteStream of the block protocol
+    struct Bytes(Vec<u8>);
+
+    impl Readable for Bytes {
+        type OutFut<'a, const N: usize> = impl 'a + Future<Output = [u8; N]>;
+        fn read<'a: 'b, 'b, const N: usize>(&'a mut self) -> Self::OutFut<'b, N> {
+            async move {
+                if self.0.len() < N {
+                    return reject(0).await;
+                }
+                let mut rv = [0; N];
+                rv.copy_from_slice(&self.0[..N]);
+                self.0.drain(..N);
+                rv
+            }
+        }
+    }
+
+    // The path as GET_PUBKEY and SIGN_TX send it, or None if the parser rejects it
+    fn parse(bytes: &[u8]) -> Option<ArrayVec<u32, 10>> {
+        let mut input = Bytes(bytes.to_vec());
+        match poll_once(TryFuture(bip_path_parser().parse(&mut input))) {
+            Poll::Ready(Some(path)) => Some(path),
+            _ => None,
+        }
+    }
+
+    fn encode(path: &[u32]) -> Vec<u8> {
+        let mut rv = Vec::from([path.len() as u8]);
+        for component in path {
+            rv.extend_from_slice(&component.to_le_bytes());
+        }
+        rv
+    }
+
+    #[test]
+    fn accepts_the_paths_of_wallets() {
+        assert!(is_bip_prefix_valid(&[44 | H, 4218 | H, H, H, H]));
+        assert!(is_bip_prefix_valid(&[44 | H, 1 | H, 3 | H, H, 7 | H]));
+        // The depth is checked on its own, behind expert mode
+        assert!(is_bip_prefix_valid(&[44 | H, 4218 | H, H]));
+        assert!(is_bip_prefix_valid(&[
+            44 | H,
+            4218 | H,
+            H,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0
+        ]));
+    }
+
+    #[test]
+    fn rejects_empty_and_partial_prefixes() {
+        assert!(!is_bip_prefix_valid(&[]));
+        assert!(!is_bip_prefix_valid(&[44 | H]));
+        assert!(!is_bip_prefix_valid(&[4218 | H]));
+        assert!(!is_bip_prefix_valid(&[4218 | H, 44 | H, H, H, H]));
+    }
+
+    #[test]
+    fn accepts_a_bare_prefix() {
+        // No account to check, GET_PUBKEY prompts about the depth of these
+        assert!(is_bip_prefix_valid(&BIP32_IOTA_PREFIX));
+        assert!(is_bip_prefix_valid(&BIP32_TESTNET_PREFIX));
+    }
+
+    #[test]
+    fn rejects_other_coin_types() {
+        // Shimmer, its neighbours, and Ethereum
+        assert!(!is_bip_prefix_valid(&[44 | H, 4219 | H, H, H, H]));
+        assert!(!is_bip_prefix_valid(&[44 | H, 4217 | H, H, H, H]));
+        assert!(!is_bip_prefix_valid(&[44 | H, 60 | H, H, H, H]));
+        assert!(!is_bip_prefix_valid(&[43 | H, 4218 | H, H, H, H]));
+    }
+
+    #[test]
+    fn rejects_unhardened_prefixes_and_accounts() {
+        assert!(!is_bip_prefix_valid(&[44, 4218 | H, H, H, H]));
+        assert!(!is_bip_prefix_valid(&[44 | H, 4218, H, H, H]));
+        assert!(!is_bip_prefix_valid(&[44 | H, 1, H, H, H]));
+        assert!(!is_bip_prefix_valid(&[44 | H, 4218 | H, 0, H, H]));
+        // Change and index are not the business of this check
+        assert!(is_bip_prefix_valid(&[44 | H, 4218 | H, H, 0, 0]));
+    }
+
+    #[test]
+    fn flags_high_accounts_and_indices() {
+        assert!(!has_high_index(&[44 | H, 4218 | H, 100 | H, H, 100 | H]));
+        assert!(has_high_index(&[44 | H, 4218 | H, 101 | H, H, H]));
+        assert!(has_high_index(&[44 | H, 4218 | H, H, H, 101 | H]));
+        assert!(has_high_index(&[44 | H, 4218 | H, H, H, 101]));
+        // The change is always 0'
+        assert!(!has_high_index(&[44 | H, 4218 | H, H, 101 | H, H]));
+        assert!(!has_high_index(&[44 | H, 4218 | H]));
+    }
+
+    #[test]
+    fn parses_little_endian_components() {
+        assert_eq!(
+            parse(&[2, 0x2c, 0, 0, 0x80, 0x7a, 0x10, 0, 0x80]).unwrap()[..],
+            BIP32_IOTA_PREFIX
+        );
+        let path = [44 | H, 4218 | H, 2 | H, H, 0xffff | H];
+        assert_eq!(parse(&encode(&path)).unwrap()[..], path);
+    }
+
+    #[test]
+    fn parses_empty_paths() {
+        let path = parse(&[0]).unwrap();
+        assert!(path.is_empty());
+        assert!(!is_bip_prefix_valid(&path));
+    }
+
+    #[test]
+    fn rejects_over_long_paths() {
+        let path = [44 | H, 4218 | H, H, H, H, 0, 0, 0, 0, 0];
+        assert_eq!(parse(&encode(&path)).unwrap()[..], path);
+        assert_eq!(parse(&encode(&[path.as_slice(), &[0][..]].concat())), None);
+        assert_eq!(parse(&[255]), None);
+    }
+
+    #[test]
+    fn rejects_truncated_paths() {
+        assert_eq!(parse(&[]), None);
+        let bytes = encode(&[44 | H, 4218 | H, H, H, H]);
+        for len in 1..bytes.len() {
+            assert_eq!(parse(&bytes[..len]), None, "{:?}", &bytes[..len]);
+        }
+    }
+}
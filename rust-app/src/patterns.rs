@@ -0,0 +1,715 @@
+// The Move calls the app recognizes, by package, module and function, and what the review shows
+// them as. Calls to anything else are left to blind signing.
+//
+// A pattern is only of the packages it lists, as a call of the same name in another package may do
+// anything at all. The review of a call of a protocol shows the package it goes to along with what
+// it does, as that is what the user has to trust, and not the function name.
+
+use crate::interface::PatternKind;
+use crate::parser::{IotaAddressRaw, ObjectIdRaw};
+use arrayvec::ArrayVec;
+
+#[cfg(feature = "lending")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LendingAction {
+    // Supplying a coin as collateral
+    Deposit,
+    // Taking back some of the collateral
+    Withdraw,
+    Borrow,
+    Repay,
+}
+
+#[cfg(feature = "lending")]
+impl LendingAction {
+    /// Whether the call takes a coin of the user, rather than being given the amount it returns.
+    pub fn takes_coin(self) -> bool {
+        matches!(self, LendingAction::Deposit | LendingAction::Repay)
+    }
+}
+
+#[cfg(feature = "liquidity")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LiquidityAction {
+    // Supplying both coins of a pool, for a share of it
+    Add,
+    // Giving back a share of the pool, for at least the amounts of both coins it is given
+    Remove,
+}
+
+// The objects a stake is held as
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StakedObject {
+    StakedIota,
+    // Of IOTA which is still timelocked, as vested, which the withdrawal returns timelocked again
+    TimelockedStakedIota,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CallKind {
+    #[cfg(feature = "lending")]
+    Lending(LendingAction),
+    #[cfg(feature = "liquidity")]
+    Liquidity(LiquidityAction),
+    // Splitting a coin into a number of coins of equal value, given along with it, which the
+    // sender keeps but for the coin itself
+    Divide,
+    // Staking a coin of IOTA with a validator, given by its address
+    Stake,
+    // Minting an amount of a coin with its treasury cap, for the recipient given
+    Mint,
+    // Withdrawing a stake, with its rewards, which the call sends to the sender
+    Unstake(StakedObject),
+}
+
+impl CallKind {
+    /// The number of coin types the call is generic over, which are its type arguments.
+    pub fn coin_types(self) -> usize {
+        match self {
+            #[cfg(feature = "lending")]
+            CallKind::Lending(_) => 1,
+            CallKind::Divide | CallKind::Mint => 1,
+            #[cfg(feature = "liquidity")]
+            CallKind::Liquidity(_) => 2,
+            CallKind::Stake | CallKind::Unstake(_) => 0,
+        }
+    }
+
+    /// The number of coins the call takes, one of each coin type in order.
+    pub fn coins(self) -> usize {
+        match self {
+            #[cfg(feature = "lending")]
+            CallKind::Lending(action) if action.takes_coin() => 1,
+            #[cfg(feature = "liquidity")]
+            CallKind::Liquidity(LiquidityAction::Add) => 2,
+            CallKind::Divide | CallKind::Stake => 1,
+            _ => 0,
+        }
+    }
+
+    /// The number of amounts the call is given, one for each coin type in order.
+    pub fn amounts(self) -> usize {
+        match self {
+            #[cfg(feature = "lending")]
+            CallKind::Lending(action) if !action.takes_coin() => 1,
+            #[cfg(feature = "liquidity")]
+            CallKind::Liquidity(LiquidityAction::Remove) => 2,
+            CallKind::Mint => 1,
+            _ => 0,
+        }
+    }
+
+    /// Whether the call is also given a number of coins, after any amounts.
+    pub fn takes_count(self) -> bool {
+        self == CallKind::Divide
+    }
+
+    /// Whether the call is also given an address, last: of the validator staked with, or of the
+    /// recipient of what is minted.
+    pub fn takes_address(self) -> bool {
+        matches!(self, CallKind::Stake | CallKind::Mint)
+    }
+
+    /// The types of the pure arguments of the call, in order: the amounts, then the count, then
+    /// the address, for the kinds which take them. Arguments of the other types are options of the
+    /// call, which do not change what the review shows.
+    pub fn pure_types(self) -> &'static [PureType] {
+        match self {
+            #[cfg(feature = "lending")]
+            CallKind::Lending(action) if !action.takes_coin() => &[PureType::U64],
+            #[cfg(feature = "liquidity")]
+            CallKind::Liquidity(LiquidityAction::Remove) => &[PureType::U64, PureType::U64],
+            CallKind::Divide => &[PureType::U64],
+            CallKind::Stake => &[PureType::Address],
+            CallKind::Mint => &[PureType::U64, PureType::Address],
+            _ => &[],
+        }
+    }
+
+    /// Whether the call takes an object of the sender which the review has to show, the stake
+    /// withdrawn.
+    pub fn takes_owned_object(self) -> bool {
+        matches!(self, CallKind::Unstake(_))
+    }
+
+    /// Whether the coin the call takes is always IOTA, rather than of a type argument.
+    pub fn takes_iota(self) -> bool {
+        self == CallKind::Stake
+    }
+
+    /// What GET_PATTERNS lists the call as.
+    pub fn pattern_kind(self) -> PatternKind {
+        match self {
+            #[cfg(feature = "lending")]
+            CallKind::Lending(action) => match action {
+                LendingAction::Deposit => PatternKind::LendingDeposit,
+                LendingAction::Withdraw => PatternKind::LendingWithdraw,
+                LendingAction::Borrow => PatternKind::LendingBorrow,
+                LendingAction::Repay => PatternKind::LendingRepay,
+            },
+            #[cfg(feature = "liquidity")]
+            CallKind::Liquidity(action) => match action {
+                LiquidityAction::Add => PatternKind::AddLiquidity,
+                LiquidityAction::Remove => PatternKind::RemoveLiquidity,
+            },
+            CallKind::Divide => PatternKind::Divide,
+            CallKind::Stake => PatternKind::Stake,
+            CallKind::Mint => PatternKind::Mint,
+            CallKind::Unstake(StakedObject::StakedIota) => PatternKind::Unstake,
+            CallKind::Unstake(StakedObject::TimelockedStakedIota) => PatternKind::UnstakeTimelocked,
+        }
+    }
+}
+
+// The Move types of the pure arguments of a call, which tell how the bytes of the input are read
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PureType {
+    Bool,
+    U8,
+    U16,
+    U64,
+    U128,
+    Address,
+}
+
+impl PureType {
+    /// The length of a value of the type in BCS.
+    pub fn size(self) -> usize {
+        match self {
+            PureType::Bool | PureType::U8 => 1,
+            PureType::U16 => 2,
+            PureType::U64 => 8,
+            PureType::U128 => 16,
+            PureType::Address => 32,
+        }
+    }
+}
+
+pub struct Pattern {
+    pub packages: &'static [ObjectIdRaw],
+    pub module: &'static str,
+    pub function: &'static str,
+    pub kind: CallKind,
+}
+
+impl Pattern {
+    /// Append the pattern as GET_PATTERNS lists it in `package`: its kind and the package, and the
+    /// module and the function, each after its length.
+    pub fn encode<const N: usize>(
+        &self,
+        package: &ObjectIdRaw,
+        rv: &mut ArrayVec<u8, N>,
+    ) -> Option<()> {
+        rv.try_push(self.kind.pattern_kind() as u8).ok()?;
+        rv.try_extend_from_slice(package).ok()?;
+        for name in [self.module, self.function] {
+            rv.try_push(name.len() as u8).ok()?;
+            rv.try_extend_from_slice(name.as_bytes()).ok()?;
+        }
+        Some(())
+    }
+}
+
+const FRAMEWORK: &[ObjectIdRaw] = &[IOTA_FRAMEWORK_ADDRESS];
+const SYSTEM: &[ObjectIdRaw] = &[IOTA_SYSTEM_ADDRESS];
+
+// The lending markets whose entry points are clear signed, by the ID of their package. A market is
+// only added once the package on mainnet has been checked against its audit, the calls of any
+// other package are blind signed like any call the app does not know. None has been yet, so the
+// lending feature which clear signs them is left out of release builds, and tests have a market
+// of their own.
+#[cfg(feature = "lending")]
+pub const LENDING_MARKETS: &[ObjectIdRaw] = &[
+    #[cfg(test)]
+    [0x1e; 32],
+];
+
+// The AMMs whose pools are clear signed, by the ID of their package, added just like the lending
+// markets. None has been yet either, so they are behind the liquidity feature.
+#[cfg(feature = "liquidity")]
+pub const AMM_POOLS: &[ObjectIdRaw] = &[
+    #[cfg(test)]
+    [0x5b; 32],
+];
+
+// The version of PATTERNS which GET_PATTERNS reports, bumped whenever a pattern is added, removed
+// or changed, or the way they are listed, so that wallets can tell which calls an app clear signs
+// without listing them again
+pub const PATTERNS_VERSION: u8 = 5;
+
+#[cfg(feature = "lending")]
+const fn lending(module: &'static str, function: &'static str, action: LendingAction) -> Pattern {
+    Pattern {
+        packages: LENDING_MARKETS,
+        module,
+        function,
+        kind: CallKind::Lending(action),
+    }
+}
+
+#[cfg(feature = "liquidity")]
+const fn liquidity(
+    module: &'static str,
+    function: &'static str,
+    action: LiquidityAction,
+) -> Pattern {
+    Pattern {
+        packages: AMM_POOLS,
+        module,
+        function,
+        kind: CallKind::Liquidity(action),
+    }
+}
+
+const fn stake(module: &'static str, function: &'static str) -> Pattern {
+    Pattern {
+        packages: SYSTEM,
+        module,
+        function,
+        kind: CallKind::Stake,
+    }
+}
+
+const fn mint(module: &'static str, function: &'static str) -> Pattern {
+    Pattern {
+        packages: FRAMEWORK,
+        module,
+        function,
+        kind: CallKind::Mint,
+    }
+}
+
+const fn unstake(module: &'static str, function: &'static str, object: StakedObject) -> Pattern {
+    Pattern {
+        packages: SYSTEM,
+        module,
+        function,
+        kind: CallKind::Unstake(object),
+    }
+}
+
+const fn divide(module: &'static str, function: &'static str) -> Pattern {
+    Pattern {
+        packages: FRAMEWORK,
+        module,
+        function,
+        kind: CallKind::Divide,
+    }
+}
+
+// The entry points of the lending markets, generic over the coin type, which take the coin or
+// the amount along with the market objects
+#[cfg(feature = "lending")]
+const LENDING_PATTERNS: &[Pattern] = &[
+    lending("lending", "deposit", LendingAction::Deposit),
+    lending("lending", "withdraw", LendingAction::Withdraw),
+    lending("lending", "borrow", LendingAction::Borrow),
+    lending("lending", "repay", LendingAction::Repay),
+    lending("lending_market", "deposit", LendingAction::Deposit),
+    lending("lending_market", "withdraw", LendingAction::Withdraw),
+    lending("lending_market", "borrow", LendingAction::Borrow),
+    lending("lending_market", "repay", LendingAction::Repay),
+    lending("lending_pool", "deposit", LendingAction::Deposit),
+    lending("lending_pool", "withdraw", LendingAction::Withdraw),
+    lending("lending_pool", "borrow", LendingAction::Borrow),
+    lending("lending_pool", "repay", LendingAction::Repay),
+];
+
+// The entry points of the pools of AMMs, generic over both coins of the pool. Adding takes a coin
+// of each, and removing takes the share of the pool along with the least it accepts of each.
+#[cfg(feature = "liquidity")]
+const LIQUIDITY_PATTERNS: &[Pattern] = &[
+    liquidity("amm", "add_liquidity", LiquidityAction::Add),
+    liquidity("amm", "remove_liquidity", LiquidityAction::Remove),
+    liquidity("pool", "add_liquidity", LiquidityAction::Add),
+    liquidity("pool", "remove_liquidity", LiquidityAction::Remove),
+    liquidity("liquidity_pool", "add_liquidity", LiquidityAction::Add),
+    liquidity(
+        "liquidity_pool",
+        "remove_liquidity",
+        LiquidityAction::Remove,
+    ),
+];
+
+const CALL_PATTERNS: &[Pattern] = &[
+    // The helper of the framework which splits a coin into n coins of equal value and keeps them
+    // for the sender, but for the coin itself, the last, with the remainder. coin::divide_into_n
+    // returns them in a vector instead, which a transaction can neither send nor drop.
+    divide("pay", "divide_and_keep"),
+    // Staking with the system, which takes the system state, the coin and the validator
+    stake("iota_system", "request_add_stake"),
+    // Withdrawing a stake, which takes the system state and the object of the stake, and a stake
+    // of timelocked IOTA, which is an object of its own type
+    unstake(
+        "iota_system",
+        "request_withdraw_stake",
+        StakedObject::StakedIota,
+    ),
+    unstake(
+        "timelocked_staking",
+        "request_withdraw_stake",
+        StakedObject::TimelockedStakedIota,
+    ),
+    // Minting with the treasury cap of a coin, which takes the cap, the amount and the recipient
+    mint("coin", "mint_and_transfer"),
+];
+
+// Every pattern, in groups which builds may leave out, in the order GET_PATTERNS lists them
+const PATTERNS: &[&[Pattern]] = &[
+    #[cfg(feature = "lending")]
+    LENDING_PATTERNS,
+    #[cfg(feature = "liquidity")]
+    LIQUIDITY_PATTERNS,
+    CALL_PATTERNS,
+];
+
+/// Every pattern of the build.
+pub fn patterns() -> impl Iterator<Item = &'static Pattern> {
+    PATTERNS.iter().flat_map(|group| group.iter())
+}
+
+/// The pattern of a call of `function` of `module` in `package`, if there is one.
+pub fn find(package: &ObjectIdRaw, module: &str, function: &str) -> Option<&'static Pattern> {
+    patterns().find(|pattern| {
+        pattern.packages.contains(package)
+            && pattern.module == module
+            && pattern.function == function
+    })
+}
+
+/// The entries GET_PATTERNS lists, each pattern along with each of its packages.
+pub fn entries() -> impl Iterator<Item = (&'static Pattern, &'static ObjectIdRaw)> {
+    patterns().flat_map(|pattern| {
+        pattern
+            .packages
+            .iter()
+            .map(move |package| (pattern, package))
+    })
+}
+
+// An ID like 0x2, with the bytes given last and zeros before
+const fn short_id(last: &[u8]) -> ObjectIdRaw {
+    let mut rv = [0; 32];
+    let mut i = 0;
+    while i < last.len() {
+        rv[32 - last.len() + i] = last[i];
+        i += 1;
+    }
+    rv
+}
+
+// The packages published at genesis, which the review names instead of showing their ID. Only
+// the exact ID has the name, so it tells the user as much as the ID would.
+pub const KNOWN_PACKAGES: &[(ObjectIdRaw, &str)] = &[
+    (short_id(&[0x01]), "Move Stdlib"),
+    (short_id(&[0x02]), "IOTA Framework"),
+    (short_id(&[0x03]), "IOTA System"),
+];
+
+/// The name of `package`, if it is a well-known one.
+pub fn package_name(package: &ObjectIdRaw) -> Option<&'static str> {
+    KNOWN_PACKAGES
+        .iter()
+        .find(|(id, _)| id == package)
+        .map(|(_, name)| *name)
+}
+
+// Addresses which nobody has the key of, by convention, so that whatever is sent to them is lost:
+// 0x0 and 0xdead
+pub const BURN_ADDRESSES: &[IotaAddressRaw] = &[short_id(&[]), short_id(&[0xde, 0xad])];
+
+/// Whether `address` is one of the burn addresses.
+pub fn is_burn_address(address: &IotaAddressRaw) -> bool {
+    BURN_ADDRESSES.contains(address)
+}
+
+// The coin type of IOTA, 0x2::iota::IOTA
+pub const IOTA_FRAMEWORK_ADDRESS: ObjectIdRaw = short_id(&[0x02]);
+pub const IOTA_COIN_MODULE: &str = "iota";
+pub const IOTA_COIN_NAME: &str = "IOTA";
+
+// The package of the system, which stakes
+pub const IOTA_SYSTEM_ADDRESS: ObjectIdRaw = short_id(&[0x03]);
+
+#[cfg(all(not(target_family = "bolos"), test))]
+mod tests {
+    use super::*;
+    use crate::parser::MAX_PURE_ARGUMENTS;
+    use std::vec::Vec;
+
+    #[cfg(feature = "lending")]
+    #[test]
+    fn finds_lending_calls_only_in_the_markets() {
+        for package in LENDING_MARKETS {
+            assert_eq!(
+                find(package, "lending", "deposit").map(|p| p.kind),
+                Some(CallKind::Lending(LendingAction::Deposit))
+            );
+            assert_eq!(
+                find(package, "lending_market", "borrow").map(|p| p.kind),
+                Some(CallKind::Lending(LendingAction::Borrow))
+            );
+        }
+        // Those of an unknown package are left to blind signing
+        for package in [[0xab; 32], IOTA_FRAMEWORK_ADDRESS, [0; 32]] {
+            assert!(find(&package, "lending", "deposit").is_none());
+            assert!(find(&package, "lending_pool", "repay").is_none());
+        }
+    }
+
+    #[cfg(feature = "lending")]
+    #[test]
+    fn does_not_find_other_calls() {
+        let package = [0x1e; 32];
+        assert!(find(&package, "lending", "liquidate").is_none());
+        assert!(find(&package, "vault", "deposit").is_none());
+        assert!(find(&package, "lending", "Deposit").is_none());
+        assert!(find(&package, "lendin", "deposit").is_none());
+        assert!(find(&package, "", "").is_none());
+    }
+
+    #[cfg(feature = "lending")]
+    #[test]
+    fn only_deposits_and_repayments_take_a_coin() {
+        assert!(LendingAction::Deposit.takes_coin());
+        assert!(LendingAction::Repay.takes_coin());
+        assert!(!LendingAction::Withdraw.takes_coin());
+        assert!(!LendingAction::Borrow.takes_coin());
+    }
+
+    #[cfg(feature = "liquidity")]
+    #[test]
+    fn finds_liquidity_calls_only_in_the_pools() {
+        for package in AMM_POOLS {
+            assert_eq!(
+                find(package, "amm", "add_liquidity").map(|p| p.kind),
+                Some(CallKind::Liquidity(LiquidityAction::Add))
+            );
+            assert_eq!(
+                find(package, "pool", "remove_liquidity").map(|p| p.kind),
+                Some(CallKind::Liquidity(LiquidityAction::Remove))
+            );
+            assert!(find(package, "amm", "swap").is_none());
+        }
+        // Those of an unknown package are left to blind signing
+        for package in [[0xab; 32], IOTA_FRAMEWORK_ADDRESS, [0; 32]] {
+            assert!(find(&package, "amm", "add_liquidity").is_none());
+            assert!(find(&package, "liquidity_pool", "remove_liquidity").is_none());
+        }
+    }
+
+    #[test]
+    fn finds_divisions_only_in_the_framework() {
+        assert_eq!(
+            find(&IOTA_FRAMEWORK_ADDRESS, "pay", "divide_and_keep").map(|p| p.kind),
+            Some(CallKind::Divide)
+        );
+        assert!(find(&[0x1e; 32], "pay", "divide_and_keep").is_none());
+        // Which returns coins the transaction could do nothing with
+        assert!(find(&IOTA_FRAMEWORK_ADDRESS, "coin", "divide_into_n").is_none());
+        assert!(CallKind::Divide.takes_count());
+        assert!(!CallKind::Stake.takes_count());
+    }
+
+    #[test]
+    fn finds_staking_only_in_the_system() {
+        assert_eq!(
+            find(&IOTA_SYSTEM_ADDRESS, "iota_system", "request_add_stake").map(|p| p.kind),
+            Some(CallKind::Stake)
+        );
+        assert!(find(&IOTA_FRAMEWORK_ADDRESS, "iota_system", "request_add_stake").is_none());
+        assert!(find(&[0x1e; 32], "iota_system", "request_add_stake").is_none());
+        assert!(CallKind::Stake.takes_address());
+    }
+
+    #[test]
+    fn finds_unstaking_of_both_kinds_of_stake() {
+        assert_eq!(
+            find(
+                &IOTA_SYSTEM_ADDRESS,
+                "iota_system",
+                "request_withdraw_stake"
+            )
+            .map(|p| p.kind),
+            Some(CallKind::Unstake(StakedObject::StakedIota))
+        );
+        assert_eq!(
+            find(
+                &IOTA_SYSTEM_ADDRESS,
+                "timelocked_staking",
+                "request_withdraw_stake"
+            )
+            .map(|p| p.kind),
+            Some(CallKind::Unstake(StakedObject::TimelockedStakedIota))
+        );
+        assert!(find(&[0x1e; 32], "timelocked_staking", "request_withdraw_stake").is_none());
+        assert!(CallKind::Unstake(StakedObject::StakedIota).takes_owned_object());
+        assert!(!CallKind::Stake.takes_owned_object());
+    }
+
+    #[test]
+    fn finds_minting_only_in_the_framework() {
+        assert_eq!(
+            find(&IOTA_FRAMEWORK_ADDRESS, "coin", "mint_and_transfer").map(|p| p.kind),
+            Some(CallKind::Mint)
+        );
+        assert!(find(&[0x1e; 32], "coin", "mint_and_transfer").is_none());
+        assert!(find(&IOTA_FRAMEWORK_ADDRESS, "coin", "mint").is_none());
+        assert!(CallKind::Mint.takes_address());
+        assert!(!CallKind::Divide.takes_address());
+    }
+
+    #[test]
+    fn each_coin_type_has_a_coin_or_an_amount() {
+        for pattern in patterns() {
+            let kind = pattern.kind;
+            // A coin of IOTA has no type argument
+            let iota_coins = if kind.takes_iota() { 1 } else { 0 };
+            assert!(
+                kind.coins() + kind.amounts() == kind.coin_types() + iota_coins,
+                "{}::{}",
+                pattern.module,
+                pattern.function
+            );
+            assert!(kind.coins() == 0 || kind.amounts() == 0);
+        }
+    }
+
+    #[test]
+    fn pure_types_are_of_the_amounts_the_count_and_the_address() {
+        for pattern in patterns() {
+            let kind = pattern.kind;
+            let types = kind.pure_types();
+            let count = if kind.takes_count() { 1 } else { 0 };
+            let address = if kind.takes_address() { 1 } else { 0 };
+            let of = |ty| types.iter().filter(|t| **t == ty).count();
+            assert_eq!(of(PureType::U64), kind.amounts() + count);
+            assert_eq!(of(PureType::Address), address);
+            assert!(types.len() <= MAX_PURE_ARGUMENTS);
+        }
+        assert_eq!(PureType::Bool.size(), 1);
+        assert_eq!(PureType::U128.size(), 16);
+    }
+
+    #[test]
+    fn names_only_the_exact_packages() {
+        let mut system = [0; 32];
+        system[31] = 3;
+        assert_eq!(package_name(&system), Some("IOTA System"));
+        assert_eq!(
+            package_name(&IOTA_FRAMEWORK_ADDRESS),
+            Some("IOTA Framework")
+        );
+        // 0xdee9 is DeepBook on Sui, and not a package IOTA is known to publish, so it has no name
+        let mut deepbook = [0; 32];
+        deepbook[30] = 0xde;
+        deepbook[31] = 0xe9;
+        assert_eq!(package_name(&deepbook), None);
+        // Ending like a known package is not enough
+        system[0] = 1;
+        assert_eq!(package_name(&system), None);
+        assert_eq!(package_name(&[0; 32]), None);
+        assert_eq!(package_name(&[0x1e; 32]), None);
+    }
+
+    #[test]
+    fn burn_addresses() {
+        let mut address = [0; 32];
+        assert!(is_burn_address(&address));
+        address[30] = 0xde;
+        address[31] = 0xad;
+        assert!(is_burn_address(&address));
+        address[0] = 1;
+        assert!(!is_burn_address(&address));
+        assert!(!is_burn_address(&IOTA_FRAMEWORK_ADDRESS));
+        assert!(!is_burn_address(&[0x4f; 32]));
+    }
+
+    #[test]
+    fn patterns_are_listed_by_kind_and_package() {
+        let mut rv: ArrayVec<u8, 220> = ArrayVec::new();
+        let stake = find(&IOTA_SYSTEM_ADDRESS, "iota_system", "request_add_stake");
+        assert!(stake
+            .unwrap()
+            .encode(&IOTA_SYSTEM_ADDRESS, &mut rv)
+            .is_some());
+        assert_eq!(rv[0], PatternKind::Stake as u8);
+        assert_eq!(rv[1..33], IOTA_SYSTEM_ADDRESS);
+        assert_eq!(rv[33..], *b"\x0biota_system\x11request_add_stake");
+
+        let mut rv: ArrayVec<u8, 220> = ArrayVec::new();
+        let mint = find(&IOTA_FRAMEWORK_ADDRESS, "coin", "mint_and_transfer");
+        assert!(mint
+            .unwrap()
+            .encode(&IOTA_FRAMEWORK_ADDRESS, &mut rv)
+            .is_some());
+        assert_eq!(rv[0], PatternKind::Mint as u8);
+        assert_eq!(rv[1..33], IOTA_FRAMEWORK_ADDRESS);
+        assert_eq!(rv[33..], *b"\x04coin\x11mint_and_transfer");
+    }
+
+    #[test]
+    fn lists_patterns_once_for_each_package() {
+        let listed = |module, function| {
+            entries()
+                .filter(|(p, _)| p.module == module && p.function == function)
+                .map(|(_, package)| *package)
+                .collect::<Vec<_>>()
+        };
+        #[cfg(feature = "lending")]
+        assert_eq!(listed("lending", "withdraw"), LENDING_MARKETS);
+        #[cfg(feature = "liquidity")]
+        assert_eq!(listed("pool", "add_liquidity"), AMM_POOLS);
+        assert_eq!(listed("pay", "divide_and_keep"), [IOTA_FRAMEWORK_ADDRESS]);
+        assert!(listed("pool", "swap").is_empty());
+    }
+
+    #[test]
+    fn pattern_kinds_tell_the_calls_apart() {
+        let kinds = [
+            #[cfg(feature = "lending")]
+            CallKind::Lending(LendingAction::Deposit),
+            #[cfg(feature = "lending")]
+            CallKind::Lending(LendingAction::Withdraw),
+            #[cfg(feature = "lending")]
+            CallKind::Lending(LendingAction::Borrow),
+            #[cfg(feature = "lending")]
+            CallKind::Lending(LendingAction::Repay),
+            #[cfg(feature = "liquidity")]
+            CallKind::Liquidity(LiquidityAction::Add),
+            #[cfg(feature = "liquidity")]
+            CallKind::Liquidity(LiquidityAction::Remove),
+            CallKind::Divide,
+            CallKind::Stake,
+            CallKind::Mint,
+            CallKind::Unstake(StakedObject::StakedIota),
+            CallKind::Unstake(StakedObject::TimelockedStakedIota),
+        ];
+        for (i, a) in kinds.iter().enumerate() {
+            for b in &kinds[i + 1..] {
+                assert_ne!(a.pattern_kind(), b.pattern_kind());
+            }
+        }
+        // Every entry fits along with the version and the count in a response
+        for (pattern, package) in entries() {
+            let mut rv: ArrayVec<u8, 218> = ArrayVec::new();
+            assert!(pattern.encode(package, &mut rv).is_some());
+        }
+        assert!(entries().count() <= u8::MAX as usize);
+    }
+
+    #[test]
+    fn patterns_are_unique() {
+        let patterns: Vec<_> = patterns().collect();
+        for (i, a) in patterns.iter().enumerate() {
+            for b in &patterns[i + 1..] {
+                assert!(
+                    a.packages != b.packages || a.module != b.module || a.function != b.function,
+                    "{}::{}",
+                    a.module,
+                    a.function
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use arrayvec::ArrayString;
+use core::fmt::Write;
+
+// What the parser is busy with, so the busy menu can show that a large transaction is still
+// being worked through. The menu labels cannot borrow from the menu, hence the static.
+static mut PROGRESS: ArrayString<24> = ArrayString::new_const();
+
+// What the host asked for, shown instead of a generic label
+static mut INSTRUCTION: Option<&'static str> = None;
+
+pub fn set(stage: &str, current: u32, total: u32) {
+    let progress = unsafe { &mut PROGRESS };
+    progress.clear();
+    // Too long to show is not worth failing over, the label is only informative
+    let _ = write!(progress, "{stage} {current}/{total}");
+}
+
+pub fn clear() {
+    unsafe { PROGRESS.clear() };
+}
+
+pub fn label() -> &'static str {
+    unsafe { PROGRESS.as_str() }
+}
+
+pub fn set_instruction(name: &'static str) {
+    unsafe { INSTRUCTION = Some(name) };
+}
+
+pub fn clear_instruction() {
+    unsafe { INSTRUCTION = None };
+}
+
+pub fn instruction() -> &'static str {
+    unsafe { INSTRUCTION }.unwrap_or("Working...")
+}
@@ -0,0 +1,185 @@
+use crate::interface::OperationKind;
+use crate::parser::{IotaAddressRaw, Operation, TxSummary};
+#[cfg(feature = "lending")]
+use crate::patterns::LendingAction;
+#[cfg(feature = "liquidity")]
+use crate::patterns::LiquidityAction;
+use crate::patterns::StakedObject;
+use arrayvec::ArrayVec;
+
+// What the last transaction signed did, as the app parsed it, for GET_RECEIPT. Hosts and auditors
+// can keep it as a record of what the user approved, rather than of what the host meant to send.
+
+struct Receipt {
+    hash: [u8; 32],
+    kind: OperationKind,
+    recipient: Option<IotaAddressRaw>,
+    // None when blind signed, as nothing was parsed
+    gas_budget: Option<u64>,
+    amounts: ArrayVec<u64, 2>,
+}
+
+struct State {
+    receipt: Option<Receipt>,
+    // Whether the transaction of the receipt was signed, rather than rejected or given up on
+    signed: bool,
+}
+
+// RAM only, like the labels of the session
+static mut STATE: State = State {
+    receipt: None,
+    signed: false,
+};
+
+fn kind(operation: &Operation) -> OperationKind {
+    match operation {
+        Operation::Transfer(_) => OperationKind::Transfer,
+        Operation::ObjectTransfer(_) => OperationKind::ObjectTransfer,
+        #[cfg(feature = "lending")]
+        Operation::Lending(lending) => match lending.action {
+            LendingAction::Deposit => OperationKind::LendingDeposit,
+            LendingAction::Withdraw => OperationKind::LendingWithdraw,
+            LendingAction::Borrow => OperationKind::LendingBorrow,
+            LendingAction::Repay => OperationKind::LendingRepay,
+        },
+        #[cfg(feature = "liquidity")]
+        Operation::Liquidity(liquidity) => match liquidity.action {
+            LiquidityAction::Add => OperationKind::AddLiquidity,
+            LiquidityAction::Remove => OperationKind::RemoveLiquidity,
+        },
+        Operation::Divide(_) => OperationKind::Divide,
+        Operation::Stake(_) => OperationKind::Stake,
+        Operation::Unstake(unstake) => match unstake.object {
+            StakedObject::StakedIota => OperationKind::Unstake,
+            StakedObject::TimelockedStakedIota => OperationKind::UnstakeTimelocked,
+        },
+        Operation::Mint(_) => OperationKind::Mint,
+    }
+}
+
+// The amounts the review shows, in the smallest unit of their coins
+fn amounts(operation: &Operation) -> ArrayVec<u64, 2> {
+    let mut rv = ArrayVec::new();
+    match operation {
+        Operation::Transfer(transfer) => rv.push(transfer.total_amount),
+        Operation::ObjectTransfer(transfer) => rv.extend(transfer.split_amount),
+        #[cfg(feature = "lending")]
+        Operation::Lending(lending) => rv.push(lending.amount),
+        #[cfg(feature = "liquidity")]
+        Operation::Liquidity(liquidity) => rv.extend(liquidity.amounts),
+        Operation::Divide(divide) => rv.push(divide.amount),
+        Operation::Stake(stake) => rv.push(stake.amount),
+        Operation::Unstake(_) => {}
+        Operation::Mint(mint) => rv.push(mint.amount),
+    }
+    rv
+}
+
+/// Drop the receipt, as a transaction is about to be signed, so that there is none unless it is.
+pub fn clear() {
+    unsafe { STATE.signed = false };
+}
+
+/// Keep what the transaction of `hash` does, from `summary` or as blind signed without one, for
+/// once it is signed.
+#[inline(never)]
+pub fn prepare(hash: &[u8; 32], summary: Option<&TxSummary>) {
+    let state = unsafe { &mut STATE };
+    state.receipt = Some(match summary {
+        Some(summary) => Receipt {
+            hash: *hash,
+            kind: kind(&summary.operation),
+            recipient: summary.operation.recipient().copied(),
+            gas_budget: Some(summary.gas.budget),
+            amounts: amounts(&summary.operation),
+        },
+        None => Receipt {
+            hash: *hash,
+            kind: OperationKind::BlindSigned,
+            recipient: None,
+            gas_budget: None,
+            amounts: ArrayVec::new(),
+        },
+    });
+}
+
+/// The transaction prepared has been signed.
+pub fn signed() {
+    unsafe { STATE.signed = true };
+}
+
+/// Append the receipt as GET_RECEIPT returns it, if the last transaction was signed: its hash, the
+/// kind of operation and the gas budget, then 1 and the recipient or 0, and the amounts after
+/// their count.
+#[inline(never)]
+pub fn encode<const N: usize>(rv: &mut ArrayVec<u8, N>) -> Option<()> {
+    let state = unsafe { &STATE };
+    let receipt = state.receipt.as_ref().filter(|_| state.signed)?;
+    rv.try_extend_from_slice(&receipt.hash).ok()?;
+    rv.try_push(receipt.kind as u8).ok()?;
+    rv.try_extend_from_slice(&receipt.gas_budget.unwrap_or(0).to_le_bytes())
+        .ok()?;
+    match &receipt.recipient {
+        Some(recipient) => {
+            rv.try_push(1).ok()?;
+            rv.try_extend_from_slice(recipient).ok()?;
+        }
+        None => rv.try_push(0).ok()?,
+    }
+    rv.try_push(receipt.amounts.len() as u8).ok()?;
+    for amount in &receipt.amounts {
+        rv.try_extend_from_slice(&amount.to_le_bytes()).ok()?;
+    }
+    Some(())
+}
+
+#[cfg(all(target_family = "bolos", test))]
+mod tests {
+    use super::*;
+    use crate::parser::UnstakeSummary;
+    #[cfg(feature = "liquidity")]
+    use crate::parser::{CoinType, LiquiditySummary};
+    use ledger_device_sdk::assert_eq_err as assert_eq;
+    use ledger_device_sdk::testing::TestType;
+    use testmacro::test_item as test;
+
+    #[cfg(feature = "liquidity")]
+    #[test]
+    fn liquidity_shows_both_amounts() {
+        let operation = Operation::Liquidity(LiquiditySummary {
+            action: LiquidityAction::Remove,
+            package: [0x1e; 32],
+            coin_types: [CoinType::Iota, CoinType::Iota],
+            amounts: [5, 7],
+            recipient: None,
+        });
+        assert_eq!(kind(&operation), OperationKind::RemoveLiquidity);
+        assert_eq!(amounts(&operation).as_slice(), &[5, 7]);
+    }
+
+    #[test]
+    fn unstakes_have_no_amount() {
+        let operation = Operation::Unstake(UnstakeSummary {
+            package: [0x03; 32],
+            object: StakedObject::TimelockedStakedIota,
+            staked: [0x5b; 32],
+        });
+        assert_eq!(kind(&operation), OperationKind::UnstakeTimelocked);
+        assert_eq!(amounts(&operation).is_empty(), true);
+    }
+
+    #[test]
+    fn receipt_once_signed() {
+        let mut rv = ArrayVec::<u8, 100>::new();
+        clear();
+        prepare(&[0xd7; 32], None);
+        assert_eq!(encode(&mut rv).is_none(), true);
+        signed();
+        assert_eq!(encode(&mut rv).is_some(), true);
+        assert_eq!(rv[..32], [0xd7; 32]);
+        // Blind signed, with no gas budget, recipient or amounts
+        assert_eq!(rv[32..], [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        clear();
+        assert_eq!(encode(&mut rv).is_none(), true);
+    }
+}
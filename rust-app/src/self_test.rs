@@ -0,0 +1,172 @@
+use crate::implementation::IotaPubKeyAddress;
+use crate::interface::HARDENED;
+#[cfg(feature = "test_vectors")]
+use crate::test_vectors::{eddsa_sign, with_public_keys};
+use alamgu_async_block::HostIO;
+use ledger_crypto_helpers::common::CryptographyError;
+#[cfg(not(feature = "test_vectors"))]
+use ledger_crypto_helpers::eddsa::{eddsa_sign, with_public_keys};
+use ledger_crypto_helpers::hasher::{Blake2b, Hasher};
+use ledger_device_sdk::ecc::{CurvesId, ECPublicKey};
+use ledger_secure_sdk_sys::{
+    cx_ecfp_generate_pair_no_throw, cx_ecfp_init_private_key_no_throw, cx_ecfp_private_key_t,
+    cx_ecfp_public_key_t, cx_eddsa_sign_no_throw, cx_edwards_compress_point_no_throw,
+    CX_CURVE_Ed25519, CX_SHA512,
+};
+use zeroize::Zeroize;
+
+// Known-answer tests of the cryptography the app signs with, which SELF_TEST runs so that a
+// custodian can check a device before it takes part in a signing ceremony. Each answers one byte,
+// 1 if it passed and 0 if it failed, see docs/apdu.md.
+//
+// Nothing here returns a key or a signature, and the one signature made with the seed of the
+// device is of a message which no transaction can be signed as, see below.
+
+const PASSED: u8 = 1;
+const FAILED: u8 = 0;
+
+// BLAKE2b-256 of "abc", as the IOTA SDKs compute it
+const BLAKE2B_MESSAGE: &[u8] = b"abc";
+const BLAKE2B_DIGEST: [u8; 32] = [
+    0xbd, 0xdd, 0x81, 0x3c, 0x63, 0x42, 0x39, 0x72, 0x31, 0x71, 0xef, 0x3f, 0xee, 0x98, 0x57, 0x9b,
+    0x94, 0x96, 0x4e, 0x3b, 0xb1, 0xcb, 0x3e, 0x42, 0x72, 0x62, 0xc8, 0xc0, 0x68, 0xd5, 0x23, 0x19,
+];
+
+// TEST 2 of RFC 8032, section 7.1
+const ED25519_SECRET_KEY: [u8; 32] = [
+    0x4c, 0xcd, 0x08, 0x9b, 0x28, 0xff, 0x96, 0xda, 0x9d, 0xb6, 0xc3, 0x46, 0xec, 0x11, 0x4e, 0x0f,
+    0x5b, 0x8a, 0x31, 0x9f, 0x35, 0xab, 0xa6, 0x24, 0xda, 0x8c, 0xf6, 0xed, 0x4f, 0xb8, 0xa6, 0xfb,
+];
+const ED25519_PUBLIC_KEY: [u8; 32] = [
+    0x3d, 0x40, 0x17, 0xc3, 0xe8, 0x43, 0x89, 0x5a, 0x92, 0xb7, 0x0a, 0xa7, 0x4d, 0x1b, 0x7e, 0xbc,
+    0x9c, 0x98, 0x2c, 0xcf, 0x2e, 0xc4, 0x96, 0x8c, 0xc0, 0xcd, 0x55, 0xf1, 0x2a, 0xf4, 0x66, 0x0c,
+];
+const ED25519_MESSAGE: &[u8] = &[0x72];
+const ED25519_SIGNATURE: [u8; 64] = [
+    0x92, 0xa0, 0x09, 0xa9, 0xf0, 0xd4, 0xca, 0xb8, 0x72, 0x0e, 0x82, 0x0b, 0x5f, 0x64, 0x25, 0x40,
+    0xa2, 0xb2, 0x7b, 0x54, 0x16, 0x50, 0x3f, 0x8f, 0xb3, 0x76, 0x22, 0x23, 0xeb, 0xdb, 0x69, 0xda,
+    0x08, 0x5a, 0xc1, 0xe4, 0x3e, 0x15, 0x99, 0x6e, 0x45, 0x8f, 0x36, 0x13, 0xd0, 0xf1, 0x1d, 0x8c,
+    0x38, 0x7b, 0x2e, 0xae, 0xb4, 0x30, 0x2a, 0xee, 0xb0, 0x0d, 0x29, 0x16, 0x12, 0xbb, 0x0c, 0x00,
+];
+
+// The highest account of the testnet coin type, which no wallet scans for funds
+const DEVICE_KEY_PATH: [u32; 5] = [44 | HARDENED, 1 | HARDENED, u32::MAX, HARDENED, HARDENED];
+// Transactions are signed as the 32 bytes of their BLAKE2b digest, so a message of another length
+// can never be taken for one
+const DEVICE_KEY_MESSAGE: &[u8] = b"IOTA Ledger app self-test";
+
+fn result(passed: bool) -> u8 {
+    if passed {
+        PASSED
+    } else {
+        FAILED
+    }
+}
+
+#[inline(never)]
+fn blake2b_known_answer() -> bool {
+    let mut hasher: Blake2b = Hasher::new();
+    hasher.update(BLAKE2B_MESSAGE);
+    let digest: [u8; 32] = hasher.finalize();
+    digest == BLAKE2B_DIGEST
+}
+
+// Signs the message of RFC 8032 with its key, bypassing the derivation from the seed, and checks
+// the public key and the signature against the RFC, and that verifying accepts only the signature
+#[inline(never)]
+fn ed25519_known_answer() -> bool {
+    let mut private_key: cx_ecfp_private_key_t = unsafe { core::mem::zeroed() };
+    let mut public_key: cx_ecfp_public_key_t = unsafe { core::mem::zeroed() };
+    let mut sig = [0; 64];
+    let signed = unsafe {
+        cx_ecfp_init_private_key_no_throw(
+            CX_CURVE_Ed25519,
+            ED25519_SECRET_KEY.as_ptr(),
+            ED25519_SECRET_KEY.len(),
+            &mut private_key,
+        ) == 0
+            && cx_ecfp_generate_pair_no_throw(
+                CX_CURVE_Ed25519,
+                &mut public_key,
+                &mut private_key,
+                true,
+            ) == 0
+            && cx_edwards_compress_point_no_throw(
+                CX_CURVE_Ed25519,
+                public_key.W.as_mut_ptr(),
+                public_key.W_len,
+            ) == 0
+            && cx_eddsa_sign_no_throw(
+                &private_key,
+                CX_SHA512,
+                ED25519_MESSAGE.as_ptr(),
+                ED25519_MESSAGE.len(),
+                sig.as_mut_ptr(),
+                sig.len(),
+            ) == 0
+    };
+    private_key.d.zeroize();
+    if !signed || public_key.W[1..33] != ED25519_PUBLIC_KEY || sig != ED25519_SIGNATURE {
+        return false;
+    }
+    let mut key = ECPublicKey::<65, 'E'>::new(CurvesId::Ed25519);
+    key.pubkey.copy_from_slice(&public_key.W);
+    key.keylength = 33;
+    let mut tampered = sig;
+    tampered[0] ^= 1;
+    key.verify((&sig[..], sig.len() as u32), ED25519_MESSAGE)
+        && !key.verify((&tampered[..], tampered.len() as u32), ED25519_MESSAGE)
+}
+
+// Derives a key from the seed of the device, with the derivation SLIP-0010 of the wallets, and
+// checks that what it signs verifies, as nothing else is known of that key
+#[inline(never)]
+fn device_key_sign_verify() -> bool {
+    let sig = match eddsa_sign(&DEVICE_KEY_PATH, true, DEVICE_KEY_MESSAGE) {
+        Ok(sig) => sig.0,
+        Err(_) => return false,
+    };
+    let mut tampered = sig;
+    tampered[0] ^= 1;
+    with_public_keys(
+        &DEVICE_KEY_PATH,
+        true,
+        |key, _: &IotaPubKeyAddress| -> Result<bool, CryptographyError> {
+            Ok(key.verify((&sig[..], sig.len() as u32), DEVICE_KEY_MESSAGE)
+                && !key.verify((&tampered[..], tampered.len() as u32), DEVICE_KEY_MESSAGE))
+        },
+    )
+    .unwrap_or(false)
+}
+
+pub async fn self_test_apdu(io: HostIO) {
+    let rv = [
+        result(blake2b_known_answer()),
+        result(ed25519_known_answer()),
+        result(device_key_sign_verify()),
+    ];
+    io.result_final(&rv).await;
+}
+
+#[cfg(all(target_family = "bolos", test))]
+mod tests {
+    use super::*;
+    use ledger_device_sdk::assert_eq_err as assert_eq;
+    use ledger_device_sdk::testing::TestType;
+    use testmacro::test_item as test;
+
+    #[test]
+    fn blake2b_answers() {
+        assert_eq!(blake2b_known_answer(), true);
+    }
+
+    #[test]
+    fn ed25519_answers() {
+        assert_eq!(ed25519_known_answer(), true);
+    }
+
+    #[test]
+    fn device_key_verifies() {
+        assert_eq!(device_key_sign_verify(), true);
+    }
+}
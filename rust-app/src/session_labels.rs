@@ -0,0 +1,89 @@
+use crate::interface::{IOTA_ADDRESS_LENGTH, MAX_LABEL_LENGTH};
+use crate::utils::ct_eq;
+use arrayvec::{ArrayString, ArrayVec};
+
+// Names the host has given recipients for this session, with LABEL_RECIPIENT. Nothing checks
+// them, so reviews only ever show them as what the host claims, marked unverified, unlike the
+// labels of the address book, which the user approved.
+
+#[cfg(target_os = "nanos")]
+const MAX_LABELS: usize = 4;
+#[cfg(not(target_os = "nanos"))]
+const MAX_LABELS: usize = 16;
+
+struct Label {
+    address: [u8; IOTA_ADDRESS_LENGTH],
+    label: ArrayString<MAX_LABEL_LENGTH>,
+}
+
+// RAM only, so the labels are gone once the app is closed.
+static mut LABELS: ArrayVec<Label, MAX_LABELS> = ArrayVec::new_const();
+
+/// Label an address for the rest of the session, replacing its label if it had one. Once there are
+/// as many labels as can be kept the oldest is dropped to make room.
+#[inline(never)]
+pub fn set(address: &[u8; IOTA_ADDRESS_LENGTH], label: &str) -> Option<()> {
+    let label = ArrayString::from(label).ok()?;
+    let labels = unsafe { &mut LABELS };
+    labels.retain(|entry| entry.address != *address);
+    if labels.is_full() {
+        labels.remove(0);
+    }
+    labels.push(Label {
+        address: *address,
+        label,
+    });
+    Some(())
+}
+
+/// The label the host gave the address, if any. Every label is compared, in constant time, like
+/// the lookups of the address book.
+#[inline(never)]
+pub fn lookup(address: &[u8; IOTA_ADDRESS_LENGTH]) -> Option<ArrayString<MAX_LABEL_LENGTH>> {
+    let labels = unsafe { &LABELS };
+    let mut found = None;
+    for entry in labels.iter() {
+        if ct_eq(&entry.address, address) {
+            found = Some(entry.label);
+        }
+    }
+    found
+}
+
+#[cfg(all(target_family = "bolos", test))]
+mod tests {
+    use super::*;
+    use ledger_device_sdk::assert_eq_err as assert_eq;
+    use ledger_device_sdk::testing::TestType;
+    use testmacro::test_item as test;
+
+    fn clear() {
+        unsafe { LABELS.clear() };
+    }
+
+    #[test]
+    fn labels_replace_those_of_the_same_address() {
+        clear();
+        assert_eq!(set(&[0x4f; 32], "Exchange").is_some(), true);
+        assert_eq!(set(&[0x4f; 32], "Shop").is_some(), true);
+        assert_eq!(lookup(&[0x4f; 32]).as_deref(), Some("Shop"));
+        assert_eq!(lookup(&[0x4e; 32]).is_none(), true);
+        clear();
+    }
+
+    #[test]
+    fn oldest_label_is_dropped_once_full() {
+        clear();
+        for i in 0..=MAX_LABELS {
+            assert_eq!(set(&[i as u8; 32], "Label").is_some(), true);
+        }
+        assert_eq!(lookup(&[0; 32]).is_none(), true);
+        assert_eq!(lookup(&[MAX_LABELS as u8; 32]).is_some(), true);
+        clear();
+    }
+
+    #[test]
+    fn labels_longer_than_saved_ones_are_refused() {
+        assert_eq!(set(&[0x4f; 32], "Seventeen letters").is_none(), true);
+    }
+}
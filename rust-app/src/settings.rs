@@ -1,9 +1,84 @@
 use ledger_device_sdk::nvm::{AtomicStorage, SingleStorage};
 use ledger_device_sdk::NVMData;
 
+#[cfg(any(target_os = "stax", target_os = "flex"))]
+use ledger_device_sdk::nbgl::SETTINGS_SIZE;
+
+// Bump when the meaning of the stored settings changes, and teach migrate about the old version.
+const SETTINGS_VERSION: u8 = 2;
+
+/// The choices for the busy timeout, in minutes, where 0 means no timeout.
+pub const TIMEOUT_OPTIONS: [u8; 4] = [0, 1, 5, 15];
+const DEFAULT_TIMEOUT_MINUTES: u8 = 5;
+
+#[cfg(not(any(target_os = "stax", target_os = "flex")))]
+#[derive(Clone, Copy)]
+#[repr(C)]
+struct StoredSettings {
+    // Before settings were versioned they were a single byte of flags, which lands here. Only
+    // read by migrate.
+    legacy_flags: u8,
+    version: u8,
+    // Added in version 2
+    timeout_minutes: u8,
+    flags: u32,
+}
+
 // This is necessary to store the object in NVM and not in RAM
+#[cfg(not(any(target_os = "stax", target_os = "flex")))]
+#[link_section = ".nvm_data"]
+static mut SETTINGS: NVMData<AtomicStorage<StoredSettings>> =
+    NVMData::new(AtomicStorage::new(&StoredSettings {
+        legacy_flags: 0,
+        version: 0,
+        timeout_minutes: 0,
+        flags: 0,
+    }));
+
+// On NBGL the settings switches of the home screen write to this storage directly, one byte per
+// switch, so it has to have the layout the SDK expects. Every byte may be the one of a setting, so
+// the version is kept apart.
+#[cfg(any(target_os = "stax", target_os = "flex"))]
 #[link_section = ".nvm_data"]
-static mut SETTINGS: NVMData<AtomicStorage<u8>> = NVMData::new(AtomicStorage::new(&0));
+static mut SETTINGS: NVMData<AtomicStorage<[u8; SETTINGS_SIZE]>> =
+    NVMData::new(AtomicStorage::new(&[0; SETTINGS_SIZE]));
+
+#[cfg(any(target_os = "stax", target_os = "flex"))]
+#[link_section = ".nvm_data"]
+static mut VERSION: NVMData<AtomicStorage<u8>> = NVMData::new(AtomicStorage::new(&0));
+
+// Where the version was kept before it had a storage of its own
+#[cfg(any(target_os = "stax", target_os = "flex"))]
+const LEGACY_VERSION_INDEX: usize = SETTINGS_SIZE - 1;
+
+// Each setting is a byte of the switches, which have no room for more
+#[cfg(any(target_os = "stax", target_os = "flex"))]
+const _: () = assert!(Setting::LimitReview as usize < SETTINGS_SIZE);
+
+#[derive(Clone, Copy)]
+pub enum Setting {
+    BlindSigning = 0,
+    // Show the gas price, expiration, gas objects, each command and the objects of the sender when
+    // reviewing
+    ExpertMode = 1,
+    // Show amounts in nanos instead of IOTA
+    BaseUnits = 2,
+    // Allow blind signing for the next signature only
+    BlindSigningOnce = 3,
+    // Show addresses in the review by their first and last characters only
+    TruncatedAddresses = 4,
+    // Ask the user before giving out a public key, just like when verifying an address
+    ConfirmPubkey = 5,
+    // Show the transaction hash for clear signed transactions too
+    ShowHash = 6,
+    // Show hashes in Base64 instead of hex
+    Base64Hashes = 7,
+    // Separate the decimals of amounts with a comma instead of a period
+    DecimalComma = 8,
+    // Ask before going on with a long review, so it is not clicked through without reading. The
+    // NBGL review has its own navigation, so this is only offered on BAGL.
+    LimitReview = 9,
+}
 
 #[derive(Clone, Copy)]
 pub struct Settings;
@@ -14,17 +89,155 @@ impl Default for Settings {
     }
 }
 
+// The ticker fires every 100ms
+const TICKS_PER_MINUTE: u32 = 600;
+
+impl Settings {
+    /// Whether an operation has waited for the host for longer than the timeout.
+    pub fn busy_timed_out(&self, busy_ticks: u32) -> bool {
+        self.timeout_minutes().map_or(false, |minutes| {
+            busy_ticks >= minutes as u32 * TICKS_PER_MINUTE
+        })
+    }
+
+    pub fn blind_signing_allowed(&self) -> bool {
+        self.is_enabled(Setting::BlindSigning) || self.is_enabled(Setting::BlindSigningOnce)
+    }
+
+    /// Disarm the single use blind signing, once it has been used.
+    pub fn blind_signing_used(&mut self) {
+        if self.is_enabled(Setting::BlindSigningOnce) {
+            self.set_enabled(Setting::BlindSigningOnce, false);
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "stax", target_os = "flex")))]
 impl Settings {
     #[inline(never)]
-    pub fn get(&self) -> u8 {
+    fn get(&self) -> StoredSettings {
         let settings = unsafe { SETTINGS.get_mut() };
         return *settings.get_ref();
     }
 
     // The inline(never) is important. Otherwise weird segmentation faults happen on speculos.
     #[inline(never)]
-    pub fn set(&mut self, v: &u8) {
+    fn set(&mut self, v: &StoredSettings) {
         let settings = unsafe { SETTINGS.get_mut() };
         settings.update(v);
     }
+
+    /// Bring the stored settings up to the current version, to be called once at startup.
+    pub fn migrate(&mut self) {
+        let stored = self.get();
+        let flags = match stored.version {
+            SETTINGS_VERSION => return,
+            // Unversioned, which was a bitfield with blind signing as bit 0 just like now.
+            0 => stored.legacy_flags as u32,
+            // Only lacks the timeout
+            1 => stored.flags,
+            // Written by a newer version of the app, which we cannot make sense of.
+            _ => 0,
+        };
+        self.set(&StoredSettings {
+            legacy_flags: 0,
+            version: SETTINGS_VERSION,
+            timeout_minutes: DEFAULT_TIMEOUT_MINUTES,
+            flags,
+        });
+    }
+
+    /// Restore every setting to its default.
+    pub fn reset(&mut self) {
+        self.set(&StoredSettings {
+            legacy_flags: 0,
+            version: SETTINGS_VERSION,
+            timeout_minutes: DEFAULT_TIMEOUT_MINUTES,
+            flags: 0,
+        });
+    }
+
+    // Each setting is one bit.
+    pub fn is_enabled(&self, setting: Setting) -> bool {
+        self.get().flags & (1 << setting as u32) != 0
+    }
+
+    pub fn set_enabled(&mut self, setting: Setting, enabled: bool) {
+        let bit = 1 << setting as u32;
+        let mut stored = self.get();
+        if enabled {
+            stored.flags |= bit;
+        } else {
+            stored.flags &= !bit;
+        }
+        self.set(&stored);
+    }
+
+    /// How long an operation may wait for the host before it is cancelled, if at all.
+    pub fn timeout_minutes(&self) -> Option<u8> {
+        match self.get().timeout_minutes {
+            0 => None,
+            minutes => Some(minutes),
+        }
+    }
+
+    pub fn set_timeout_minutes(&mut self, minutes: u8) {
+        let mut stored = self.get();
+        stored.timeout_minutes = minutes;
+        self.set(&stored);
+    }
+}
+
+#[cfg(any(target_os = "stax", target_os = "flex"))]
+impl Settings {
+    /// Bring the stored settings up to the current version, to be called once at startup.
+    #[inline(never)]
+    pub fn migrate(&mut self) {
+        let version = unsafe { VERSION.get_mut() };
+        let settings = unsafe { SETTINGS.get_mut() };
+        let mut switches = *settings.get_ref();
+        match *version.get_ref() {
+            SETTINGS_VERSION => return,
+            // The version was the last byte of the switches, laid out just like now otherwise. That
+            // byte is the one of a setting now, which starts off.
+            0 if switches[LEGACY_VERSION_INDEX] <= SETTINGS_VERSION => {
+                switches[LEGACY_VERSION_INDEX] = 0
+            }
+            // Written by a newer version of the app, which we cannot make sense of.
+            _ => switches = [0; SETTINGS_SIZE],
+        }
+        settings.update(&switches);
+        version.update(&SETTINGS_VERSION);
+    }
+
+    /// Restore every setting to its default.
+    #[inline(never)]
+    pub fn reset(&mut self) {
+        let settings = unsafe { SETTINGS.get_mut() };
+        settings.update(&[0; SETTINGS_SIZE]);
+    }
+
+    // Each setting is the byte of its switch on the home screen.
+    #[inline(never)]
+    pub fn is_enabled(&self, setting: Setting) -> bool {
+        let settings = unsafe { SETTINGS.get_mut() };
+        settings.get_ref()[setting as usize] != 0
+    }
+
+    #[inline(never)]
+    pub fn set_enabled(&mut self, setting: Setting, enabled: bool) {
+        let settings = unsafe { SETTINGS.get_mut() };
+        let mut switches = *settings.get_ref();
+        switches[setting as usize] = enabled as u8;
+        settings.update(&switches);
+    }
+
+    pub fn nbgl_storage(&self) -> &'static mut AtomicStorage<[u8; SETTINGS_SIZE]> {
+        unsafe { SETTINGS.get_mut() }
+    }
+
+    // The switches cannot hold anything but on or off, so the timeout is not configurable here.
+    pub fn timeout_minutes(&self) -> Option<u8> {
+        Some(DEFAULT_TIMEOUT_MINUTES)
+    }
 }
@@ -0,0 +1,34 @@
+use arrayvec::ArrayString;
+use core::fmt::Write;
+use ledger_device_sdk::nvm::{AtomicStorage, SingleStorage};
+use ledger_device_sdk::NVMData;
+
+// How many signatures the app has produced, so the user can spot signing they did not expect.
+// Not a setting, so resetting the settings leaves it alone.
+
+#[link_section = ".nvm_data"]
+static mut SIGNATURE_COUNT: NVMData<AtomicStorage<u32>> = NVMData::new(AtomicStorage::new(&0));
+
+// The menu labels cannot borrow from the menu, hence the static.
+static mut LABEL: ArrayString<10> = ArrayString::new_const();
+
+#[inline(never)]
+pub fn get() -> u32 {
+    let count = unsafe { SIGNATURE_COUNT.get_mut() };
+    *count.get_ref()
+}
+
+#[inline(never)]
+pub fn increment() {
+    let count = unsafe { SIGNATURE_COUNT.get_mut() };
+    let next = count.get_ref().saturating_add(1);
+    count.update(&next);
+}
+
+pub fn label() -> &'static str {
+    let label = unsafe { &mut LABEL };
+    label.clear();
+    // u32::MAX has 10 digits, so this always fits
+    let _ = write!(label, "{}", get());
+    label.as_str()
+}
@@ -0,0 +1,128 @@
+use crate::interface::HARDENED;
+use ledger_crypto_helpers::common::{Address, CryptographyError};
+use ledger_device_sdk::ecc::{CurvesId, ECPublicKey};
+use ledger_secure_sdk_sys::{
+    cx_ecfp_generate_pair_no_throw, cx_ecfp_init_private_key_no_throw, cx_ecfp_private_key_t,
+    cx_ecfp_public_key_t, cx_eddsa_sign_no_throw, cx_edwards_compress_point_no_throw,
+    cx_hmac_sha512, CX_CURVE_Ed25519, CX_SHA512,
+};
+use zeroize::{Zeroize, Zeroizing};
+
+// Keys derived from a fixed seed instead of the one of the device, for builds with the
+// test_vectors feature. Everything the app returns is then a known answer, which the host SDK can
+// check against its own derivation, see docs/test-vectors.md.
+//
+// Stands in for the functions of ledger_crypto_helpers::eddsa of the same names.
+
+// The seed of the first SLIP-0010 test vector
+const TEST_SEED: [u8; 16] = [
+    0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f,
+];
+
+pub struct EdDSASig(pub [u8; 64]);
+
+fn hmac_sha512(key: &[u8], data: &[u8]) -> Zeroizing<[u8; 64]> {
+    let mut mac = Zeroizing::new([0; 64]);
+    unsafe {
+        cx_hmac_sha512(
+            key.as_ptr(),
+            key.len(),
+            data.as_ptr(),
+            data.len(),
+            mac.as_mut_ptr(),
+            mac.len(),
+        );
+    }
+    mac
+}
+
+// SLIP-0010 for Ed25519, which only has hardened children
+fn derive_slip10(path: &[u32]) -> Result<Zeroizing<[u8; 32]>, CryptographyError> {
+    let mut node = hmac_sha512(b"ed25519 seed", &TEST_SEED);
+    for component in path {
+        if component & HARDENED == 0 {
+            return Err(CryptographyError::NoneError);
+        }
+        let mut data = Zeroizing::new([0; 37]);
+        data[1..33].copy_from_slice(&node[0..32]);
+        data[33..].copy_from_slice(&component.to_be_bytes());
+        node = hmac_sha512(&node[32..], &data[..]);
+    }
+    let mut key = Zeroizing::new([0; 32]);
+    key.copy_from_slice(&node[0..32]);
+    Ok(key)
+}
+
+fn with_private_key<V>(
+    path: &[u32],
+    slip10: bool,
+    f: impl FnOnce(&mut cx_ecfp_private_key_t) -> Result<V, CryptographyError>,
+) -> Result<V, CryptographyError> {
+    // The BIP32-Ed25519 scheme of the Ledger SDK is left out, there is no test seed for it.
+    if !slip10 {
+        return Err(CryptographyError::NoneError);
+    }
+    let raw_key = derive_slip10(path)?;
+    let mut private_key: cx_ecfp_private_key_t = unsafe { core::mem::zeroed() };
+    let rv = match unsafe {
+        cx_ecfp_init_private_key_no_throw(
+            CX_CURVE_Ed25519,
+            raw_key.as_ptr(),
+            raw_key.len(),
+            &mut private_key,
+        )
+    } {
+        0 => f(&mut private_key),
+        _ => Err(CryptographyError::NoneError),
+    };
+    private_key.d.zeroize();
+    rv
+}
+
+pub fn with_public_keys<V, A: Address<A, ECPublicKey<65, 'E'>>>(
+    path: &[u32],
+    slip10: bool,
+    f: impl FnOnce(&ECPublicKey<65, 'E'>, &A) -> Result<V, CryptographyError>,
+) -> Result<V, CryptographyError> {
+    let mut key = ECPublicKey::<65, 'E'>::new(CurvesId::Ed25519);
+    with_private_key(path, slip10, |private_key| {
+        let mut public_key: cx_ecfp_public_key_t = unsafe { core::mem::zeroed() };
+        // Compressed like the keys of ledger_crypto_helpers, which the rest of the app expects
+        let ok = unsafe {
+            cx_ecfp_generate_pair_no_throw(CX_CURVE_Ed25519, &mut public_key, private_key, true)
+                == 0
+                && cx_edwards_compress_point_no_throw(
+                    CX_CURVE_Ed25519,
+                    public_key.W.as_mut_ptr(),
+                    public_key.W_len,
+                ) == 0
+        };
+        if !ok {
+            return Err(CryptographyError::NoneError);
+        }
+        key.pubkey.copy_from_slice(&public_key.W);
+        key.keylength = 33;
+        Ok(())
+    })?;
+    let address = A::get_address(&key)?;
+    f(&key, &address)
+}
+
+pub fn eddsa_sign(path: &[u32], slip10: bool, msg: &[u8]) -> Result<EdDSASig, CryptographyError> {
+    with_private_key(path, slip10, |private_key| {
+        let mut sig = [0; 64];
+        match unsafe {
+            cx_eddsa_sign_no_throw(
+                private_key,
+                CX_SHA512,
+                msg.as_ptr(),
+                msg.len(),
+                sig.as_mut_ptr(),
+                sig.len(),
+            )
+        } {
+            0 => Ok(EdDSASig(sig)),
+            _ => Err(CryptographyError::NoneError),
+        }
+    })
+}
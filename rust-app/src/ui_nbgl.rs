@@ -0,0 +1,136 @@
+// NBGL replacements for the ledger_prompts_ui prompts used on the Nano devices.
+//
+// The parser prompts field by field, which maps poorly to NBGL, so the fields are collected here
+// instead and the whole list is shown as a single tag/value review by final_accept_prompt.
+
+use crate::display;
+use arrayvec::{ArrayString, ArrayVec};
+use include_gif::include_gif;
+use ledger_device_sdk::nbgl::{Field, NbglChoice, NbglGlyph, NbglReview};
+
+pub const APP_ICON: NbglGlyph = NbglGlyph::from_include(include_gif!("iota_64x64.gif", NBGL));
+const WARNING_ICON: NbglGlyph = NbglGlyph::from_include(include_gif!("warning_64x64.gif", NBGL));
+
+// Everything the review of a transaction collects, so that it is never turned down for lack of room
+const MAX_REVIEW_FIELDS: usize = display::MAX_REVIEW_FIELDS + display::MAX_REVIEW_EXTRAS;
+const MAX_TITLE_LENGTH: usize = 32;
+const MAX_VALUE_LENGTH: usize = 160;
+
+pub struct ScrollerError;
+
+impl From<core::fmt::Error> for ScrollerError {
+    fn from(_: core::fmt::Error) -> Self {
+        ScrollerError
+    }
+}
+
+/// Collects the text of one review field, standing in for ledger_prompts_ui's PromptWrite.
+pub struct PromptWrite<'a, const N: usize> {
+    value: &'a mut ArrayString<MAX_VALUE_LENGTH>,
+}
+
+impl<'a, const N: usize> core::fmt::Write for PromptWrite<'a, N> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        self.value.try_push_str(s).map_err(|_| core::fmt::Error)
+    }
+}
+
+struct ReviewField {
+    title: ArrayString<MAX_TITLE_LENGTH>,
+    value: ArrayString<MAX_VALUE_LENGTH>,
+}
+
+static mut REVIEW: ArrayVec<ReviewField, MAX_REVIEW_FIELDS> = ArrayVec::new_const();
+
+// Set once a warning has been shown for the current review, which then cannot be skipped.
+static mut WARNED: bool = false;
+
+#[inline(never)]
+pub fn scroller<F: for<'b> Fn(&mut PromptWrite<'b, 16>) -> Result<(), ScrollerError>>(
+    title: &str,
+    prompt_function: F,
+) -> Option<()> {
+    let mut field = ReviewField {
+        title: ArrayString::from(title).ok()?,
+        value: ArrayString::new(),
+    };
+    prompt_function(&mut PromptWrite {
+        value: &mut field.value,
+    })
+    .ok()?;
+    unsafe { REVIEW.try_push(field) }.ok()
+}
+
+// NBGL paginates long values by itself
+#[inline(never)]
+pub fn scroller_paginated<F: for<'b> Fn(&mut PromptWrite<'b, 16>) -> Result<(), ScrollerError>>(
+    title: &str,
+    prompt_function: F,
+) -> Option<()> {
+    scroller(title, prompt_function)
+}
+
+/// Show all the fields collected since the last review, and ask to approve them.
+#[inline(never)]
+pub fn final_accept_prompt(prompt: &[&str]) -> Option<()> {
+    let title = prompt.first().copied().unwrap_or("Review");
+    // Any further lines are a recap, shown on the page where the user approves.
+    let mut finish: ArrayString<MAX_VALUE_LENGTH> = ArrayString::new();
+    for line in prompt {
+        if !finish.is_empty() {
+            let _ = finish.try_push('\n');
+        }
+        let _ = finish.try_push_str(line);
+    }
+    let finish = if finish.is_empty() {
+        title
+    } else {
+        finish.as_str()
+    };
+    // Like other NBGL apps, offer to go straight to the recap, if there is one and there was no
+    // warning
+    let skipped = prompt.len() > 1
+        && unsafe { !WARNED && !REVIEW.is_empty() }
+        && !NbglChoice::new().glyph(&APP_ICON).show(
+            title,
+            "The details can be skipped, the last page has a recap",
+            "Review details",
+            "Skip to signing",
+        );
+    if skipped {
+        unsafe { REVIEW.clear() };
+    }
+    let approved = {
+        let fields: ArrayVec<Field, MAX_REVIEW_FIELDS> = unsafe { &REVIEW }
+            .iter()
+            .map(|f| Field {
+                name: f.title.as_str(),
+                value: f.value.as_str(),
+            })
+            .collect();
+        NbglReview::new()
+            .titles(title, "", finish)
+            .glyph(&APP_ICON)
+            .show(&fields)
+    };
+    reset_review();
+    approved.then_some(())
+}
+
+/// Drop fields left over by a flow which was rejected before reaching its final prompt.
+pub fn reset_review() {
+    unsafe {
+        REVIEW.clear();
+        WARNED = false;
+    }
+}
+
+/// Shown right away rather than collected, so that it comes before the review it warns about.
+#[inline(never)]
+pub fn warning_screen(title: &'static str, detail: &'static str) -> Option<()> {
+    unsafe { WARNED = true };
+    NbglChoice::new()
+        .glyph(&WARNING_ICON)
+        .show(title, detail, "Continue", "Reject")
+        .then_some(())
+}
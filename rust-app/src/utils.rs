@@ -1,4 +1,10 @@
-use ledger_prompts_ui::{PromptWrite, ScrollerError};
+#[cfg(not(any(target_os = "stax", target_os = "flex")))]
+pub use ledger_prompts_ui::{final_accept_prompt, PromptWrite, ScrollerError};
+
+#[cfg(any(target_os = "stax", target_os = "flex"))]
+pub use crate::ui_nbgl::{
+    final_accept_prompt, scroller, scroller_paginated, warning_screen, PromptWrite, ScrollerError,
+};
 
 // A couple type ascription functions to help the compiler along.
 pub const fn mkfn<A, B, C>(q: fn(&A, &mut B) -> C) -> fn(&A, &mut B) -> C {
@@ -13,7 +19,7 @@ q
 }
 */
 
-#[cfg(not(target_os = "nanos"))]
+#[cfg(not(any(target_os = "nanos", target_os = "stax", target_os = "flex")))]
 #[inline(never)]
 pub fn scroller<F: for<'b> Fn(&mut PromptWrite<'b, 16>) -> Result<(), ScrollerError>>(
     title: &str,
@@ -31,7 +37,7 @@ pub fn scroller<F: for<'b> Fn(&mut PromptWrite<'b, 16>) -> Result<(), ScrollerEr
     ledger_prompts_ui::write_scroller(false, title, prompt_function)
 }
 
-#[cfg(not(target_os = "nanos"))]
+#[cfg(not(any(target_os = "nanos", target_os = "stax", target_os = "flex")))]
 #[inline(never)]
 pub fn scroller_paginated<F: for<'b> Fn(&mut PromptWrite<'b, 16>) -> Result<(), ScrollerError>>(
     title: &str,
@@ -49,17 +55,112 @@ pub fn scroller_paginated<F: for<'b> Fn(&mut PromptWrite<'b, 16>) -> Result<(),
     ledger_prompts_ui::write_scroller(true, title, prompt_function)
 }
 
-use core::future::Future;
-use core::pin::Pin;
-use core::task::{Context, Poll};
-use pin_project::pin_project;
-#[pin_project]
-pub struct NoinlineFut<F: Future>(#[pin] pub F);
-
-impl<F: Future> Future for NoinlineFut<F> {
-    type Output = F::Output;
-    #[inline(never)]
-    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
-        self.project().0.poll(cx)
+#[cfg(not(any(target_os = "stax", target_os = "flex")))]
+use ledger_prompts_ui::{Menu, MenuLabelBottom, MenuLabelTop, MENU_ICON_X, MENU_ICON_Y};
+
+#[cfg(not(any(target_os = "stax", target_os = "flex")))]
+const WARNING_ICON: ledger_device_sdk::ui::bagls::Icon =
+    ledger_device_sdk::ui::bagls::Icon::from(&ledger_device_sdk::ui::bitmaps::WARNING)
+        .set_x(MENU_ICON_X)
+        .set_y(MENU_ICON_Y);
+
+#[cfg(not(any(target_os = "stax", target_os = "flex")))]
+struct WarningScreen(&'static str);
+
+#[cfg(not(any(target_os = "stax", target_os = "flex")))]
+impl Menu for WarningScreen {
+    type BothResult = ();
+    fn move_left(&mut self) {}
+    fn move_right(&mut self) {}
+    fn handle_both(&mut self) -> Option<Self::BothResult> {
+        None
+    }
+    fn label<'a>(&self) -> (MenuLabelTop<'a>, MenuLabelBottom<'a>) {
+        (
+            MenuLabelTop::Icon(&WARNING_ICON),
+            MenuLabelBottom {
+                text: self.0,
+                bold: true,
+            },
+        )
+    }
+}
+
+/// A screen with the warning icon, which normal reviews never show, so that blind signing cannot
+/// be mistaken for a clear signed review at a glance. Right continues, left rejects, as does
+/// leaving it for longer than the busy timeout.
+///
+/// Only the title fits, so the detail is left to the screen which follows.
+#[cfg(not(any(target_os = "stax", target_os = "flex")))]
+#[inline(never)]
+pub fn warning_screen(title: &'static str, _detail: &'static str) -> Option<()> {
+    use crate::interface::Ins;
+    use crate::settings::Settings;
+    use ledger_device_sdk::buttons::ButtonEvent;
+    use ledger_device_sdk::io::{Comm, Event};
+    ledger_prompts_ui::show_menu(&WarningScreen(title));
+    // The main loop does not run while this is shown, so the ticks are counted here instead
+    let mut ticks: u32 = 0;
+    let mut comm = Comm::new();
+    loop {
+        match comm.next_event::<Ins>() {
+            Event::Button(ButtonEvent::RightButtonRelease) => return Some(()),
+            Event::Button(ButtonEvent::LeftButtonRelease) => return None,
+            Event::Ticker => {
+                ticks += 1;
+                if Settings.busy_timed_out(ticks) {
+                    return None;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Ask whether the user wants to see more detail.
+#[cfg(not(any(target_os = "stax", target_os = "flex")))]
+pub fn want_details(prompt: &[&str]) -> bool {
+    final_accept_prompt(prompt).is_some()
+}
+
+// NBGL reviews have the room to always show the detail.
+#[cfg(any(target_os = "stax", target_os = "flex"))]
+pub fn want_details(_prompt: &[&str]) -> bool {
+    true
+}
+
+/// Whether `a` and `b` are equal, taking the same time wherever they differ, so that how long a
+/// comparison with something kept on the device takes tells nothing about it.
+#[inline(never)]
+pub fn ct_eq<T>(a: &[T], b: &[T]) -> bool
+where
+    T: Copy + Default + PartialEq + core::ops::BitXor<Output = T> + core::ops::BitOr<Output = T>,
+{
+    if a.len() != b.len() {
+        return false;
+    }
+    let diff = a
+        .iter()
+        .zip(b)
+        .fold(T::default(), |diff, (x, y)| diff | (*x ^ *y));
+    core::hint::black_box(diff) == T::default()
+}
+
+pub use crate::futures::{poll_once, NoinlineFut};
+
+// We are single-threaded in fact, albeit with nontrivial code flow. We don't need to worry about
+// full atomicity of the below globals.
+pub struct SingleThreaded<T>(pub T);
+unsafe impl<T> Send for SingleThreaded<T> {}
+unsafe impl<T> Sync for SingleThreaded<T> {}
+impl<T> core::ops::Deref for SingleThreaded<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+impl<T> core::ops::DerefMut for SingleThreaded<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
     }
 }
@@ -0,0 +1,288 @@
+//! Tasks for the repo, run from `rust-app/` with `cargo xtask <task>`:
+//!
+//! - `apdu-docs` writes docs/apdu-spec.md and docs/apdu-spec.json from the APDU contract of
+//!   `ledger_iota_interface::spec`, and `apdu-docs --check` fails if they are not up to date.
+
+use ledger_iota_interface::spec::{
+    self, Field, Framing, Instruction, Layout, CHUNKED_P1, CHUNKED_RESUME_RESPONSE, CLA,
+};
+use ledger_iota_interface::{API_VERSION, MAX_LABEL_LENGTH};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::process::exit;
+
+const GENERATED: &str =
+    "<!-- Generated by `cargo xtask apdu-docs` from rust-app/interface/src/spec.rs, do not edit -->\n\n";
+
+fn docs_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../../docs")
+}
+
+// The length of a field as the tables of docs/apdu.md give it, and what it holds
+fn describe(layout: Layout) -> (String, String) {
+    let (length, description) = match layout {
+        Layout::U8 => ("`1`".to_string(), "u8".to_string()),
+        Layout::U32Le => ("`4`".to_string(), "u32, little endian".to_string()),
+        Layout::Fixed(n) => (format!("`{n}`"), "bytes".to_string()),
+        Layout::LengthPrefixed(n) => (format!("`1 + {n}`"), format!("`{n}`, then {n} bytes")),
+        Layout::Bip32Path => (
+            "`1 + 4n`".to_string(),
+            "the number of derivation steps `n`, at most 10, then each step as a u32, little \
+             endian"
+                .to_string(),
+        ),
+        Layout::SizePrefixed => (
+            "`4 + n`".to_string(),
+            "the size `n` as a u32, little endian, then `n` bytes".to_string(),
+        ),
+        Layout::Label => (
+            "`1 + n`".to_string(),
+            format!(
+                "the length `n`, at most {MAX_LABEL_LENGTH}, then `n` bytes of printable ASCII"
+            ),
+        ),
+        Layout::Rest => (
+            "`<variable>`".to_string(),
+            "the rest of the data".to_string(),
+        ),
+    };
+    (length, description)
+}
+
+fn layout_name(layout: Layout) -> String {
+    match layout {
+        Layout::U8 => "u8".to_string(),
+        Layout::U32Le => "u32le".to_string(),
+        Layout::Fixed(n) => format!("fixed({n})"),
+        Layout::LengthPrefixed(n) => format!("length_prefixed({n})"),
+        Layout::Bip32Path => "bip32_path".to_string(),
+        Layout::SizePrefixed => "size_prefixed".to_string(),
+        Layout::Label => "label".to_string(),
+        Layout::Rest => "rest".to_string(),
+    }
+}
+
+fn framing_name(framing: Framing) -> &'static str {
+    match framing {
+        Framing::Block => "block",
+        Framing::Chunked => "chunked",
+        Framing::None => "none",
+    }
+}
+
+fn hex_list(values: &[u8]) -> String {
+    let values: Vec<String> = values.iter().map(|v| format!("`{v:02X}`")).collect();
+    values.join(", ")
+}
+
+fn fields_table(out: &mut String, fields: &[Field]) {
+    if fields.is_empty() {
+        out.push_str("None.\n\n");
+        return;
+    }
+    out.push_str("| Length | Name | Description |\n");
+    out.push_str("| ------ | ---- | ----------- |\n");
+    for field in fields {
+        let (length, description) = describe(field.layout);
+        writeln!(out, "| {length} | `{}` | {description} |", field.name).unwrap();
+    }
+    out.push('\n');
+}
+
+fn instruction_markdown(out: &mut String, instruction: &Instruction) {
+    writeln!(out, "## {}\n", instruction.name).unwrap();
+    writeln!(out, "{}.\n", instruction.description).unwrap();
+    writeln!(out, "| CLA | INS | P1 | P2 |").unwrap();
+    writeln!(out, "| --- | --- | -- | -- |").unwrap();
+    writeln!(
+        out,
+        "| `{CLA:02X}` | `{:02X}` | {} | {} |\n",
+        instruction.ins as u8,
+        hex_list(instruction.p1),
+        hex_list(instruction.p2)
+    )
+    .unwrap();
+    let framing = match instruction.framing {
+        Framing::Block => "Parameters and response go through the Block Protocol.",
+        Framing::Chunked => {
+            "Sent in APDUs of their own, with P1 of the first one `00`, see SIGN_TX_CHUNKED in \
+             [APDUs](/docs/apdu.md)."
+        }
+        Framing::None => "Has no parameters and no response.",
+    };
+    writeln!(out, "{framing}").unwrap();
+    if instruction.prompts {
+        writeln!(out, "Asks the user to approve it on the device.").unwrap();
+    }
+    out.push('\n');
+    if instruction.framing == Framing::None {
+        return;
+    }
+    if instruction.params.is_empty() {
+        out.push_str("**Input data**\n\nNone.\n\n");
+    }
+    for (i, param) in instruction.params.iter().enumerate() {
+        writeln!(out, "**Parameter {}**\n", i + 1).unwrap();
+        fields_table(out, param);
+    }
+    out.push_str("**Output data**\n\n");
+    fields_table(out, instruction.response);
+    if instruction.framing == Framing::Chunked {
+        writeln!(
+            out,
+            "The following APDUs have P1 {} and P2 `00`. P1 `03` answers with:\n",
+            hex_list(&CHUNKED_P1[1..])
+        )
+        .unwrap();
+        fields_table(out, CHUNKED_RESUME_RESPONSE);
+    }
+}
+
+fn markdown() -> String {
+    let mut out = String::new();
+    out.push_str(GENERATED);
+    out.push_str("# APDU specification\n\n");
+    writeln!(
+        out,
+        "Version {API_VERSION} of the APDU interface, as the app checks it. \
+         [APDUs](/docs/apdu.md) describes what the commands do, and \
+         [apdu-spec.json](/docs/apdu-spec.json) has the same as this page for tools.\n"
+    )
+    .unwrap();
+    for instruction in spec::INSTRUCTIONS {
+        instruction_markdown(&mut out, instruction);
+    }
+    out.push_str("## Status Words\n\n");
+    out.push_str("| SW | Name | Description |\n");
+    out.push_str("| -- | ---- | ----------- |\n");
+    for sw in spec::STATUS_WORDS {
+        writeln!(
+            out,
+            "| `0x{:04X}` | `{}` | {} |",
+            sw.sw, sw.name, sw.description
+        )
+        .unwrap();
+    }
+    out
+}
+
+fn json_string(s: &str) -> String {
+    let mut rv = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => rv.push_str("\\\""),
+            '\\' => rv.push_str("\\\\"),
+            c => rv.push(c),
+        }
+    }
+    rv.push('"');
+    rv
+}
+
+fn json_bytes(values: &[u8]) -> String {
+    let values: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+    format!("[{}]", values.join(", "))
+}
+
+fn json_fields(fields: &[Field]) -> String {
+    let fields: Vec<String> = fields
+        .iter()
+        .map(|f| {
+            format!(
+                "{{ \"name\": {}, \"layout\": {} }}",
+                json_string(f.name),
+                json_string(&layout_name(f.layout))
+            )
+        })
+        .collect();
+    format!("[{}]", fields.join(", "))
+}
+
+fn json() -> String {
+    let mut out = String::new();
+    out.push_str("{\n");
+    writeln!(out, "  \"api_version\": {},", json_string(API_VERSION)).unwrap();
+    writeln!(out, "  \"cla\": {CLA},").unwrap();
+    out.push_str("  \"instructions\": [\n");
+    let instructions: Vec<String> = spec::INSTRUCTIONS
+        .iter()
+        .map(|i| {
+            let params: Vec<String> = i.params.iter().map(|p| json_fields(p)).collect();
+            let mut rv = String::new();
+            rv.push_str("    {\n");
+            writeln!(rv, "      \"name\": {},", json_string(i.name)).unwrap();
+            writeln!(rv, "      \"description\": {},", json_string(i.description)).unwrap();
+            writeln!(rv, "      \"ins\": {},", i.ins as u8).unwrap();
+            writeln!(rv, "      \"p1\": {},", json_bytes(i.p1)).unwrap();
+            writeln!(rv, "      \"p2\": {},", json_bytes(i.p2)).unwrap();
+            writeln!(
+                rv,
+                "      \"framing\": {},",
+                json_string(framing_name(i.framing))
+            )
+            .unwrap();
+            writeln!(rv, "      \"prompts\": {},", i.prompts).unwrap();
+            writeln!(rv, "      \"params\": [{}],", params.join(", ")).unwrap();
+            writeln!(rv, "      \"response\": {}", json_fields(i.response)).unwrap();
+            rv.push_str("    }");
+            rv
+        })
+        .collect();
+    out.push_str(&instructions.join(",\n"));
+    out.push_str("\n  ],\n");
+    writeln!(
+        out,
+        "  \"chunked\": {{ \"p1\": {}, \"resume_response\": {} }},",
+        json_bytes(CHUNKED_P1),
+        json_fields(CHUNKED_RESUME_RESPONSE)
+    )
+    .unwrap();
+    out.push_str("  \"status_words\": [\n");
+    let status_words: Vec<String> = spec::STATUS_WORDS
+        .iter()
+        .map(|sw| {
+            format!(
+                "    {{ \"sw\": {}, \"name\": {}, \"description\": {} }}",
+                sw.sw,
+                json_string(sw.name),
+                json_string(sw.description)
+            )
+        })
+        .collect();
+    out.push_str(&status_words.join(",\n"));
+    out.push_str("\n  ]\n}\n");
+    out
+}
+
+fn apdu_docs(check: bool) {
+    let docs = docs_dir();
+    let mut stale = false;
+    for (name, contents) in [("apdu-spec.md", markdown()), ("apdu-spec.json", json())] {
+        let path = docs.join(name);
+        if check {
+            if std::fs::read_to_string(&path).ok().as_deref() != Some(contents.as_str()) {
+                eprintln!("docs/{name} is not up to date, run `cargo xtask apdu-docs`");
+                stale = true;
+            }
+        } else {
+            std::fs::write(&path, contents).expect("cannot write the docs");
+            eprintln!("wrote docs/{name}");
+        }
+    }
+    if stale {
+        exit(1);
+    }
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(|a| a.as_str()).collect();
+    match args.as_slice() {
+        ["apdu-docs"] => apdu_docs(false),
+        ["apdu-docs", "--check"] => apdu_docs(true),
+        _ => {
+            eprintln!("usage: cargo xtask apdu-docs [--check]");
+            exit(2);
+        }
+    }
+}
@@ -0,0 +1,167 @@
+import { Transaction } from "@iota/iota-sdk/transactions";
+import * as fs from "fs";
+import * as path from "path";
+
+// Builds the transactions of ts-tests/sdk-corpus.json with the IOTA TypeScript SDK, the way
+// wallets and dApps build them, and writes them along with what the app is expected to do with
+// each. Run again after updating the SDK; a diff of the corpus then shows how its serialization
+// changed, and ts-tests/sdk-corpus-tests.ts whether the app still recognizes the transactions.
+//
+// Everything a transaction refers to is given, so that no node is needed to build it.
+
+const OUTPUT = path.join(__dirname, "..", "ts-tests", "sdk-corpus.json");
+
+const SENDER = "0x" + "6f".repeat(32);
+const RECIPIENT = "0x" + "4f".repeat(32);
+const OTHER_RECIPIENT = "0x" + "5e".repeat(32);
+const VALIDATOR = "0x" + "77".repeat(32);
+// A made up DEX, as the app knows none
+const DEX_PACKAGE = "0x" + "de".repeat(32);
+const DEX_POOL = "0x" + "d0".repeat(32);
+
+// The Base58 of 32 zero bytes
+const DIGEST = "11111111111111111111111111111111";
+
+const GAS_COIN = { objectId: "0x" + "12".repeat(32), version: 7, digest: DIGEST };
+const STAKED_IOTA = { objectId: "0x" + "5a".repeat(32), version: 3, digest: DIGEST };
+const SYSTEM_STATE = { objectId: "0x5", initialSharedVersion: 1, mutable: true };
+
+// The bytes of a Move module, which the app never looks into
+const MODULE = Buffer.from("a11ceb0b060000000100000000", "hex").toString("base64");
+
+// clear-sign: signed with the default settings, after a review of what it does
+// blind-sign: rejected with the default settings, and signed once blind signing is enabled
+type Expected = "clear-sign" | "blind-sign";
+
+const CORPUS: { name: string, description: string, expected: Expected, build: (tx: Transaction) => void }[] = [
+    {
+        name: "transfer",
+        description: "Some of the gas coin to one recipient",
+        expected: "clear-sign",
+        build: tx => {
+            const [coin] = tx.splitCoins(tx.gas, [1_000_000]);
+            tx.transferObjects([coin], RECIPIENT);
+        },
+    },
+    {
+        name: "transfer-two-amounts",
+        description: "Two coins split off the gas coin, both to one recipient",
+        expected: "clear-sign",
+        build: tx => {
+            const [a, b] = tx.splitCoins(tx.gas, [1_000_000, 2_000_000]);
+            tx.transferObjects([a, b], RECIPIENT);
+        },
+    },
+    {
+        name: "transfer-all",
+        description: "The whole gas coin to one recipient, counted as an object as it has no amount to show",
+        expected: "clear-sign",
+        build: tx => {
+            tx.transferObjects([tx.gas], RECIPIENT);
+        },
+    },
+    {
+        name: "transfer-two-recipients",
+        description: "Some of the gas coin to each of two recipients",
+        expected: "blind-sign",
+        build: tx => {
+            const [a, b] = tx.splitCoins(tx.gas, [1_000_000, 2_000_000]);
+            tx.transferObjects([a], RECIPIENT);
+            tx.transferObjects([b], OTHER_RECIPIENT);
+        },
+    },
+    {
+        name: "stake",
+        description: "Some of the gas coin staked with a validator",
+        expected: "clear-sign",
+        build: tx => {
+            const [coin] = tx.splitCoins(tx.gas, [1_000_000_000]);
+            tx.moveCall({
+                target: "0x3::iota_system::request_add_stake",
+                arguments: [tx.sharedObjectRef(SYSTEM_STATE), coin, tx.pure.address(VALIDATOR)],
+            });
+        },
+    },
+    {
+        name: "stake-all",
+        description: "The gas coin itself staked, which cannot run, as only TransferObjects takes it by value",
+        expected: "blind-sign",
+        build: tx => {
+            tx.moveCall({
+                target: "0x3::iota_system::request_add_stake",
+                arguments: [tx.sharedObjectRef(SYSTEM_STATE), tx.gas, tx.pure.address(VALIDATOR)],
+            });
+        },
+    },
+    {
+        name: "unstake",
+        description: "A StakedIota object withdrawn",
+        expected: "clear-sign",
+        build: tx => {
+            tx.moveCall({
+                target: "0x3::iota_system::request_withdraw_stake",
+                arguments: [tx.sharedObjectRef(SYSTEM_STATE), tx.objectRef(STAKED_IOTA)],
+            });
+        },
+    },
+    {
+        name: "swap",
+        description: "Some of the gas coin swapped on a DEX, and the result kept",
+        expected: "blind-sign",
+        build: tx => {
+            const [coin] = tx.splitCoins(tx.gas, [1_000_000]);
+            const [out] = tx.moveCall({
+                target: `${DEX_PACKAGE}::pool::swap_a_to_b`,
+                typeArguments: ["0x2::iota::IOTA", `${DEX_PACKAGE}::usdc::USDC`],
+                arguments: [
+                    tx.sharedObjectRef({ objectId: DEX_POOL, initialSharedVersion: 12, mutable: true }),
+                    coin,
+                    tx.pure.u64(990),
+                ],
+            });
+            tx.transferObjects([out], SENDER);
+        },
+    },
+    {
+        name: "publish",
+        description: "A package published, keeping its UpgradeCap",
+        expected: "blind-sign",
+        build: tx => {
+            const cap = tx.publish({ modules: [MODULE], dependencies: ["0x1", "0x2"] });
+            tx.transferObjects([cap], SENDER);
+        },
+    },
+];
+
+const sdkVersion = function (): string {
+    const file = require.resolve("@iota/iota-sdk/package.json");
+    return JSON.parse(fs.readFileSync(file, "utf8")).version;
+}
+
+const main = async function () {
+    const transactions = [];
+    for (const entry of CORPUS) {
+        const tx = new Transaction();
+        tx.setSender(SENDER);
+        tx.setGasPrice(1000);
+        tx.setGasBudget(10_000_000);
+        tx.setGasPayment([GAS_COIN]);
+        entry.build(tx);
+        const bytes = await tx.build();
+        transactions.push({
+            name: entry.name,
+            description: entry.description,
+            expected: entry.expected,
+            // With the intent of a transaction, as SIGN_TX takes it
+            txn: Buffer.concat([Buffer.from([0, 0, 0]), Buffer.from(bytes)]).toString("hex"),
+        });
+    }
+    const corpus = { sdk: "@iota/iota-sdk", version: sdkVersion(), transactions };
+    fs.writeFileSync(OUTPUT, JSON.stringify(corpus, null, 2) + "\n");
+    console.log(`Wrote ${transactions.length} transactions of @iota/iota-sdk ${corpus.version} to ${OUTPUT}`);
+}
+
+main().catch(e => {
+    console.error(e);
+    process.exit(1);
+});
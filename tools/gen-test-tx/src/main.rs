@@ -0,0 +1,386 @@
+//! Writes the BCS of an IOTA transaction built from the command line, as SIGN_TX takes it, so
+//! that a review flow of the app can be reproduced on a device with `generic-cli sign`:
+//!
+//! ```text
+//! gen-test-tx --gas-budget 2000000 transfer 0x4f23...ba4b 1000000,2500000
+//! ```
+//!
+//! Run it without arguments for the commands and options.
+
+use iota_tx_fixtures::types::{Address, Argument, Digest, GasData, ObjectRef};
+use iota_tx_fixtures::{TransactionBuilder, DEFAULT_GAS_BUDGET, DEFAULT_GAS_PRICE};
+use std::io::Write;
+use std::process::exit;
+
+const USAGE: &str = "\
+usage: gen-test-tx [<option> ...] <command> [<command> ...]
+
+Writes the transaction as hex, with the intent of a transaction, as SIGN_TX takes it.
+
+Commands, added to the transaction in the order given:
+  transfer <recipient> <amount>[,<amount> ...]
+      split coins of the amounts off the gas coin, and transfer them to the recipient
+  transfer-gas <recipient>
+      transfer the whole gas coin to the recipient
+  stake <validator> <amount>
+      split a coin of the amount off the gas coin, and stake it with the validator
+  unstake <object>
+      withdraw a StakedIota object
+  move-call <package>::<module>::<function> <argument>[,<argument> ...]
+      call a Move function, with `-` for no arguments; an argument is `gas`, `u64:<n>`,
+      `address:<address>` or `result:<n>`, the result of the command n of the transaction,
+      counting from 0, as transfer and stake add two commands to it
+
+Options:
+  --sender <address>          the sender, and owner of the gas (default 0x6f6f...6f)
+  --gas-price <n>             (default 1000)
+  --gas-budget <n>            (default 10000000)
+  --gas-object <object>       pay for gas with the object, repeated for several
+  --expiration <epoch>        expire the transaction after the epoch
+  --no-intent                 write the TransactionData without the intent
+  --raw                       write the bytes instead of hex
+
+An address is up to 32 bytes of hex, with or without 0x, padded with zeros in front like 0x5.
+An object is <object ID>:<version>, or <object ID>:<version>:<digest>.
+";
+
+fn fail(message: &str) -> ! {
+    eprintln!("gen-test-tx: {message}\n\n{USAGE}");
+    exit(2);
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn parse_address(s: &str) -> Result<Address, String> {
+    let digits = s.strip_prefix("0x").unwrap_or(s);
+    if digits.is_empty() || digits.len() > 64 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("not an address: {s}"));
+    }
+    let digits = format!("{digits:0>64}");
+    let mut rv = [0; 32];
+    for (i, byte) in rv.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&digits[2 * i..2 * i + 2], 16).unwrap();
+    }
+    Ok(rv)
+}
+
+fn parse_u64(s: &str) -> Result<u64, String> {
+    s.replace('_', "")
+        .parse()
+        .map_err(|_| format!("not a number: {s}"))
+}
+
+fn parse_u16(s: &str) -> Result<u16, String> {
+    s.parse().map_err(|_| format!("not a command index: {s}"))
+}
+
+fn parse_object(s: &str) -> Result<ObjectRef, String> {
+    let parts: Vec<&str> = s.split(':').collect();
+    let (id, version, digest) = match parts.as_slice() {
+        [id, version] => (id, version, [0xd1; 32]),
+        [id, version, digest] => (id, version, parse_address(digest)?),
+        _ => return Err(format!("not an object: {s}")),
+    };
+    Ok(ObjectRef {
+        id: parse_address(id)?,
+        version: parse_u64(version)?,
+        digest: Digest(digest),
+    })
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum CallArgument {
+    Gas,
+    U64(u64),
+    Address(Address),
+    Result(u16),
+}
+
+fn parse_arguments(s: &str) -> Result<Vec<CallArgument>, String> {
+    if s == "-" {
+        return Ok(Vec::new());
+    }
+    s.split(',')
+        .map(|arg| match arg.split_once(':') {
+            None if arg == "gas" => Ok(CallArgument::Gas),
+            Some(("u64", n)) => Ok(CallArgument::U64(parse_u64(n)?)),
+            Some(("address", a)) => Ok(CallArgument::Address(parse_address(a)?)),
+            Some(("result", n)) => Ok(CallArgument::Result(parse_u16(n)?)),
+            _ => Err(format!("not an argument: {arg}")),
+        })
+        .collect()
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum TxCommand {
+    Transfer(Address, Vec<u64>),
+    TransferGas(Address),
+    Stake(Address, u64),
+    Unstake(ObjectRef),
+    MoveCall {
+        package: Address,
+        module: String,
+        function: String,
+        arguments: Vec<CallArgument>,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Options {
+    sender: Address,
+    gas_price: u64,
+    gas_budget: u64,
+    gas_objects: Vec<ObjectRef>,
+    expiration: Option<u64>,
+    intent: bool,
+    raw: bool,
+    commands: Vec<TxCommand>,
+}
+
+fn parse_args(args: &[&str]) -> Result<Options, String> {
+    let mut options = Options {
+        sender: [0x6f; 32],
+        gas_price: DEFAULT_GAS_PRICE,
+        gas_budget: DEFAULT_GAS_BUDGET,
+        gas_objects: Vec::new(),
+        expiration: None,
+        intent: true,
+        raw: false,
+        commands: Vec::new(),
+    };
+    let mut args = args.iter().copied();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{arg} takes an argument"));
+        match arg {
+            "--sender" => options.sender = parse_address(value()?)?,
+            "--gas-price" => options.gas_price = parse_u64(value()?)?,
+            "--gas-budget" => options.gas_budget = parse_u64(value()?)?,
+            "--gas-object" => options.gas_objects.push(parse_object(value()?)?),
+            "--expiration" => options.expiration = Some(parse_u64(value()?)?),
+            "--no-intent" => options.intent = false,
+            "--raw" => options.raw = true,
+            "transfer" => {
+                let recipient = parse_address(value()?)?;
+                let amounts = value()?
+                    .split(',')
+                    .map(parse_u64)
+                    .collect::<Result<_, _>>()?;
+                options
+                    .commands
+                    .push(TxCommand::Transfer(recipient, amounts));
+            }
+            "transfer-gas" => options
+                .commands
+                .push(TxCommand::TransferGas(parse_address(value()?)?)),
+            "stake" => {
+                let validator = parse_address(value()?)?;
+                let amount = parse_u64(value()?)?;
+                options.commands.push(TxCommand::Stake(validator, amount));
+            }
+            "unstake" => options
+                .commands
+                .push(TxCommand::Unstake(parse_object(value()?)?)),
+            "move-call" => {
+                let target = value()?;
+                let (package, module, function) = match target.split("::").collect::<Vec<_>>()[..] {
+                    [package, module, function] => (parse_address(package)?, module, function),
+                    _ => return Err(format!("not a Move function: {target}")),
+                };
+                let arguments = parse_arguments(value()?)?;
+                options.commands.push(TxCommand::MoveCall {
+                    package,
+                    module: module.to_string(),
+                    function: function.to_string(),
+                    arguments,
+                });
+            }
+            _ => return Err(format!("unknown command or option: {arg}")),
+        }
+    }
+    if options.commands.is_empty() {
+        return Err("no commands given".to_string());
+    }
+    Ok(options)
+}
+
+fn build(options: &Options) -> Result<TransactionBuilder, String> {
+    let mut tx = TransactionBuilder::new(options.sender);
+    if options.gas_objects.is_empty() {
+        tx.gas_budget(options.gas_price, options.gas_budget);
+    } else {
+        tx.gas_data(GasData {
+            payment: options.gas_objects.clone(),
+            owner: options.sender,
+            price: options.gas_price,
+            budget: options.gas_budget,
+        });
+    }
+    if let Some(epoch) = options.expiration {
+        tx.expiration(epoch);
+    }
+    for (i, command) in options.commands.iter().enumerate() {
+        match command {
+            TxCommand::Transfer(recipient, amounts) => {
+                let coins = tx.split_coins(tx.gas_coin(), amounts);
+                // One amount is the result itself, more are its nested results
+                let coins: Vec<Argument> = match coins {
+                    Argument::Result(r) if amounts.len() > 1 => (0..amounts.len() as u16)
+                        .map(|j| Argument::NestedResult(r, j))
+                        .collect(),
+                    coin => vec![coin],
+                };
+                tx.transfer_objects(&coins, *recipient);
+            }
+            TxCommand::TransferGas(recipient) => {
+                tx.transfer_objects(&[tx.gas_coin()], *recipient);
+            }
+            TxCommand::Stake(validator, amount) => {
+                let coin = tx.split_coins(tx.gas_coin(), &[*amount]);
+                tx.request_add_stake(coin, *validator);
+            }
+            TxCommand::Unstake(staked_iota) => {
+                tx.request_withdraw_stake(*staked_iota);
+            }
+            TxCommand::MoveCall {
+                package,
+                module,
+                function,
+                arguments,
+            } => {
+                let arguments = arguments
+                    .iter()
+                    .map(|arg| match arg {
+                        CallArgument::Gas => Ok(tx.gas_coin()),
+                        CallArgument::U64(n) => Ok(tx.pure(n)),
+                        CallArgument::Address(a) => Ok(tx.pure(a)),
+                        CallArgument::Result(r)
+                            if (*r as usize) < tx.transaction_data().commands.len() =>
+                        {
+                            Ok(Argument::Result(*r))
+                        }
+                        CallArgument::Result(r) => Err(format!(
+                            "move-call {i} uses result:{r}, which is not a command before it"
+                        )),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                tx.move_call(*package, module, function, vec![], &arguments);
+            }
+        }
+    }
+    Ok(tx)
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(|a| a.as_str()).collect();
+    if args.is_empty() || args == ["--help"] {
+        print!("{USAGE}");
+        return;
+    }
+    let options = parse_args(&args).unwrap_or_else(|e| fail(&e));
+    let tx = build(&options).unwrap_or_else(|e| fail(&e));
+    let bytes = if options.intent {
+        tx.intent_message()
+    } else {
+        tx.build()
+    };
+    let mut stdout = std::io::stdout();
+    if options.raw {
+        stdout.write_all(&bytes).unwrap();
+    } else {
+        writeln!(stdout, "{}", hex(&bytes)).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn generate(args: &[&str]) -> Vec<u8> {
+        let options = parse_args(args).unwrap();
+        build(&options).unwrap().intent_message()
+    }
+
+    #[test]
+    fn pads_short_addresses() {
+        assert_eq!(parse_address("0x5").unwrap(), iota_tx_fixtures::short_id(5));
+        assert_eq!(
+            parse_address("4f").unwrap(),
+            iota_tx_fixtures::short_id(0x4f)
+        );
+        assert!(parse_address(&"1".repeat(65)).is_err());
+        assert!(parse_address("0xno").is_err());
+    }
+
+    #[test]
+    fn transfers_as_the_fixtures_do() {
+        assert_eq!(
+            generate(&["transfer", "4f", "1_000_000"]),
+            iota_tx_fixtures::transfer([0x6f; 32], parse_address("4f").unwrap(), 1_000_000)
+                .intent_message()
+        );
+    }
+
+    #[test]
+    fn transfers_every_amount() {
+        let options = parse_args(&["transfer", "4f", "1,2,3"]).unwrap();
+        let tx = build(&options).unwrap();
+        assert_eq!(
+            tx.transaction_data().commands[1],
+            iota_tx_fixtures::types::Command::TransferObjects(
+                (0..3).map(|j| Argument::NestedResult(0, j)).collect(),
+                Argument::Input(3)
+            )
+        );
+    }
+
+    #[test]
+    fn sets_the_gas() {
+        let options = parse_args(&[
+            "--sender",
+            "0x1",
+            "--gas-price",
+            "5",
+            "--gas-budget",
+            "600",
+            "--gas-object",
+            "0x12:7",
+            "--gas-object",
+            "0x13:8:0x2",
+            "stake",
+            "0x77",
+            "1000",
+        ])
+        .unwrap();
+        let tx = build(&options).unwrap();
+        let gas = &tx.transaction_data().gas_data;
+        assert_eq!(gas.owner, iota_tx_fixtures::short_id(1));
+        assert_eq!((gas.price, gas.budget), (5, 600));
+        assert_eq!(gas.payment.len(), 2);
+        assert_eq!(gas.payment[1].digest, Digest(iota_tx_fixtures::short_id(2)));
+    }
+
+    #[test]
+    fn rejects_results_of_later_commands() {
+        let options = parse_args(&["move-call", "0x2::coin::zero", "result:0"]).unwrap();
+        assert!(build(&options).is_err());
+        let options = parse_args(&[
+            "transfer-gas",
+            "0x4f",
+            "move-call",
+            "0x2::coin::zero",
+            "gas,u64:5,result:0",
+        ])
+        .unwrap();
+        assert!(build(&options).is_ok());
+    }
+
+    #[test]
+    fn needs_a_command() {
+        assert!(parse_args(&["--raw"]).is_err());
+        assert!(parse_args(&["transfer", "4f"]).is_err());
+        assert!(parse_args(&["swap"]).is_err());
+    }
+}
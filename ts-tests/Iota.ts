@@ -25,6 +25,42 @@ export type GetPublicKeyResult = {
   address: Uint8Array;
 };
 
+export type Pattern = {
+  kind: number;
+  package: Buffer | null;
+  module: string;
+  function: string;
+};
+
+export type GetPatternsResult = {
+  version: number;
+  patterns: Pattern[];
+};
+
+export type GetLimitsResult = {
+  maxTxSize: number;
+  maxChunkedTxSize: number;
+  maxInputs: number;
+  maxCommands: number;
+  maxSplitAmounts: number;
+};
+
+export type GetReceiptResult = {
+  hash: Buffer;
+  kind: number;
+  gasBudget: bigint | null;
+  recipient: Buffer | null;
+  amounts: bigint[];
+};
+
+export function bip32Payload(path: string): Buffer {
+  const components = path.split("/").map(c => c.endsWith("'") ? (parseInt(c) | 0x80000000) >>> 0 : parseInt(c));
+  const rv = Buffer.alloc(1 + 4 * components.length);
+  rv.writeUInt8(components.length, 0);
+  components.forEach((c, i) => rv.writeUInt32LE(c, 1 + 4 * i));
+  return rv;
+}
+
 /**
  * Iota API
  *
@@ -55,5 +91,101 @@ app.
     }
     return { publicKey, address };
   }
-}
 
+  /**
+    * Saves the address for a BIP32 path to the address book of the ledger app.
+    *
+    * @param path - the path of the address.
+    * @param label - the label to show for transfers to the address.
+    */
+  async saveAddress(
+    path: string,
+    label: string,
+  ): Promise<void> {
+    const labelBytes = Buffer.from(label, "ascii");
+    await this.sendChunks(0x00, 0x05, 0x00, 0x00, [
+      bip32Payload(path),
+      Buffer.concat([Buffer.from([labelBytes.length]), labelBytes]),
+    ]);
+  }
+
+  /**
+    * Labels a recipient for the rest of the session, which the ledger app shows as unverified.
+    *
+    * @param address - the address of the recipient, 32 bytes.
+    * @param label - the label to show for transfers to the address.
+    */
+  async labelRecipient(
+    address: Buffer,
+    label: string,
+  ): Promise<void> {
+    const labelBytes = Buffer.from(label, "ascii");
+    await this.sendChunks(0x00, 0x07, 0x00, 0x00, [
+      address,
+      Buffer.concat([Buffer.from([labelBytes.length]), labelBytes]),
+    ]);
+  }
+
+  /**
+    * Lists the Move calls which the ledger app clear signs, see GET_PATTERNS in docs/apdu.md.
+    */
+  async getPatterns(): Promise<GetPatternsResult> {
+    const rv = await this.sendChunks(0x00, 0x08, 0x00, 0x00, []);
+    const patterns: Pattern[] = [];
+    let i = 2;
+    const name = () => {
+      const start = i + 1;
+      i = start + rv[i];
+      return rv.subarray(start, i).toString("ascii");
+    };
+    // Before version 4, a byte before the package said whether there was one
+    const flagged = rv[0] < 4;
+    for (let n = 0; n < rv[1]; n++) {
+      const kind = rv[i++];
+      const hasPackage = !flagged || rv[i++] == 1;
+      const pkg = hasPackage ? Buffer.from(rv.subarray(i, i + 32)) : null;
+      i += hasPackage ? 32 : 0;
+      const module = name();
+      patterns.push({ kind, package: pkg, module, function: name() });
+    }
+    return { version: rv[0], patterns };
+  }
+
+  /**
+    * Gets the largest transactions which the ledger app signs and reviews, see GET_LIMITS in
+    * docs/apdu.md.
+    */
+  async getLimits(): Promise<GetLimitsResult> {
+    const rv = await this.sendChunks(0x00, 0x09, 0x00, 0x00, []);
+    return {
+      maxTxSize: rv.readUInt32LE(0),
+      maxChunkedTxSize: rv.readUInt32LE(4),
+      maxInputs: rv[8],
+      maxCommands: rv[9],
+      maxSplitAmounts: rv[10],
+    };
+  }
+
+  /**
+    * Gets what the last transaction signed does, as the ledger app parsed it for the review, see
+    * GET_RECEIPT in docs/apdu.md.
+    */
+  async getReceipt(): Promise<GetReceiptResult> {
+    const rv = await this.sendChunks(0x00, 0x0a, 0x00, 0x00, []);
+    const kind = rv[32];
+    const hasRecipient = rv[41] == 1;
+    let i = hasRecipient ? 74 : 42;
+    const amounts: bigint[] = [];
+    for (let n = rv[i++]; n > 0; n--, i += 8) {
+      amounts.push(rv.readBigUInt64LE(i));
+    }
+    return {
+      hash: Buffer.from(rv.subarray(0, 32)),
+      kind,
+      // Nothing was parsed of a transaction blind signed
+      gasBudget: kind == 0 ? null : rv.readBigUInt64LE(33),
+      recipient: hasRecipient ? Buffer.from(rv.subarray(42, 74)) : null,
+      amounts,
+    };
+  }
+}
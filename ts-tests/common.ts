@@ -12,16 +12,142 @@ export const VERSION = {
   patch: 0,
 };
 
+// Transfer of 0.001 IOTA, with a gas budget of 1036
+export const TRANSFER_TXN = Buffer.from("000000000002000840420f000000000000204f2370b2a4810ad6c8e1cfd92cc8c8818fef8f59e3a80cea17871f78d850ba4b0202000101000001010200000101006fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e210112a6d0c44edc630d2724b1f57fea4f93308b1d22164402c65778bd99379c4733070000000000000020f2fd3c87b227f1015182fe4348ed680d7ed32bcd3269704252c03e1d0b13d30d6fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e2101000000000000000c0400000000000000", "hex");
+
+// Not recognized, so it is blind signed
+export const UNKNOWN_TXN = Buffer.from("00000000050205546e7f126d2f40331a543b9608439b582fd0d103000000000000002080fdabcc90498e7eb8413b140c4334871eeafa5a86203fd9cfdb032f604f49e1284af431cf032b5d85324135bf9a3073e920d7f5020000000000000020a06f410c175e828c24cee84cb3bd95cff25c33fbbdcb62c6596e8e423784ffe702d08074075c7097f361e8b443e2075a852a2292e8a08074075c7097f361e8b443e2075a852a2292e80180969800000000001643fb2578ff7191c643079a62c1cca8ec2752bc05546e7f126d2f40331a543b9608439b582fd0d103000000000000002080fdabcc90498e7eb8413b140c4334871eeafa5a86203fd9cfdb032f604f49e101000000000000002c01000000000000", "hex");
+
+// Deposit of 1 IOTA split off the gas coin, with lending::deposit of the package 0x1e1e...1e1e
+export const LENDING_DEPOSIT_TXN = Buffer.from("000000000002000800ca9a3b0000000001013c3c3c3c3c3c3c3c3c3c3c3c3c3c3c3c3c3c3c3c3c3c3c3c3c3c3c3c3c3c3c3c09000000000000000102020001010000001e1e1e1e1e1e1e1e1e1e1e1e1e1e1e1e1e1e1e1e1e1e1e1e1e1e1e1e1e1e1e1e076c656e64696e67076465706f7369740107000000000000000000000000000000000000000000000000000000000000000204696f746104494f544100020101000200006f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f011212121212121212121212121212121212121212121212121212121212121212070000000000000020f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f26f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6fe803000000000000809698000000000000", "hex");
+
+// Withdrawal of 0.5 IOTA with lending::withdraw of the same package, sent to 0x6f6f...6f6f
+export const LENDING_WITHDRAW_TXN = Buffer.from("00000000000301013c3c3c3c3c3c3c3c3c3c3c3c3c3c3c3c3c3c3c3c3c3c3c3c3c3c3c3c3c3c3c3c09000000000000000100080065cd1d0000000000206f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f02001e1e1e1e1e1e1e1e1e1e1e1e1e1e1e1e1e1e1e1e1e1e1e1e1e1e1e1e1e1e1e1e076c656e64696e670877697468647261770107000000000000000000000000000000000000000000000000000000000000000204696f746104494f5441000201000001010001010200000102006f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f011212121212121212121212121212121212121212121212121212121212121212070000000000000020f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f26f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6fe803000000000000809698000000000000", "hex");
+
+// Adding 2 IOTA and 3000000 base units of 0xc0c0...c0c0::usdc::USDC to a pool of the package
+// 0x5b5b...5b5b, with pool::add_liquidity, and sending the share to 0x6f6f...6f6f
+export const ADD_LIQUIDITY_TXN = Buffer.from("000000000005000800943577000000000100cccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccc040000000000000020d2d2d2d2d2d2d2d2d2d2d2d2d2d2d2d2d2d2d2d2d2d2d2d2d2d2d2d2d2d2d2d20008c0c62d000000000001019b9b9b9b9b9b9b9b9b9b9b9b9b9b9b9b9b9b9b9b9b9b9b9b9b9b9b9b9b9b9b9b0b000000000000000100206f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f040200010100000201010001010200005b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b04706f6f6c0d6164645f6c69717569646974790207000000000000000000000000000000000000000000000000000000000000000204696f746104494f54410007c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c004757364630455534443000301030002000002010001010202000104006f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f011212121212121212121212121212121212121212121212121212121212121212070000000000000020f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f26f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6fe803000000000000809698000000000000", "hex");
+
+// Removing that share with pool::remove_liquidity, for at least 1.9 IOTA and 2900000 base units
+export const REMOVE_LIQUIDITY_TXN = Buffer.from("00000000000501019b9b9b9b9b9b9b9b9b9b9b9b9b9b9b9b9b9b9b9b9b9b9b9b9b9b9b9b9b9b9b9b0b000000000000000101005e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e060000000000000020d3d3d3d3d3d3d3d3d3d3d3d3d3d3d3d3d3d3d3d3d3d3d3d3d3d3d3d3d3d3d3d3000800b33f7100000000000820402c000000000000206f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f02005b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b04706f6f6c1072656d6f76655f6c69717569646974790207000000000000000000000000000000000000000000000000000000000000000204696f746104494f54410007c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c00475736463045553444300040100000101000102000103000102030000000003000001000104006f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f011212121212121212121212121212121212121212121212121212121212121212070000000000000020f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f26f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6fe803000000000000809698000000000000", "hex");
+
+// Transfer of 0.001 IOTA to 0x0, which nobody can spend from
+export const BURN_TRANSFER_TXN = Buffer.from("000000000002000840420f0000000000002000000000000000000000000000000000000000000000000000000000000000000202000101000001010200000101006f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f011212121212121212121212121212121212121212121212121212121212121212070000000000000020f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f26f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6fe803000000000000809698000000000000", "hex");
+
+// Sending the objects 0xa1a1...a1a1 and 0xa2a2...a2a2, along with 0.5 IOTA split off the gas coin,
+// to 0x4f4f...4f4f
+export const OBJECT_TRANSFER_TXN = Buffer.from("0000000000040100a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1080000000000000020d4d4d4d4d4d4d4d4d4d4d4d4d4d4d4d4d4d4d4d4d4d4d4d4d4d4d4d4d4d4d4d400080065cd1d000000000100a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2020000000000000020d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d500204f4f4f4f4f4f4f4f4f4f4f4f4f4f4f4f4f4f4f4f4f4f4f4f4f4f4f4f4f4f4f4f0202000101010001030100000200000102000103006f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f011212121212121212121212121212121212121212121212121212121212121212070000000000000020f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f26f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6fe803000000000000809698000000000000", "hex");
+
+// Dividing 3 IOTA split off the gas coin into 3 coins with pay::divide_and_keep, and sending the
+// coin divided on to 0x4f4f...4f4f
+export const DIVIDE_TXN = Buffer.from("0000000000030008005ed0b2000000000008030000000000000000204f4f4f4f4f4f4f4f4f4f4f4f4f4f4f4f4f4f4f4f4f4f4f4f4f4f4f4f4f4f4f4f03020001010000000000000000000000000000000000000000000000000000000000000000000002037061790f6469766964655f616e645f6b6565700107000000000000000000000000000000000000000000000000000000000000000204696f746104494f5441000202000001010001010200000102006f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f011212121212121212121212121212121212121212121212121212121212121212070000000000000020f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f26f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6fe803000000000000809698000000000000", "hex");
+
+// Staking 1 IOTA split off the gas coin with the validator 0x7777...7777
+export const STAKE_TXN = Buffer.from("000000000003000800ca9a3b000000000101000000000000000000000000000000000000000000000000000000000000000501000000000000000100207777777777777777777777777777777777777777777777777777777777777777020200010100000000000000000000000000000000000000000000000000000000000000000000030b696f74615f73797374656d11726571756573745f6164645f7374616b6500030101000200000102006f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f011212121212121212121212121212121212121212121212121212121212121212070000000000000020f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f26f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6fe803000000000000809698000000000000", "hex");
+
+// Staking the gas coin itself with the validator 0x7777...7777, which IOTA does not run, as only
+// TransferObjects takes the gas coin by value
+export const STAKE_ALL_TXN = Buffer.from("0000000000020101000000000000000000000000000000000000000000000000000000000000000501000000000000000100207777777777777777777777777777777777777777777777777777777777777777010000000000000000000000000000000000000000000000000000000000000000030b696f74615f73797374656d11726571756573745f6164645f7374616b650003010000000101006f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f011212121212121212121212121212121212121212121212121212121212121212070000000000000020f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f26f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6fe803000000000000809698000000000000", "hex");
+
+// Minting 1000000000 base units of 0xc0c0...c0c0::usdc::USDC for 0x4f4f...4f4f with
+// coin::mint_and_transfer, with the treasury cap 0x7c7c...7c7c
+export const MINT_TXN = Buffer.from("00000000000301007c7c7c7c7c7c7c7c7c7c7c7c7c7c7c7c7c7c7c7c7c7c7c7c7c7c7c7c7c7c7c7c050000000000000020d6d6d6d6d6d6d6d6d6d6d6d6d6d6d6d6d6d6d6d6d6d6d6d6d6d6d6d6d6d6d6d6000800ca9a3b0000000000204f4f4f4f4f4f4f4f4f4f4f4f4f4f4f4f4f4f4f4f4f4f4f4f4f4f4f4f4f4f4f4f0100000000000000000000000000000000000000000000000000000000000000000204636f696e116d696e745f616e645f7472616e736665720107c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c00475736463045553444300030100000101000102006f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f011212121212121212121212121212121212121212121212121212121212121212070000000000000020f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f26f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6fe803000000000000809698000000000000", "hex");
+
+// Withdrawing the stake 0x5a5a...5a5a, a StakedIota, with iota_system::request_withdraw_stake
+export const UNSTAKE_TXN = Buffer.from("0000000000020101000000000000000000000000000000000000000000000000000000000000000501000000000000000101005a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a030000000000000020d1d1d1d1d1d1d1d1d1d1d1d1d1d1d1d1d1d1d1d1d1d1d1d1d1d1d1d1d1d1d1d1010000000000000000000000000000000000000000000000000000000000000000030b696f74615f73797374656d16726571756573745f77697468647261775f7374616b6500020100000101006f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f011212121212121212121212121212121212121212121212121212121212121212070000000000000020f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f26f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6fe803000000000000809698000000000000", "hex");
+
+// Withdrawing the timelocked stake 0x5b5b...5b5b, a TimelockedStakedIota, with
+// timelocked_staking::request_withdraw_stake
+export const TIMELOCKED_UNSTAKE_TXN = Buffer.from("0000000000020101000000000000000000000000000000000000000000000000000000000000000501000000000000000101005b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b040000000000000020d7d7d7d7d7d7d7d7d7d7d7d7d7d7d7d7d7d7d7d7d7d7d7d7d7d7d7d7d7d7d7d7010000000000000000000000000000000000000000000000000000000000000000031274696d656c6f636b65645f7374616b696e6716726571756573745f77697468647261775f7374616b6500020100000101006f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f011212121212121212121212121212121212121212121212121212121212121212070000000000000020f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f2f26f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6f6fe803000000000000809698000000000000", "hex");
+
+// The address of IOTA_BIP_PATH for the default speculos mnemonic
+const IOTA_ADDRESS = "1b3669e321893ee49c387a08fc251dbfff37cd2a981e6c473a5b2afde19d363e";
+
+// What signing TRANSFER_TXN with the default settings shows, fresh for each test as checking the
+// prompts patches them on some devices. From is the address of the signing key.
+export const transferPrompts = (from: string = IOTA_ADDRESS) => [
+  {
+    "header": "Transfer",
+    "prompt": "1 recipient, 1 amount, gas IOTA 0.000001036",
+    "paginate": true,
+  },
+  {
+    "header": "From",
+    "prompt": "0x" + from,
+    "paginate": true
+  },
+  {
+    "header": "Account",
+    "prompt": "#0, Index #0"
+  },
+  {
+    "header": "To",
+    "prompt": "0x4f2370b2a4810ad6c8e1cfd92cc8c8818fef8f59e3a80cea17871f78d850ba4b",
+    "paginate": true
+  },
+  {
+    "header": "Recipient",
+    "prompt": "Not in address book"
+  },
+  {
+    "header": "Amount",
+    "prompt": "IOTA 0.001"
+  },
+  {
+    "header": "Max Gas",
+    "prompt": "IOTA 0.000001036"
+  },
+  {
+    "text": "Sign Transaction?",
+    "x": 19,
+    "y": 11,
+    "clear": false
+  },
+  {
+    "text": "To 0x4f23...ba4b",
+    "x": 22,
+    "y": 11,
+    "clear": false
+  },
+  {
+    "text": "IOTA 0.001",
+    "x": 38,
+    "y": 11,
+    "clear": false
+  },
+  {
+    "text": "Gas IOTA 0.000001036",
+    "x": 11,
+    "y": 11,
+    "clear": false
+  },
+  {
+    "text": "Confirm",
+    "x": 43,
+    "y": 11,
+    "clear": false
+  }
+];
+
 const ignoredScreens = ["", "Cancel", "Working...", "Quit", "Version"
 
+  /* Busy screens, naming what the host asked for */
+  , "Getting version", "Verifying address", "Exporting pubkey", "Signing", "Saving address"
+
   /* App name and version */
   , "IOTA", "lOTA", `${VERSION.major}.${VERSION.minor}.${VERSION.patch}`
 
-  , "Settings", "Blind Signing", "Enabled", "Disabled", "Back"
+  , "Settings", "Blind Signing", "Blind Sign Once", "Expert Mode", "Amount Unit", "nanos", "Addresses", "Truncated", "Full", "Confirm Pubkey", "Show Hash", "Hash Format", "Hex", "Base64", "Decimal Mark", "Period", "Comma", "Limit Reviews", "Enabled", "Disabled", "Back"
   /* The next ones are specifically for S+ in which OCR is broken */
   , "ettings", "Blind igning"
 ];
 
+// Progress shown on the busy menu while a transaction is being parsed
+const progressScreen = /^Parsing (input|command) \d+\/\d+$/;
+
+// Shown before blind signing; it is clicked through like any other screen, but it has no header
+const blindSignWarningScreen = "Blind Sign";
+
 const API_PORT: number = 5005;
 
 const BASE_URL: string = `http://127.0.0.1:${API_PORT}`;
@@ -31,6 +157,7 @@ const setAcceptAutomationRules = async function () {
     version: 1,
     rules: [
       ...ignoredScreens.map(txt => { return { "text": txt, "actions": [] } }),
+      { "regexp": progressScreen.source, "actions": [] },
       { "y": 16, "actions": [] },
       { "y": 31, "actions": [] },
       { "y": 46, "actions": [] },
@@ -64,7 +191,7 @@ const setAcceptAutomationRules = async function () {
 }
 
 const processPrompts = function (prompts: any[]) {
-  const i = prompts.filter((a: any) => !ignoredScreens.includes(a["text"])); // .values();
+  const i = prompts.filter((a: any) => !ignoredScreens.includes(a["text"]) && !progressScreen.test(a["text"]) && a["text"] != blindSignWarningScreen); // .values();
   let header = "";
   let prompt = "";
   let rv = [];
@@ -190,15 +317,89 @@ const sendCommandExpectFail = async function (command: any) {
   expect.fail("Command should have failed");
 }
 
-let toggleBlindSigningSettings = async function () {
+// Run command accepting everything like sendCommandAndAccept does, returning the screens shown as
+// speculos recorded them instead of checking them. Progress screens are left out, as how many
+// show up depends on timing.
+const recordScreens = async function (command: any) {
+  await setAcceptAutomationRules();
+  await Axios.delete(BASE_URL + "/events");
+
+  const transport = await Transport.open(BASE_URL + "/apdu");
+  const client = new Iota(transport);
+  await command(client);
+
+  const events = (await Axios.get(BASE_URL + "/events")).data["events"] as any[];
+  return events.filter((value: any) => !progressScreen.test(value["text"]));
+}
+
+const settingsHelpLines = 3;
+
+// Toggles the nth entry of the settings submenu, and goes back to the main menu
+let toggleSettingsEntry = async function (n: number) {
   await Axios.post(BASE_URL + "/button/right", { "action": "press-and-release" });
   await Axios.post(BASE_URL + "/button/right", { "action": "press-and-release" });
   await Axios.post(BASE_URL + "/button/both", { "action": "press-and-release" });
+  for (let i = 0; i < n; i++) {
+    await Axios.post(BASE_URL + "/button/right", { "action": "press-and-release" });
+  }
   await Axios.post(BASE_URL + "/button/both", { "action": "press-and-release" });
-  await Axios.post(BASE_URL + "/button/right", { "action": "press-and-release" });
+  // Each change is confirmed after three lines of help
+  for (let i = 0; i < settingsHelpLines; i++) {
+    await Axios.post(BASE_URL + "/button/right", { "action": "press-and-release" });
+  }
+  await Axios.post(BASE_URL + "/button/both", { "action": "press-and-release" });
+  // Back is just before the first entry
+  for (let i = 0; i <= n; i++) {
+    await Axios.post(BASE_URL + "/button/left", { "action": "press-and-release" });
+  }
   await Axios.post(BASE_URL + "/button/both", { "action": "press-and-release" });
   await Axios.post(BASE_URL + "/button/left", { "action": "press-and-release" });
   await Axios.post(BASE_URL + "/button/left", { "action": "press-and-release" });
 }
 
-export { sendCommandAndAccept, BASE_URL, IOTA_BIP_PATH, TESTNET_BIP_PATH, sendCommandExpectFail, toggleBlindSigningSettings }
+let toggleBlindSigningSettings = async function () {
+  await toggleSettingsEntry(0);
+}
+
+let toggleBlindSigningOnceSettings = async function () {
+  await toggleSettingsEntry(1);
+}
+
+let toggleExpertModeSettings = async function () {
+  await toggleSettingsEntry(2);
+}
+
+let toggleUnitsSettings = async function () {
+  await toggleSettingsEntry(3);
+}
+
+let toggleAddressSettings = async function () {
+  await toggleSettingsEntry(4);
+}
+
+let toggleConfirmPubkeySettings = async function () {
+  await toggleSettingsEntry(6);
+}
+
+let toggleShowHashSettings = async function () {
+  await toggleSettingsEntry(7);
+}
+
+let toggleHashFormatSettings = async function () {
+  await toggleSettingsEntry(8);
+}
+
+let toggleDecimalMarkSettings = async function () {
+  await toggleSettingsEntry(9);
+}
+
+let toggleLimitReviewSettings = async function () {
+  await toggleSettingsEntry(10);
+}
+
+// Confirmed just like a toggle
+let resetSettings = async function () {
+  await toggleSettingsEntry(11);
+}
+
+export { sendCommandAndAccept, recordScreens, BASE_URL, IOTA_BIP_PATH, TESTNET_BIP_PATH, sendCommandExpectFail, toggleBlindSigningSettings, toggleBlindSigningOnceSettings, toggleExpertModeSettings, toggleUnitsSettings, toggleAddressSettings, toggleConfirmPubkeySettings, toggleShowHashSettings, toggleHashFormatSettings, toggleDecimalMarkSettings, toggleLimitReviewSettings, resetSettings }
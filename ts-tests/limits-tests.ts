@@ -0,0 +1,17 @@
+import { sendCommandAndAccept } from "./common";
+import { expect } from 'chai';
+import { describe, it } from 'mocha';
+
+describe("limits tests", function () {
+  it("reports the largest transactions the app handles", async () => {
+    await sendCommandAndAccept(async (client: any) => {
+      const rv = await client.getLimits();
+      expect(rv.maxTxSize).to.equal(128 * 1024);
+      // Smaller on the Nano S, see chunked.rs
+      expect(rv.maxChunkedTxSize).to.be.oneOf([768, 4096]);
+      expect(rv.maxInputs).to.equal(32);
+      expect(rv.maxCommands).to.equal(8);
+      expect(rv.maxSplitAmounts).to.equal(8);
+    }, []);
+  });
+});
@@ -0,0 +1,109 @@
+import { TRANSFER_TXN, UNKNOWN_TXN, recordScreens, BASE_URL, IOTA_BIP_PATH, toggleBlindSigningSettings, toggleExpertModeSettings } from "./common";
+import { expect } from 'chai';
+import { describe, it } from 'mocha';
+import Axios from 'axios';
+import Transport from "./http-transport";
+import type Iota from "./Iota";
+import * as fs from 'fs';
+import * as path from 'path';
+
+// Fails once the app needs more stack or flash than the budget of the device in
+// memory-budgets.json, which default.nix also checks the static stack estimate and the flash of
+// release builds against. Running out of either only shows up on hardware, as a crash.
+//
+// The stack test needs a build with the memory_stats feature, and is skipped unless MEMORY_STATS
+// is set. The flash test reads the section headers of the ELF of the app named by APP_ELF, which
+// should be a release build. Both are skipped unless DEVICE names the device speculos runs.
+const DEVICE = process.env.DEVICE || "";
+const BUDGETS = JSON.parse(fs.readFileSync(path.join(__dirname, "memory-budgets.json"), "utf8"));
+
+// What the counters of GET_VERSION are read after: the parsers which go deepest
+const WORKLOAD = [
+    { txn: TRANSFER_TXN, settings: [] },
+    { txn: TRANSFER_TXN, settings: [toggleExpertModeSettings] },
+    { txn: UNKNOWN_TXN, settings: [toggleBlindSigningSettings] },
+];
+
+// The peak stack depth and the state of a command in progress, see docs/apdu.md
+const memoryStats = async function () {
+    const transport = await Transport.open(BASE_URL + "/apdu");
+    // The whole GET_VERSION in the first APDU of the block protocol, with no parameters
+    const rv = await transport.send(0x00, 0x00, 0x00, 0x00, Buffer.from([0x00]));
+    // RESULT_FINAL, the result, and the status word
+    expect(rv[0]).to.equal(0x01);
+    const counters = rv.subarray(rv.length - 10, rv.length - 2);
+    return { stack: counters.readUInt32LE(0), state: counters.readUInt32LE(4) };
+}
+
+// Flash holds the sections which are loaded and have contents, as in the text and data of size(1)
+const flashSize = function (elf: Buffer) {
+    expect(elf.subarray(0, 4).toString("latin1")).to.equal("\x7fELF");
+    // 32-bit little-endian, as every Ledger device is
+    expect(elf[4]).to.equal(1);
+    expect(elf[5]).to.equal(1);
+    const sectionHeaders = elf.readUInt32LE(0x20);
+    const sectionHeaderSize = elf.readUInt16LE(0x2e);
+    const sections = elf.readUInt16LE(0x30);
+    const SHT_NOBITS = 8;
+    const SHF_ALLOC = 2;
+    let rv = 0;
+    for (let i = 0; i < sections; i++) {
+        const header = sectionHeaders + i * sectionHeaderSize;
+        const type = elf.readUInt32LE(header + 4);
+        const flags = elf.readUInt32LE(header + 8);
+        if (type != SHT_NOBITS && (flags & SHF_ALLOC)) {
+            rv += elf.readUInt32LE(header + 20);
+        }
+    }
+    return rv;
+}
+
+describe('memory budget tests', function () {
+
+    before(function () {
+        if (!DEVICE) {
+            this.skip();
+        }
+        expect(BUDGETS).to.have.property(DEVICE);
+    });
+
+    afterEach(async function () {
+        await Axios.post(BASE_URL + "/automation", { version: 1, rules: [] });
+        await Axios.delete(BASE_URL + "/events");
+    });
+
+    it('stays within the stack budget while signing', async function () {
+        if (!process.env.MEMORY_STATS) {
+            this.skip();
+        }
+        for (const entry of WORKLOAD) {
+            for (const toggle of entry.settings) {
+                await toggle();
+            }
+            try {
+                await recordScreens(async (client: Iota) => {
+                    await client.signTransaction(IOTA_BIP_PATH, entry.txn);
+                });
+            } finally {
+                for (const toggle of entry.settings) {
+                    await toggle();
+                }
+            }
+        }
+        const stats = await memoryStats();
+        console.log(`${DEVICE}: ${stats.stack} of ${BUDGETS[DEVICE].stack} bytes of stack, ${stats.state} bytes of command state`);
+        expect(stats.stack).to.be.greaterThan(0);
+        expect(stats.stack).to.be.at.most(BUDGETS[DEVICE].stack);
+    });
+
+    it('stays within the flash budget', function () {
+        const elf = process.env.APP_ELF;
+        if (!elf) {
+            this.skip();
+            return;
+        }
+        const size = flashSize(fs.readFileSync(elf));
+        console.log(`${DEVICE}: ${size} of ${BUDGETS[DEVICE].flash} bytes of flash`);
+        expect(size).to.be.at.most(BUDGETS[DEVICE].flash);
+    });
+});
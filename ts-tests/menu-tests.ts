@@ -0,0 +1,257 @@
+import { UNKNOWN_TXN, recordScreens, sendCommandExpectFail, BASE_URL, IOTA_BIP_PATH } from "./common";
+import { expect } from 'chai';
+import { describe, it } from 'mocha';
+import Axios from 'axios';
+import Transport from "./http-transport";
+import type Iota from "./Iota";
+
+// Drives the menus of menu.rs with button presses, checking the screen after each of them. Every
+// test starts and ends on the main screen of the app, with the settings at their defaults.
+
+const MAIN_MENU = ["IOTA", "Version", "Settings", "About", "Quit"];
+
+// The default build has the expert screens, and so Expert Mode
+const SETTINGS_MENU = ["Blind Signing", "Blind Sign Once", "Expert Mode", "Amount Unit", "Addresses", "Timeout", "Confirm Pubkey", "Show Hash", "Hash Format", "Decimal Mark", "Limit Reviews", "Reset Settings", "Back"];
+
+const ABOUT_MENU = ["App", "API Version", "SDK Version", "Commit", "Signatures", "Back"];
+
+// Each change of a setting is confirmed after three lines of help
+const settingsHelpLines = 3;
+
+// The OCR of speculos drops S and reads I as l on S+, see common.ts
+const ocr = (text: string) => text.trim().replace(/S/g, "").replace(/I/g, "l");
+
+const press = async function (button: "left" | "right" | "both") {
+    await Axios.post(BASE_URL + "/button/" + button, { "action": "press-and-release" });
+}
+
+const currentScreen = async function (): Promise<string[]> {
+    const events = (await Axios.get(BASE_URL + "/events?currentscreenonly=true")).data["events"] as any[];
+    return events.map((value: any) => ocr(value["text"]));
+}
+
+// Speculos draws the next screen a little after the button press
+const expectScreen = async function (...texts: string[]) {
+    let screen: string[] = [];
+    for (let attempt = 0; attempt < 10; attempt++) {
+        screen = await currentScreen();
+        if (texts.every(text => screen.includes(ocr(text)))) {
+            return;
+        }
+        await new Promise(resolve => setTimeout(resolve, 100));
+    }
+    expect(screen).to.include.members(texts.map(ocr));
+}
+
+// Walks a whole menu to the right and then to the left, wrapping around at both ends
+const cycleMenu = async function (entries: string[]) {
+    for (const entry of [...entries.slice(1), entries[0]]) {
+        await press("right");
+        await expectScreen(entry);
+    }
+    for (const entry of [...entries].reverse()) {
+        await press("left");
+        await expectScreen(entry);
+    }
+}
+
+const openSettingsEntry = async function (n: number) {
+    await press("right");
+    await press("right");
+    await press("both");
+    for (let i = 0; i < n; i++) {
+        await press("right");
+    }
+    await expectScreen(SETTINGS_MENU[n]);
+}
+
+// From the nth entry of the settings back to the main screen, through Back just before the first
+const leaveSettingsEntry = async function (n: number) {
+    for (let i = 0; i <= n; i++) {
+        await press("left");
+    }
+    await press("both");
+    await expectScreen("Settings");
+    await press("left");
+    await press("left");
+    await expectScreen("IOTA");
+}
+
+// Opens the help of the current entry and confirms or rejects the change
+const changeSetting = async function (accept: boolean) {
+    await press("both");
+    for (let i = 0; i < settingsHelpLines; i++) {
+        await press("right");
+    }
+    if (!accept) {
+        await press("right");
+    }
+    await press("both");
+}
+
+const expectBlindSigning = async function (enabled: boolean) {
+    if (enabled) {
+        const screens = await recordScreens(async (client: Iota) => {
+            const sig = await client.signTransaction(IOTA_BIP_PATH, UNKNOWN_TXN);
+            expect(sig.signature.length).to.equal(64);
+        });
+        expect(screens.map((value: any) => ocr(value["text"]))).to.include(ocr("Blind Sign Transaction?"));
+    } else {
+        await sendCommandExpectFail(async (client: Iota) => {
+            await client.signTransaction(IOTA_BIP_PATH, UNKNOWN_TXN);
+        });
+    }
+    await Axios.post(BASE_URL + "/automation", { version: 1, rules: [] });
+}
+
+describe('menu tests', function () {
+
+    beforeEach(async function () {
+        await Axios.post(BASE_URL + "/automation", { version: 1, rules: [] });
+        await expectScreen("IOTA");
+    });
+
+    afterEach(async function () {
+        await Axios.post(BASE_URL + "/automation", { version: 1, rules: [] });
+        await Axios.delete(BASE_URL + "/events");
+    });
+
+    it('cycles through the main menu', async () => {
+        await cycleMenu(MAIN_MENU);
+    });
+
+    it('cycles through the settings and goes back', async () => {
+        await press("right");
+        await press("right");
+        await expectScreen("Settings");
+        await press("both");
+        await expectScreen(SETTINGS_MENU[0]);
+        await cycleMenu(SETTINGS_MENU);
+        await press("left");
+        await expectScreen("Back");
+        await press("both");
+        await expectScreen("Settings");
+        await press("left");
+        await press("left");
+        await expectScreen("IOTA");
+    });
+
+    it('cycles through the about menu and goes back', async () => {
+        await press("left");
+        await press("left");
+        await expectScreen("About");
+        await press("both");
+        await expectScreen(ABOUT_MENU[0], "IOTA Rebased");
+        // The entries only show something, pressing both does nothing
+        await press("both");
+        await expectScreen(ABOUT_MENU[0], "IOTA Rebased");
+        await cycleMenu(ABOUT_MENU);
+        await press("left");
+        await expectScreen("Back");
+        await press("both");
+        await expectScreen("About");
+        await press("right");
+        await press("right");
+        await expectScreen("IOTA");
+    });
+
+    it('changes the blind signing flag only once the change is confirmed', async () => {
+        await expectBlindSigning(false);
+
+        await openSettingsEntry(0);
+        await expectScreen("Blind Signing", "Disabled");
+        await changeSetting(false);
+        await expectScreen("Blind Signing", "Disabled");
+        await leaveSettingsEntry(0);
+        await expectBlindSigning(false);
+
+        await openSettingsEntry(0);
+        await changeSetting(true);
+        await expectScreen("Blind Signing", "Enabled");
+        await leaveSettingsEntry(0);
+        await expectBlindSigning(true);
+        // Stays enabled until it is disabled again
+        await expectBlindSigning(true);
+
+        await openSettingsEntry(0);
+        await changeSetting(true);
+        await expectScreen("Blind Signing", "Disabled");
+        await leaveSettingsEntry(0);
+        await expectBlindSigning(false);
+    });
+
+    it('uses up single use blind signing', async () => {
+        await openSettingsEntry(1);
+        await changeSetting(true);
+        await expectScreen("Blind Sign Once", "Enabled");
+        await leaveSettingsEntry(1);
+        await expectBlindSigning(true);
+        await expectBlindSigning(false);
+
+        await openSettingsEntry(1);
+        await expectScreen("Blind Sign Once", "Disabled");
+        await leaveSettingsEntry(1);
+    });
+
+    it('cycles through the timeouts', async () => {
+        await openSettingsEntry(5);
+        await expectScreen("Timeout", "5 minutes");
+        for (const label of ["15 minutes", "Off", "1 minute", "5 minutes"]) {
+            await changeSetting(true);
+            await expectScreen("Timeout", label);
+        }
+        await leaveSettingsEntry(5);
+    });
+
+    it('resets the settings', async () => {
+        await openSettingsEntry(0);
+        await changeSetting(true);
+        await expectScreen("Blind Signing", "Enabled");
+        await press("right");
+        await press("right");
+        await changeSetting(true);
+        await expectScreen("Expert Mode", "Enabled");
+
+        for (let i = 2; i < 11; i++) {
+            await press("right");
+        }
+        await expectScreen("Reset Settings");
+        await changeSetting(true);
+        for (let i = 11; i > 2; i--) {
+            await press("left");
+        }
+        await expectScreen("Expert Mode", "Disabled");
+        await press("left");
+        await press("left");
+        await expectScreen("Blind Signing", "Disabled");
+        await leaveSettingsEntry(0);
+        await expectBlindSigning(false);
+    });
+
+    it('switches between working and cancel while busy, and cancels', async () => {
+        const transport = await Transport.open(BASE_URL + "/apdu");
+        // Start signing with the block protocol, and leave the request for the first block open
+        const start = Buffer.concat([Buffer.from([0x00]), Buffer.alloc(32, 1), Buffer.alloc(32, 2)]);
+        const rv = await transport.send(0x00, 0x03, 0x00, 0x00, start);
+        // GET_CHUNK
+        expect(rv[0]).to.equal(0x02);
+
+        await expectScreen("Signing");
+        await press("right");
+        await expectScreen("Cancel");
+        await press("left");
+        await expectScreen("Signing");
+        await press("right");
+        await press("both");
+        await expectScreen("IOTA");
+
+        // The host is told about the cancel with its next APDU
+        let err = null;
+        try {
+            await transport.send(0x00, 0x03, 0x00, 0x00, Buffer.from([0x02]));
+        } catch (e) {
+            err = e;
+        }
+        expect(err).to.not.equal(null);
+    });
+});
@@ -0,0 +1,25 @@
+import { sendCommandAndAccept } from "./common";
+import { expect } from 'chai';
+import { describe, it } from 'mocha';
+
+describe("patterns tests", function () {
+  it("lists the calls the app clear signs", async () => {
+    await sendCommandAndAccept(async (client: any) => {
+      const rv = await client.getPatterns();
+      expect(rv.version).to.equal(5);
+      expect(rv.patterns.length).to.equal(5);
+      // No lending market or AMM is known, so none of their calls are listed
+      expect(rv.patterns.every((p: any) => p.package != null)).to.equal(true);
+      expect(rv.patterns.some((p: any) => p.kind <= 6)).to.equal(false);
+      // Dividing with the framework package, 0x2, kind 7
+      expect(rv.patterns[0].kind).to.equal(7);
+      expect(rv.patterns[0].package.toString("hex")).to.equal("00".repeat(31) + "02");
+      expect(rv.patterns[0].function).to.equal("divide_and_keep");
+      // Staking with the system package, 0x3, kind 8
+      const stake = rv.patterns.find((p: any) => p.function == "request_add_stake");
+      expect(stake.kind).to.equal(8);
+      expect(stake.package.toString("hex")).to.equal("00".repeat(31) + "03");
+      expect(stake.module).to.equal("iota_system");
+    }, []);
+  });
+});
@@ -1,8 +1,9 @@
-import { sendCommandAndAccept, BASE_URL, IOTA_BIP_PATH, TESTNET_BIP_PATH } from "./common";
+import { sendCommandAndAccept, sendCommandExpectFail, BASE_URL, IOTA_BIP_PATH, TESTNET_BIP_PATH, toggleConfirmPubkeySettings } from "./common";
 import { expect } from 'chai';
 import { describe, it } from 'mocha';
 import Axios from 'axios';
 import type Iota from "./Iota";
+import { bip32Payload } from "./Iota";
 
 // Public key and address for default speculos mnemonic: "glory promote mansion idle axis finger extra february uncover one trip resource lawn turtle enact monster seven myth punch hobby comfort wild raise skin"
 // with BIP32 Path: 44'/4218'/0'/0'/0' (Testnet: 44'/1'/0'/0'/0')
@@ -28,6 +29,45 @@ describe('public key tests', () => {
         }, []);
     });
 
+    it('asks before providing a public key, if set to', async () => {
+        await toggleConfirmPubkeySettings();
+        await Axios.delete(BASE_URL + "/events");
+
+        await sendCommandAndAccept(async (client: Iota) => {
+            const rv = await client.getPublicKey(IOTA_BIP_PATH);
+            expect(new Buffer(rv.publicKey).toString('hex')).to.equal(IOTA_PUBLIC_KEY);
+            return;
+        }, [
+            {
+                "header": "Provide Public Key",
+                "prompt": "",
+            },
+            {
+                "header": "Account",
+                "prompt": "#0, Index #0",
+            },
+            {
+                "header": "Path",
+                "prompt": "m/44'/4218'/0'/0'/0'",
+                "paginate": true,
+            },
+            {
+                "header": "Address",
+                "prompt": "0x" + IOTA_ADDRESS,
+                "paginate": true,
+            },
+            {
+                "text": "Confirm",
+                "x": 43,
+                "y": 11,
+                "clear": false
+            },
+        ]);
+        await Axios.delete(BASE_URL + "/events");
+        // reset back to not asking
+        await toggleConfirmPubkeySettings();
+    });
+
     it('provides a public key testnet coin type', async () => {
 
         await sendCommandAndAccept(async (client: Iota) => {
@@ -38,6 +78,65 @@ describe('public key tests', () => {
         }, []);
     });
 
+    it('provides a different public key with the BIP32-Ed25519 derivation', async () => {
+
+        await sendCommandAndAccept(async (client: Iota) => {
+            const rv = await client.sendChunks(0x00, 0x02, 0x00, 0x01, [bip32Payload(IOTA_BIP_PATH)]);
+            const publicKey = rv.subarray(1, 1 + rv[0]);
+            expect(publicKey.length).to.equal(32);
+            expect(publicKey.toString('hex')).to.not.equal(IOTA_PUBLIC_KEY);
+            return;
+        }, []);
+    });
+
+    it('rejects an unknown derivation', async () => {
+        await sendCommandExpectFail(async (client: Iota) => {
+            await client.sendChunks(0x00, 0x02, 0x00, 0x02, [bip32Payload(IOTA_BIP_PATH)]);
+        });
+    });
+
+    it('rejects a path with an account which is not hardened', async () => {
+        await sendCommandExpectFail(async (client: Iota) => {
+            await client.getPublicKey("44'/4218'/0/0'/0'");
+        });
+    });
+
+    it('rejects a path which is not 5 components deep', async () => {
+        await sendCommandExpectFail(async (client: Iota) => {
+            await client.getPublicKey("44'/4218'/0'");
+        });
+    });
+
+    it('asks before providing a public key with an unusually high index', async () => {
+
+        await sendCommandAndAccept(async (client: Iota) => {
+            const rv = await client.getPublicKey("44'/4218'/0'/0'/101'");
+            expect(rv.publicKey.length).to.equal(32);
+            return;
+        }, [
+            {
+                "header": "WARNING",
+                "prompt": "Unusually high account or index",
+            },
+            {
+                "header": "Account",
+                "prompt": "#0, Index #101",
+            },
+            {
+                "text": "Use This Path?",
+                "x": 27,
+                "y": 11,
+                "clear": false
+            },
+            {
+                "text": "Confirm",
+                "x": 43,
+                "y": 11,
+                "clear": false
+            },
+        ]);
+    });
+
     it('does address verification', async () => {
 
         await sendCommandAndAccept(async (client: Iota) => {
@@ -50,6 +149,15 @@ describe('public key tests', () => {
                 "header": "Provide Public Key",
                 "prompt": "",
             },
+            {
+                "header": "Account",
+                "prompt": "#0, Index #0",
+            },
+            {
+                "header": "Path",
+                "prompt": "m/44'/4218'/0'/0'/0'",
+                "paginate": true,
+            },
             {
                 "header": "Address",
                 "prompt": "0x" + IOTA_ADDRESS,
@@ -63,4 +171,42 @@ describe('public key tests', () => {
             },
         ]);
     });
+
+    it('saves an address to the address book', async () => {
+
+        await sendCommandAndAccept(async (client: Iota) => {
+            await client.saveAddress(TESTNET_BIP_PATH, "Savings");
+            return;
+        }, [
+            {
+                "header": "Save Address",
+                "prompt": "",
+            },
+            {
+                "header": "Account",
+                "prompt": "#0, Index #0",
+            },
+            {
+                "header": "Address",
+                "prompt": "0x" + TESTNET_ADDRESS,
+                "paginate": true,
+            },
+            {
+                "header": "Label",
+                "prompt": "Savings",
+            },
+            {
+                "text": "Save Address?",
+                "x": 30,
+                "y": 11,
+                "clear": false
+            },
+            {
+                "text": "Confirm",
+                "x": 43,
+                "y": 11,
+                "clear": false
+            },
+        ]);
+    });
 });
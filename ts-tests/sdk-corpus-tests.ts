@@ -0,0 +1,70 @@
+import { sendCommandExpectFail, recordScreens, BASE_URL, IOTA_BIP_PATH, toggleBlindSigningSettings } from "./common";
+import { expect } from 'chai';
+import { describe, it } from 'mocha';
+import Axios from 'axios';
+import type Iota from "./Iota";
+import * as fs from 'fs';
+import * as path from 'path';
+
+// Transactions as the IOTA TypeScript SDK builds them, which sdk-corpus/generate.ts writes to
+// sdk-corpus.json, each with whether the app clear signs it or only blind signs it. A change to
+// how the SDK serializes transactions which the app then fails to recognize shows up here.
+//
+// Skipped until the corpus has been generated.
+const CORPUS_FILE = path.join(__dirname, "sdk-corpus.json");
+
+// The OCR of speculos drops S and reads I as l on S+, see common.ts
+const ocr = (text: string) => text.trim().replace(/S/g, "").replace(/I/g, "l");
+
+const signAndRecord = async function (txn: Buffer) {
+    const screens = await recordScreens(async (client: Iota) => {
+        const sig = await client.signTransaction(IOTA_BIP_PATH, txn);
+        expect(sig.signature.length).to.equal(64);
+    });
+    return screens.map((value: any) => ocr(value["text"]));
+}
+
+const corpus = fs.existsSync(CORPUS_FILE) ? JSON.parse(fs.readFileSync(CORPUS_FILE, "utf8")) : null;
+
+describe(`SDK corpus tests${corpus ? " of " + corpus.sdk + " " + corpus.version : ""}`, function () {
+
+    before(function () {
+        if (!corpus) {
+            this.skip();
+        }
+    });
+
+    afterEach(async function () {
+        await Axios.post(BASE_URL + "/automation", { version: 1, rules: [] });
+        await Axios.delete(BASE_URL + "/events");
+    });
+
+    for (const entry of (corpus ? corpus.transactions : [])) {
+        const txn = Buffer.from(entry.txn, "hex");
+
+        if (entry.expected == "clear-sign") {
+            it(`clear signs ${entry.name}: ${entry.description}`, async () => {
+                const screens = await signAndRecord(txn);
+                expect(screens).to.include(ocr("Sign Transaction?"));
+                expect(screens).to.not.include(ocr("Blind Sign Transaction?"));
+            });
+        } else if (entry.expected == "blind-sign") {
+            it(`only blind signs ${entry.name}: ${entry.description}`, async () => {
+                await sendCommandExpectFail(async (client: Iota) => {
+                    await client.signTransaction(IOTA_BIP_PATH, txn);
+                });
+                await toggleBlindSigningSettings();
+                try {
+                    const screens = await signAndRecord(txn);
+                    expect(screens).to.include(ocr("Blind Sign Transaction?"));
+                } finally {
+                    await toggleBlindSigningSettings();
+                }
+            });
+        } else {
+            it(`knows what to expect of ${entry.name}`, () => {
+                expect.fail(`unknown expectation ${entry.expected}`);
+            });
+        }
+    }
+});
@@ -0,0 +1,14 @@
+import { BASE_URL } from "./common";
+import { expect } from 'chai';
+import { describe, it } from 'mocha';
+import Transport from "./http-transport";
+
+describe("self-test tests", function () {
+  it("passes all known-answer tests", async () => {
+    const transport = await Transport.open(BASE_URL + "/apdu");
+    // The whole SELF_TEST in the first APDU of the block protocol, with no parameters
+    const rv = await transport.send(0x00, 0x06, 0x00, 0x00, Buffer.from([0x00]));
+    // RESULT_FINAL, then blake2b, ed25519 and device_key, see docs/apdu.md, and the status word
+    expect(rv.toString("hex")).to.equal("010101019000");
+  });
+});
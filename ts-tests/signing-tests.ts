@@ -1,8 +1,9 @@
-import { VERSION, sendCommandAndAccept, BASE_URL, sendCommandExpectFail, toggleBlindSigningSettings, IOTA_BIP_PATH, TESTNET_BIP_PATH } from "./common";
+import { VERSION, TRANSFER_TXN, LENDING_DEPOSIT_TXN, LENDING_WITHDRAW_TXN, ADD_LIQUIDITY_TXN, REMOVE_LIQUIDITY_TXN, OBJECT_TRANSFER_TXN, DIVIDE_TXN, STAKE_TXN, STAKE_ALL_TXN, UNSTAKE_TXN, TIMELOCKED_UNSTAKE_TXN, MINT_TXN, BURN_TRANSFER_TXN, transferPrompts, sendCommandAndAccept, recordScreens, BASE_URL, sendCommandExpectFail, toggleBlindSigningSettings, toggleBlindSigningOnceSettings, toggleExpertModeSettings, toggleUnitsSettings, toggleAddressSettings, toggleShowHashSettings, toggleHashFormatSettings, toggleDecimalMarkSettings, toggleLimitReviewSettings, resetSettings, IOTA_BIP_PATH, TESTNET_BIP_PATH } from "./common";
 import { expect } from 'chai';
 import { describe, it } from 'mocha';
 import Axios from 'axios';
 import type Iota from "./Iota";
+import { bip32Payload } from "./Iota";
 import * as blake2b from "blake2b";
 import { instantiate, Nacl } from "js-nacl";
 
@@ -10,6 +11,20 @@ let nacl: Nacl = null;
 
 instantiate(n => { nacl = n; });
 
+// Encode a BIP32 path like "44'/4218'/0'/0'/0'" as the app expects it
+// Sign using SIGN_TX_CHUNKED instead of the block protocol
+async function signWithP1Chunks(client: any, path: string, txn: Buffer): Promise<Buffer> {
+    const CHUNK_SIZE = 200;
+    await client.transport.send(0x00, 0x04, 0x00, 0x00, bip32Payload(path));
+    for (let i = 0; i + CHUNK_SIZE < txn.length; i += CHUNK_SIZE) {
+        await client.transport.send(0x00, 0x04, 0x01, 0x00, txn.subarray(i, i + CHUNK_SIZE));
+    }
+    const last = txn.subarray(Math.floor((txn.length - 1) / CHUNK_SIZE) * CHUNK_SIZE);
+    const rv = await client.transport.send(0x00, 0x04, 0x02, 0x00, last);
+    // Strip the status word
+    return rv.subarray(0, rv.length - 2);
+}
+
 function testTransaction(path: string, txn: Buffer, prompts: any[]) {
     return async () => {
         await sendCommandAndAccept(async (client: Iota) => {
@@ -31,31 +46,806 @@ function testTransaction(path: string, txn: Buffer, prompts: any[]) {
     }
 }
 
-describe("Signing tests", function () {
-    before(async function () {
-        while (!nacl) await new Promise(r => setTimeout(r, 100));
-    })
+// The recipient of TRANSFER_TXN
+const TRANSFER_RECIPIENT = "4f2370b2a4810ad6c8e1cfd92cc8c8818fef8f59e3a80cea17871f78d850ba4b";
+
+// The OCR of speculos drops S and reads I as l on S+, see common.ts
+const ocr = (text: string) => text.trim().replace(/S/g, "").replace(/I/g, "l");
+
+// The text of every screen shown while signing, for the reviews of which only some fields matter
+const signAndRecord = async function (txn: Buffer) {
+    const screens = await recordScreens(async (client: Iota) => {
+        const sig = await client.signTransaction(IOTA_BIP_PATH, txn);
+        expect(sig.signature.length).to.equal(64);
+    });
+    await Axios.post(BASE_URL + "/automation", { version: 1, rules: [] });
+    await Axios.delete(BASE_URL + "/events");
+    return screens.map((value: any) => ocr(value["text"]));
+}
+
+describe("Signing tests", function () {
+    before(async function () {
+        while (!nacl) await new Promise(r => setTimeout(r, 100));
+    })
+
+    // Commented for now as this will crash at the "Working..." screen: https://github.com/iotaledger/ledger-app-iota/issues/11
+    it("can sign a transaction",
+        testTransaction(
+            IOTA_BIP_PATH,
+            TRANSFER_TXN,
+            [
+                {
+                    "header": "Transfer",
+                    "prompt": "1 recipient, 1 amount, gas IOTA 0.000001036",
+                    "paginate": true,
+                },
+                {
+                    "header": "From",
+                    "prompt": "0x1b3669e321893ee49c387a08fc251dbfff37cd2a981e6c473a5b2afde19d363e",
+                    "paginate": true
+                },
+                {
+                    "header": "Account",
+                    "prompt": "#0, Index #0"
+                },
+                {
+                    "header": "To",
+                    "prompt": "0x4f2370b2a4810ad6c8e1cfd92cc8c8818fef8f59e3a80cea17871f78d850ba4b",
+                    "paginate": true
+                },
+                {
+                    "header": "Recipient",
+                    "prompt": "Not in address book"
+                },
+                {
+                    "header": "Amount",
+                    "prompt": "IOTA 0.001"
+                },
+                {
+                    "header": "Max Gas",
+                    "prompt": "IOTA 0.000001036"
+                },
+                {
+                    "text": "Sign Transaction?",
+                    "x": 19,
+                    "y": 11,
+                    "clear": false
+                },
+                {
+                    "text": "To 0x4f23...ba4b",
+                    "x": 22,
+                    "y": 11,
+                    "clear": false
+                },
+                {
+                    "text": "IOTA 0.001",
+                    "x": 38,
+                    "y": 11,
+                    "clear": false
+                },
+                {
+                    "text": "Gas IOTA 0.000001036",
+                    "x": 11,
+                    "y": 11,
+                    "clear": false
+                },
+                {
+                    "text": "Confirm",
+                    "x": 43,
+                    "y": 11,
+                    "clear": false
+                }
+            ]
+        ));
+
+    it("can sign a transaction with P1 chunking", async function () {
+        const path = IOTA_BIP_PATH;
+        const txn = TRANSFER_TXN;
+        await sendCommandAndAccept(async (client: any) => {
+            const { publicKey } = await client.getPublicKey(path);
+            await Axios.delete(BASE_URL + "/events");
+
+            const signature = await signWithP1Chunks(client, path, txn);
+            expect(signature.length).to.equal(64);
+            const pass = nacl.crypto_sign_verify_detached(
+                signature,
+                blake2b(32).update(txn).digest(),
+                publicKey,
+            );
+            expect(pass).to.equal(true);
+        }, transferPrompts());
+    });
+
+    it("returns the receipt of what was signed", async function () {
+        await sendCommandAndAccept(async (client: Iota) => {
+            await client.signTransaction(IOTA_BIP_PATH, TRANSFER_TXN);
+            const rv = await client.getReceipt();
+            expect(rv.hash.toString("hex")).to.equal(blake2b(32).update(TRANSFER_TXN).digest("hex"));
+            // A transfer, see OperationKind
+            expect(rv.kind).to.equal(1);
+            expect(rv.recipient.toString("hex")).to.equal(TRANSFER_RECIPIENT);
+            expect(rv.amounts.map(String)).to.deep.equal(["1000000"]);
+            expect(String(rv.gasBudget)).to.equal("1036");
+        }, transferPrompts());
+    });
+
+    it("can resume signing with P1 chunking", async function () {
+        const path = IOTA_BIP_PATH;
+        const txn = TRANSFER_TXN;
+        await sendCommandAndAccept(async (client: any) => {
+            const { publicKey } = await client.getPublicKey(path);
+            await Axios.delete(BASE_URL + "/events");
+
+            await client.transport.send(0x00, 0x04, 0x00, 0x00, bip32Payload(path));
+            await client.transport.send(0x00, 0x04, 0x01, 0x00, txn.subarray(0, 100));
+            // As if the connection was lost after the first chunk
+            const received = await client.transport.send(0x00, 0x04, 0x03, 0x00, Buffer.alloc(0));
+            expect(received.readUInt32LE(0)).to.equal(100);
+            const rv = await client.transport.send(0x00, 0x04, 0x02, 0x00, txn.subarray(100));
+            const signature = rv.subarray(0, rv.length - 2);
+            expect(signature.length).to.equal(64);
+            const pass = nacl.crypto_sign_verify_detached(
+                signature,
+                blake2b(32).update(txn).digest(),
+                publicKey,
+            );
+            expect(pass).to.equal(true);
+        }, transferPrompts());
+    });
+
+    it("shows more details in expert mode", async function () {
+        await toggleExpertModeSettings();
+        await Axios.delete(BASE_URL + "/events");
+        await testTransaction(
+            IOTA_BIP_PATH,
+            TRANSFER_TXN,
+            [
+                {
+                    "header": "Transfer",
+                    "prompt": "1 recipient, 1 amount, gas IOTA 0.000001036",
+                    "paginate": true,
+                },
+                {
+                    "header": "From",
+                    "prompt": "0x1b3669e321893ee49c387a08fc251dbfff37cd2a981e6c473a5b2afde19d363e",
+                    "paginate": true
+                },
+                {
+                    "header": "Account",
+                    "prompt": "#0, Index #0"
+                },
+                {
+                    "header": "Command",
+                    "prompt": "1/2: SplitCoins"
+                },
+                {
+                    "header": "Command",
+                    "prompt": "2/2: TransferObjects"
+                },
+                {
+                    "header": "To",
+                    "prompt": "0x4f2370b2a4810ad6c8e1cfd92cc8c8818fef8f59e3a80cea17871f78d850ba4b",
+                    "paginate": true
+                },
+                {
+                    "header": "Recipient",
+                    "prompt": "Not in address book"
+                },
+                {
+                    "header": "Amount",
+                    "prompt": "IOTA 0.001"
+                },
+                {
+                    "header": "Gas Object",
+                    "prompt": "0x12a6d0c44edc630d2724b1f57fea4f93308b1d22164402c65778bd99379c4733",
+                    "paginate": true
+                },
+                {
+                    "header": "Gas Price",
+                    "prompt": "1"
+                },
+                {
+                    "header": "Max Gas",
+                    "prompt": "IOTA 0.000001036"
+                },
+                {
+                    "header": "Expiration",
+                    "prompt": "None"
+                },
+                {
+                    "text": "Sign Transaction?",
+                    "x": 19,
+                    "y": 11,
+                    "clear": false
+                },
+                {
+                    "text": "To 0x4f23...ba4b",
+                    "x": 22,
+                    "y": 11,
+                    "clear": false
+                },
+                {
+                    "text": "IOTA 0.001",
+                    "x": 38,
+                    "y": 11,
+                    "clear": false
+                },
+                {
+                    "text": "Gas IOTA 0.000001036",
+                    "x": 11,
+                    "y": 11,
+                    "clear": false
+                },
+                {
+                    "text": "Confirm",
+                    "x": 43,
+                    "y": 11,
+                    "clear": false
+                }
+            ]
+        )();
+        await Axios.delete(BASE_URL + "/events");
+        // reset back to disabled
+        await toggleExpertModeSettings();
+    });
+
+    it("asks before showing more than 8 items", async function () {
+        await toggleExpertModeSettings();
+        await toggleLimitReviewSettings();
+        await Axios.delete(BASE_URL + "/events");
+        await testTransaction(
+            IOTA_BIP_PATH,
+            TRANSFER_TXN,
+            [
+                {
+                    "header": "Transfer",
+                    "prompt": "1 recipient, 1 amount, gas IOTA 0.000001036",
+                    "paginate": true,
+                },
+                {
+                    "header": "From",
+                    "prompt": "0x1b3669e321893ee49c387a08fc251dbfff37cd2a981e6c473a5b2afde19d363e",
+                    "paginate": true
+                },
+                {
+                    "header": "Account",
+                    "prompt": "#0, Index #0"
+                },
+                {
+                    "header": "Command",
+                    "prompt": "1/2: SplitCoins"
+                },
+                {
+                    "header": "Command",
+                    "prompt": "2/2: TransferObjects"
+                },
+                {
+                    "header": "To",
+                    "prompt": "0x4f2370b2a4810ad6c8e1cfd92cc8c8818fef8f59e3a80cea17871f78d850ba4b",
+                    "paginate": true
+                },
+                {
+                    "header": "Recipient",
+                    "prompt": "Not in address book"
+                },
+                {
+                    "header": "Amount",
+                    "prompt": "IOTA 0.001"
+                },
+                {
+                    "text": "Show 4 more items?",
+                    "x": 16,
+                    "y": 11,
+                    "clear": false
+                },
+                {
+                    "text": "Confirm",
+                    "x": 43,
+                    "y": 11,
+                    "clear": false
+                },
+                {
+                    "header": "Gas Object",
+                    "prompt": "0x12a6d0c44edc630d2724b1f57fea4f93308b1d22164402c65778bd99379c4733",
+                    "paginate": true
+                },
+                {
+                    "header": "Gas Price",
+                    "prompt": "1"
+                },
+                {
+                    "header": "Max Gas",
+                    "prompt": "IOTA 0.000001036"
+                },
+                {
+                    "header": "Expiration",
+                    "prompt": "None"
+                },
+                {
+                    "text": "Sign Transaction?",
+                    "x": 19,
+                    "y": 11,
+                    "clear": false
+                },
+                {
+                    "text": "To 0x4f23...ba4b",
+                    "x": 22,
+                    "y": 11,
+                    "clear": false
+                },
+                {
+                    "text": "IOTA 0.001",
+                    "x": 38,
+                    "y": 11,
+                    "clear": false
+                },
+                {
+                    "text": "Gas IOTA 0.000001036",
+                    "x": 11,
+                    "y": 11,
+                    "clear": false
+                },
+                {
+                    "text": "Confirm",
+                    "x": 43,
+                    "y": 11,
+                    "clear": false
+                }
+            ]
+        )();
+        await Axios.delete(BASE_URL + "/events");
+        // reset back to disabled
+        await toggleLimitReviewSettings();
+        await toggleExpertModeSettings();
+    });
+
+    it("can show amounts in nanos", async function () {
+        await toggleUnitsSettings();
+        await Axios.delete(BASE_URL + "/events");
+        await testTransaction(
+            IOTA_BIP_PATH,
+            TRANSFER_TXN,
+            [
+                {
+                    "header": "Transfer",
+                    "prompt": "1 recipient, 1 amount, gas 1036 nanos",
+                    "paginate": true,
+                },
+                {
+                    "header": "From",
+                    "prompt": "0x1b3669e321893ee49c387a08fc251dbfff37cd2a981e6c473a5b2afde19d363e",
+                    "paginate": true
+                },
+                {
+                    "header": "Account",
+                    "prompt": "#0, Index #0"
+                },
+                {
+                    "header": "To",
+                    "prompt": "0x4f2370b2a4810ad6c8e1cfd92cc8c8818fef8f59e3a80cea17871f78d850ba4b",
+                    "paginate": true
+                },
+                {
+                    "header": "Recipient",
+                    "prompt": "Not in address book"
+                },
+                {
+                    "header": "Amount",
+                    "prompt": "1000000 nanos"
+                },
+                {
+                    "header": "Max Gas",
+                    "prompt": "1036 nanos"
+                },
+                {
+                    "text": "Sign Transaction?",
+                    "x": 19,
+                    "y": 11,
+                    "clear": false
+                },
+                {
+                    "text": "To 0x4f23...ba4b",
+                    "x": 22,
+                    "y": 11,
+                    "clear": false
+                },
+                {
+                    "text": "1000000 nanos",
+                    "x": 30,
+                    "y": 11,
+                    "clear": false
+                },
+                {
+                    "text": "Gas 1036 nanos",
+                    "x": 27,
+                    "y": 11,
+                    "clear": false
+                },
+                {
+                    "text": "Confirm",
+                    "x": 43,
+                    "y": 11,
+                    "clear": false
+                }
+            ]
+        )();
+        await Axios.delete(BASE_URL + "/events");
+        // reset back to IOTA
+        await toggleUnitsSettings();
+    });
+
+    it("can show the hash of a clear signed transaction", async function () {
+        await toggleShowHashSettings();
+        await Axios.delete(BASE_URL + "/events");
+        await testTransaction(
+            IOTA_BIP_PATH,
+            TRANSFER_TXN,
+            [
+                {
+                    "header": "Transfer",
+                    "prompt": "1 recipient, 1 amount, gas IOTA 0.000001036",
+                    "paginate": true,
+                },
+                {
+                    "header": "From",
+                    "prompt": "0x1b3669e321893ee49c387a08fc251dbfff37cd2a981e6c473a5b2afde19d363e",
+                    "paginate": true
+                },
+                {
+                    "header": "Account",
+                    "prompt": "#0, Index #0"
+                },
+                {
+                    "header": "To",
+                    "prompt": "0x4f2370b2a4810ad6c8e1cfd92cc8c8818fef8f59e3a80cea17871f78d850ba4b",
+                    "paginate": true
+                },
+                {
+                    "header": "Recipient",
+                    "prompt": "Not in address book"
+                },
+                {
+                    "header": "Amount",
+                    "prompt": "IOTA 0.001"
+                },
+                {
+                    "header": "Max Gas",
+                    "prompt": "IOTA 0.000001036"
+                },
+                {
+                    "header": "Transaction Hash",
+                    "prompt": "1:5bfd 60a9 f84d b299 2:f016 54fb 8c3b c2f2 3:ce1a 58d4 4e84 3dd7 4:855a 504e d59d 9c84"
+                },
+                {
+                    "text": "Sign Transaction?",
+                    "x": 19,
+                    "y": 11,
+                    "clear": false
+                },
+                {
+                    "text": "To 0x4f23...ba4b",
+                    "x": 22,
+                    "y": 11,
+                    "clear": false
+                },
+                {
+                    "text": "IOTA 0.001",
+                    "x": 38,
+                    "y": 11,
+                    "clear": false
+                },
+                {
+                    "text": "Gas IOTA 0.000001036",
+                    "x": 11,
+                    "y": 11,
+                    "clear": false
+                },
+                {
+                    "text": "Confirm",
+                    "x": 43,
+                    "y": 11,
+                    "clear": false
+                }
+            ]
+        )();
+        await Axios.delete(BASE_URL + "/events");
+        // reset back to hiding the hash
+        await toggleShowHashSettings();
+    });
+
+    it("can show amounts with a decimal comma", async function () {
+        await toggleDecimalMarkSettings();
+        await Axios.delete(BASE_URL + "/events");
+        await testTransaction(
+            IOTA_BIP_PATH,
+            TRANSFER_TXN,
+            [
+                {
+                    "header": "Transfer",
+                    "prompt": "1 recipient, 1 amount, gas IOTA 0,000001036",
+                    "paginate": true,
+                },
+                {
+                    "header": "From",
+                    "prompt": "0x1b3669e321893ee49c387a08fc251dbfff37cd2a981e6c473a5b2afde19d363e",
+                    "paginate": true
+                },
+                {
+                    "header": "Account",
+                    "prompt": "#0, Index #0"
+                },
+                {
+                    "header": "To",
+                    "prompt": "0x4f2370b2a4810ad6c8e1cfd92cc8c8818fef8f59e3a80cea17871f78d850ba4b",
+                    "paginate": true
+                },
+                {
+                    "header": "Recipient",
+                    "prompt": "Not in address book"
+                },
+                {
+                    "header": "Amount",
+                    "prompt": "IOTA 0,001"
+                },
+                {
+                    "header": "Max Gas",
+                    "prompt": "IOTA 0,000001036"
+                },
+                {
+                    "text": "Sign Transaction?",
+                    "x": 19,
+                    "y": 11,
+                    "clear": false
+                },
+                {
+                    "text": "To 0x4f23...ba4b",
+                    "x": 22,
+                    "y": 11,
+                    "clear": false
+                },
+                {
+                    "text": "IOTA 0,001",
+                    "x": 38,
+                    "y": 11,
+                    "clear": false
+                },
+                {
+                    "text": "Gas IOTA 0,000001036",
+                    "x": 11,
+                    "y": 11,
+                    "clear": false
+                },
+                {
+                    "text": "Confirm",
+                    "x": 43,
+                    "y": 11,
+                    "clear": false
+                }
+            ]
+        )();
+        await Axios.delete(BASE_URL + "/events");
+        // reset back to a decimal period
+        await toggleDecimalMarkSettings();
+    });
+
+    it("can show hashes in Base64", async function () {
+        await toggleShowHashSettings();
+        await toggleHashFormatSettings();
+        await Axios.delete(BASE_URL + "/events");
+        await testTransaction(
+            IOTA_BIP_PATH,
+            TRANSFER_TXN,
+            [
+                {
+                    "header": "Transfer",
+                    "prompt": "1 recipient, 1 amount, gas IOTA 0.000001036",
+                    "paginate": true,
+                },
+                {
+                    "header": "From",
+                    "prompt": "0x1b3669e321893ee49c387a08fc251dbfff37cd2a981e6c473a5b2afde19d363e",
+                    "paginate": true
+                },
+                {
+                    "header": "Account",
+                    "prompt": "#0, Index #0"
+                },
+                {
+                    "header": "To",
+                    "prompt": "0x4f2370b2a4810ad6c8e1cfd92cc8c8818fef8f59e3a80cea17871f78d850ba4b",
+                    "paginate": true
+                },
+                {
+                    "header": "Recipient",
+                    "prompt": "Not in address book"
+                },
+                {
+                    "header": "Amount",
+                    "prompt": "IOTA 0.001"
+                },
+                {
+                    "header": "Max Gas",
+                    "prompt": "IOTA 0.000001036"
+                },
+                {
+                    "header": "Transaction Hash",
+                    "prompt": "1:W/1g qfhN spnw FlT7 2:jDvC 8s4a WNRO hD3X 3:hVpQ TtWd nIQ="
+                },
+                {
+                    "text": "Sign Transaction?",
+                    "x": 19,
+                    "y": 11,
+                    "clear": false
+                },
+                {
+                    "text": "To 0x4f23...ba4b",
+                    "x": 22,
+                    "y": 11,
+                    "clear": false
+                },
+                {
+                    "text": "IOTA 0.001",
+                    "x": 38,
+                    "y": 11,
+                    "clear": false
+                },
+                {
+                    "text": "Gas IOTA 0.000001036",
+                    "x": 11,
+                    "y": 11,
+                    "clear": false
+                },
+                {
+                    "text": "Confirm",
+                    "x": 43,
+                    "y": 11,
+                    "clear": false
+                }
+            ]
+        )();
+        await Axios.delete(BASE_URL + "/events");
+        // reset back to hiding the hash, in hex
+        await toggleHashFormatSettings();
+        await toggleShowHashSettings();
+    });
+
+    it("can reset the settings", async function () {
+        await toggleUnitsSettings();
+        await resetSettings();
+        await Axios.delete(BASE_URL + "/events");
+        await testTransaction(
+            IOTA_BIP_PATH,
+            TRANSFER_TXN,
+            [
+                {
+                    "header": "Transfer",
+                    "prompt": "1 recipient, 1 amount, gas IOTA 0.000001036",
+                    "paginate": true,
+                },
+                {
+                    "header": "From",
+                    "prompt": "0x1b3669e321893ee49c387a08fc251dbfff37cd2a981e6c473a5b2afde19d363e",
+                    "paginate": true
+                },
+                {
+                    "header": "Account",
+                    "prompt": "#0, Index #0"
+                },
+                {
+                    "header": "To",
+                    "prompt": "0x4f2370b2a4810ad6c8e1cfd92cc8c8818fef8f59e3a80cea17871f78d850ba4b",
+                    "paginate": true
+                },
+                {
+                    "header": "Recipient",
+                    "prompt": "Not in address book"
+                },
+                {
+                    "header": "Amount",
+                    "prompt": "IOTA 0.001"
+                },
+                {
+                    "header": "Max Gas",
+                    "prompt": "IOTA 0.000001036"
+                },
+                {
+                    "text": "Sign Transaction?",
+                    "x": 19,
+                    "y": 11,
+                    "clear": false
+                },
+                {
+                    "text": "To 0x4f23...ba4b",
+                    "x": 22,
+                    "y": 11,
+                    "clear": false
+                },
+                {
+                    "text": "IOTA 0.001",
+                    "x": 38,
+                    "y": 11,
+                    "clear": false
+                },
+                {
+                    "text": "Gas IOTA 0.000001036",
+                    "x": 11,
+                    "y": 11,
+                    "clear": false
+                },
+                {
+                    "text": "Confirm",
+                    "x": 43,
+                    "y": 11,
+                    "clear": false
+                }
+            ]
+        )();
+    });
 
-    // Commented for now as this will crash at the "Working..." screen: https://github.com/iotaledger/ledger-app-iota/issues/11
-    it("can sign a transaction",
-        testTransaction(
+    it("can show truncated addresses", async function () {
+        await toggleAddressSettings();
+        await Axios.delete(BASE_URL + "/events");
+        await testTransaction(
             IOTA_BIP_PATH,
-            Buffer.from("000000000002000840420f000000000000204f2370b2a4810ad6c8e1cfd92cc8c8818fef8f59e3a80cea17871f78d850ba4b0202000101000001010200000101006fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e210112a6d0c44edc630d2724b1f57fea4f93308b1d22164402c65778bd99379c4733070000000000000020f2fd3c87b227f1015182fe4348ed680d7ed32bcd3269704252c03e1d0b13d30d6fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e2101000000000000000c0400000000000000", "hex"),
+            TRANSFER_TXN,
             [
                 {
                     "header": "Transfer",
-                    "prompt": "",
+                    "prompt": "1 recipient, 1 amount, gas IOTA 0.000001036",
+                    "paginate": true,
+                },
+                {
+                    "header": "From",
+                    "prompt": "0x1b3669e3...e19d363e",
+                    "paginate": true
+                },
+                {
+                    "text": "Show Full Address?",
+                    "x": 17,
+                    "y": 11,
+                    "clear": false
+                },
+                {
+                    "text": "Confirm",
+                    "x": 43,
+                    "y": 11,
+                    "clear": false
                 },
                 {
                     "header": "From",
                     "prompt": "0x1b3669e321893ee49c387a08fc251dbfff37cd2a981e6c473a5b2afde19d363e",
                     "paginate": true
                 },
+                {
+                    "header": "Account",
+                    "prompt": "#0, Index #0"
+                },
+                {
+                    "header": "To",
+                    "prompt": "0x4f2370b2...d850ba4b",
+                    "paginate": true
+                },
+                {
+                    "text": "Show Full Address?",
+                    "x": 17,
+                    "y": 11,
+                    "clear": false
+                },
+                {
+                    "text": "Confirm",
+                    "x": 43,
+                    "y": 11,
+                    "clear": false
+                },
                 {
                     "header": "To",
                     "prompt": "0x4f2370b2a4810ad6c8e1cfd92cc8c8818fef8f59e3a80cea17871f78d850ba4b",
                     "paginate": true
                 },
+                {
+                    "header": "Recipient",
+                    "prompt": "Not in address book"
+                },
                 {
                     "header": "Amount",
                     "prompt": "IOTA 0.001"
@@ -70,6 +860,24 @@ describe("Signing tests", function () {
                     "y": 11,
                     "clear": false
                 },
+                {
+                    "text": "To 0x4f23...ba4b",
+                    "x": 22,
+                    "y": 11,
+                    "clear": false
+                },
+                {
+                    "text": "IOTA 0.001",
+                    "x": 38,
+                    "y": 11,
+                    "clear": false
+                },
+                {
+                    "text": "Gas IOTA 0.000001036",
+                    "x": 11,
+                    "y": 11,
+                    "clear": false
+                },
                 {
                     "text": "Confirm",
                     "x": 43,
@@ -77,7 +885,11 @@ describe("Signing tests", function () {
                     "clear": false
                 }
             ]
-        ));
+        )();
+        await Axios.delete(BASE_URL + "/events");
+        // reset back to full addresses
+        await toggleAddressSettings();
+    });
 
     it("can blind sign an unknown transaction", async function () {
         const path = IOTA_BIP_PATH;
@@ -90,7 +902,7 @@ describe("Signing tests", function () {
                 },
                 {
                     "header": "Transaction Hash",
-                    "prompt": "0xfc2bce70e1cb980a6d49a32ff770a782ee13dabdecee085b82e0fdad5e92fcdd"
+                    "prompt": "1:fc2b ce70 e1cb 980a 2:6d49 a32f f770 a782 3:ee13 dabd ecee 085b 4:82e0 fdad 5e92 fcdd"
                 },
                 {
                     "text": "Blind Sign Transaction?",
@@ -125,7 +937,7 @@ describe("Signing tests", function () {
                 },
                 {
                     "header": "Transaction Hash",
-                    "prompt": "0xfc2bce70e1cb980a6d49a32ff770a782ee13dabdecee085b82e0fdad5e92fcdd"
+                    "prompt": "1:fc2b ce70 e1cb 980a 2:6d49 a32f f770 a782 3:ee13 dabd ecee 085b 4:82e0 fdad 5e92 fcdd"
                 },
                 {
                     "text": "Blind Sign Transaction?",
@@ -149,6 +961,43 @@ describe("Signing tests", function () {
         await toggleBlindSigningSettings();
     });
 
+    it("only blind signs once with single use blind signing", async function () {
+        const path = IOTA_BIP_PATH;
+        const txn = Buffer.from("00000000050205546e7f126d2f40331a543b9608439b582fd0d103000000000000002080fdabcc90498e7eb8413b140c4334871eeafa5a86203fd9cfdb032f604f49e1284af431cf032b5d85324135bf9a3073e920d7f5020000000000000020a06f410c175e828c24cee84cb3bd95cff25c33fbbdcb62c6596e8e423784ffe702d08074075c7097f361e8b443e2075a852a2292e8a08074075c7097f361e8b443e2075a852a2292e80180969800000000001643fb2578ff7191c643079a62c1cca8ec2752bc05546e7f126d2f40331a543b9608439b582fd0d103000000000000002080fdabcc90498e7eb8413b140c4334871eeafa5a86203fd9cfdb032f604f49e101000000000000002c01000000000000", "hex");
+        const prompts =
+            [
+                {
+                    "header": "WARNING",
+                    "prompt": "Transaction not recognized"
+                },
+                {
+                    "header": "Transaction Hash",
+                    "prompt": "1:fc2b ce70 e1cb 980a 2:6d49 a32f f770 a782 3:ee13 dabd ecee 085b 4:82e0 fdad 5e92 fcdd"
+                },
+                {
+                    "text": "Blind Sign Transaction?",
+                    "x": 4,
+                    "y": 11,
+                    "clear": false
+                },
+                {
+                    "text": "Confirm",
+                    "x": 43,
+                    "y": 11,
+                    "clear": false
+                }
+            ];
+
+        await toggleBlindSigningOnceSettings();
+        await Axios.delete(BASE_URL + "/events");
+        await testTransaction(path, txn, prompts)();
+        await Axios.delete(BASE_URL + "/events");
+        // The approval was used up by the first signature
+        await sendCommandExpectFail(async (client: Iota) => {
+            await client.signTransaction(path, txn);
+        });
+    });
+
     it("should reject signing a non-IOTA coin transaction, if blind signing is not enabled", async function () {
         const path = IOTA_BIP_PATH;
         const txn = Buffer.from("AAAAAAADAQAe2uv1Mds+xCVK5Jv/Dv5cgEl/9DthDcpbjWcsmFpzbs6BNQAAAAAAIKPD8GQqgBpJZRV+nFDRE7rqR0Za8x0pyfLusVdpPPVRAAgADl+jHAAAAAAg5y3MHATlk+Ik5cPIdEz5iPANs1jcXZHVGjh4Mb16lwkCAgEAAAEBAQABAQIAAAECAF/sd27xyQe/W+gY4WRtPlQro1siWQu79s0pxbbCSRafAfnjaU5yJSFFDJznsAaBqbkiR9CB8DJqWki8fn8AUZeQz4E1AAAAAAAgTRU/MsawTJirpVwjDF8gyiEbaT0+7J0V8ifUEGGBkcVf7Hdu8ckHv1voGOFkbT5UK6NbIlkLu/bNKcW2wkkWn+gDAAAAAAAA8NdGAAAAAAAA", "base64");
@@ -176,7 +1025,7 @@ describe("Signing tests", function () {
         await sendCommandExpectFail(async (client: any) => {
             client.oldSendChunks = client.sendChunks;
             client.sendChunks = (cla, ins, p1, p2, payload) => {
-                payload[0][3] = payload[0][3] + 20; // Add 20*2^24 to the transaction length, so we'll run out of input.
+                payload[0][3] = payload[0][3] + 20; // Add 20*2^24 to the transaction length, which is more than is sent and more than is accepted.
                 const rv = client.oldSendChunks(cla, ins, p1, p2, payload);
                 return rv;
             }
@@ -193,4 +1042,253 @@ describe("Signing tests", function () {
         // reset back to disabled
         await toggleBlindSigningSettings();
     });
+
+    it("should reject lending calls of an unknown market, if blind signing is not enabled", async function () {
+        // The package 0x1e1e...1e1e is not one of an audited market, whatever its module is called
+        for (const txn of [LENDING_DEPOSIT_TXN, LENDING_WITHDRAW_TXN]) {
+            await sendCommandExpectFail(async (client: Iota) => {
+                await client.signTransaction(IOTA_BIP_PATH, txn);
+            });
+        }
+    });
+
+    it("should reject liquidity calls of an unknown AMM, if blind signing is not enabled", async function () {
+        // The package 0x5b5b...5b5b is not one of an audited AMM, whatever its module is called
+        for (const txn of [ADD_LIQUIDITY_TXN, REMOVE_LIQUIDITY_TXN]) {
+            await sendCommandExpectFail(async (client: Iota) => {
+                await client.signTransaction(IOTA_BIP_PATH, txn);
+            });
+        }
+    });
+
+    it("clear signs a transfer of objects, counting them by kind", async function () {
+        const screens = await signAndRecord(OBJECT_TRANSFER_TXN);
+        for (const text of ["Transfer", "To", "Amount", "IOTA 0.5", "Sign Transaction?"]) {
+            expect(screens).to.include(ocr(text));
+        }
+        // Wrapped over lines, on the smaller screens
+        expect(screens.join(" ")).to.include(ocr("Transferring 3 objects (1 Coin<IOTA>, 2 other)"));
+        expect(screens).to.not.include(ocr("Blind Sign Transaction?"));
+    });
+
+    it("warns before a transfer to a burn address", async function () {
+        const screens = await signAndRecord(BURN_TRANSFER_TXN);
+        for (const text of ["Burn Address", "WARNING", "Transfer", "Sign Transaction?"]) {
+            expect(screens).to.include(ocr(text));
+        }
+        expect(screens.join(" ")).to.include(ocr("Funds will be unrecoverable"));
+        const transfer = await signAndRecord(TRANSFER_TXN);
+        expect(transfer).to.not.include(ocr("Burn Address"));
+    });
+
+    it("warns of a recipient which only looks like a saved address", async function () {
+        await recordScreens(async (client: Iota) => {
+            await client.saveAddress(TESTNET_BIP_PATH, "Savings");
+        });
+        // The first and last 4 characters of the address of TESTNET_BIP_PATH, 0x3a0de157...973e9955
+        const lookalike = "3a0d" + "00".repeat(28) + "9955";
+        const txn = Buffer.from(TRANSFER_TXN.toString("hex").replace(TRANSFER_RECIPIENT, lookalike), "hex");
+        const screens = await signAndRecord(txn);
+        for (const text of ["Similar Address", "WARNING", "Not in address book", "Sign Transaction?"]) {
+            expect(screens).to.include(ocr(text));
+        }
+        expect(screens.join(" ")).to.include(ocr("Similar to saved address 'Savings', possible poisoning"));
+        const transfer = await signAndRecord(TRANSFER_TXN);
+        expect(transfer).to.not.include(ocr("Similar Address"));
+    });
+
+    it("shows the label of a recipient given by the host as unverified", async function () {
+        // A recipient of its own, as the label stays for the rest of the session
+        const labelled = "5e".repeat(32);
+        await recordScreens(async (client: Iota) => {
+            await client.labelRecipient(Buffer.from(labelled, "hex"), "Exchange");
+        });
+        const txn = Buffer.from(TRANSFER_TXN.toString("hex").replace(TRANSFER_RECIPIENT, labelled), "hex");
+        const screens = await signAndRecord(txn);
+        expect(screens).to.include(ocr("Claimed Name"));
+        expect(screens.join(" ")).to.include(ocr("Exchange (unverified)"));
+        // Not taken for a saved address
+        expect(screens).to.not.include(ocr("Known: Exchange"));
+        const other = await signAndRecord(TRANSFER_TXN);
+        expect(other).to.not.include(ocr("Claimed Name"));
+    });
+
+ async function () {
+        const screens = await signAndRecord(DIVIDE_TXN);
+        for (const text of ["Divide", "Package", "To", "Amount", "IOTA 3.0", "Sign Transaction?", "Into 3 coins"]) {
+            expect(screens).to.include(ocr(text));
+        }
+        expect(screens.join(" ")).to.include(ocr("IOTA Framework (0x2)"));
+        expect(screens.join(" ")).to.include(ocr("Split IOTA 3.0 into 3 coins of IOTA 1.0"));
+        expect(screens).to.not.include(ocr("Blind Sign Transaction?"));
+    });
+
+    it("clear signs a stake, with the validator", async function () {
+        const screens = await signAndRecord(STAKE_TXN);
+        for (const text of ["Stake", "Validator", "Package", "Amount", "IOTA 1.0", "Sign Transaction?"]) {
+            expect(screens).to.include(ocr(text));
+        }
+        expect(screens.join(" ")).to.include(ocr("IOTA System (0x3)"));
+        expect(screens).to.not.include(ocr("Blind Sign Transaction?"));
+    });
+
+    it("should reject a stake of the gas coin itself, if blind signing is not enabled", async function () {
+        await sendCommandExpectFail(async (client: Iota) => {
+            await client.signTransaction(IOTA_BIP_PATH, STAKE_ALL_TXN);
+        });
+    });
+
+    it("names a validator saved in the address book", async function () {
+        await recordScreens(async (client: Iota) => {
+            await client.saveAddress(TESTNET_BIP_PATH, "Savings");
+        });
+        // The address of TESTNET_BIP_PATH, staked with instead of 0x7777...7777
+        const saved = "3a0de157f4b6c4bed40caf93def86c3e6946ed35d27c7121ef264336973e9955";
+        const txn = Buffer.from(STAKE_TXN.toString("hex").replace("77".repeat(32), saved), "hex");
+        const screens = await signAndRecord(txn);
+        expect(screens).to.include(ocr("Validator"));
+        expect(screens.join(" ")).to.include(ocr("Known: Savings (0x3a0d"));
+        const unsaved = await signAndRecord(STAKE_TXN);
+        expect(unsaved.join(" ")).to.not.include(ocr("Known:"));
+    });
+
+    it("clear signs an unstake, with the stake withdrawn", async function () {
+        const screens = await signAndRecord(UNSTAKE_TXN);
+        for (const text of ["Unstake", "Package", "Withdraw", "Sign Transaction?", "with all rewards"]) {
+            expect(screens).to.include(ocr(text));
+        }
+        expect(screens.join(" ")).to.include(ocr("Withdraw stake 0x5a5a"));
+        // In place of an amount, which the transaction does not have
+        expect(screens.join(" ")).to.include(ocr("All accrued rewards, along with the stake"));
+        expect(screens).to.not.include(ocr("Amount"));
+        expect(screens.join(" ")).to.include(ocr("IOTA System (0x3)"));
+        expect(screens).to.not.include(ocr("Timelocked, with all rewards"));
+        expect(screens).to.not.include(ocr("Blind Sign Transaction?"));
+    });
+
+    it("clear signs an unstake of a timelocked stake", async function () {
+        const screens = await signAndRecord(TIMELOCKED_UNSTAKE_TXN);
+        for (const text of ["Unstake", "Package", "Timelocked, with all rewards", "Sign Transaction?"]) {
+            expect(screens).to.include(ocr(text));
+        }
+        expect(screens.join(" ")).to.include(ocr("Withdraw timelocked stake 0x5b5b"));
+        expect(screens.join(" ")).to.include(ocr("of which the principal stays timelocked"));
+        expect(screens).to.not.include(ocr("Blind Sign Transaction?"));
+    });
+
+    it("warns before clear signing a mint", async function () {
+        const screens = await signAndRecord(MINT_TXN);
+        for (const text of ["WARNING", "Mint", "Coin Type", "To", "Amount", "1000000000 base units", "Sign Transaction?"]) {
+            expect(screens).to.include(ocr(text));
+        }
+        expect(screens.join(" ")).to.include(ocr("New coins will be created"));
+        expect(screens.join(" ")).to.include(ocr("Mint 1000000000 base units of USDC"));
+        expect(screens).to.not.include(ocr("Blind Sign Transaction?"));
+        const transfer = await signAndRecord(TRANSFER_TXN);
+        expect(transfer.join(" ")).to.not.include(ocr("New coins will be created"));
+    });
+
+    it("shows the pure arguments of the call in expert mode", async function () {
+        await toggleExpertModeSettings();
+        const screens = await signAndRecord(MINT_TXN);
+        await toggleExpertModeSettings();
+        // Paginated along with the call
+        expect(screens.join(" ")).to.include(ocr("coin::mint_and_transfer, given u64 1000000000, address 0x4f4f"));
+    });
+
+    it("shows the version and digest of each object in expert mode", async function () {
+        await toggleExpertModeSettings();
+        const screens = await signAndRecord(OBJECT_TRANSFER_TXN);
+        await toggleExpertModeSettings();
+        expect(screens).to.include(ocr("Object"));
+        // The object may be paginated, so only the start of the review of it is matched
+        expect(screens.join(" ")).to.include(ocr("1/2: 0xa1a1"));
+        const defaults = await signAndRecord(OBJECT_TRANSFER_TXN);
+        expect(defaults).to.not.include(ocr("Object"));
+    });
+
+    it("Rejects a transaction larger than the network accepts before showing it", async function () {
+        const path = IOTA_BIP_PATH;
+
+        await toggleBlindSigningSettings();
+        await Axios.delete(BASE_URL + "/events");
+        await sendCommandExpectFail(async (client: any) => {
+            client.oldSendChunks = client.sendChunks;
+            client.sendChunks = (cla, ins, p1, p2, payload) => {
+                payload[0].writeUInt32LE(128 * 1024 + 1, 0);
+                return client.oldSendChunks(cla, ins, p1, p2, payload);
+            }
+            await client.signTransaction(path, TRANSFER_TXN);
+        });
+        const events = (await Axios.get(BASE_URL + "/events")).data["events"] as any[];
+        expect(events.some((e: any) => e["text"].includes("Transaction"))).to.equal(false);
+        await Axios.delete(BASE_URL + "/events");
+        // reset back to disabled
+        await toggleBlindSigningSettings();
+    });
+
+    it("Rejects a blind sign with an invalid path before showing it", async function () {
+        const path = "44'/0'/0'/0'/0'";
+        const txn = Buffer.from("00000000050205546e7f126d2f40331a543b9608439b582fd0d103000000000000002080fdabcc90498e7eb8413b140c4334871eeafa5a86203fd9cfdb032f604f49e1284af431cf032b5d85324135bf9a3073e920d7f5020000000000000020a06f410c175e828c24cee84cb3bd95cff25c33fbbdcb62c6596e8e423784ffe702d08074075c7097f361e8b443e2075a852a2292e8a08074075c7097f361e8b443e2075a852a2292e80180969800000000001643fb2578ff7191c643079a62c1cca8ec2752bc05546e7f126d2f40331a543b9608439b582fd0d103000000000000002080fdabcc90498e7eb8413b140c4334871eeafa5a86203fd9cfdb032f604f49e101000000000000002c01000000000000", "hex");
+
+        await toggleBlindSigningSettings();
+        await Axios.delete(BASE_URL + "/events");
+        await sendCommandExpectFail(async (client: Iota) => {
+            await client.signTransaction(path, txn);
+        });
+        const events = (await Axios.get(BASE_URL + "/events")).data["events"] as any[];
+        expect(events.some((e: any) => e["text"].includes("Transaction"))).to.equal(false);
+        await Axios.delete(BASE_URL + "/events");
+        // reset back to disabled
+        await toggleBlindSigningSettings();
+    });
+
+    it("Rejects a transaction with too many inputs, if blind signing is not enabled", async function () {
+        const path = IOTA_BIP_PATH;
+        // TRANSFER_TXN, but claiming 33 inputs
+        const txn = Buffer.from(TRANSFER_TXN);
+        txn[5] = 33;
+
+        await sendCommandExpectFail(async (client: Iota) => {
+            await client.signTransaction(path, txn);
+        });
+    });
+
+    it("Rejects a chunked transaction with too many inputs with SW_TOO_COMPLEX", async function () {
+        const path = IOTA_BIP_PATH;
+        // TRANSFER_TXN, but claiming 33 inputs
+        const txn = Buffer.from(TRANSFER_TXN);
+        txn[5] = 33;
+
+        // Rejected before anything is shown
+        await sendCommandAndAccept(async (client: any) => {
+            await client.transport.send(0x00, 0x04, 0x00, 0x00, bip32Payload(path));
+            const rv = await client.transport.send(0x00, 0x04, 0x02, 0x00, txn, [0x9000, 0xb002]);
+            expect(rv.readUInt16BE(rv.length - 2)).to.equal(0xb002);
+        }, []);
+    });
+
+    it("Rejects a known transaction followed by more bytes", async function () {
+        const path = IOTA_BIP_PATH;
+        const txn = Buffer.concat([TRANSFER_TXN, Buffer.from("00", "hex")]);
+
+        await sendCommandExpectFail(async (client: Iota) => {
+            await client.signTransaction(path, txn);
+        });
+    });
+
+    it("Rejects a transaction with an over-long ULEB128, even with blind signing enabled", async function () {
+        const path = IOTA_BIP_PATH;
+        // The intent version of TRANSFER_TXN, 0 encoded in two bytes instead of one
+        const txn = Buffer.concat([Buffer.from("8000", "hex"), TRANSFER_TXN.subarray(1)]);
+
+        await toggleBlindSigningSettings();
+        await Axios.delete(BASE_URL + "/events");
+        await sendCommandExpectFail(async (client: Iota) => {
+            await client.signTransaction(path, txn);
+        });
+        await Axios.delete(BASE_URL + "/events");
+        // reset back to disabled
+        await toggleBlindSigningSettings();
+    });
 });
@@ -0,0 +1,68 @@
+import { TRANSFER_TXN, UNKNOWN_TXN, recordScreens, BASE_URL, IOTA_BIP_PATH, TESTNET_BIP_PATH, toggleBlindSigningSettings, toggleExpertModeSettings, toggleUnitsSettings, toggleAddressSettings, toggleDecimalMarkSettings } from "./common";
+import { expect } from 'chai';
+import { describe, it } from 'mocha';
+import Axios from 'axios';
+import type Iota from "./Iota";
+import * as fs from 'fs';
+import * as path from 'path';
+
+// Golden snapshots of every screen shown while signing a corpus of transactions, one set per
+// device, so that changes to titles, pagination or truncation show up as a diff to review.
+//
+// Skipped unless DEVICE names the device speculos runs (nanos, nanosplus or nanox). A missing
+// snapshot is recorded instead of checked, and UPDATE_SNAPSHOTS=1 records all of them again.
+const DEVICE = process.env.DEVICE;
+const UPDATE = !!process.env.UPDATE_SNAPSHOTS;
+const SNAPSHOT_DIR = path.join(__dirname, "snapshots");
+
+// Each setting is toggled before signing and toggled back after
+const CORPUS = [
+    { name: "transfer", path: IOTA_BIP_PATH, txn: TRANSFER_TXN, settings: [] },
+    { name: "transfer-testnet", path: TESTNET_BIP_PATH, txn: TRANSFER_TXN, settings: [] },
+    { name: "transfer-expert", path: IOTA_BIP_PATH, txn: TRANSFER_TXN, settings: [toggleExpertModeSettings] },
+    { name: "transfer-nanos", path: IOTA_BIP_PATH, txn: TRANSFER_TXN, settings: [toggleUnitsSettings] },
+    { name: "transfer-decimal-comma", path: IOTA_BIP_PATH, txn: TRANSFER_TXN, settings: [toggleDecimalMarkSettings] },
+    { name: "transfer-truncated", path: IOTA_BIP_PATH, txn: TRANSFER_TXN, settings: [toggleAddressSettings] },
+    { name: "blind-sign", path: IOTA_BIP_PATH, txn: UNKNOWN_TXN, settings: [toggleBlindSigningSettings] },
+];
+
+describe('screen snapshot tests', function () {
+
+    before(function () {
+        if (!DEVICE) {
+            this.skip();
+        }
+    });
+
+    afterEach(async function () {
+        await Axios.post(BASE_URL + "/automation", { version: 1, rules: [] });
+        await Axios.delete(BASE_URL + "/events");
+    });
+
+    for (const entry of CORPUS) {
+        it(`shows the same screens as before for ${entry.name}`, async () => {
+            for (const toggle of entry.settings) {
+                await toggle();
+            }
+            let screens: any[];
+            try {
+                screens = await recordScreens(async (client: Iota) => {
+                    await client.signTransaction(entry.path, entry.txn);
+                });
+            } finally {
+                for (const toggle of entry.settings) {
+                    await toggle();
+                }
+            }
+
+            const file = path.join(SNAPSHOT_DIR, DEVICE, entry.name + ".json");
+            if (UPDATE || !fs.existsSync(file)) {
+                fs.mkdirSync(path.dirname(file), { recursive: true });
+                fs.writeFileSync(file, JSON.stringify(screens, null, 2) + "\n");
+                console.log(`Recorded ${file}`);
+                return;
+            }
+            expect(screens).to.deep.equal(JSON.parse(fs.readFileSync(file, "utf8")));
+        });
+    }
+});
@@ -0,0 +1,53 @@
+import { TRANSFER_TXN, transferPrompts, sendCommandAndAccept, BASE_URL, IOTA_BIP_PATH, TESTNET_BIP_PATH } from "./common";
+import { expect } from 'chai';
+import { describe, it } from 'mocha';
+import Axios from 'axios';
+import type Iota from "./Iota";
+
+// The known answers of docs/test-vectors.md, for a build with the test_vectors feature. Skipped
+// unless TEST_VECTORS is set, as every other build derives its keys from the seed of the device.
+const VECTORS = [
+    {
+        path: IOTA_BIP_PATH,
+        publicKey: "151aa94931bfc508c59f1b503a40b03d2ec2b624fd4105c18b5a213815216706",
+        address: "0af6157eab19f0af5b0f8e39ce16de83f7408fb03a341de73b4120a604b247b2",
+        transferSignature: "0d92db4d7479ae257740dc03c7262a59a913d5b22b853d10a246d61f3c2baeeb7ddf87b13f8dcb73890233cec9642f85dec5dc2d4799f1508508cac501d02f05",
+    },
+    {
+        path: TESTNET_BIP_PATH,
+        publicKey: "41e822f64c8bde7c9929fa80f104bad8a1385642109302152cc837b0e6847bbd",
+        address: "00f88a66922bfe3f29b8dda8f329f78055caf132a6d5fe3e1455383802e838f3",
+        transferSignature: "ee363a9c0dba1d9ee26dbcfdfad043b66f94c517c83483cbdf0594acb47bee80fa7f6092d803dc9acb0bd069328062ccc9eafb22bc51e5d1bfb1d12e8663a90b",
+    },
+];
+
+describe('test vector tests', function () {
+
+    before(function () {
+        if (!process.env.TEST_VECTORS) {
+            this.skip();
+        }
+    });
+
+    afterEach(async function () {
+        await Axios.post(BASE_URL + "/automation", { version: 1, rules: [] });
+        await Axios.delete(BASE_URL + "/events");
+    });
+
+    for (const vector of VECTORS) {
+        it(`provides the known public key for ${vector.path}`, async () => {
+            await sendCommandAndAccept(async (client: Iota) => {
+                const rv = await client.getPublicKey(vector.path);
+                expect(Buffer.from(rv.publicKey).toString('hex')).to.equal(vector.publicKey);
+                expect(Buffer.from(rv.address).toString('hex')).to.equal(vector.address);
+            }, []);
+        });
+
+        it(`signs a transfer with the known signature for ${vector.path}`, async () => {
+            await sendCommandAndAccept(async (client: Iota) => {
+                const sig = await client.signTransaction(vector.path, TRANSFER_TXN);
+                expect(Buffer.from(sig.signature).toString('hex')).to.equal(vector.transferSignature);
+            }, transferPrompts(vector.address));
+        });
+    }
+});